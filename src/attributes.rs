@@ -0,0 +1,458 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Attributes, named channels storing one color, normal, scalar or label per element
+
+use std::{
+    cmp::Ordering,
+    hash::{Hash, Hasher},
+};
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+macro_rules! for_values {
+    ($values:expr, $x:ident => $body:expr) => {
+        match $values {
+            AttributeValues::Rgb($x) => $body,
+            AttributeValues::Norm3D($x) => $body,
+            AttributeValues::Scalar($x) => $body,
+            AttributeValues::Label($x) => $body,
+        }
+    };
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+/// The values of a single attribute channel
+/// Floating point values are compared via their total order (see f64::total_cmp), therefore NaN equals itself
+pub enum AttributeValues {
+    Rgb(Vec<Rgb>),
+    Norm3D(Vec<Norm3D>),
+    Scalar(Vec<f64>),
+    Label(Vec<u32>),
+}
+
+impl AttributeValues {
+    /// Number of stored values
+    pub fn len(&self) -> usize {
+        for_values!(self, x => x.len())
+    }
+    /// Whether no values are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Creates values of the same type, n times the default value
+    fn defaults_like(&self, n: usize) -> Self {
+        match self {
+            Self::Rgb(_) => Self::Rgb(vec![IsAttribute::default_attribute(); n]),
+            Self::Norm3D(_) => Self::Norm3D(vec![IsAttribute::default_attribute(); n]),
+            Self::Scalar(_) => Self::Scalar(vec![IsAttribute::default_attribute(); n]),
+            Self::Label(_) => Self::Label(vec![IsAttribute::default_attribute(); n]),
+        }
+    }
+
+    /// Index of the type, used to order values of different types
+    fn type_index(&self) -> u8 {
+        match self {
+            Self::Rgb(_) => 0,
+            Self::Norm3D(_) => 1,
+            Self::Scalar(_) => 2,
+            Self::Label(_) => 3,
+        }
+    }
+
+    fn is_same_type(&self, other: &Self) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    fn resize(&mut self, n: usize) {
+        for_values!(self, x => x.resize(n, IsAttribute::default_attribute()))
+    }
+
+    fn reserve(&mut self, n: usize) {
+        for_values!(self, x => x.reserve(n))
+    }
+
+    fn insert_default(&mut self, index: usize) {
+        for_values!(self, x => x.insert(index, IsAttribute::default_attribute()))
+    }
+
+    fn apply_view(&mut self, view: &View) -> Result<()> {
+        for_values!(self, x => x.apply_view(view))
+    }
+
+    fn permute(&mut self, order: &[usize]) {
+        for_values!(self, x => permute(x, order))
+    }
+
+    fn select(&self, ids: &[usize]) -> Self {
+        match self {
+            Self::Rgb(x) => Self::Rgb(ids.iter().map(|i| x[*i].clone()).collect()),
+            Self::Norm3D(x) => Self::Norm3D(ids.iter().map(|i| x[*i].clone()).collect()),
+            Self::Scalar(x) => Self::Scalar(ids.iter().map(|i| x[*i]).collect()),
+            Self::Label(x) => Self::Label(ids.iter().map(|i| x[*i]).collect()),
+        }
+    }
+
+    /// Appends the values of other, which must be of the same type
+    fn append(&mut self, other: Self) {
+        match (self, other) {
+            (Self::Rgb(x), Self::Rgb(mut y)) => x.append(&mut y),
+            (Self::Norm3D(x), Self::Norm3D(mut y)) => x.append(&mut y),
+            (Self::Scalar(x), Self::Scalar(mut y)) => x.append(&mut y),
+            (Self::Label(x), Self::Label(mut y)) => x.append(&mut y),
+            _ => {}
+        }
+    }
+}
+
+impl PartialEq for AttributeValues {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for AttributeValues {}
+
+impl PartialOrd for AttributeValues {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AttributeValues {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Self::Rgb(x), Self::Rgb(y)) => x.cmp(y),
+            (Self::Norm3D(x), Self::Norm3D(y)) => cmp_slices(x, y, |a, b| {
+                a.x()
+                    .total_cmp(&b.x())
+                    .then_with(|| a.y().total_cmp(&b.y()))
+                    .then_with(|| a.z().total_cmp(&b.z()))
+            }),
+            (Self::Scalar(x), Self::Scalar(y)) => cmp_slices(x, y, |a, b| a.total_cmp(b)),
+            (Self::Label(x), Self::Label(y)) => x.cmp(y),
+            _ => self.type_index().cmp(&other.type_index()),
+        }
+    }
+}
+
+impl Hash for AttributeValues {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Rgb(x) => x.hash(state),
+            Self::Norm3D(x) => x.hash(state),
+            Self::Scalar(x) => {
+                for value in x {
+                    hash_f64(*value, state)
+                }
+            }
+            Self::Label(x) => x.hash(state),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone, Hash)]
+/// A named attribute channel
+pub struct AttributeChannel {
+    pub name: String,
+    pub values: AttributeValues,
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Default, Debug, PartialEq, PartialOrd, Ord, Eq, Clone, Hash)]
+/// Attributes, named channels storing one color, normal, scalar or label per element.
+/// All channels always have one value per element, elements added without a value get the default of their channel
+pub struct Attributes {
+    n: usize,
+    channels: Vec<AttributeChannel>,
+}
+
+impl Attributes {
+    /// Creates new attributes without any channels for n elements
+    pub fn new(n: usize) -> Self {
+        Self {
+            n,
+            channels: Vec::new(),
+        }
+    }
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.n
+    }
+    /// Whether there are no elements
+    pub fn is_empty(&self) -> bool {
+        self.n == 0
+    }
+    /// Whether there are no channels
+    pub fn has_channels(&self) -> bool {
+        !self.channels.is_empty()
+    }
+    /// All channels in order of insertion
+    pub fn channels(&self) -> &[AttributeChannel] {
+        &self.channels
+    }
+    /// The names of all channels in order of insertion
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.channels.iter().map(|x| x.name.as_str())
+    }
+    /// Returns the values of the channel with the given name
+    pub fn values(&self, name: &str) -> Option<&AttributeValues> {
+        self.channels
+            .iter()
+            .find(|x| x.name == name)
+            .map(|x| &x.values)
+    }
+    /// Returns the values of the channel with the given name and type
+    pub fn get<T>(&self, name: &str) -> Option<&[T]>
+    where
+        T: IsAttribute,
+    {
+        self.values(name)
+            .and_then(T::from_values)
+            .map(|x| x.as_slice())
+    }
+    /// Returns the mutable values of the channel with the given name and type
+    pub fn get_mut<T>(&mut self, name: &str) -> Option<&mut [T]>
+    where
+        T: IsAttribute,
+    {
+        self.channels
+            .iter_mut()
+            .find(|x| x.name == name)
+            .and_then(|x| T::from_values_mut(&mut x.values))
+            .map(|x| x.as_mut_slice())
+    }
+    /// Adds a channel, replacing any existing channel of the same name.
+    /// Fails if the number of values doesn't match the number of elements
+    pub fn insert<T>(&mut self, name: &str, values: Vec<T>) -> Result<()>
+    where
+        T: IsAttribute,
+    {
+        self.insert_values(name, T::into_values(values))
+    }
+    /// Adds a channel, replacing any existing channel of the same name.
+    /// Fails if the number of values doesn't match the number of elements
+    pub fn insert_values(&mut self, name: &str, values: AttributeValues) -> Result<()> {
+        if values.len() != self.n {
+            return Err(ErrorKind::IncorrectAttributeLength);
+        }
+
+        match self.channels.iter_mut().find(|x| x.name == name) {
+            Some(channel) => channel.values = values,
+            None => self.channels.push(AttributeChannel {
+                name: name.to_string(),
+                values,
+            }),
+        }
+
+        Ok(())
+    }
+    /// Removes and returns the channel with the given name
+    pub fn remove(&mut self, name: &str) -> Option<AttributeValues> {
+        let index = self.channels.iter().position(|x| x.name == name)?;
+        Some(self.channels.remove(index).values)
+    }
+    /// Overwrites the values starting at element start with the channels of other, adding missing channels.
+    /// Channels of other which would exceed the number of elements or have a different type are ignored
+    pub fn write_at(&mut self, start: usize, other: Attributes) {
+        for channel in other.channels {
+            if start + channel.values.len() > self.n {
+                continue;
+            }
+
+            let index = match self.channels.iter().position(|x| x.name == channel.name) {
+                Some(index) => index,
+                None => {
+                    self.channels.push(AttributeChannel {
+                        name: channel.name,
+                        values: channel.values.defaults_like(self.n),
+                    });
+                    self.channels.len() - 1
+                }
+            };
+
+            let target = &mut self.channels[index].values;
+            match (target, channel.values) {
+                (AttributeValues::Rgb(x), AttributeValues::Rgb(y)) => write_slice(x, start, y),
+                (AttributeValues::Norm3D(x), AttributeValues::Norm3D(y)) => {
+                    write_slice(x, start, y)
+                }
+                (AttributeValues::Scalar(x), AttributeValues::Scalar(y)) => {
+                    write_slice(x, start, y)
+                }
+                (AttributeValues::Label(x), AttributeValues::Label(y)) => write_slice(x, start, y),
+                _ => {}
+            }
+        }
+    }
+    /// Adds an element, using the default value of each channel
+    pub fn push_default(&mut self) {
+        self.resize(self.n + 1)
+    }
+    /// Inserts an element at index, using the default value of each channel
+    pub fn insert_default(&mut self, index: usize) {
+        for channel in &mut self.channels {
+            channel.values.insert_default(index);
+        }
+        self.n += 1;
+    }
+    /// Resizes to n elements, new elements use the default value of each channel
+    pub fn resize(&mut self, n: usize) {
+        for channel in &mut self.channels {
+            channel.values.resize(n);
+        }
+        self.n = n;
+    }
+    /// Reserves space for at least n additional elements
+    pub fn reserve(&mut self, n: usize) {
+        for channel in &mut self.channels {
+            channel.values.reserve(n);
+        }
+    }
+    /// Removes all elements, keeping the channels
+    pub fn clear(&mut self) {
+        self.resize(0)
+    }
+    /// Reorders the elements, the i-th element becomes the element at order[i]
+    pub fn permute(&mut self, order: &[usize]) {
+        for channel in &mut self.channels {
+            channel.values.permute(order);
+        }
+        self.n = order.len();
+    }
+    /// Creates new attributes of the elements at ids, which may contain duplicates (error if any id is out of bounds)
+    pub fn select(&self, ids: &[usize]) -> Result<Self> {
+        if ids.iter().any(|i| *i >= self.n) {
+            return Err(ErrorKind::IndexOutOfBounds);
+        }
+        Ok(Self {
+            n: ids.len(),
+            channels: self
+                .channels
+                .iter()
+                .map(|x| AttributeChannel {
+                    name: x.name.clone(),
+                    values: x.values.select(ids),
+                })
+                .collect(),
+        })
+    }
+    /// Appends the elements of other.
+    /// Channels missing in either are filled with defaults, channels of the same name but different type are dropped from both
+    pub fn append(&mut self, mut other: Attributes) {
+        let n_self = self.n;
+        let n_total = self.n + other.n;
+
+        let mismatched: Vec<String> = self
+            .channels
+            .iter()
+            .filter(|x| {
+                other
+                    .values(&x.name)
+                    .is_some_and(|y| !x.values.is_same_type(y))
+            })
+            .map(|x| x.name.clone())
+            .collect();
+        self.channels.retain(|x| !mismatched.contains(&x.name));
+        other.channels.retain(|x| !mismatched.contains(&x.name));
+
+        for channel in &mut self.channels {
+            match other.remove(&channel.name) {
+                Some(values) => channel.values.append(values),
+                None => channel.values.resize(n_total),
+            }
+        }
+
+        for mut channel in other.channels {
+            if self.values(&channel.name).is_some() {
+                continue;
+            }
+            let mut values = channel.values.defaults_like(n_self);
+            values.append(channel.values);
+            channel.values = values;
+            self.channels.push(channel);
+        }
+
+        self.n = n_total;
+    }
+    /// Transforms all normal channels, normalizing the results
+    pub fn transform_normals(&mut self, m: &Matrix4) {
+        let t = m.normal_transformation();
+        for channel in &mut self.channels {
+            if let AttributeValues::Norm3D(normals) = &mut channel.values {
+                for n in normals.iter_mut() {
+                    let p = Point3D::new(n.x(), n.y(), n.z()).transformed(&t);
+                    *n = Norm3D::new(p).unwrap_or_else(|_| Norm3D::norm_z());
+                }
+            }
+        }
+    }
+}
+
+impl IsViewBuildable for Attributes {
+    fn apply_view(&mut self, view: &View) -> Result<()> {
+        if let View::Restricted(indices) = view {
+            if indices.iter().any(|x| *x >= self.n) {
+                return Err(ErrorKind::IndexOutOfBounds);
+            }
+            for channel in &mut self.channels {
+                channel.values.apply_view(view)?;
+            }
+            self.n = indices.len();
+        }
+        Ok(())
+    }
+
+    fn from_view(&self, view: &View) -> Option<Self> {
+        let mut cloned = self.clone();
+        cloned.apply_view(view).ok()?;
+        Some(cloned)
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Lexicographical comparison of two slices
+fn cmp_slices<T, F>(x: &[T], y: &[T], cmp: F) -> Ordering
+where
+    F: Fn(&T, &T) -> Ordering,
+{
+    x.iter()
+        .zip(y)
+        .map(|(a, b)| cmp(a, b))
+        .find(|o| *o != Ordering::Equal)
+        .unwrap_or_else(|| x.len().cmp(&y.len()))
+}
+
+fn write_slice<T>(target: &mut [T], start: usize, values: Vec<T>) {
+    for (i, x) in values.into_iter().enumerate() {
+        target[start + i] = x;
+    }
+}
@@ -0,0 +1,501 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//! rust-3d command line tool to convert, inspect, filter, transform and merge files
+
+use rust_3d::{io::*, *};
+
+use std::{
+    env, fmt,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    process,
+};
+
+//------------------------------------------------------------------------------
+
+const USAGE: &str = "usage: rust-3d <command> [arguments]
+
+commands:
+    convert <input> <output>
+    info <input>...
+    filter <input> <output> box <cx> <cy> <cz> <size_x> <size_y> <size_z>
+    filter <input> <output> sphere <cx> <cy> <cz> <radius>
+    transform <input> <output> [--translate <x> <y> <z>] [--scale <x> <y> <z>] [--rotate <x> <y> <z>]
+        rotation in degrees, applied in the order scale, rotate, translate
+    merge <output> <input>...
+
+supported output formats:
+    ply, stl, obj, off, gltf, glb, xyz, pcd, las
+
+exit codes:
+    0 success
+    1 input / output error
+    2 invalid usage";
+
+const CHUNK_SIZE: usize = 256;
+
+type Mesh = Mesh3D<Point3D, PointCloud3D<Point3D>, Vec<usize>>;
+type Cloud = PointCloud3D<Point3D>;
+
+//------------------------------------------------------------------------------
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(()) => (),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(e.exit_code());
+        }
+    }
+}
+
+fn run(args: &[String]) -> CliResult<()> {
+    let (command, args) = args
+        .split_first()
+        .ok_or_else(|| CliError::Usage("missing command".to_string()))?;
+
+    match command.as_str() {
+        "convert" => convert(args),
+        "info" => info(args),
+        "filter" => filter(args),
+        "transform" => transform(args),
+        "merge" => merge(args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(CliError::Usage(format!("unknown command '{}'", command))),
+    }
+}
+
+//------------------------------------------------------------------------------
+
+fn convert(args: &[String]) -> CliResult<()> {
+    match args {
+        [input, output] => save(Path::new(output), &load(Path::new(input))?),
+        _ => Err(CliError::Usage(
+            "convert expects <input> <output>".to_string(),
+        )),
+    }
+}
+
+fn info(args: &[String]) -> CliResult<()> {
+    if args.is_empty() {
+        return Err(CliError::Usage(
+            "info expects at least one <input>".to_string(),
+        ));
+    }
+
+    for input in args {
+        let path = Path::new(input);
+        let format = detect_format(path)?;
+        let data = load(path)?;
+
+        println!("file: {}", input);
+        println!("format: {}", format_name(format));
+        match &data {
+            Data::Mesh(mesh) => {
+                println!("vertices: {}", mesh.num_vertices());
+                println!("faces: {}", mesh.num_faces());
+            }
+            Data::Points(cloud) => println!("points: {}", cloud.len()),
+        }
+        match data.bounding_box_maybe() {
+            Some(bb) => {
+                println!("min: {}", str_point(&bb.min_p()));
+                println!("max: {}", str_point(&bb.max_p()));
+                println!("size: {} {} {}", bb.size_x(), bb.size_y(), bb.size_z());
+            }
+            None => println!("bounding box: none"),
+        }
+    }
+
+    Ok(())
+}
+
+fn filter(args: &[String]) -> CliResult<()> {
+    let (input, output, shape, values) = match args {
+        [input, output, shape, values @ ..] => (input, output, shape, parse_numbers(values)?),
+        _ => {
+            return Err(CliError::Usage(
+                "filter expects <input> <output> <box|sphere> <values>".to_string(),
+            ))
+        }
+    };
+
+    let data = load(Path::new(input))?;
+
+    let filtered = match (shape.as_str(), values.as_slice()) {
+        ("box", &[cx, cy, cz, sx, sy, sz]) => {
+            let filter = FilterBox3D::new(Box3D {
+                center: Point3D::new(cx, cy, cz),
+                size_x: positive(sx)?,
+                size_y: positive(sy)?,
+                size_z: positive(sz)?,
+            });
+            data.filtered(&filter)
+        }
+        ("sphere", &[cx, cy, cz, r]) => {
+            let filter = FilterSphere::new(Sphere {
+                center: Point3D::new(cx, cy, cz),
+                radius: positive(r)?,
+            });
+            data.filtered(&filter)
+        }
+        ("box", _) => {
+            return Err(CliError::Usage(
+                "box expects <cx> <cy> <cz> <size_x> <size_y> <size_z>".to_string(),
+            ))
+        }
+        ("sphere", _) => {
+            return Err(CliError::Usage(
+                "sphere expects <cx> <cy> <cz> <radius>".to_string(),
+            ))
+        }
+        _ => return Err(CliError::Usage(format!("unknown filter '{}'", shape))),
+    };
+
+    save(Path::new(output), &filtered)
+}
+
+fn transform(args: &[String]) -> CliResult<()> {
+    let (input, output, options) = match args {
+        [input, output, options @ ..] => (input, output, options),
+        _ => {
+            return Err(CliError::Usage(
+                "transform expects <input> <output> [options]".to_string(),
+            ))
+        }
+    };
+
+    let mut translation = Matrix4::identity();
+    let mut rotation = Matrix4::identity();
+    let mut scale = Matrix4::identity();
+
+    let mut rest = options;
+    while let Some((option, values)) = rest.split_first() {
+        if values.len() < 3 {
+            return Err(CliError::Usage(format!("{} expects <x> <y> <z>", option)));
+        }
+        let [x, y, z] = match parse_numbers(&values[..3])?.as_slice() {
+            &[x, y, z] => [x, y, z],
+            _ => unreachable!(), // length checked above
+        };
+
+        match option.as_str() {
+            "--translate" => translation = Matrix4::translation(x, y, z),
+            "--scale" => scale = Matrix4::scale(x, y, z),
+            "--rotate" => {
+                rotation = Matrix4::rotation(
+                    Rad(x.to_radians()),
+                    Rad(y.to_radians()),
+                    Rad(z.to_radians()),
+                )
+            }
+            _ => return Err(CliError::Usage(format!("unknown option '{}'", option))),
+        }
+
+        rest = &values[3..];
+    }
+
+    let mut data = load(Path::new(input))?;
+    data.transform(&(translation * rotation * scale));
+
+    save(Path::new(output), &data)
+}
+
+fn merge(args: &[String]) -> CliResult<()> {
+    let (output, inputs) = match args {
+        [output, inputs @ ..] if !inputs.is_empty() => (output, inputs),
+        _ => {
+            return Err(CliError::Usage(
+                "merge expects <output> <input>...".to_string(),
+            ))
+        }
+    };
+
+    let mut all = Vec::with_capacity(inputs.len());
+    for input in inputs {
+        all.push(load(Path::new(input))?);
+    }
+
+    let merged = if all.iter().all(|x| matches!(x, Data::Mesh(_))) {
+        let mut result = Mesh::default();
+        for data in &all {
+            if let Data::Mesh(mesh) = data {
+                append_mesh(&mut result, mesh, |_| true);
+            }
+        }
+        Data::Mesh(result)
+    } else {
+        let mut result = Cloud::new();
+        for data in &all {
+            result.append_ra(&data.points());
+        }
+        Data::Points(result)
+    };
+
+    save(Path::new(output), &merged)
+}
+
+//------------------------------------------------------------------------------
+
+/// Data loaded from a file, meshes are kept as such
+enum Data {
+    Mesh(Mesh),
+    Points(Cloud),
+}
+
+impl Data {
+    fn points(&self) -> Cloud {
+        match self {
+            Self::Mesh(mesh) => {
+                let mut cloud = Cloud::with_capacity(mesh.num_vertices());
+                for i in 0..mesh.num_vertices() {
+                    cloud.push(mesh.vertex(VId(i)).unwrap()); // safe since iterating num_vertices
+                }
+                cloud
+            }
+            Self::Points(cloud) => cloud.clone(),
+        }
+    }
+
+    fn bounding_box_maybe(&self) -> Option<BoundingBox3D> {
+        match self {
+            Self::Mesh(mesh) => mesh.bounding_box_maybe(),
+            Self::Points(cloud) => cloud.bounding_box_maybe(),
+        }
+    }
+
+    fn transform(&mut self, m: &Matrix4) {
+        match self {
+            Self::Mesh(mesh) => mesh.transform(m),
+            Self::Points(cloud) => cloud.transform(m),
+        }
+    }
+
+    /// Keeps allowed points, faces are kept if all of their vertices are allowed
+    fn filtered<F>(&self, filter: &F) -> Self
+    where
+        F: IsFilter<Point3D>,
+    {
+        match self {
+            Self::Mesh(mesh) => {
+                let mut result = Mesh::default();
+                append_mesh(&mut result, mesh, |p| filter.is_allowed(p));
+                Self::Mesh(result)
+            }
+            Self::Points(cloud) => {
+                let mut result = Cloud::new();
+                for i in 0..cloud.len() {
+                    if filter.is_allowed(&cloud[i]) {
+                        result.push(cloud[i].clone());
+                    }
+                }
+                Self::Points(result)
+            }
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Appends all vertices of source which match allowed and all faces consisting of those
+fn append_mesh<F>(target: &mut Mesh, source: &Mesh, allowed: F)
+where
+    F: Fn(&Point3D) -> bool,
+{
+    let n_vertices = source.num_vertices();
+    let mut new_ids = Vec::with_capacity(n_vertices);
+
+    for i in 0..n_vertices {
+        let p = source.vertex(VId(i)).unwrap(); // safe since iterating num_vertices
+        if allowed(&p) {
+            new_ids.push(Some(target.add_vertex(p)));
+        } else {
+            new_ids.push(None);
+        }
+    }
+
+    for i in 0..source.num_faces() {
+        let f = source.face_vertex_ids(FId(i)).unwrap(); // safe since iterating num_faces
+        if let (Some(a), Some(b), Some(c)) = (new_ids[f.a.0], new_ids[f.b.0], new_ids[f.c.0]) {
+            target.try_add_connection(a, b, c).unwrap(); // safe since ids were just added
+        }
+    }
+}
+
+fn load(path: &Path) -> CliResult<Data> {
+    if detect_format(path)?.is_mesh() {
+        let mut mesh = Mesh::default();
+        load_mesh_auto::<_, _, CHUNK_SIZE>(path, &mut mesh)?;
+        if mesh.num_faces() > 0 {
+            return Ok(Data::Mesh(mesh));
+        }
+    }
+
+    let mut cloud = Cloud::new();
+    load_points_auto::<_, _, CHUNK_SIZE>(path, &mut cloud)?;
+    Ok(Data::Points(cloud))
+}
+
+fn save(path: &Path, data: &Data) -> CliResult<()> {
+    let extension = path
+        .extension()
+        .and_then(|x| x.to_str())
+        .map(|x| x.to_lowercase())
+        .unwrap_or_default();
+
+    check_output(path, &extension, data)?;
+
+    let mut write = BufWriter::new(File::create(path).map_err(IOError::from)?);
+
+    match (extension.as_str(), data) {
+        ("ply", Data::Mesh(mesh)) => save_ply_binary(&mut write, mesh, &Precision::P32),
+        ("ply", _) => {
+            save_ply_points_binary(&mut write, &data.points(), &Precision::P32, None, None, &[])
+        }
+        ("stl", Data::Mesh(mesh)) => save_stl_binary(&mut write, mesh, b"rust-3d", 0),
+        ("obj", Data::Mesh(mesh)) => save_obj_mesh(&mut write, mesh, None, None, None),
+        ("obj", _) => save_obj_points(&mut write, &data.points(), None),
+        ("off", Data::Mesh(mesh)) => save_off_mesh(&mut write, mesh, None, None),
+        ("off", _) => save_off_points(&mut write, &data.points(), None, None),
+        ("gltf", Data::Mesh(mesh)) => save_gltf(&mut write, mesh, None, None),
+        ("glb", Data::Mesh(mesh)) => save_glb(&mut write, mesh, None, None),
+        ("xyz", _) => save_xyz(&mut write, &data.points(), " ", "\n"),
+        ("pcd", _) => save_pcd(&mut write, &data.points(), &PcdDataFormat::Binary),
+        ("las", _) => save_las(
+            &mut write,
+            &data.points(),
+            LasVersion::default(),
+            0,
+            None,
+            &[],
+        ),
+        _ => unreachable!(), // checked by check_output
+    }?;
+
+    write.flush().map_err(IOError::from)?;
+
+    Ok(())
+}
+
+/// Ensures the output format can be written before creating the file
+fn check_output(path: &Path, extension: &str, data: &Data) -> CliResult<()> {
+    match (extension, data) {
+        ("stl", Data::Points(_)) | ("gltf", Data::Points(_)) | ("glb", Data::Points(_)) => {
+            Err(CliError::Usage(format!(
+                "{} requires a mesh with faces as input",
+                path.display()
+            )))
+        }
+        ("ply", _)
+        | ("stl", _)
+        | ("obj", _)
+        | ("off", _)
+        | ("gltf", _)
+        | ("glb", _)
+        | ("xyz", _)
+        | ("pcd", _)
+        | ("las", _) => Ok(()),
+        _ => Err(CliError::Usage(format!(
+            "unsupported output format of {}",
+            path.display()
+        ))),
+    }
+}
+
+fn parse_numbers(values: &[String]) -> CliResult<Vec<f64>> {
+    values
+        .iter()
+        .map(|x| {
+            x.parse::<f64>()
+                .map_err(|_| CliError::Usage(format!("invalid number '{}'", x)))
+        })
+        .collect()
+}
+
+fn positive(x: f64) -> CliResult<Positive> {
+    Positive::new(x).ok_or_else(|| CliError::Usage(format!("{} must be positive", x)))
+}
+
+fn str_point(p: &Point3D) -> String {
+    format!("{} {} {}", p.x(), p.y(), p.z())
+}
+
+fn format_name(format: FileFormat) -> &'static str {
+    match format {
+        FileFormat::Ply => "ply",
+        FileFormat::StlAscii => "stl (ascii)",
+        FileFormat::StlBinary => "stl (binary)",
+        FileFormat::Obj => "obj",
+        FileFormat::Off => "off",
+        FileFormat::Gltf => "gltf",
+        FileFormat::Glb => "glb",
+        FileFormat::Las => "las",
+        FileFormat::E57 => "e57",
+        FileFormat::Pcd => "pcd",
+        FileFormat::Ptx => "ptx",
+        FileFormat::Pts => "pts",
+        FileFormat::Psl => "psl",
+        FileFormat::Xyz => "xyz",
+        FileFormat::Gcode => "gcode",
+    }
+}
+
+//------------------------------------------------------------------------------
+
+type CliResult<T> = std::result::Result<T, CliError>;
+
+/// Errors of the command line tool
+enum CliError {
+    Usage(String),
+    IO(IOError),
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            Self::IO(_) => 1,
+            Self::Usage(_) => 2,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Usage(x) => write!(f, "error: {}\n\n{}", x, USAGE),
+            Self::IO(x) => write!(f, "error: {}", x),
+        }
+    }
+}
+
+impl From<IOError> for CliError {
+    fn from(e: IOError) -> Self {
+        CliError::IO(e)
+    }
+}
@@ -136,13 +136,19 @@ pub fn sort_vec_3d_x<P>(xs: &mut Vec<P>)
 where
     P: Is3D,
 {
-    xs.sort_by(|a, b| {
-        a.x()
-            .partial_cmp(&b.x())
-            .or_else(|| a.y().partial_cmp(&b.y()))
-            .or_else(|| a.z().partial_cmp(&b.z()))
-            .unwrap_or(Ordering::Equal)
-    });
+    xs.sort_by(cmp_3d_x);
+}
+
+/// Helper function to compare two Is3D by x, as used by sort_vec_3d_x
+pub fn cmp_3d_x<P>(a: &P, b: &P) -> Ordering
+where
+    P: Is3D,
+{
+    a.x()
+        .partial_cmp(&b.x())
+        .or_else(|| a.y().partial_cmp(&b.y()))
+        .or_else(|| a.z().partial_cmp(&b.z()))
+        .unwrap_or(Ordering::Equal)
 }
 
 /// Helper function to sort a Vec of Is3D by y
@@ -150,13 +156,19 @@ pub fn sort_vec_3d_y<P>(xs: &mut Vec<P>)
 where
     P: Is3D,
 {
-    xs.sort_by(|a, b| {
-        a.y()
-            .partial_cmp(&b.y())
-            .or_else(|| a.z().partial_cmp(&b.z()))
-            .or_else(|| a.x().partial_cmp(&b.x()))
-            .unwrap_or(Ordering::Equal)
-    });
+    xs.sort_by(cmp_3d_y);
+}
+
+/// Helper function to compare two Is3D by y, as used by sort_vec_3d_y
+pub fn cmp_3d_y<P>(a: &P, b: &P) -> Ordering
+where
+    P: Is3D,
+{
+    a.y()
+        .partial_cmp(&b.y())
+        .or_else(|| a.z().partial_cmp(&b.z()))
+        .or_else(|| a.x().partial_cmp(&b.x()))
+        .unwrap_or(Ordering::Equal)
 }
 
 /// Helper function to sort a Vec of Is3D by z
@@ -164,13 +176,19 @@ pub fn sort_vec_3d_z<P>(xs: &mut Vec<P>)
 where
     P: Is3D,
 {
-    xs.sort_by(|a, b| {
-        a.z()
-            .partial_cmp(&b.z())
-            .or_else(|| a.x().partial_cmp(&b.x()))
-            .or_else(|| a.y().partial_cmp(&b.y()))
-            .unwrap_or(Ordering::Equal)
-    });
+    xs.sort_by(cmp_3d_z);
+}
+
+/// Helper function to compare two Is3D by z, as used by sort_vec_3d_z
+pub fn cmp_3d_z<P>(a: &P, b: &P) -> Ordering
+where
+    P: Is3D,
+{
+    a.z()
+        .partial_cmp(&b.z())
+        .or_else(|| a.x().partial_cmp(&b.x()))
+        .or_else(|| a.y().partial_cmp(&b.y()))
+        .unwrap_or(Ordering::Equal)
 }
 
 //@todo move to plane or use there
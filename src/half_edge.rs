@@ -261,8 +261,13 @@ where
 
 //------------------------------------------------------------------------------
 
-fn safe_append_at<IC>(vec: &mut Vec<IC>, capacity: usize, support: usize, i: usize, val: usize)
-where
+pub(crate) fn safe_append_at<IC>(
+    vec: &mut Vec<IC>,
+    capacity: usize,
+    support: usize,
+    i: usize,
+    val: usize,
+) where
     IC: IsIndexContainer,
 {
     if i >= vec.len() {
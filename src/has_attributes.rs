@@ -0,0 +1,42 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! HasVertexAttributes and HasFaceAttributes traits used for types which store per-element Attributes
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// HasVertexAttributes trait used for types which store Attributes per vertex / point
+pub trait HasVertexAttributes {
+    /// Should return the attributes of the vertices
+    fn vertex_attributes(&self) -> &Attributes;
+    /// Should return the mutable attributes of the vertices, with one element per vertex
+    fn vertex_attributes_mut(&mut self) -> &mut Attributes;
+}
+
+/// HasFaceAttributes trait used for types which store Attributes per face
+pub trait HasFaceAttributes {
+    /// Should return the attributes of the faces
+    fn face_attributes(&self) -> &Attributes;
+    /// Should return the mutable attributes of the faces, with one element per face
+    fn face_attributes_mut(&mut self) -> &mut Attributes;
+}
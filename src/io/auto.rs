@@ -0,0 +1,367 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for loading files of any supported format, detecting the format automatically
+
+use crate::*;
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use super::*;
+
+//------------------------------------------------------------------------------
+
+/// Detects the format of a file by its magic bytes, extension and content
+pub fn detect_format(path: &Path) -> IOResult<FileFormat> {
+    File::open(path)
+        .map_err(IOError::from)
+        .and_then(|x| detect(&mut BufReader::new(x), path))
+        .map_err(|e| e.in_file(path))
+}
+
+/// Loads an IsMesh3D from a file of any mesh format, returning the detected format
+/// .ply files with polygonal faces are triangulated as fan
+pub fn load_mesh_auto<EM, P, const CHUNK_SIZE: usize>(
+    path: &Path,
+    mesh: &mut EM,
+) -> IOResult<FileFormat>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3>,
+    P: IsBuildable3D + IsMatrix4Transformable + Clone + Default,
+{
+    load_mesh::<_, _, CHUNK_SIZE>(path, mesh).map_err(|e| e.in_file(path))
+}
+
+/// Loads points from a file of any format into IsPushable<Is3D>, returning the detected format
+/// Meshes are loaded as their vertices, .stl files as three points per face
+pub fn load_points_auto<IP, P, const CHUNK_SIZE: usize>(
+    path: &Path,
+    ip: &mut IP,
+) -> IOResult<FileFormat>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+{
+    load_points::<_, _, CHUNK_SIZE>(path, ip).map_err(|e| e.in_file(path))
+}
+
+//------------------------------------------------------------------------------
+
+fn load_mesh<EM, P, const CHUNK_SIZE: usize>(path: &Path, mesh: &mut EM) -> IOResult<FileFormat>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3>,
+    P: IsBuildable3D + IsMatrix4Transformable + Clone + Default,
+{
+    let mut read = BufReader::new(File::open(path)?);
+    let format = detect(&mut read, path)?;
+
+    match format {
+        FileFormat::Ply => load_ply_mesh::<_, _, _, CHUNK_SIZE>(read, mesh),
+        FileFormat::StlAscii | FileFormat::StlBinary => {
+            load_stl_mesh_unique::<_, _, Point3D, _, _, CHUNK_SIZE>(
+                read,
+                stl_format(format),
+                mesh,
+                &mut Vec::new(),
+            )
+        }
+        FileFormat::Obj => load_obj_mesh::<_, _, _, CHUNK_SIZE>(read, mesh),
+        FileFormat::Off => load_off_mesh::<_, _, _, CHUNK_SIZE>(read, mesh),
+        FileFormat::Gltf => load_gltf::<_, _, _, CHUNK_SIZE>(read, folder(path), mesh),
+        FileFormat::Glb => load_glb::<_, _, _, CHUNK_SIZE>(read, folder(path), mesh),
+        _ => Err(IOError::NoMeshFormat(format)),
+    }?;
+
+    Ok(format)
+}
+
+fn load_points<IP, P, const CHUNK_SIZE: usize>(path: &Path, ip: &mut IP) -> IOResult<FileFormat>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+{
+    let mut read = BufReader::new(File::open(path)?);
+    let format = detect(&mut read, path)?;
+
+    match format {
+        FileFormat::Ply => load_ply_points::<_, _, _, CHUNK_SIZE>(read, ip),
+        FileFormat::StlAscii | FileFormat::StlBinary => {
+            load_stl_triplets::<_, _, Point3D, _, _, CHUNK_SIZE>(
+                read,
+                stl_format(format),
+                ip,
+                &mut Vec::new(),
+            )
+        }
+        FileFormat::Obj => load_obj_points::<_, _, _, CHUNK_SIZE>(read, ip),
+        FileFormat::Off => load_off_points::<_, _, _, CHUNK_SIZE>(read, ip),
+        FileFormat::Gltf | FileFormat::Glb => {
+            let iterator = if format == FileFormat::Gltf {
+                GltfIterator::<P, _, CHUNK_SIZE>::new_gltf(read, folder(path))
+            } else {
+                GltfIterator::<P, _, CHUNK_SIZE>::new_glb(read, folder(path))
+            }?;
+
+            for chunk in iterator {
+                for x in chunk? {
+                    match x {
+                        FaceDataReserve::Data(p) => ip.push(p),
+                        FaceDataReserve::ReserveDataFaces(n, _) => ip.reserve(n),
+                        FaceDataReserve::ReserveDataFacesExact(n, _) => ip.reserve_exact(n),
+                        FaceDataReserve::Face(_) => (),
+                    }
+                }
+            }
+            Ok(())
+        }
+        FileFormat::Las => load_las::<_, _, _, CHUNK_SIZE>(read, ip),
+        FileFormat::E57 => load_e57::<_, _, _, CHUNK_SIZE>(read, ip),
+        FileFormat::Pcd => load_pcd::<_, _, _, CHUNK_SIZE>(read, ip),
+        FileFormat::Ptx => load_ptx::<_, _, _, CHUNK_SIZE>(read, ip),
+        FileFormat::Pts => load_pts::<_, _, _, CHUNK_SIZE>(read, ip),
+        FileFormat::Psl => load_psl::<_, _, _, CHUNK_SIZE>(read, ip),
+        FileFormat::Xyz => load_xyz::<_, _, _, CHUNK_SIZE>(read, ip),
+        FileFormat::Gcode => load_gcode_points::<_, _, _, CHUNK_SIZE>(read, ip),
+    }?;
+
+    Ok(format)
+}
+
+//------------------------------------------------------------------------------
+
+/// Number of bytes used for sniffing
+const HEAD_SIZE: usize = 1024;
+
+/// Detects the format, leaving read at its start
+fn detect<R>(read: &mut R, path: &Path) -> IOResult<FileFormat>
+where
+    R: BufRead + Seek,
+{
+    let length = read.seek(SeekFrom::End(0))?;
+    read.seek(SeekFrom::Start(0))?;
+
+    let mut head = Vec::with_capacity(HEAD_SIZE);
+    (&mut *read).take(HEAD_SIZE as u64).read_to_end(&mut head)?;
+    read.seek(SeekFrom::Start(0))?;
+
+    let extension = path
+        .extension()
+        .and_then(|x| x.to_str())
+        .map(|x| x.to_ascii_lowercase());
+
+    if let Some(format) = from_magic(&head) {
+        return Ok(format);
+    }
+
+    // binary .stl files may also start with 'solid'
+    if is_ascii_stl(&head) {
+        return Ok(FileFormat::StlAscii);
+    }
+
+    if is_binary_stl(&head, length) {
+        return Ok(FileFormat::StlBinary);
+    }
+
+    if let Some(format) = extension.as_deref().and_then(from_extension) {
+        return Ok(format);
+    }
+
+    from_content(&head).ok_or(IOError::UnknownFileFormat)
+}
+
+fn from_magic(head: &[u8]) -> Option<FileFormat> {
+    if ply::starts_with_header(head) {
+        Some(FileFormat::Ply)
+    } else if head.len() >= 4
+        && u32::from_le_bytes([head[0], head[1], head[2], head[3]]) == gltf::VALID_MAGIC
+    {
+        Some(FileFormat::Glb)
+    } else if head.starts_with(b"LASF") {
+        Some(FileFormat::Las)
+    } else if head.starts_with(b"ASTM-E57") {
+        Some(FileFormat::E57)
+    } else {
+        None
+    }
+}
+
+/// ASCII .stl files start with 'solid', followed by a complete first facet or the end of the solid
+/// The facet is validated by stl::is_ascii_deep on the head, which rejects solids without any facet,
+/// so these are accepted by their 'endsolid' line instead
+fn is_ascii_stl(head: &[u8]) -> bool {
+    if !head.starts_with(b"solid") {
+        return false;
+    }
+
+    stl::is_ascii_deep::<_, 1>(head)
+        || head
+            .split(|x| *x == b'\n')
+            .skip(1)
+            .map(trim_start_whitespace)
+            .find(|x| !x.is_empty())
+            .is_some_and(|x| x.starts_with(b"endsolid"))
+}
+
+/// Binary .stl files consist of an 80 byte header, the triangle count and 50 bytes per triangle
+fn is_binary_stl(head: &[u8], length: u64) -> bool {
+    if head.len() < 84 {
+        return false;
+    }
+    let n_triangles = u32::from_le_bytes([head[80], head[81], head[82], head[83]]) as u64;
+    length == 84 + 50 * n_triangles
+}
+
+fn from_extension(extension: &str) -> Option<FileFormat> {
+    match extension {
+        "ply" => Some(FileFormat::Ply),
+        "stl" => Some(FileFormat::StlBinary),
+        "obj" => Some(FileFormat::Obj),
+        "off" => Some(FileFormat::Off),
+        "gltf" => Some(FileFormat::Gltf),
+        "glb" => Some(FileFormat::Glb),
+        "las" | "laz" => Some(FileFormat::Las),
+        "e57" => Some(FileFormat::E57),
+        "pcd" => Some(FileFormat::Pcd),
+        "ptx" => Some(FileFormat::Ptx),
+        "pts" => Some(FileFormat::Pts),
+        "psl" => Some(FileFormat::Psl),
+        "xyz" | "csv" => Some(FileFormat::Xyz),
+        "gcode" | "gco" | "nc" => Some(FileFormat::Gcode),
+        _ => None,
+    }
+}
+
+/// Sniffs the first lines of text formats
+fn from_content(head: &[u8]) -> Option<FileFormat> {
+    if !head.is_ascii() {
+        return None;
+    }
+
+    let trimmed = trim_start_whitespace(head);
+    if trimmed.starts_with(b"{") {
+        return if contains(head, b"\"asset\"") {
+            Some(FileFormat::Gltf)
+        } else {
+            None
+        };
+    }
+    if head.starts_with(b"# .PCD") {
+        return Some(FileFormat::Pcd);
+    }
+
+    // A full head might end within a line, which is therefore ignored
+    let complete = match head.iter().rposition(|x| *x == b'\n') {
+        Some(end) if head.len() == HEAD_SIZE => &head[..end],
+        _ => head,
+    };
+    let mut lines = complete
+        .split(|x| *x == b'\n')
+        .map(trim_start_whitespace)
+        .filter(|x| !x.is_empty());
+
+    let first = lines.clone().find(|x| !x.starts_with(b"#"))?;
+    let keyword = first
+        .split(|x| x.is_ascii_whitespace())
+        .next()
+        .unwrap_or(first);
+
+    if keyword.ends_with(b"OFF") {
+        return Some(FileFormat::Off);
+    }
+    if keyword == b"VERSION" || keyword == b"FIELDS" {
+        return Some(FileFormat::Pcd);
+    }
+    if [
+        &b"v"[..],
+        b"vn",
+        b"vt",
+        b"f",
+        b"o",
+        b"g",
+        b"mtllib",
+        b"usemtl",
+    ]
+    .contains(&keyword)
+    {
+        return Some(FileFormat::Obj);
+    }
+
+    let mut commands = lines
+        .clone()
+        .filter(|x| !x.starts_with(b";"))
+        .take(8)
+        .peekable();
+    let is_command =
+        |x: &[u8]| x.len() >= 2 && matches!(x[0], b'G' | b'M' | b'N') && x[1].is_ascii_digit();
+    if commands.peek().is_some() && commands.all(is_command) {
+        return Some(FileFormat::Gcode);
+    }
+
+    let counts: Vec<usize> = lines.by_ref().take(3).map(count_numbers).collect();
+    match counts.as_slice() {
+        [1, 1, 3, ..] => Some(FileFormat::Ptx),
+        [1, n, ..] if *n >= 4 => Some(FileFormat::Pts),
+        [n, ..] if *n >= 3 => Some(FileFormat::Xyz),
+        _ => None,
+    }
+}
+
+/// Number of values in a line, 0 if any word isn't a number
+fn count_numbers(line: &[u8]) -> usize {
+    let mut n = 0;
+    for word in line
+        .split(|x| x.is_ascii_whitespace() || *x == b',' || *x == b';')
+        .filter(|x| !x.is_empty())
+    {
+        if super::utils::from_ascii::<f64>(word).is_none() {
+            return 0;
+        }
+        n += 1;
+    }
+    n
+}
+
+fn trim_start_whitespace(text: &[u8]) -> &[u8] {
+    let start = text
+        .iter()
+        .position(|x| !x.is_ascii_whitespace())
+        .unwrap_or(text.len());
+    &text[start..]
+}
+
+fn contains(text: &[u8], pattern: &[u8]) -> bool {
+    text.windows(pattern.len()).any(|x| x == pattern)
+}
+
+fn stl_format(format: FileFormat) -> StlFormat {
+    match format {
+        FileFormat::StlAscii => StlFormat::Ascii,
+        _ => StlFormat::Binary,
+    }
+}
+
+fn folder(path: &Path) -> std::path::PathBuf {
+    path.parent().map(|x| x.to_path_buf()).unwrap_or_default()
+}
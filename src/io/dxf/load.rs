@@ -0,0 +1,352 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for load functions of the dxf file format
+
+use crate::*;
+
+use std::{f64::consts::PI, io::BufRead};
+
+use super::{
+    super::{types::*, utils::*},
+    types::*,
+};
+
+//------------------------------------------------------------------------------
+
+/// Loads the supported entities of the ENTITIES section of an ASCII .dxf file
+/// Bulge arcs of LWPOLYLINEs and POLYLINEs are tessellated with segments spanning at most max_angle
+/// max_angle must be at least MIN_MAX_ANGLE
+pub fn load_dxf<P, R>(read: R, max_angle: Rad) -> IOResult<Vec<DxfEntity<P>>>
+where
+    P: IsBuildable2D + Clone,
+    R: BufRead,
+{
+    if max_angle.0.is_nan() || max_angle.0 < MIN_MAX_ANGLE {
+        return Err(IOError::Dxf(DxfError::MaxAngle));
+    }
+
+    let mut pairs = PairReader::new(read);
+    let mut entities = Vec::new();
+    let mut in_entities = false;
+    let mut current: Option<RawEntity> = None;
+
+    while let Some((code, value)) = pairs.next()? {
+        if code != 0 {
+            if let Some(raw) = &mut current {
+                raw.values.push((code, value));
+            } else if code == 2 && pairs.last_was_section && value == "ENTITIES" {
+                in_entities = true;
+            }
+            pairs.last_was_section = false;
+            continue;
+        }
+
+        // The VERTEX entities of a POLYLINE are collected until its SEQEND
+        if let Some(raw) = &mut current {
+            if raw.kind == "POLYLINE" && value == "VERTEX" {
+                raw.values.push((code, value));
+                continue;
+            }
+        }
+
+        if let Some(raw) = current.take() {
+            if let Some(entity) = raw.build(max_angle)? {
+                entities.push(entity);
+            }
+        }
+
+        pairs.last_was_section = value == "SECTION";
+
+        match value.as_str() {
+            "ENDSEC" => in_entities = false,
+            "EOF" => break,
+            _ if in_entities => current = Some(RawEntity::new(value, pairs.line)),
+            _ => (),
+        }
+    }
+
+    if let Some(raw) = current.take() {
+        if let Some(entity) = raw.build(max_angle)? {
+            entities.push(entity);
+        }
+    }
+
+    Ok(entities)
+}
+
+/// Lower limit for the max_angle of load_dxf
+pub const MIN_MAX_ANGLE: f64 = 1e-3;
+
+//------------------------------------------------------------------------------
+
+/// Reads pairs of group code and value lines
+struct PairReader<R>
+where
+    R: BufRead,
+{
+    read: R,
+    line_buffer: Vec<u8>,
+    line: usize,
+    /// Whether the previous pair started a section
+    last_was_section: bool,
+}
+
+impl<R> PairReader<R>
+where
+    R: BufRead,
+{
+    fn new(read: R) -> Self {
+        Self {
+            read,
+            line_buffer: Vec::new(),
+            line: 0,
+            last_was_section: false,
+        }
+    }
+
+    fn next(&mut self) -> IOResult<Option<(i32, String)>> {
+        let code = match fetch_line_or_none(&mut self.read, &mut self.line_buffer)? {
+            Some(line) => from_ascii::<i32>(trim_start(line)),
+            None => return Ok(None),
+        };
+        self.line += 1;
+        let code = code.ok_or(IOError::Dxf(DxfError::GroupCode(self.line)))?;
+
+        let value = fetch_line(&mut self.read, &mut self.line_buffer)?;
+        self.line += 1;
+        let value = String::from_utf8_lossy(trim_start(value)).to_string();
+
+        Ok(Some((code, value)))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Group codes and values of an entity
+/// The VERTEX entities of POLYLINEs are appended, each starting with a pair of group code 0
+struct RawEntity {
+    kind: String,
+    line: usize,
+    values: Vec<(i32, String)>,
+}
+
+impl RawEntity {
+    fn new(kind: String, line: usize) -> Self {
+        Self {
+            kind,
+            line,
+            values: Vec::new(),
+        }
+    }
+
+    fn build<P>(&self, max_angle: Rad) -> IOResult<Option<DxfEntity<P>>>
+    where
+        P: IsBuildable2D + Clone,
+    {
+        let layer = self.string(8).unwrap_or("0");
+        // Entities with an extrusion direction of (0, 0, -1) are mirrored at the y axis
+        let mirror = self.number(230)?.unwrap_or(1.0) < 0.0;
+        let x = |x: f64| if mirror { -x } else { x };
+
+        let geometry = match self.kind.as_str() {
+            "POINT" => DxfGeometry::Point(P::new(self.required(10)?, self.required(20)?)),
+            "LINE" => DxfGeometry::Line(LineSegment2D {
+                start: Point2D::new(self.required(10)?, self.required(20)?),
+                end: Point2D::new(self.required(11)?, self.required(21)?),
+            }),
+            "CIRCLE" => DxfGeometry::Circle(Circle {
+                center: Point2D::new(x(self.required(10)?), self.required(20)?),
+                radius: self.radius()?,
+            }),
+            "ARC" => {
+                let circle = Circle {
+                    center: Point2D::new(x(self.required(10)?), self.required(20)?),
+                    radius: self.radius()?,
+                };
+                let start = self.required(50)?.to_radians();
+                let end = self.required(51)?.to_radians();
+                // Mirroring reverses the direction, swapping start and end
+                let (start, end) = if mirror {
+                    (PI - end, PI - start)
+                } else {
+                    (start, end)
+                };
+                DxfGeometry::Arc {
+                    circle,
+                    start: Rad(start),
+                    end: Rad(end),
+                }
+            }
+            "LWPOLYLINE" | "POLYLINE" => {
+                let flags = self.number(70)?.unwrap_or(0.0) as i32;
+                // Polygon and polyface meshes aren't supported
+                if flags & (16 | 64) != 0 {
+                    return Ok(None);
+                }
+                let closed = flags & 1 == 1;
+                let mut vertices: Vec<([f64; 2], f64)> = Vec::new();
+
+                // The position of a POLYLINE itself only defines its elevation
+                let vertex_values = if self.kind == "POLYLINE" {
+                    &self.values[self.own_values().len()..]
+                } else {
+                    &self.values[..]
+                };
+
+                for (code, value) in vertex_values {
+                    match code {
+                        10 => vertices.push(([x(self.parse(value)?), 0.0], 0.0)),
+                        20 | 42 => {
+                            let v = self.parse(value)?;
+                            let last = vertices.last_mut().ok_or(self.error())?;
+                            if *code == 20 {
+                                last.0[1] = v
+                            } else if mirror {
+                                last.1 = -v
+                            } else {
+                                last.1 = v
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+
+                let n = vertices.len();
+                let n_segments = if closed { n } else { n.saturating_sub(1) };
+                let mut pc = PointCloud2D::with_capacity(n);
+
+                for (i, ([vx, vy], bulge)) in vertices.iter().enumerate() {
+                    pc.push(P::new(*vx, *vy));
+                    if i < n_segments && *bulge != 0.0 {
+                        let ([nx, ny], _) = vertices[(i + 1) % n];
+                        for p in tessellate_bulge::<P>([*vx, *vy], [nx, ny], *bulge, max_angle) {
+                            pc.push(p);
+                        }
+                    }
+                }
+
+                if closed {
+                    DxfGeometry::Polygon(Polygon2D::from(pc))
+                } else {
+                    DxfGeometry::Polyline(pc)
+                }
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(DxfEntity::new(layer, geometry)))
+    }
+
+    /// The values of the entity itself, excluding appended VERTEX entities
+    fn own_values(&self) -> &[(i32, String)] {
+        let end = self
+            .values
+            .iter()
+            .position(|(code, _)| *code == 0)
+            .unwrap_or(self.values.len());
+        &self.values[..end]
+    }
+
+    fn string(&self, code: i32) -> Option<&str> {
+        self.own_values()
+            .iter()
+            .find(|(c, _)| *c == code)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn number(&self, code: i32) -> IOResult<Option<f64>> {
+        match self.string(code) {
+            Some(value) => Ok(Some(self.parse(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    fn required(&self, code: i32) -> IOResult<f64> {
+        self.number(code)?.ok_or_else(|| self.error())
+    }
+
+    fn radius(&self) -> IOResult<Positive> {
+        Positive::new(self.required(40)?).ok_or_else(|| self.error())
+    }
+
+    fn parse(&self, value: &str) -> IOResult<f64> {
+        value.trim().parse().map_err(|_| self.error())
+    }
+
+    fn error(&self) -> IOError {
+        IOError::Dxf(DxfError::Entity(self.line))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Points strictly between start and end on the arc defined by bulge
+/// bulge is the tangent of a quarter of the arc's angle, positive for counter-clockwise arcs
+fn tessellate_bulge<P>(start: [f64; 2], end: [f64; 2], bulge: f64, max_angle: Rad) -> Vec<P>
+where
+    P: IsBuildable2D + Clone,
+{
+    let [dx, dy] = [end[0] - start[0], end[1] - start[1]];
+    let chord = (dx * dx + dy * dy).sqrt();
+    let sweep = 4.0 * bulge.atan();
+    let n_segments = (sweep.abs() / max_angle.0).ceil() as usize;
+
+    if chord == 0.0 || n_segments < 2 {
+        return Vec::new();
+    }
+
+    // Center is offset from the chord's middle along its left normal
+    let offset = (1.0 - bulge * bulge) / (4.0 * bulge);
+    let center = P::new(
+        start[0] + dx / 2.0 - dy * offset,
+        start[1] + dy / 2.0 + dx * offset,
+    );
+    let radius = chord * (1.0 + bulge * bulge) / (4.0 * bulge.abs());
+    let angle_start = (start[1] - center.y()).atan2(start[0] - center.x());
+
+    // arc only runs counter-clockwise, clockwise arcs are created from their end and reversed
+    let from = if sweep > 0.0 {
+        angle_start
+    } else {
+        angle_start + sweep
+    };
+
+    let diameter = match Positive::new(2.0 * radius) {
+        Some(x) => x,
+        None => return Vec::new(),
+    };
+
+    let mut points: Vec<P> = arc(
+        &center,
+        n_segments + 1,
+        diameter,
+        Rad(from),
+        Rad(from + sweep.abs()),
+    )
+    .into();
+
+    if sweep < 0.0 {
+        points.reverse();
+    }
+
+    points[1..n_segments].to_vec()
+}
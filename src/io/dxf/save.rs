@@ -0,0 +1,187 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for save functions of the dxf file format
+
+use std::io::Write;
+
+use super::{super::types::*, types::*};
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// Saves entities as ASCII dxf of version R12 (AC1009), creating a layer table for all used layers
+/// Polylines and polygons are written as POLYLINE entities, since LWPOLYLINE requires R2000
+pub fn save_dxf<P, W>(write: &mut W, entities: &[DxfEntity<P>]) -> IOResult<()>
+where
+    P: IsBuildable2D + Clone,
+    W: Write,
+{
+    let mut layers: Vec<&str> = vec!["0"];
+    for entity in entities {
+        if !layers.contains(&entity.layer.as_str()) {
+            layers.push(&entity.layer);
+        }
+    }
+
+    let mut writer = DxfWriter { write };
+
+    writer.pair(999, "Created by rust-3d")?;
+
+    writer.pair(0, "SECTION")?;
+    writer.pair(2, "HEADER")?;
+    writer.pair(9, "$ACADVER")?;
+    writer.pair(1, "AC1009")?;
+    writer.pair(0, "ENDSEC")?;
+
+    writer.pair(0, "SECTION")?;
+    writer.pair(2, "TABLES")?;
+
+    // The line type referenced by all layers
+    writer.pair(0, "TABLE")?;
+    writer.pair(2, "LTYPE")?;
+    writer.pair(70, "1")?;
+    writer.pair(0, "LTYPE")?;
+    writer.pair(2, "CONTINUOUS")?;
+    writer.pair(70, "0")?;
+    writer.pair(3, "Solid line")?;
+    writer.pair(72, "65")?;
+    writer.pair(73, "0")?;
+    writer.pair(40, "0.0")?;
+    writer.pair(0, "ENDTAB")?;
+
+    writer.pair(0, "TABLE")?;
+    writer.pair(2, "LAYER")?;
+    writer.pair(70, &layers.len().to_string())?;
+    for layer in &layers {
+        writer.pair(0, "LAYER")?;
+        writer.pair(2, layer)?;
+        writer.pair(70, "0")?;
+        writer.pair(62, "7")?;
+        writer.pair(6, "CONTINUOUS")?;
+    }
+    writer.pair(0, "ENDTAB")?;
+    writer.pair(0, "ENDSEC")?;
+
+    writer.pair(0, "SECTION")?;
+    writer.pair(2, "ENTITIES")?;
+    for entity in entities {
+        writer.entity(entity)?;
+    }
+    writer.pair(0, "ENDSEC")?;
+
+    writer.pair(0, "EOF")?;
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+struct DxfWriter<'a, W>
+where
+    W: Write,
+{
+    write: &'a mut W,
+}
+
+impl<'a, W> DxfWriter<'a, W>
+where
+    W: Write,
+{
+    fn entity<P>(&mut self, entity: &DxfEntity<P>) -> IOResult<()>
+    where
+        P: IsBuildable2D + Clone,
+    {
+        match &entity.geometry {
+            DxfGeometry::Point(p) => {
+                self.start("POINT", &entity.layer)?;
+                self.position(10, p)?;
+            }
+            DxfGeometry::Line(line) => {
+                self.start("LINE", &entity.layer)?;
+                self.position(10, &line.start)?;
+                self.position(11, &line.end)?;
+            }
+            DxfGeometry::Polyline(pc) => {
+                self.polyline(&entity.layer, &pc.data, false)?;
+            }
+            DxfGeometry::Polygon(pg) => {
+                let vertices: Vec<P> = (0..pg.num_vertices())
+                    .filter_map(|i| pg.vertex(VId(i)))
+                    .collect();
+                self.polyline(&entity.layer, &vertices, true)?;
+            }
+            DxfGeometry::Circle(circle) => {
+                self.start("CIRCLE", &entity.layer)?;
+                self.position(10, &circle.center)?;
+                self.pair(40, &circle.radius.to_string())?;
+            }
+            DxfGeometry::Arc { circle, start, end } => {
+                self.start("ARC", &entity.layer)?;
+                self.position(10, &circle.center)?;
+                self.pair(40, &circle.radius.to_string())?;
+                self.pair(50, &start.0.to_degrees().to_string())?;
+                self.pair(51, &end.0.to_degrees().to_string())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn polyline<P>(&mut self, layer: &str, vertices: &[P], closed: bool) -> IOResult<()>
+    where
+        P: Is2D,
+    {
+        self.start("POLYLINE", layer)?;
+        // Vertices follow, the position only defines the elevation
+        self.pair(66, "1")?;
+        self.pair(10, "0.0")?;
+        self.pair(20, "0.0")?;
+        self.pair(30, "0.0")?;
+        self.pair(70, if closed { "1" } else { "0" })?;
+        for v in vertices {
+            self.start("VERTEX", layer)?;
+            self.position(10, v)?;
+        }
+        self.start("SEQEND", layer)
+    }
+
+    fn start(&mut self, kind: &str, layer: &str) -> IOResult<()> {
+        self.pair(0, kind)?;
+        self.pair(8, layer)
+    }
+
+    /// Writes x with the code and y with code + 10
+    fn position<P>(&mut self, code: u16, p: &P) -> IOResult<()>
+    where
+        P: Is2D,
+    {
+        self.pair(code, &p.x().to_string())?;
+        self.pair(code + 10, &p.y().to_string())
+    }
+
+    fn pair(&mut self, code: u16, value: &str) -> IOResult<()> {
+        self.write
+            .write_all(format!("{:>3}\n{}\n", code, value).as_bytes())?;
+        Ok(())
+    }
+}
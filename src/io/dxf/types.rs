@@ -0,0 +1,72 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for types of the dxf file format
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// Geometry of a dxf entity
+#[derive(Debug, Clone, PartialEq)]
+pub enum DxfGeometry<P>
+where
+    P: IsBuildable2D,
+{
+    /// POINT
+    Point(P),
+    /// LINE
+    Line(LineSegment2D),
+    /// Open LWPOLYLINE or POLYLINE, bulge arcs are tessellated
+    Polyline(PointCloud2D<P>),
+    /// Closed LWPOLYLINE or POLYLINE, bulge arcs are tessellated
+    Polygon(Polygon2D<P>),
+    /// CIRCLE
+    Circle(Circle),
+    /// ARC, running counter-clockwise from start to end
+    Arc {
+        circle: Circle,
+        start: Rad,
+        end: Rad,
+    },
+}
+
+/// A dxf entity together with the name of its layer
+#[derive(Debug, Clone, PartialEq)]
+pub struct DxfEntity<P>
+where
+    P: IsBuildable2D,
+{
+    pub layer: String,
+    pub geometry: DxfGeometry<P>,
+}
+
+impl<P> DxfEntity<P>
+where
+    P: IsBuildable2D,
+{
+    pub fn new(layer: &str, geometry: DxfGeometry<P>) -> Self {
+        Self {
+            layer: layer.to_string(),
+            geometry,
+        }
+    }
+}
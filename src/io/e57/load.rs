@@ -0,0 +1,674 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for load functions of the e57 file format
+//! Points are transformed into world coordinates by the pose of their scan
+
+use crate::*;
+
+use std::{
+    convert::TryInto,
+    io::{Read, Seek, SeekFrom},
+    iter::FusedIterator,
+    marker::PhantomData,
+};
+
+use super::{super::types::*, paged::*, types::*, xml::*};
+
+//------------------------------------------------------------------------------
+
+/// Iterator to incrementally load a .e57 file
+pub struct E57Iterator<P, R, const CHUNK_SIZE: usize>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+    inner: E57PointIterator<P, R, CHUNK_SIZE>,
+}
+
+impl<P, R, const CHUNK_SIZE: usize> E57Iterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+    pub fn new(read: R) -> IOResult<Self> {
+        Ok(Self {
+            inner: E57PointIterator::new(read)?,
+        })
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> Iterator for E57Iterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+    type Item = IOResult<StackVec<DataReserve<P>, CHUNK_SIZE>>;
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|chunk| {
+            let mut result = StackVec::default();
+            for x in chunk? {
+                let x = match x {
+                    DataReserve::Data((p, _)) => DataReserve::Data(p),
+                    DataReserve::Reserve(x) => DataReserve::Reserve(x),
+                    DataReserve::ReserveExact(x) => DataReserve::ReserveExact(x),
+                };
+                result.push(x).unwrap(); // unwrap safe since both have the same size
+            }
+            Ok(result)
+        })
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for E57Iterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+}
+
+//------------------------------------------------------------------------------
+
+/// Iterator to incrementally load a .e57 file together with the attributes of each point
+pub struct E57PointIterator<P, R, const CHUNK_SIZE: usize>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+    read: PagedReader<R>,
+    scans: Vec<Scan>,
+    i_scan: usize,
+    decoder: Option<ScanDecoder>,
+    values: Vec<f64>,
+    is_done: bool,
+    phantom_p: PhantomData<P>,
+}
+
+impl<P, R, const CHUNK_SIZE: usize> E57PointIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+    /// Reads the header and the XML section, points are read while iterating
+    pub fn new(mut read: R) -> IOResult<Self> {
+        let header = read_file_header(&mut read)
+            .map_err(|e| e.context(IOElement::Header, IOLocation::Byte(0)))?;
+
+        let mut read = PagedReader::new(read, header.page_size);
+
+        let mut xml = vec![0u8; header.xml_logical_length as usize];
+        read.read_logical(read.logical_offset(header.xml_physical_offset), &mut xml)?;
+
+        let root = std::str::from_utf8(&xml)
+            .ok()
+            .and_then(XmlElement::parse)
+            .ok_or(IOError::E57(E57Error::Xml))?;
+
+        Ok(Self {
+            read,
+            scans: parse_scans(&root)?,
+            i_scan: 0,
+            decoder: None,
+            values: Vec::new(),
+            is_done: false,
+            phantom_p: PhantomData,
+        })
+    }
+
+    #[inline(always)]
+    fn fetch_one(&mut self) -> IOResult<Option<(P, E57Point)>> {
+        let scan = &self.scans[self.i_scan];
+        // unwrap safe since this is only called with an active decoder
+        let decoder = self.decoder.as_mut().unwrap();
+        decoder.next_record(&mut self.read, scan, &mut self.values)?;
+
+        Ok(make_point(&self.values, scan, self.i_scan))
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> Iterator for E57PointIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+    type Item = IOResult<StackVec<DataReserve<(P, E57Point)>, CHUNK_SIZE>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+
+        let mut chunk = StackVec::default();
+
+        while chunk.has_space() {
+            match &self.decoder {
+                None => {
+                    if self.i_scan >= self.scans.len() {
+                        self.is_done = true;
+                        break;
+                    }
+
+                    match ScanDecoder::new(&mut self.read, &self.scans[self.i_scan]) {
+                        Ok(decoder) => {
+                            // unwrap safe since we only call this if chunk.has_space()
+                            chunk
+                                .push(DataReserve::Reserve(decoder.n_reserve()))
+                                .unwrap();
+                            self.decoder = Some(decoder);
+                        }
+                        Err(e) => {
+                            self.is_done = true;
+                            return Some(Err(e));
+                        }
+                    }
+                }
+                Some(decoder) => {
+                    if decoder.n_remaining == 0 {
+                        self.decoder = None;
+                        self.i_scan += 1;
+                        continue;
+                    }
+
+                    let index =
+                        (self.scans[self.i_scan].record_count - decoder.n_remaining) as usize;
+
+                    match self.fetch_one() {
+                        Ok(Some(x)) => {
+                            // unwrap safe since we only call this if chunk.has_space()
+                            chunk.push(DataReserve::Data(x)).unwrap();
+                        }
+                        Ok(None) => (),
+                        Err(e) => {
+                            self.is_done = true;
+                            return Some(
+                                Err(e.context(IOElement::Point, IOLocation::Index(index))),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+
+        if chunk.has_data() {
+            Some(Ok(chunk))
+        } else {
+            None
+        }
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for E57PointIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+}
+
+//------------------------------------------------------------------------------
+
+/// Loads points from .e57 file into IsPushable<Is3D>
+pub fn load_e57<IP, P, R, const CHUNK_SIZE: usize>(read: R, ip: &mut IP) -> IOResult<()>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+    let iterator = E57Iterator::<_, _, CHUNK_SIZE>::new(read)?;
+
+    for chunk in iterator {
+        for x in chunk? {
+            match x {
+                DataReserve::Data(x) => ip.push(x),
+                DataReserve::Reserve(x) => ip.reserve(x),
+                DataReserve::ReserveExact(x) => ip.reserve_exact(x),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads points from .e57 file into IsPushable<Is3D> and their attributes into IsPushable<E57Point>
+pub fn load_e57_points<IP, P, R, IPP, const CHUNK_SIZE: usize>(
+    read: R,
+    ip: &mut IP,
+    e57_points: &mut IPP,
+) -> IOResult<()>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+    IPP: IsPushable<E57Point>,
+{
+    let iterator = E57PointIterator::<_, _, CHUNK_SIZE>::new(read)?;
+
+    for chunk in iterator {
+        for x in chunk? {
+            match x {
+                DataReserve::Data((p, e57_point)) => {
+                    ip.push(p);
+                    e57_points.push(e57_point);
+                }
+                DataReserve::Reserve(x) => {
+                    ip.reserve(x);
+                    e57_points.reserve(x);
+                }
+                DataReserve::ReserveExact(x) => {
+                    ip.reserve_exact(x);
+                    e57_points.reserve_exact(x);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+/// Bits of a single field, concatenated over all data packets
+#[derive(Default)]
+struct BitStream {
+    bytes: Vec<u8>,
+    bit_pos: usize,
+}
+
+impl BitStream {
+    fn available(&self) -> usize {
+        self.bytes.len() * 8 - self.bit_pos
+    }
+
+    fn append(&mut self, data: &[u8]) {
+        // drop consumed bytes once they add up
+        if self.bit_pos >= 8 * 4096 {
+            self.bytes.drain(..self.bit_pos / 8);
+            self.bit_pos %= 8;
+        }
+        self.bytes.extend_from_slice(data);
+    }
+
+    /// Reads n_bits, least significant bits first
+    fn read(&mut self, n_bits: u32) -> u64 {
+        let mut result = 0;
+        let mut done = 0;
+
+        while done < n_bits {
+            let offset = (self.bit_pos % 8) as u32;
+            let take = (8 - offset).min(n_bits - done);
+            let bits = (self.bytes[self.bit_pos / 8] >> offset) as u64 & ((1 << take) - 1);
+            result |= bits << done;
+            done += take;
+            self.bit_pos += take as usize;
+        }
+
+        result
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Reads the file header, checking it against the file length
+fn read_file_header<R>(read: &mut R) -> IOResult<FileHeader>
+where
+    R: Read + Seek,
+{
+    let file_length = read.seek(SeekFrom::End(0))?;
+    read.seek(SeekFrom::Start(0))?;
+
+    let mut buffer = [0u8; HEADER_SIZE];
+    read.read_exact(&mut buffer)?;
+    let header = FileHeader::from_bytes(&buffer)?;
+
+    // Sizes are used for allocations and must be validated before
+    if header.page_size > file_length
+        || header.xml_logical_length > file_length
+        || header.xml_physical_offset > file_length
+    {
+        return Err(IOError::E57(E57Error::Header));
+    }
+
+    Ok(header)
+}
+
+//------------------------------------------------------------------------------
+
+/// Decodes the records of the compressedVector of a scan
+struct ScanDecoder {
+    /// Logical offset of the next packet
+    packet_offset: u64,
+    /// Logical end of the binary section
+    section_end: u64,
+    streams: Vec<BitStream>,
+    n_remaining: u64,
+}
+
+impl ScanDecoder {
+    const DATA_PACKET: u8 = 1;
+
+    fn new<R>(read: &mut PagedReader<R>, scan: &Scan) -> IOResult<Self>
+    where
+        R: Read + Seek,
+    {
+        let start = read.logical_offset(scan.section_offset);
+        let mut header = [0u8; 32];
+        read.read_logical(start, &mut header)?;
+
+        if header[0] != 1 {
+            return Err(IOError::E57(E57Error::Section));
+        }
+
+        let section_length = u64::from_le_bytes(header[8..16].try_into()?);
+        let data_offset = u64::from_le_bytes(header[16..24].try_into()?);
+
+        Ok(Self {
+            packet_offset: read.logical_offset(data_offset),
+            section_end: start.saturating_add(section_length),
+            streams: scan.fields.iter().map(|_| BitStream::default()).collect(),
+            n_remaining: scan.record_count,
+        })
+    }
+
+    /// Number of points to reserve, limited by the section length to guard against corrupt counts
+    fn n_reserve(&self) -> usize {
+        self.n_remaining
+            .min(self.section_end.saturating_sub(self.packet_offset)) as usize
+    }
+
+    fn next_record<R>(
+        &mut self,
+        read: &mut PagedReader<R>,
+        scan: &Scan,
+        values: &mut Vec<f64>,
+    ) -> IOResult<()>
+    where
+        R: Read + Seek,
+    {
+        values.clear();
+
+        for (i, field) in scan.fields.iter().enumerate() {
+            let n_bits = field.n_bits();
+            while self.streams[i].available() < n_bits as usize {
+                self.read_packet(read)?;
+            }
+            values.push(field.decode(self.streams[i].read(n_bits)));
+        }
+
+        self.n_remaining -= 1;
+
+        Ok(())
+    }
+
+    /// Appends the buffers of the next data packet to the streams, skipping index and empty packets
+    fn read_packet<R>(&mut self, read: &mut PagedReader<R>) -> IOResult<()>
+    where
+        R: Read + Seek,
+    {
+        loop {
+            if self.packet_offset >= self.section_end {
+                return Err(IOError::E57(E57Error::Packet));
+            }
+
+            let mut header = [0u8; 4];
+            read.read_logical(self.packet_offset, &mut header)?;
+            let length = u16::from_le_bytes([header[2], header[3]]) as u64 + 1;
+
+            if header[0] != Self::DATA_PACKET {
+                self.packet_offset += length;
+                continue;
+            }
+
+            let mut count = [0u8; 2];
+            read.read_logical(self.packet_offset + 4, &mut count)?;
+            let n_streams = u16::from_le_bytes(count) as usize;
+
+            if n_streams != self.streams.len() {
+                return Err(IOError::E57(E57Error::Packet));
+            }
+
+            let mut lengths = vec![0u8; 2 * n_streams];
+            read.read_logical(self.packet_offset + 6, &mut lengths)?;
+
+            let packet_end = self.packet_offset + length;
+            let mut pos = self.packet_offset + 6 + lengths.len() as u64;
+            let mut buffer = Vec::new();
+
+            for (stream, l) in self.streams.iter_mut().zip(lengths.chunks_exact(2)) {
+                let l = u16::from_le_bytes([l[0], l[1]]) as u64;
+                if pos + l > packet_end {
+                    return Err(IOError::E57(E57Error::Packet));
+                }
+                buffer.resize(l as usize, 0);
+                read.read_logical(pos, &mut buffer)?;
+                stream.append(&buffer);
+                pos += l;
+            }
+
+            self.packet_offset = packet_end;
+            return Ok(());
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+fn make_point<P>(values: &[f64], scan: &Scan, i_scan: usize) -> Option<(P, E57Point)>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+{
+    let ids = &scan.ids;
+
+    let mut p = if let Some([x, y, z]) = ids.cartesian {
+        if ids.cartesian_invalid.is_some_and(|i| values[i] != 0.0) {
+            return None;
+        }
+        P::new(values[x], values[y], values[z])
+    } else if let Some([r, a, e]) = ids.spherical {
+        if ids.spherical_invalid.is_some_and(|i| values[i] != 0.0) {
+            return None;
+        }
+        let (range, azimuth, elevation) = (values[r], values[a], values[e]);
+        P::new(
+            range * elevation.cos() * azimuth.cos(),
+            range * elevation.cos() * azimuth.sin(),
+            range * elevation.sin(),
+        )
+    } else {
+        return None;
+    };
+
+    if let Some(transformation) = &scan.transformation {
+        p.transform(transformation)
+    }
+
+    let color = ids.color.map(|[r, g, b]| {
+        let component = |i: usize, [min, max]: [f64; 2]| {
+            let x = if max > min {
+                (values[i] - min) / (max - min) * 255.0
+            } else {
+                values[i]
+            };
+            x.round().clamp(0.0, 255.0) as u8
+        };
+        Rgb::new(
+            component(r, scan.color_limits[0]),
+            component(g, scan.color_limits[1]),
+            component(b, scan.color_limits[2]),
+        )
+    });
+
+    Some((
+        p,
+        E57Point {
+            scan: i_scan,
+            intensity: ids.intensity.map(|i| values[i]),
+            color,
+            row: ids.row.map(|i| values[i] as i64),
+            column: ids.column.map(|i| values[i] as i64),
+        },
+    ))
+}
+
+//------------------------------------------------------------------------------
+
+fn parse_scans(root: &XmlElement) -> IOResult<Vec<Scan>> {
+    match root.child("data3D") {
+        None => Ok(Vec::new()),
+        Some(data3d) => data3d.children.iter().map(parse_scan).collect(),
+    }
+}
+
+fn parse_scan(scan: &XmlElement) -> IOResult<Scan> {
+    let invalid = || IOError::E57(E57Error::Scan);
+
+    let points = scan.child("points").ok_or_else(invalid)?;
+
+    if let Some(codecs) = points.child("codecs") {
+        let is_bit_pack = |codec: &XmlElement| {
+            codec
+                .children
+                .iter()
+                .all(|x| x.name == "inputs" || x.name == "bitPackCodec")
+        };
+        if !codecs.children.iter().all(is_bit_pack) {
+            return Err(IOError::E57(E57Error::UnsupportedCodec));
+        }
+    }
+
+    let prototype = points.child("prototype").ok_or_else(invalid)?;
+
+    let mut fields = Vec::with_capacity(prototype.children.len());
+    let mut ids = FieldIds::default();
+    let mut cartesian = [None; 3];
+    let mut spherical = [None; 3];
+    let mut color = [None; 3];
+
+    for (i, field) in prototype.children.iter().enumerate() {
+        fields.push(match parse_field(field) {
+            Some(x) => x,
+            None => return Err(IOError::E57(E57Error::UnsupportedField(field.name.clone()))),
+        });
+
+        match field.name.as_str() {
+            "cartesianX" => cartesian[0] = Some(i),
+            "cartesianY" => cartesian[1] = Some(i),
+            "cartesianZ" => cartesian[2] = Some(i),
+            "sphericalRange" => spherical[0] = Some(i),
+            "sphericalAzimuth" => spherical[1] = Some(i),
+            "sphericalElevation" => spherical[2] = Some(i),
+            "cartesianInvalidState" => ids.cartesian_invalid = Some(i),
+            "sphericalInvalidState" => ids.spherical_invalid = Some(i),
+            "intensity" => ids.intensity = Some(i),
+            "colorRed" => color[0] = Some(i),
+            "colorGreen" => color[1] = Some(i),
+            "colorBlue" => color[2] = Some(i),
+            "rowIndex" => ids.row = Some(i),
+            "columnIndex" => ids.column = Some(i),
+            _ => (),
+        }
+    }
+
+    let all = |x: [Option<usize>; 3]| Some([x[0]?, x[1]?, x[2]?]);
+    ids.cartesian = all(cartesian);
+    ids.spherical = all(spherical);
+    ids.color = all(color);
+
+    if ids.cartesian.is_none() && ids.spherical.is_none() {
+        return Err(invalid());
+    }
+
+    let mut color_limits = [[0.0, 255.0]; 3];
+    if let Some(ids) = ids.color {
+        let limits = scan.child("colorLimits");
+        for (c, (i, name)) in ids.iter().zip(&["Red", "Green", "Blue"]).enumerate() {
+            let from_xml = limits.and_then(|x| {
+                Some([
+                    x.child_value(&format!("color{}Minimum", name))?,
+                    x.child_value(&format!("color{}Maximum", name))?,
+                ])
+            });
+            if let Some(x) = from_xml.or_else(|| fields[*i].limits()) {
+                color_limits[c] = x
+            }
+        }
+    }
+
+    let transformation = match scan.child("pose") {
+        None => None,
+        Some(pose) => Some(parse_pose(pose).ok_or_else(invalid)?),
+    };
+
+    Ok(Scan {
+        transformation,
+        section_offset: points
+            .attribute("fileOffset")
+            .and_then(|x| x.trim().parse().ok())
+            .ok_or_else(invalid)?,
+        record_count: points
+            .attribute("recordCount")
+            .and_then(|x| x.trim().parse().ok())
+            .ok_or_else(invalid)?,
+        fields,
+        ids,
+        color_limits,
+    })
+}
+
+fn parse_field(field: &XmlElement) -> Option<FieldType> {
+    match field.attribute("type")? {
+        "Float" => Some(FieldType::Float {
+            is_single: field.attribute("precision") == Some("single"),
+        }),
+        "Integer" => Some(FieldType::Integer {
+            min: field.attribute_or("minimum", i64::MIN)?,
+            max: field.attribute_or("maximum", i64::MAX)?,
+        }),
+        "ScaledInteger" => Some(FieldType::ScaledInteger {
+            min: field.attribute_or("minimum", i64::MIN)?,
+            max: field.attribute_or("maximum", i64::MAX)?,
+            scale: field.attribute_or("scale", 1.0)?,
+            offset: field.attribute_or("offset", 0.0)?,
+        }),
+        _ => None,
+    }
+}
+
+fn parse_pose(pose: &XmlElement) -> Option<Matrix4> {
+    let rotation = match pose.child("rotation") {
+        None => Matrix4::identity(),
+        Some(r) => Matrix4::from_unit_quaternion(&[
+            r.child_value_or("x", 0.0)?,
+            r.child_value_or("y", 0.0)?,
+            r.child_value_or("z", 0.0)?,
+            r.child_value_or("w", 1.0)?,
+        ]),
+    };
+
+    let translation = match pose.child("translation") {
+        None => Matrix4::identity(),
+        Some(t) => Matrix4::translation(
+            t.child_value_or("x", 0.0)?,
+            t.child_value_or("y", 0.0)?,
+            t.child_value_or("z", 0.0)?,
+        ),
+    };
+
+    Some(translation * rotation)
+}
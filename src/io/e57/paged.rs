@@ -0,0 +1,123 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for reading the logical content of the checksummed pages of .e57 files
+
+use std::io::{Read, Seek, SeekFrom};
+
+use super::{super::types::*, types::*};
+
+//------------------------------------------------------------------------------
+
+/// Reads logical bytes of a .e57 file, verifying the CRC-32C checksum of each page
+pub struct PagedReader<R>
+where
+    R: Read + Seek,
+{
+    read: R,
+    page_size: u64,
+    page_index: Option<u64>,
+    page: Vec<u8>,
+    crc_table: [u32; 256],
+}
+
+impl<R> PagedReader<R>
+where
+    R: Read + Seek,
+{
+    pub fn new(read: R, page_size: u64) -> Self {
+        Self {
+            read,
+            page_size,
+            page_index: None,
+            page: vec![0; page_size as usize],
+            crc_table: crc32c_table(),
+        }
+    }
+
+    /// Converts a physical offset within the file to the logical offset
+    pub fn logical_offset(&self, physical: u64) -> u64 {
+        physical / self.page_size * (self.page_size - CHECKSUM_SIZE) + physical % self.page_size
+    }
+
+    pub fn read_logical(&mut self, mut offset: u64, buffer: &mut [u8]) -> IOResult<()> {
+        let logical_page_size = self.page_size - CHECKSUM_SIZE;
+        let mut done = 0;
+
+        while done < buffer.len() {
+            let in_page = (offset % logical_page_size) as usize;
+            self.load_page(offset / logical_page_size)?;
+
+            let n = (buffer.len() - done).min(logical_page_size as usize - in_page);
+            buffer[done..done + n].copy_from_slice(&self.page[in_page..in_page + n]);
+
+            done += n;
+            offset += n as u64;
+        }
+
+        Ok(())
+    }
+
+    fn load_page(&mut self, index: u64) -> IOResult<()> {
+        if self.page_index == Some(index) {
+            return Ok(());
+        }
+
+        self.page_index = None;
+        self.read.seek(SeekFrom::Start(index * self.page_size))?;
+        self.read.read_exact(&mut self.page)?;
+
+        let n_data = (self.page_size - CHECKSUM_SIZE) as usize;
+        let mut expected = [0u8; 4];
+        expected.copy_from_slice(&self.page[n_data..]);
+
+        if crc32c(&self.crc_table, &self.page[..n_data]) != u32::from_be_bytes(expected) {
+            return Err(IOError::E57(E57Error::Checksum(index)));
+        }
+
+        self.page_index = Some(index);
+        Ok(())
+    }
+}
+
+//------------------------------------------------------------------------------
+
+fn crc32c_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    for (i, x) in table.iter_mut().enumerate() {
+        let mut crc = i as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x82F6_3B78
+            } else {
+                crc >> 1
+            };
+        }
+        *x = crc;
+    }
+    table
+}
+
+fn crc32c(table: &[u32; 256], data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| {
+        table[((crc ^ *byte as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
@@ -0,0 +1,174 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for types of the e57 file format
+
+use crate::*;
+
+use std::convert::TryInto;
+
+use super::super::types::*;
+
+//------------------------------------------------------------------------------
+
+/// All attributes of a point of a .e57 file, except for its position
+/// Fields which aren't part of the scan are None
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct E57Point {
+    /// Index of the scan within the file
+    pub scan: usize,
+    pub intensity: Option<f64>,
+    pub color: Option<Rgb>,
+    /// Position within the grid of the scan
+    pub row: Option<i64>,
+    /// Position within the grid of the scan
+    pub column: Option<i64>,
+}
+
+//------------------------------------------------------------------------------
+
+pub const SIGNATURE: &[u8; 8] = b"ASTM-E57";
+pub const HEADER_SIZE: usize = 48;
+pub const CHECKSUM_SIZE: u64 = 4;
+/// Upper limit for the page size, the standard uses 1024
+pub const MAX_PAGE_SIZE: u64 = 1024 * 1024;
+
+pub struct FileHeader {
+    pub xml_physical_offset: u64,
+    pub xml_logical_length: u64,
+    pub page_size: u64,
+}
+
+impl FileHeader {
+    pub fn from_bytes(bytes: &[u8; HEADER_SIZE]) -> IOResult<Self> {
+        if &bytes[0..8] != SIGNATURE {
+            return Err(IOError::E57(E57Error::Header));
+        }
+
+        let major = u32::from_le_bytes(bytes[8..12].try_into()?);
+        if major != 1 {
+            return Err(IOError::UnsupportedVersion);
+        }
+
+        let page_size = u64::from_le_bytes(bytes[40..48].try_into()?);
+        if page_size <= CHECKSUM_SIZE || page_size > MAX_PAGE_SIZE {
+            return Err(IOError::E57(E57Error::Header));
+        }
+
+        Ok(Self {
+            xml_physical_offset: u64::from_le_bytes(bytes[24..32].try_into()?),
+            xml_logical_length: u64::from_le_bytes(bytes[32..40].try_into()?),
+            page_size,
+        })
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub enum FieldType {
+    Float {
+        is_single: bool,
+    },
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    ScaledInteger {
+        min: i64,
+        max: i64,
+        scale: f64,
+        offset: f64,
+    },
+}
+
+impl FieldType {
+    /// Number of bits used by the bitPackCodec
+    pub fn n_bits(&self) -> u32 {
+        match self {
+            Self::Float { is_single: true } => 32,
+            Self::Float { is_single: false } => 64,
+            Self::Integer { min, max } | Self::ScaledInteger { min, max, .. } => {
+                if max <= min {
+                    0
+                } else {
+                    let range = max.wrapping_sub(*min) as u64;
+                    64 - range.leading_zeros()
+                }
+            }
+        }
+    }
+
+    /// Converts the raw bits into the value
+    pub fn decode(&self, raw: u64) -> f64 {
+        match self {
+            Self::Float { is_single: true } => f32::from_bits(raw as u32) as f64,
+            Self::Float { is_single: false } => f64::from_bits(raw),
+            Self::Integer { min, .. } => min.wrapping_add(raw as i64) as f64,
+            Self::ScaledInteger {
+                min, scale, offset, ..
+            } => min.wrapping_add(raw as i64) as f64 * scale + offset,
+        }
+    }
+
+    /// The range of possible values, if limited
+    pub fn limits(&self) -> Option<[f64; 2]> {
+        match self {
+            Self::Float { .. } => None,
+            Self::Integer { min, max } => Some([*min as f64, *max as f64]),
+            Self::ScaledInteger {
+                min,
+                max,
+                scale,
+                offset,
+            } => Some([*min as f64 * scale + offset, *max as f64 * scale + offset]),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Positions of the used fields within the records of a scan
+#[derive(Debug, Default, Clone)]
+pub struct FieldIds {
+    pub cartesian: Option<[usize; 3]>,
+    pub spherical: Option<[usize; 3]>,
+    pub cartesian_invalid: Option<usize>,
+    pub spherical_invalid: Option<usize>,
+    pub intensity: Option<usize>,
+    pub color: Option<[usize; 3]>,
+    pub row: Option<usize>,
+    pub column: Option<usize>,
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct Scan {
+    pub transformation: Option<Matrix4>,
+    /// Physical offset of the binary section
+    pub section_offset: u64,
+    pub record_count: u64,
+    pub fields: Vec<FieldType>,
+    pub ids: FieldIds,
+    /// Minimum and maximum of red, green and blue
+    pub color_limits: [[f64; 2]; 3],
+}
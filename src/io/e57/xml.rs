@@ -0,0 +1,208 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Minimal XML parser for the XML section of .e57 files
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Default, Clone)]
+pub struct XmlElement {
+    pub name: String,
+    pub attributes: Vec<(String, String)>,
+    pub children: Vec<XmlElement>,
+    pub text: String,
+}
+
+impl XmlElement {
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn child(&self, name: &str) -> Option<&XmlElement> {
+        self.children.iter().find(|x| x.name == name)
+    }
+
+    /// Parses the text of a child element
+    pub fn child_value<T: std::str::FromStr>(&self, name: &str) -> Option<T> {
+        self.child(name)?.text.trim().parse().ok()
+    }
+
+    /// Parses the text of a child element, falling back to the default of .e57 if missing
+    pub fn child_value_or<T: std::str::FromStr>(&self, name: &str, default: T) -> Option<T> {
+        match self.child(name) {
+            None => Some(default),
+            Some(x) => x.text.trim().parse().ok(),
+        }
+    }
+
+    /// Parses an attribute, falling back to the default if missing
+    pub fn attribute_or<T: std::str::FromStr>(&self, name: &str, default: T) -> Option<T> {
+        match self.attribute(name) {
+            None => Some(default),
+            Some(x) => x.trim().parse().ok(),
+        }
+    }
+
+    /// Parses the root element of the text
+    pub fn parse(text: &str) -> Option<XmlElement> {
+        let mut stack: Vec<XmlElement> = Vec::new();
+        let mut rest = text;
+
+        while !rest.is_empty() {
+            if let Some(tail) = rest.strip_prefix("<?") {
+                rest = after(tail, "?>")?;
+            } else if let Some(tail) = rest.strip_prefix("<!--") {
+                rest = after(tail, "-->")?;
+            } else if let Some(tail) = rest.strip_prefix("<![CDATA[") {
+                let end = tail.find("]]>")?;
+                if let Some(top) = stack.last_mut() {
+                    top.text.push_str(&tail[..end]);
+                }
+                rest = &tail[end + 3..];
+            } else if let Some(tail) = rest.strip_prefix("<!") {
+                rest = after(tail, ">")?;
+            } else if let Some(tail) = rest.strip_prefix("</") {
+                let end = tail.find('>')?;
+                let element = stack.pop()?;
+                if element.name != tail[..end].trim() {
+                    return None;
+                }
+                rest = &tail[end + 1..];
+                match stack.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Some(element),
+                }
+            } else if let Some(tail) = rest.strip_prefix('<') {
+                let end = tag_end(tail)?;
+                let (element, is_closed) = parse_tag(&tail[..end])?;
+                rest = &tail[end + 1..];
+                if is_closed {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(element),
+                        None => return Some(element),
+                    }
+                } else {
+                    stack.push(element)
+                }
+            } else {
+                let end = rest.find('<').unwrap_or(rest.len());
+                if let Some(top) = stack.last_mut() {
+                    top.text.push_str(&decode_entities(&rest[..end])?);
+                }
+                rest = &rest[end..];
+            }
+        }
+
+        None
+    }
+}
+
+//------------------------------------------------------------------------------
+
+fn after<'a>(text: &'a str, pattern: &str) -> Option<&'a str> {
+    text.find(pattern).map(|i| &text[i + pattern.len()..])
+}
+
+/// Position of the '>' closing a tag, ignoring those within quoted attribute values
+fn tag_end(text: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in text.char_indices() {
+        match (quote, c) {
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Parses the content of a start tag, returning whether it is self-closing
+fn parse_tag(tag: &str) -> Option<(XmlElement, bool)> {
+    let (tag, is_closed) = match tag.strip_suffix('/') {
+        Some(x) => (x, true),
+        None => (tag, false),
+    };
+
+    let name_end = tag.find(|c: char| c.is_whitespace()).unwrap_or(tag.len());
+    let name = &tag[..name_end];
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut attributes = Vec::new();
+    let mut rest = tag[name_end..].trim_start();
+    while !rest.is_empty() {
+        let eq = rest.find('=')?;
+        let key = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        let end = value[1..].find(quote)? + 1;
+        attributes.push((key.to_string(), decode_entities(&value[1..end])?));
+        rest = value[end + 1..].trim_start();
+    }
+
+    Some((
+        XmlElement {
+            name: name.to_string(),
+            attributes,
+            ..XmlElement::default()
+        },
+        is_closed,
+    ))
+}
+
+fn decode_entities(text: &str) -> Option<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        let end = rest[start..].find(';')? + start;
+        let entity = &rest[start + 1..end];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()?
+                } else {
+                    entity.strip_prefix('#')?.parse().ok()?
+                };
+                std::char::from_u32(code)?
+            }
+        };
+        result.push(c);
+        rest = &rest[end + 1..];
+    }
+    result.push_str(rest);
+
+    Some(result)
+}
@@ -0,0 +1,659 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//! Module for load functions of the gcode file format
+
+use crate::*;
+
+use std::{
+    collections::VecDeque, f64::consts::PI, io::BufRead, iter::FusedIterator, marker::PhantomData,
+};
+
+use super::{
+    super::{types::*, utils::*},
+    types::*,
+};
+
+//------------------------------------------------------------------------------
+
+/// Iterator to incrementally load a .gcode file
+pub struct GcodeIterator<P, R, const CHUNK_SIZE: usize>
+where
+    P: IsBuildable3D,
+    R: BufRead,
+{
+    inner: GcodeMoveIterator<P, R, CHUNK_SIZE>,
+}
+
+impl<P, R, const CHUNK_SIZE: usize> GcodeIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D,
+    R: BufRead,
+{
+    pub fn new(read: R) -> Self {
+        Self {
+            inner: GcodeMoveIterator::new(read),
+        }
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> Iterator for GcodeIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D,
+    R: BufRead,
+{
+    type Item = IOResult<StackVec<DataReserve<P>, CHUNK_SIZE>>;
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|chunk| {
+            let mut result = StackVec::default();
+            for x in chunk? {
+                let x = match x {
+                    DataReserve::Data((p, _)) => DataReserve::Data(p),
+                    DataReserve::Reserve(x) => DataReserve::Reserve(x),
+                    DataReserve::ReserveExact(x) => DataReserve::ReserveExact(x),
+                };
+                result.push(x).unwrap(); // unwrap safe since both have the same size
+            }
+            Ok(result)
+        })
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for GcodeIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D,
+    R: BufRead,
+{
+}
+
+//------------------------------------------------------------------------------
+
+/// Iterator to incrementally load the moves of a .gcode file
+/// Positions are in mm, G20 inch values are converted
+pub struct GcodeMoveIterator<P, R, const CHUNK_SIZE: usize>
+where
+    P: IsBuildable3D,
+    R: BufRead,
+{
+    read: R,
+    is_done: bool,
+    i_line: usize,
+    line_buffer: Vec<u8>,
+    words: Vec<(u8, f64)>,
+    pending: VecDeque<([f64; 3], GcodeMove)>,
+    max_arc_angle: f64,
+    state: MachineState,
+    phantom_p: PhantomData<P>,
+}
+
+impl<P, R, const CHUNK_SIZE: usize> GcodeMoveIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D,
+    R: BufRead,
+{
+    pub fn new(read: R) -> Self {
+        Self::with_arc_resolution(read, Rad(PI / 36.0))
+    }
+
+    /// Splits arcs into moves spanning max_angle at most
+    pub fn with_arc_resolution(read: R, max_angle: Rad) -> Self {
+        Self {
+            read,
+            is_done: false,
+            i_line: 0,
+            line_buffer: Vec::new(),
+            words: Vec::new(),
+            pending: VecDeque::new(),
+            max_arc_angle: max_angle.0.abs().max(1e-3),
+            state: MachineState::default(),
+            phantom_p: PhantomData,
+        }
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> Iterator for GcodeMoveIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D,
+    R: BufRead,
+{
+    type Item = IOResult<StackVec<DataReserve<(P, GcodeMove)>, CHUNK_SIZE>>;
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+
+        let mut chunk = StackVec::default();
+
+        loop {
+            if chunk.is_full() {
+                return Some(Ok(chunk));
+            } else if let Some(([x, y, z], m)) = self.pending.pop_front() {
+                chunk.push(DataReserve::Data((P::new(x, y, z), m))).unwrap(); // unwrap safe since we only call this if chunk.has_space()
+            } else {
+                let line = match fetch_line_or_none(&mut self.read, &mut self.line_buffer) {
+                    Ok(Some(line)) => line,
+                    Ok(None) => {
+                        self.is_done = true;
+                        if chunk.has_data() {
+                            return Some(Ok(chunk));
+                        }
+                        return None;
+                    }
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e.into()));
+                    }
+                };
+
+                self.i_line += 1;
+
+                let result = parse_words(line, &mut self.words).and_then(|_| {
+                    self.state
+                        .process(&self.words, self.max_arc_angle, &mut self.pending)
+                });
+
+                if result.is_none() {
+                    self.is_done = true;
+                    return Some(Err(IOError::LineParse(self.i_line)));
+                }
+            }
+        }
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for GcodeMoveIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D,
+    R: BufRead,
+{
+}
+
+//------------------------------------------------------------------------------
+
+/// Loads a IsPushable<Is3D> as x y z coordinates from gcode
+pub fn load_gcode_points<IP, P, R, const CHUNK_SIZE: usize>(read: R, ip: &mut IP) -> IOResult<()>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D,
+    R: BufRead,
+{
+    let iterator = GcodeIterator::<_, _, CHUNK_SIZE>::new(read);
+
+    for chunk in iterator {
+        for x in chunk? {
+            match x {
+                DataReserve::Data(x) => ip.push(x),
+                DataReserve::Reserve(x) => ip.reserve(x),
+                DataReserve::ReserveExact(x) => ip.reserve_exact(x),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads the target positions of gcode moves into IsPushable<Is3D> and the moves into IsPushable<GcodeMove>
+pub fn load_gcode_moves<IP, P, R, IPM, const CHUNK_SIZE: usize>(
+    read: R,
+    ip: &mut IP,
+    moves: &mut IPM,
+) -> IOResult<()>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D,
+    R: BufRead,
+    IPM: IsPushable<GcodeMove>,
+{
+    let iterator = GcodeMoveIterator::<_, _, CHUNK_SIZE>::new(read);
+
+    for chunk in iterator {
+        for x in chunk? {
+            match x {
+                DataReserve::Data((p, m)) => {
+                    ip.push(p);
+                    moves.push(m);
+                }
+                DataReserve::Reserve(x) => {
+                    ip.reserve(x);
+                    moves.reserve(x);
+                }
+                DataReserve::ReserveExact(x) => {
+                    ip.reserve_exact(x);
+                    moves.reserve_exact(x);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads the extruding moves of gcode as polylines, grouped into layers of equal height
+pub fn load_gcode_layers<P, R, const CHUNK_SIZE: usize>(read: R) -> IOResult<Vec<GcodeLayer<P>>>
+where
+    P: IsBuildable3D + Clone,
+    R: BufRead,
+{
+    let iterator = GcodeMoveIterator::<P, _, CHUNK_SIZE>::new(read);
+
+    let mut layers = Vec::new();
+    let mut path = PointCloud3D::<P>::new();
+    let mut last = P::new(0.0, 0.0, 0.0);
+
+    for chunk in iterator {
+        for x in chunk? {
+            if let DataReserve::Data((p, m)) = x {
+                match m.kind {
+                    GcodeMoveKind::Extrude => {
+                        let z_changed = path
+                            .data
+                            .last()
+                            .is_some_and(|x| (x.z() - p.z()).abs() > LAYER_EPS);
+                        if z_changed {
+                            finish_path(&mut layers, &mut path);
+                        }
+                        if path.len() == 0 {
+                            path.push(last.clone());
+                        }
+                        path.push(p.clone());
+                    }
+                    GcodeMoveKind::Travel => finish_path(&mut layers, &mut path),
+                }
+                last = p;
+            }
+        }
+    }
+
+    finish_path(&mut layers, &mut path);
+
+    Ok(layers)
+}
+
+//------------------------------------------------------------------------------
+
+const LAYER_EPS: f64 = 1e-6;
+const INCH: f64 = 25.4;
+
+/// Moves a path of at least one extruding move into the layer of its height
+fn finish_path<P>(layers: &mut Vec<GcodeLayer<P>>, path: &mut PointCloud3D<P>)
+where
+    P: IsBuildable3D,
+{
+    let z = match path.data.last() {
+        Some(p) if path.len() >= 2 => p.z(),
+        _ => {
+            path.data.clear();
+            return;
+        }
+    };
+
+    let polygon = Polygon3D::from(std::mem::take(path));
+
+    match layers.last_mut() {
+        Some(layer) if (layer.z - z).abs() <= LAYER_EPS => layer.paths.push(polygon),
+        _ => layers.push(GcodeLayer {
+            z,
+            paths: vec![polygon],
+        }),
+    }
+}
+
+/// Modal state of the machine, updated line by line
+struct MachineState {
+    position: [f64; 3],
+    e: f64,
+    feed_rate: Option<f64>,
+    ra: RelativeAbsolute,
+    ra_e: RelativeAbsolute,
+    is_inch: bool,
+    motion: Option<Motion>,
+    plane: Plane,
+}
+
+impl Default for MachineState {
+    fn default() -> Self {
+        Self {
+            position: [0.0; 3],
+            e: 0.0,
+            feed_rate: None,
+            ra: RelativeAbsolute::Absolute,
+            ra_e: RelativeAbsolute::Absolute,
+            is_inch: false,
+            motion: None,
+            plane: Plane::XY,
+        }
+    }
+}
+
+impl MachineState {
+    /// Processes the words of a line, adding resulting moves to pending
+    fn process(
+        &mut self,
+        words: &[(u8, f64)],
+        max_arc_angle: f64,
+        pending: &mut VecDeque<([f64; 3], GcodeMove)>,
+    ) -> Option<()> {
+        let mut motion = None;
+        let mut is_set_position = false;
+        let mut is_home = false;
+        let mut is_other = false;
+        let mut is_m = false;
+
+        for (letter, value) in words {
+            match (letter, code(*value)) {
+                (b'G', Some(0)) | (b'G', Some(1)) => motion = Some(Motion::Linear),
+                (b'G', Some(2)) => motion = Some(Motion::ArcClockwise),
+                (b'G', Some(3)) => motion = Some(Motion::ArcCounterClockwise),
+                (b'G', Some(17)) => self.plane = Plane::XY,
+                (b'G', Some(18)) => self.plane = Plane::ZX,
+                (b'G', Some(19)) => self.plane = Plane::YZ,
+                (b'G', Some(20)) => self.is_inch = true,
+                (b'G', Some(21)) => self.is_inch = false,
+                (b'G', Some(28)) => is_home = true,
+                (b'G', Some(90)) => {
+                    self.ra = RelativeAbsolute::Absolute;
+                    self.ra_e = RelativeAbsolute::Absolute;
+                }
+                (b'G', Some(91)) => {
+                    self.ra = RelativeAbsolute::Relative;
+                    self.ra_e = RelativeAbsolute::Relative;
+                }
+                (b'G', Some(92)) => is_set_position = true,
+                (b'M', Some(82)) => self.ra_e = RelativeAbsolute::Absolute,
+                (b'M', Some(83)) => self.ra_e = RelativeAbsolute::Relative,
+                // the axis words of these are parameters instead of positions
+                (b'G', Some(10)) | (b'G', Some(29)) | (b'G', Some(30)) | (b'G', Some(52)) => {
+                    is_other = true
+                }
+                (b'M', _) => is_m = true,
+                // other words don't affect the motion, e.g. G54 or M3
+                _ => (),
+            }
+        }
+
+        // M codes use axis words as parameters (e.g. M92 X80), unless there's a motion
+        if is_m && motion.is_none() {
+            is_other = true;
+        }
+
+        let unit = if self.is_inch { INCH } else { 1.0 };
+        let value = |letter: u8| {
+            words
+                .iter()
+                .rev()
+                .find(|(l, _)| *l == letter)
+                .map(|(_, v)| v * unit)
+        };
+
+        if let Some(f) = value(b'F') {
+            self.feed_rate = Some(f);
+        }
+
+        let axes = [value(b'X'), value(b'Y'), value(b'Z')];
+        let e = value(b'E');
+        let has_axes = axes.iter().any(|x| x.is_some());
+
+        if is_set_position {
+            if !has_axes && e.is_none() {
+                self.position = [0.0; 3];
+                self.e = 0.0;
+            }
+            for (p, x) in self.position.iter_mut().zip(&axes) {
+                if let Some(x) = x {
+                    *p = *x;
+                }
+            }
+            if let Some(e) = e {
+                self.e = e;
+            }
+            return Some(());
+        }
+
+        if is_home {
+            let mut target = self.position;
+            for (t, x) in target.iter_mut().zip(&axes) {
+                if !has_axes || x.is_some() {
+                    *t = 0.0;
+                }
+            }
+            self.push_linear(target, 0.0, pending);
+            return Some(());
+        }
+
+        if is_other {
+            return Some(());
+        }
+
+        if motion.is_some() {
+            self.motion = motion;
+        }
+
+        let motion = match self.motion {
+            Some(x) if has_axes || e.is_some() => x,
+            _ => return Some(()),
+        };
+
+        let mut target = self.position;
+        for (t, x) in target.iter_mut().zip(&axes) {
+            if let Some(x) = x {
+                match self.ra {
+                    RelativeAbsolute::Absolute => *t = *x,
+                    RelativeAbsolute::Relative => *t += *x,
+                }
+            }
+        }
+
+        let extrusion = match (e, self.ra_e) {
+            (None, _) => 0.0,
+            (Some(e), RelativeAbsolute::Absolute) => {
+                let delta = e - self.e;
+                self.e = e;
+                delta
+            }
+            (Some(e), RelativeAbsolute::Relative) => {
+                self.e += e;
+                e
+            }
+        };
+
+        match motion {
+            Motion::Linear => self.push_linear(target, extrusion, pending),
+            Motion::ArcClockwise | Motion::ArcCounterClockwise => {
+                let [a, b, c] = self.plane.axes();
+                let [letter_a, letter_b] = self.plane.offset_letters();
+                let offset = match (value(letter_a), value(letter_b)) {
+                    (None, None) => None,
+                    (i, j) => Some([i.unwrap_or(0.0), j.unwrap_or(0.0)]),
+                };
+                let points = arc(
+                    [self.position[a], self.position[b], self.position[c]],
+                    [target[a], target[b], target[c]],
+                    offset,
+                    value(b'R'),
+                    motion == Motion::ArcCounterClockwise,
+                    max_arc_angle,
+                )?;
+
+                let n = points.len() as f64;
+                for p in points {
+                    let mut q = [0.0; 3];
+                    q[a] = p[0];
+                    q[b] = p[1];
+                    q[c] = p[2];
+                    pending.push_back((q, self.make_move(extrusion / n)));
+                }
+                self.position = target;
+            }
+        }
+
+        Some(())
+    }
+
+    fn push_linear(
+        &mut self,
+        target: [f64; 3],
+        extrusion: f64,
+        pending: &mut VecDeque<([f64; 3], GcodeMove)>,
+    ) {
+        if target != self.position {
+            pending.push_back((target, self.make_move(extrusion)));
+            self.position = target;
+        }
+    }
+
+    fn make_move(&self, extrusion: f64) -> GcodeMove {
+        GcodeMove {
+            kind: if extrusion > 0.0 {
+                GcodeMoveKind::Extrude
+            } else {
+                GcodeMoveKind::Travel
+            },
+            feed_rate: self.feed_rate,
+            extrusion,
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Splits an arc within the plane of the first two coordinates into points, excluding the start
+/// The third coordinate is interpolated linearly, resulting in a helix
+/// The center is either defined by its offset to the start or by the radius
+fn arc(
+    start: [f64; 3],
+    end: [f64; 3],
+    center_offset: Option<[f64; 2]>,
+    radius: Option<f64>,
+    is_ccw: bool,
+    max_angle: f64,
+) -> Option<Vec<[f64; 3]>> {
+    let center = match (center_offset, radius) {
+        (Some([i, j]), _) => [start[0] + i, start[1] + j],
+        (None, Some(r)) => {
+            let dx = end[0] - start[0];
+            let dy = end[1] - start[1];
+            let d = (dx * dx + dy * dy).sqrt();
+            if d == 0.0 {
+                // a full circle can't be defined by its radius
+                return None;
+            }
+            let h = (r * r - 0.25 * d * d).max(0.0).sqrt();
+            // the shorter arc for positive radii, the longer one otherwise
+            let sign = if is_ccw == (r > 0.0) { 1.0 } else { -1.0 };
+            [
+                start[0] + 0.5 * dx - sign * h * dy / d,
+                start[1] + 0.5 * dy + sign * h * dx / d,
+            ]
+        }
+        (None, None) => return None,
+    };
+
+    let radius = (start[0] - center[0]).hypot(start[1] - center[1]);
+    let angle_start = (start[1] - center[1]).atan2(start[0] - center[0]);
+    let angle_end = (end[1] - center[1]).atan2(end[0] - center[0]);
+
+    // equal start and end result in a full circle
+    let mut sweep = angle_end - angle_start;
+    if is_ccw {
+        if sweep <= ARC_EPS {
+            sweep += 2.0 * PI;
+        }
+    } else if sweep >= -ARC_EPS {
+        sweep -= 2.0 * PI;
+    }
+
+    let n = (sweep.abs() / max_angle).ceil().max(1.0) as usize;
+
+    Some(
+        (1..=n)
+            .map(|i| {
+                if i == n {
+                    return end;
+                }
+                let t = i as f64 / n as f64;
+                let angle = angle_start + t * sweep;
+                [
+                    center[0] + radius * angle.cos(),
+                    center[1] + radius * angle.sin(),
+                    start[2] + t * (end[2] - start[2]),
+                ]
+            })
+            .collect(),
+    )
+}
+
+const ARC_EPS: f64 = 1e-9;
+
+//------------------------------------------------------------------------------
+
+/// Splits a line into its words, ignoring comments and checksums
+#[inline(always)]
+fn parse_words(line: &[u8], words: &mut Vec<(u8, f64)>) -> Option<()> {
+    words.clear();
+
+    let mut i = 0;
+    while i < line.len() {
+        let c = line[i];
+        if c == b';' || c == b'*' {
+            break;
+        } else if c == b'(' {
+            match line[i..].iter().position(|x| *x == b')') {
+                Some(end) => i += end + 1,
+                None => break,
+            }
+        } else if c.is_ascii_alphabetic() {
+            let start = i + 1;
+            let mut end = start;
+            while end < line.len() && matches!(line[end], b'0'..=b'9' | b'.' | b'-' | b'+') {
+                end += 1;
+            }
+
+            if end > start {
+                let letter = c.to_ascii_uppercase();
+                let value = from_ascii(&line[start..end])?;
+                words.push((letter, value));
+
+                // the remainder of messages is text
+                if letter == b'M' && (value == 117.0 || value == 118.0) {
+                    break;
+                }
+            }
+            i = end;
+        } else {
+            i += 1;
+        }
+    }
+
+    Some(())
+}
+
+/// The integer code of G and M words
+#[inline(always)]
+fn code(value: f64) -> Option<u32> {
+    if value >= 0.0 && value.fract() == 0.0 {
+        Some(value as u32)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,246 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for save functions of the gcode file format
+
+use crate::*;
+
+use std::{f64::consts::PI, io::Write};
+
+use super::{super::types::*, types::*};
+
+//------------------------------------------------------------------------------
+
+/// Saves layers of 2D paths as gcode, the n-th layer is printed at a height of n * layer_height
+pub fn save_gcode_layers_2d<PG, P, W>(
+    write: &mut W,
+    layers: &[Vec<PG>],
+    config: &GcodeConfig,
+) -> IOResult<()>
+where
+    PG: IsPolygon<P>,
+    P: Is2D,
+    W: Write,
+{
+    let mut writer = GcodeWriter::new(write, config)?;
+
+    for (i, layer) in layers.iter().enumerate() {
+        let z = config.layer_height * (i + 1) as f64;
+        writer.layer(i)?;
+
+        for path in layer {
+            let vertices: Vec<_> = (0..path.num_vertices())
+                .filter_map(|i| path.vertex(VId(i)))
+                .map(|p| [p.x(), p.y(), z])
+                .collect();
+            writer.path(&vertices)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Saves layers of 3D paths as gcode, using the heights of their vertices
+pub fn save_gcode_layers_3d<PG, P, W>(
+    write: &mut W,
+    layers: &[Vec<PG>],
+    config: &GcodeConfig,
+) -> IOResult<()>
+where
+    PG: IsPolygon<P>,
+    P: Is3D,
+    W: Write,
+{
+    let mut writer = GcodeWriter::new(write, config)?;
+
+    for (i, layer) in layers.iter().enumerate() {
+        writer.layer(i)?;
+
+        for path in layer {
+            let vertices: Vec<_> = (0..path.num_vertices())
+                .filter_map(|i| path.vertex(VId(i)))
+                .map(|p| [p.x(), p.y(), p.z()])
+                .collect();
+            writer.path(&vertices)?;
+        }
+    }
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+/// Writes paths while keeping track of the extruded filament and the position
+struct GcodeWriter<'a, W>
+where
+    W: Write,
+{
+    write: &'a mut W,
+    config: &'a GcodeConfig,
+    /// Filament required per mm of path
+    extrusion_per_mm: f64,
+    e: f64,
+    position: Option<[f64; 3]>,
+}
+
+impl<'a, W> GcodeWriter<'a, W>
+where
+    W: Write,
+{
+    fn new(write: &'a mut W, config: &'a GcodeConfig) -> IOResult<Self> {
+        let filament_area = 0.25 * PI * config.filament_diameter.powi(2);
+
+        write.write_all(
+            ("; Created by rust-3d\n".to_string()
+                + "G21 ; millimeters\n"
+                + "G90 ; absolute positions\n"
+                + "M82 ; absolute extrusion\n"
+                + "G92 E0\n")
+                .as_bytes(),
+        )?;
+
+        Ok(Self {
+            write,
+            config,
+            extrusion_per_mm: config.layer_height * config.extrusion_width / filament_area,
+            e: 0.0,
+            position: None,
+        })
+    }
+
+    fn layer(&mut self, i: usize) -> IOResult<()> {
+        self.write
+            .write_all(format!("; layer {}\n", i).as_bytes())?;
+        Ok(())
+    }
+
+    fn path(&mut self, vertices: &[[f64; 3]]) -> IOResult<()> {
+        let (start, rest) = match vertices.split_first() {
+            Some(x) if !x.1.is_empty() => x,
+            _ => return Ok(()),
+        };
+
+        if self.position != Some(*start) {
+            self.travel(start)?;
+        }
+
+        let closing = if self.config.close_paths {
+            Some(start)
+        } else {
+            None
+        };
+
+        let mut previous = *start;
+        let mut is_first = true;
+        for v in rest.iter().chain(closing) {
+            let length = ((v[0] - previous[0]).powi(2)
+                + (v[1] - previous[1]).powi(2)
+                + (v[2] - previous[2]).powi(2))
+            .sqrt();
+            self.e += length * self.extrusion_per_mm;
+
+            let mut line = format!(
+                "G1 X{} Y{} Z{} E{}",
+                number(v[0]),
+                number(v[1]),
+                number(v[2]),
+                number(self.e)
+            );
+            if is_first {
+                line += &format!(" F{}", number(self.config.print_feed_rate));
+                is_first = false;
+            }
+            line += "\n";
+            self.write.write_all(line.as_bytes())?;
+
+            previous = *v;
+        }
+
+        self.position = Some(previous);
+
+        Ok(())
+    }
+
+    fn travel(&mut self, target: &[f64; 3]) -> IOResult<()> {
+        let config = self.config;
+        let is_retracting = config.retraction_length > 0.0 && self.e > 0.0;
+
+        if is_retracting {
+            self.retract(self.e - config.retraction_length)?;
+        }
+
+        let hop = config.z_hop.max(0.0);
+        if hop > 0.0 {
+            if let Some(p) = self.position {
+                self.write
+                    .write_all(format!("G0 Z{}\n", number(p[2] + hop)).as_bytes())?;
+            }
+        }
+
+        self.write.write_all(
+            format!(
+                "G0 X{} Y{} Z{} F{}\n",
+                number(target[0]),
+                number(target[1]),
+                number(target[2] + hop),
+                number(config.travel_feed_rate)
+            )
+            .as_bytes(),
+        )?;
+
+        if hop > 0.0 {
+            self.write
+                .write_all(format!("G0 Z{}\n", number(target[2])).as_bytes())?;
+        }
+
+        if is_retracting {
+            self.retract(self.e)?;
+        }
+
+        Ok(())
+    }
+
+    /// Moves the filament to the absolute e, without changing the stored extrusion
+    fn retract(&mut self, e: f64) -> IOResult<()> {
+        self.write.write_all(
+            format!(
+                "G1 E{} F{}\n",
+                number(e),
+                number(self.config.retraction_feed_rate)
+            )
+            .as_bytes(),
+        )?;
+        Ok(())
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Formats with 5 decimals at most, dropping trailing zeros
+fn number(x: f64) -> String {
+    let s = format!("{:.5}", x);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" {
+        "0".to_string()
+    } else {
+        s.to_string()
+    }
+}
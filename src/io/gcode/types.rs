@@ -0,0 +1,137 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for types of the gcode file format
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// Whether a move deposits material
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GcodeMoveKind {
+    Travel,
+    Extrude,
+}
+
+/// A move to the position it is loaded together with
+/// Arcs are split into several moves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GcodeMove {
+    pub kind: GcodeMoveKind,
+    /// Feed rate in mm/min, if already set
+    pub feed_rate: Option<f64>,
+    /// Length of extruded filament in mm, negative for retractions
+    pub extrusion: f64,
+}
+
+//------------------------------------------------------------------------------
+
+/// Extruding moves of the same height, which are connected as polylines
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeLayer<P>
+where
+    P: IsBuildable3D,
+{
+    pub z: f64,
+    /// Each path consists of the start position followed by the extruded positions
+    pub paths: Vec<Polygon3D<P>>,
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeAbsolute {
+    Relative,
+    Absolute,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Linear,
+    ArcClockwise,
+    ArcCounterClockwise,
+}
+
+/// Plane of arcs, selected via G17, G18 and G19
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Plane {
+    XY,
+    ZX,
+    YZ,
+}
+
+impl Plane {
+    /// Indices of the two axes of the plane, followed by the one of the normal
+    pub fn axes(self) -> [usize; 3] {
+        match self {
+            Self::XY => [0, 1, 2],
+            Self::ZX => [2, 0, 1],
+            Self::YZ => [1, 2, 0],
+        }
+    }
+
+    /// Letters of the center offsets along the two axes of the plane
+    pub fn offset_letters(self) -> [u8; 2] {
+        match self {
+            Self::XY => [b'I', b'J'],
+            Self::ZX => [b'K', b'I'],
+            Self::YZ => [b'J', b'K'],
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Configuration for writing gcode
+/// Lengths are in mm, feed rates in mm/min
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcodeConfig {
+    /// Height of each layer, also defining the z of 2D layers
+    pub layer_height: f64,
+    pub extrusion_width: f64,
+    pub filament_diameter: f64,
+    pub print_feed_rate: f64,
+    pub travel_feed_rate: f64,
+    /// Filament retracted before travel moves, 0.0 to disable
+    pub retraction_length: f64,
+    pub retraction_feed_rate: f64,
+    /// Lift of the nozzle during travel moves, 0.0 to disable
+    pub z_hop: f64,
+    /// Whether paths end by returning to their start
+    pub close_paths: bool,
+}
+
+impl Default for GcodeConfig {
+    fn default() -> Self {
+        Self {
+            layer_height: 0.2,
+            extrusion_width: 0.4,
+            filament_diameter: 1.75,
+            print_feed_rate: 1800.0,
+            travel_feed_rate: 6000.0,
+            retraction_length: 1.0,
+            retraction_feed_rate: 2400.0,
+            z_hop: 0.0,
+            close_paths: true,
+        }
+    }
+}
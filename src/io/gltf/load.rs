@@ -25,19 +25,16 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 use crate::*;
 
 use std::{
-    cell::RefCell,
-    collections::{hash_map::Entry, HashMap},
     convert::TryFrom,
     fs::File,
-    io::{BufReader, Cursor, Read, Seek, SeekFrom},
+    io::{Read, Seek, SeekFrom},
     iter::FusedIterator,
     marker::PhantomData,
     path::PathBuf,
-    rc::Rc,
 };
 
 use super::{
-    super::{byte_reader::*, types::*, utils::*},
+    super::{byte_reader::*, types::*},
     types::*,
 };
 
@@ -60,23 +57,7 @@ where
 
     for chunk in iterator {
         for x in chunk? {
-            match x {
-                FaceDataReserve::Data(p) => {
-                    mesh.add_vertex(p);
-                }
-                FaceDataReserve::Face([a, b, c]) => {
-                    mesh.try_add_connection(VId(a), VId(b), VId(c))
-                        .or(Err(IOError::InvalidMeshIndices))?;
-                }
-                FaceDataReserve::ReserveDataFaces(n_vertices, n_faces) => {
-                    mesh.reserve_vertices(n_vertices);
-                    mesh.reserve_faces(n_faces);
-                }
-                FaceDataReserve::ReserveDataFacesExact(n_vertices, n_faces) => {
-                    mesh.reserve_vertices_exact(n_vertices);
-                    mesh.reserve_faces_exact(n_faces);
-                }
-            }
+            add_to_mesh(mesh, x)?;
         }
     }
 
@@ -98,280 +79,133 @@ where
 
     for chunk in iterator {
         for x in chunk? {
-            match x {
-                FaceDataReserve::Data(p) => {
-                    mesh.add_vertex(p);
-                }
-                FaceDataReserve::Face([a, b, c]) => {
-                    mesh.try_add_connection(VId(a), VId(b), VId(c))
-                        .or(Err(IOError::InvalidMeshIndices))?;
-                }
-                FaceDataReserve::ReserveDataFaces(n_vertices, n_faces) => {
-                    mesh.reserve_vertices(n_vertices);
-                    mesh.reserve_faces(n_faces);
-                }
-                FaceDataReserve::ReserveDataFacesExact(n_vertices, n_faces) => {
-                    mesh.reserve_vertices_exact(n_vertices);
-                    mesh.reserve_faces_exact(n_faces);
-                }
-            }
+            add_to_mesh(mesh, x)?;
         }
     }
 
     Ok(())
 }
 
-//------------------------------------------------------------------------------
-
-/// Iterator to incrementally load a .glTF or .glb file
-pub struct GltfIterator<P, R, const CHUNK_SIZE: usize>
+/// Loads an IsMesh3D from the glb file format
+/// The attributes of each vertex are pushed in the same order into attributes
+pub fn load_glb_with_attributes<EM, P, R, IPA, const CHUNK_SIZE: usize>(
+    read: R,
+    folder_path: PathBuf,
+    mesh: &mut EM,
+    attributes: &mut IPA,
+) -> IOResult<()>
 where
-    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3>,
+    P: IsBuildable3D + IsMatrix4Transformable + Clone + Default,
     R: Read + Seek,
+    IPA: IsPushable<GltfVertexAttributes>,
 {
-    chunk_offset: u64,
-    root: Root,
-    is_done: bool,
-    node_trace: Vec<usize>,
-    current_primitive: usize,
-    pf_iterator: PointFaceIterator<P, R, CHUNK_SIZE>,
-    phantom: PhantomData<P>,
+    let iterator = GltfAttributeIterator::<P, R, CHUNK_SIZE>::new_glb(read, folder_path)?;
+
+    for chunk in iterator {
+        for x in chunk? {
+            add_to_mesh_with_attributes(mesh, attributes, x)?;
+        }
+    }
+
+    Ok(())
 }
 
-impl<P, R, const CHUNK_SIZE: usize> GltfIterator<P, R, CHUNK_SIZE>
+/// Loads an IsMesh3D from the glTF file format
+/// The attributes of each vertex are pushed in the same order into attributes
+pub fn load_gltf_with_attributes<EM, P, R, IPA, const CHUNK_SIZE: usize>(
+    read: R,
+    folder_path: PathBuf,
+    mesh: &mut EM,
+    attributes: &mut IPA,
+) -> IOResult<()>
 where
-    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3>,
+    P: IsBuildable3D + IsMatrix4Transformable + Clone + Default,
     R: Read + Seek,
+    IPA: IsPushable<GltfVertexAttributes>,
 {
-    /// Creates an iterator for reading a .glb file
-    pub fn new_glb(mut read: R, folder_path: PathBuf) -> IOResult<Self> {
-        let _header = read_file_header(&mut read)?;
-        let pos_chunk_json = read.seek(SeekFrom::Current(0))?;
-        let chunk_json =
-            read_chunk(&mut read, pos_chunk_json).and_then(|x| JSONChunk::try_from(x))?;
-        let pos_chunk_bin = read.seek(SeekFrom::Current(0))? + 8; // +8 since two u32 are part of the header
-        let chunk_bin = read_chunk_header(&mut read, pos_chunk_bin)
-            .and_then(|x| BinChunkHeader::try_from(x))?;
-
-        let json = parse_json(&chunk_json)?;
-
-        let root = Root::new(&json)?;
-
-        let mut result = Self {
-            root,
-            chunk_offset: chunk_bin.pos,
-            is_done: false,
-            node_trace: vec![0],
-            current_primitive: 0,
-            pf_iterator: PointFaceIterator::new(read, folder_path),
-            phantom: PhantomData,
-        };
-
-        result.node_trace = result.decended_left(result.node_trace.clone());
-        result.fetch_data()?;
-
-        Ok(result)
-    }
-
-    /// Creates an iterator for reading a .glTF file
-    pub fn new_gltf(mut read: R, folder_path: PathBuf) -> IOResult<Self> {
-        let json: serde_json::Value = serde_json::from_reader(&mut read)?;
-        let root = Root::new(&json)?;
+    let iterator = GltfAttributeIterator::<P, R, CHUNK_SIZE>::new_gltf(read, folder_path)?;
 
-        let mut result = Self {
-            root,
-            chunk_offset: 0,
-            is_done: false,
-            node_trace: vec![0],
-            current_primitive: 0,
-            pf_iterator: PointFaceIterator::new(read, folder_path),
-            phantom: PhantomData,
-        };
-
-        result.node_trace = result.decended_left(result.node_trace.clone());
-        result.fetch_data()?;
-
-        Ok(result)
-    }
-
-    #[inline(always)]
-    fn current_node(&self) -> Option<&Node> {
-        self.node_of_trace(&self.node_trace)
-    }
-
-    #[inline(always)]
-    fn node_of_trace(&self, trace: &[usize]) -> Option<&Node> {
-        if trace.is_empty() {
-            None
-        } else if trace.len() == 1 {
-            self.root.root_nodes.get(self.node_trace[0])
-        } else {
-            Self::follow_trace(self.root.root_nodes.get(self.node_trace[0])?, &trace[1..])
+    for chunk in iterator {
+        for x in chunk? {
+            add_to_mesh_with_attributes(mesh, attributes, x)?;
         }
     }
 
-    #[inline(always)]
-    fn follow_trace<'a>(node: &'a Node, trace: &[usize]) -> Option<&'a Node> {
-        if trace.is_empty() {
-            Some(node)
-        } else {
-            match &node.mesh_or_children {
-                MeshOrChildren::Mesh(_) => None,
-                MeshOrChildren::Children(children) => {
-                    Self::follow_trace(children.get(trace[0])?, &trace[1..])
-                } //@todo recursion limit?
-            }
-        }
-    }
+    Ok(())
+}
 
-    #[inline(always)]
-    fn fetch_data(&mut self) -> IOResult<bool> {
-        if let (Some(current_node), Some(mesh)) = (
-            self.current_node(),
-            self.current_node().and_then(|x| x.mesh_or_children.mesh()),
-        ) {
-            if mesh.primitives.is_empty() {
-                return Ok(false);
-            }
-            let primitive = &mesh.primitives[self.current_primitive];
-
-            let acc_pos = &primitive.positions;
-            let bw_pos = &acc_pos.buffer_view;
-            let (uri_or_data_pos, offset_pos) = match &bw_pos.buffer.uri_or_data {
-                None => (None, self.chunk_offset),
-                Some(x) => (Some(x.clone()), 0),
-            };
-
-            let p_settings = PointIterSettings {
-                uri_or_data: uri_or_data_pos,
-                seek_start: offset_pos + acc_pos.byte_offset + bw_pos.byte_offset,
-                to_fetch: acc_pos.count as usize,
-                bytes_to_skip: if let Some(stride) = bw_pos.byte_stride {
-                    let size = 3 * 4;
-                    if stride < size {
-                        return Err(IOError::Gltf(GltfError::Stride));
-                    }
-                    (stride - size) as usize
-                } else {
-                    0
-                },
-                transformation: current_node.transformation.clone(),
-            };
-
-            let f_settings = if let Some(acc_id) = &primitive.indices {
-                let bw_id = &acc_id.buffer_view;
-                let ct = acc_id.component_type;
-
-                let (uri_or_data_id, offset_id) = match &bw_id.buffer.uri_or_data {
-                    None => (None, self.chunk_offset),
-                    Some(x) => (Some(x.clone()), 0),
-                };
+/// Loads an IsMesh3D from the glb file format, storing the NORMAL and COLOR_0 values as "normal" and "color" Attributes
+/// Channels are only created if any vertex has such a value, vertices without one get the default value
+pub fn load_glb_attributed<EM, P, R, const CHUNK_SIZE: usize>(
+    read: R,
+    folder_path: PathBuf,
+    mesh: &mut EM,
+) -> IOResult<()>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3> + HasVertexAttributes,
+    P: IsBuildable3D + IsMatrix4Transformable + Clone + Default,
+    R: Read + Seek,
+{
+    let n_before = mesh.vertex_attributes_mut().len();
+    let mut loaded = Vec::new();
+    load_glb_with_attributes::<_, _, _, _, CHUNK_SIZE>(read, folder_path, mesh, &mut loaded)?;
+    mesh.vertex_attributes_mut()
+        .write_at(n_before, into_attributes(loaded)?);
 
-                Some(FaceIterSettings {
-                    uri_or_data: uri_or_data_id,
-                    seek_start: offset_id + acc_id.byte_offset + bw_id.byte_offset,
-                    to_fetch: acc_id.count as usize / 3,
-                    bytes_to_skip: if let Some(stride) = bw_id.byte_stride {
-                        let size = match ct {
-                            IndexComponentType::U8 => 3 * 1,  // 3 * 1byte
-                            IndexComponentType::U16 => 3 * 2, // 3 * 2bytes
-                            IndexComponentType::U32 => 3 * 4, // 3 * 4bytes
-                        };
-                        if stride < size {
-                            return Err(IOError::Gltf(GltfError::Stride));
-                        }
-                        (stride - size) as usize
-                    } else {
-                        0
-                    },
-                    component_type: ct,
-                })
-            } else {
-                None
-            };
+    Ok(())
+}
 
-            self.pf_iterator.update(p_settings, f_settings)?;
-            Ok(true)
-        } else {
-            Ok(false)
-        }
-    }
+/// Loads an IsMesh3D from the glTF file format, storing the NORMAL and COLOR_0 values as "normal" and "color" Attributes
+/// Channels are only created if any vertex has such a value, vertices without one get the default value
+pub fn load_gltf_attributed<EM, P, R, const CHUNK_SIZE: usize>(
+    read: R,
+    folder_path: PathBuf,
+    mesh: &mut EM,
+) -> IOResult<()>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3> + HasVertexAttributes,
+    P: IsBuildable3D + IsMatrix4Transformable + Clone + Default,
+    R: Read + Seek,
+{
+    let n_before = mesh.vertex_attributes_mut().len();
+    let mut loaded = Vec::new();
+    load_gltf_with_attributes::<_, _, _, _, CHUNK_SIZE>(read, folder_path, mesh, &mut loaded)?;
+    mesh.vertex_attributes_mut()
+        .write_at(n_before, into_attributes(loaded)?);
 
-    #[inline(always)]
-    fn ensure_data_available(&mut self) -> IOResult<bool> {
-        if !self.pf_iterator.is_done() {
-            return Ok(true);
-        }
-        if self.next_primitive()? {
-            if self.fetch_data()? {
-                Ok(true)
-            } else {
-                self.ensure_data_available() //@todo take care of to deep recursion
-            }
-        } else {
-            Ok(false)
-        }
-    }
+    Ok(())
+}
 
-    #[inline(always)]
-    fn next_primitive(&mut self) -> IOResult<bool> {
-        if let Some(mesh) = self.current_node().and_then(|x| x.mesh_or_children.mesh()) {
-            let n_primitives = mesh.primitives.len();
-            if self.current_primitive + 1 < n_primitives {
-                self.current_primitive += 1;
-                Ok(true)
-            } else {
-                self.next_node()
-            }
-        } else {
-            self.next_node()
-        }
-    }
+//------------------------------------------------------------------------------
 
-    #[inline(always)]
-    fn decended_left(&self, mut trace: Vec<usize>) -> Vec<usize> {
-        if let Some(node) = self.node_of_trace(&trace) {
-            if !node
-                .mesh_or_children
-                .children()
-                .map(|x| x.is_empty())
-                .unwrap_or(true)
-            {
-                trace.push(0);
-                self.decended_left(trace)
-            } else {
-                trace
-            }
-        } else {
-            trace
-        }
-    }
+/// Iterator to incrementally load a .glTF or .glb file
+pub struct GltfIterator<P, R, const CHUNK_SIZE: usize>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+    inner: GltfAttributeIterator<P, R, CHUNK_SIZE>,
+}
 
-    #[inline(always)]
-    fn trace_for_next_node(&self, mut trace: Vec<usize>) -> Option<Vec<usize>> {
-        if trace.is_empty() {
-            None
-        } else {
-            let i = trace.len() - 1;
-            trace[i] += 1;
-            if self.node_of_trace(&trace).is_some() {
-                Some(self.decended_left(trace))
-            } else {
-                trace.pop();
-                self.trace_for_next_node(trace)
-            }
-        }
+impl<P, R, const CHUNK_SIZE: usize> GltfIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: Read + Seek,
+{
+    /// Creates an iterator for reading a .glb file
+    pub fn new_glb(read: R, folder_path: PathBuf) -> IOResult<Self> {
+        Ok(Self {
+            inner: GltfAttributeIterator::from_glb(read, folder_path, false)?,
+        })
     }
 
-    #[inline(always)]
-    fn next_node(&mut self) -> IOResult<bool> {
-        self.current_primitive = 0;
-        if let Some(trace) = self.trace_for_next_node(self.node_trace.clone()) {
-            self.node_trace = trace;
-            Ok(true)
-        } else {
-            Ok(false)
-        }
+    /// Creates an iterator for reading a .glTF file
+    pub fn new_gltf(read: R, folder_path: PathBuf) -> IOResult<Self> {
+        Ok(Self {
+            inner: GltfAttributeIterator::from_gltf(read, folder_path, false)?,
+        })
     }
 }
 
@@ -383,18 +217,23 @@ where
     type Item = IOResult<StackVec<FaceDataReserve<P>, CHUNK_SIZE>>;
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
-        if self.is_done {
-            return None;
-        }
-
-        match self.ensure_data_available() {
-            Ok(true) => self.pf_iterator.next(),
-            Ok(false) => {
-                self.is_done = true;
-                None
+        self.inner.next().map(|chunk| {
+            let mut result = StackVec::default();
+            for x in chunk? {
+                let x = match x {
+                    FaceDataReserve::Data((p, _)) => FaceDataReserve::Data(p),
+                    FaceDataReserve::Face(x) => FaceDataReserve::Face(x),
+                    FaceDataReserve::ReserveDataFaces(n_v, n_f) => {
+                        FaceDataReserve::ReserveDataFaces(n_v, n_f)
+                    }
+                    FaceDataReserve::ReserveDataFacesExact(n_v, n_f) => {
+                        FaceDataReserve::ReserveDataFacesExact(n_v, n_f)
+                    }
+                };
+                result.push(x).unwrap(); // unwrap safe since both have the same size
             }
-            Err(e) => return Some(Err(e)),
-        }
+            Ok(result)
+        })
     }
 }
 
@@ -407,255 +246,338 @@ where
 
 //------------------------------------------------------------------------------
 
-#[derive(Default, Debug)]
-struct PointIterSettings {
-    pub uri_or_data: Option<UriOrDataPointer>,
-    pub seek_start: u64,
-    pub to_fetch: usize,
-    pub bytes_to_skip: usize,
-    pub transformation: Option<Matrix4>,
-}
-
-#[derive(Default, Debug, Clone)]
-struct FaceIterSettings {
-    pub uri_or_data: Option<UriOrDataPointer>,
-    pub seek_start: u64,
-    pub to_fetch: usize,
-    pub bytes_to_skip: usize,
-    pub component_type: IndexComponentType,
-}
-
-struct PointFaceIterator<P, R, const CHUNK_SIZE: usize>
+/// Iterator to incrementally load a .glTF or .glb file together with the attributes of each vertex
+/// Walks all nodes of the scene, applying their transformations
+pub struct GltfAttributeIterator<P, R, const CHUNK_SIZE: usize>
 where
     P: IsBuildable3D + IsMatrix4Transformable + Default,
     R: Read + Seek,
 {
-    root_read: R,
+    read: R,
     folder_path: PathBuf,
-    uri_readers: HashMap<PathBuf, Rc<RefCell<FileOrDataPointer>>>,
-    p_settings: PointIterSettings,
-    f_settings: Option<FaceIterSettings>,
-    p_reader: Option<Rc<RefCell<FileOrDataPointer>>>,
-    f_reader: Option<Rc<RefCell<FileOrDataPointer>>>,
-    points_pushed: usize,
+    /// Start of the binary chunk, only available for .glb
+    chunk_offset: Option<u64>,
+    with_attributes: bool,
+    primitives: Vec<(Option<Matrix4>, Primitive)>,
+    i_primitive: usize,
+    data: PrimitiveData,
     index_offset: usize,
-    data_faces_to_reserve: [usize; 2],
     is_done: bool,
     phantom: PhantomData<P>,
 }
 
-impl<P, R, const CHUNK_SIZE: usize> PointFaceIterator<P, R, CHUNK_SIZE>
+impl<P, R, const CHUNK_SIZE: usize> GltfAttributeIterator<P, R, CHUNK_SIZE>
 where
     P: IsBuildable3D + IsMatrix4Transformable + Default,
     R: Read + Seek,
 {
-    pub fn new(root_read: R, folder_path: PathBuf) -> Self {
-        Self {
-            root_read,
+    /// Creates an iterator for reading a .glb file
+    pub fn new_glb(read: R, folder_path: PathBuf) -> IOResult<Self> {
+        Self::from_glb(read, folder_path, true)
+    }
+
+    /// Creates an iterator for reading a .glTF file
+    pub fn new_gltf(read: R, folder_path: PathBuf) -> IOResult<Self> {
+        Self::from_gltf(read, folder_path, true)
+    }
+
+    fn from_glb(mut read: R, folder_path: PathBuf, with_attributes: bool) -> IOResult<Self> {
+        let _header = read_file_header(&mut read)?;
+        let pos_chunk_json = read.stream_position()?;
+        let chunk_json = read_chunk(&mut read, pos_chunk_json).and_then(JSONChunk::try_from)?;
+        let pos_chunk_bin = read.stream_position()? + 8; // +8 since two u32 are part of the header
+        let chunk_bin =
+            read_chunk_header(&mut read, pos_chunk_bin).and_then(BinChunkHeader::try_from)?;
+
+        let json = parse_json(&chunk_json)?;
+
+        Self::from_json(
+            read,
+            folder_path,
+            &json,
+            Some(chunk_bin.pos),
+            with_attributes,
+        )
+    }
+
+    fn from_gltf(mut read: R, folder_path: PathBuf, with_attributes: bool) -> IOResult<Self> {
+        let json: serde_json::Value = serde_json::from_reader(&mut read)?;
+
+        Self::from_json(read, folder_path, &json, None, with_attributes)
+    }
+
+    fn from_json(
+        read: R,
+        folder_path: PathBuf,
+        json: &serde_json::Value,
+        chunk_offset: Option<u64>,
+        with_attributes: bool,
+    ) -> IOResult<Self> {
+        let root = Root::new(json)?;
+
+        Ok(Self {
+            read,
             folder_path,
-            uri_readers: HashMap::default(),
-            p_settings: Default::default(),
-            f_settings: Default::default(),
-            p_reader: None,
-            f_reader: None,
-            points_pushed: 0,
+            chunk_offset,
+            with_attributes,
+            primitives: root.primitives(),
+            i_primitive: 0,
+            data: PrimitiveData::default(),
             index_offset: 0,
-            data_faces_to_reserve: [0, 0],
             is_done: false,
             phantom: PhantomData,
-        }
+        })
     }
 
-    pub fn update(&mut self, p: PointIterSettings, f: Option<FaceIterSettings>) -> IOResult<()> {
-        self.p_settings = p;
-        self.f_settings = f;
-        self.index_offset = self.points_pushed;
-        self.data_faces_to_reserve = [
-            self.p_settings.to_fetch,
-            self.f_settings.as_ref().map(|x| x.to_fetch).unwrap_or(0),
-        ];
-
-        if self.p_settings.to_fetch != 0 {
-            self.seek_to_points()
-        } else {
-            self.seek_to_faces()
+    /// Loads the data of the next primitive, returns false if there is none
+    fn next_primitive(&mut self) -> IOResult<bool> {
+        if self.i_primitive >= self.primitives.len() {
+            return Ok(false);
         }
-    }
 
-    pub fn is_done(&self) -> bool {
-        self.p_settings.to_fetch == 0
-            && self.f_settings.as_ref().map(|x| x.to_fetch).unwrap_or(0) == 0
+        self.index_offset += self.data.n_vertices;
+
+        let (transformation, primitive) = self.primitives[self.i_primitive].clone();
+        self.i_primitive += 1;
+
+        let positions = self.read_accessor(&primitive.positions)?;
+        let n_vertices = primitive.positions.count as usize;
+
+        // Non-indexed geometry uses each three consecutive vertices as face
+        let indices: Vec<usize> = match &primitive.indices {
+            Some(x) => self
+                .read_accessor(x)?
+                .into_iter()
+                .map(|x| x as usize)
+                .collect(),
+            None => (0..n_vertices).collect(),
+        };
+
+        let (normals, tex_coords, colors) = if self.with_attributes {
+            (
+                self.read_optional_accessor(&primitive.normals)?,
+                self.read_optional_accessor(&primitive.tex_coords)?,
+                self.read_optional_accessor(&primitive.colors)?,
+            )
+        } else {
+            (None, None, None)
+        };
+
+        self.data = PrimitiveData {
+            n_vertices,
+            n_faces: indices.len() / 3,
+            i_vertex: 0,
+            i_face: 0,
+            is_reserved: false,
+            normal_transformation: transformation.as_ref().map(Matrix4::normal_transformation),
+            transformation,
+            positions,
+            indices,
+            normals,
+            tex_coords,
+            n_color_components: primitive
+                .colors
+                .map(|x| x.accessor_type.n_components())
+                .unwrap_or(0),
+            colors,
+        };
+
+        Ok(true)
     }
 
     #[inline(always)]
-    fn fetch_point<R2>(
-        read: &mut R2,
-        p_settings: &PointIterSettings,
-    ) -> IOResult<FaceDataReserve<P>>
-    where
-        R2: Read,
-    {
-        let x = LittleReader::read_f32(read)?;
-        let y = LittleReader::read_f32(read)?;
-        let z = LittleReader::read_f32(read)?;
-
-        if p_settings.to_fetch != 0 && p_settings.bytes_to_skip != 0 {
-            skip_bytes(read, p_settings.bytes_to_skip)?
-        }
-
-        let mut p = P::new(x as f64, y as f64, z as f64);
-        if let Some(t) = &p_settings.transformation {
+    fn fetch_vertex(&self) -> (P, GltfVertexAttributes) {
+        let data = &self.data;
+        let i = data.i_vertex;
+
+        let mut p = P::new(
+            data.positions[3 * i],
+            data.positions[3 * i + 1],
+            data.positions[3 * i + 2],
+        );
+        if let Some(t) = &data.transformation {
             p.transform(t)
         }
 
-        Ok(FaceDataReserve::Data(p))
+        let normal = data.normals.as_ref().and_then(|x| {
+            let mut n = Point3D::new(x[3 * i], x[3 * i + 1], x[3 * i + 2]);
+            if let Some(t) = &data.normal_transformation {
+                n.transform(t)
+            }
+            Norm3D::new(n).ok()
+        });
+
+        let tex_coord = data
+            .tex_coords
+            .as_ref()
+            .map(|x| Point2D::new(x[2 * i], x[2 * i + 1]));
+
+        let color = data.colors.as_ref().map(|x| {
+            let nc = data.n_color_components;
+            Rgb::new(
+                color_component(x[nc * i]),
+                color_component(x[nc * i + 1]),
+                color_component(x[nc * i + 2]),
+            )
+        });
+
+        (
+            p,
+            GltfVertexAttributes {
+                normal,
+                tex_coord,
+                color,
+            },
+        )
     }
 
     #[inline(always)]
-    fn fetch_face<R2>(
-        index_offset: usize,
-        read: &mut R2,
-        f_settings: &FaceIterSettings,
-    ) -> IOResult<FaceDataReserve<P>>
-    where
-        R2: Read,
-    {
-        let o = index_offset;
-
-        match f_settings.component_type {
-            IndexComponentType::U8 => {
-                let vid1 = LittleReader::read_u8(read)?;
-                let vid2 = LittleReader::read_u8(read)?;
-                let vid3 = LittleReader::read_u8(read)?;
-
-                if f_settings.to_fetch != 0 && f_settings.bytes_to_skip != 0 {
-                    skip_bytes(read, f_settings.bytes_to_skip)?
-                }
-
-                Ok(FaceDataReserve::Face([
-                    vid1 as usize + o,
-                    vid2 as usize + o,
-                    vid3 as usize + o,
-                ]))
-            }
-            IndexComponentType::U16 => {
-                let vid1 = LittleReader::read_u16(read)?;
-                let vid2 = LittleReader::read_u16(read)?;
-                let vid3 = LittleReader::read_u16(read)?;
+    fn fetch_face(&self) -> FaceDataReserve<(P, GltfVertexAttributes)> {
+        let o = self.index_offset;
+        let i = self.data.i_face;
+        let ids = &self.data.indices;
 
-                if f_settings.to_fetch != 0 && f_settings.bytes_to_skip != 0 {
-                    skip_bytes(read, f_settings.bytes_to_skip)?
-                }
+        FaceDataReserve::Face([ids[3 * i] + o, ids[3 * i + 1] + o, ids[3 * i + 2] + o])
+    }
 
-                Ok(FaceDataReserve::Face([
-                    vid1 as usize + o,
-                    vid2 as usize + o,
-                    vid3 as usize + o,
-                ]))
-            }
+    fn read_optional_accessor(
+        &mut self,
+        accessor: &Option<Accessor>,
+    ) -> IOResult<Option<Vec<f64>>> {
+        match accessor {
+            None => Ok(None),
+            Some(x) => Ok(Some(self.read_accessor(x)?)),
+        }
+    }
 
-            IndexComponentType::U32 => {
-                let vid1 = LittleReader::read_u32(read)?;
-                let vid2 = LittleReader::read_u32(read)?;
-                let vid3 = LittleReader::read_u32(read)?;
+    /// Reads all values of an accessor, applying sparse substitutions
+    fn read_accessor(&mut self, accessor: &Accessor) -> IOResult<Vec<f64>> {
+        self.read_accessor_values(accessor)
+            .map_err(|e| e.context(IOElement::Accessor, IOLocation::Index(accessor.id)))
+    }
 
-                if f_settings.to_fetch != 0 && f_settings.bytes_to_skip != 0 {
-                    skip_bytes(read, f_settings.bytes_to_skip)?
+    fn read_accessor_values(&mut self, accessor: &Accessor) -> IOResult<Vec<f64>> {
+        let n_components = accessor.accessor_type.n_components();
+        let count = accessor.count as usize;
+        let element_size = accessor.element_size();
+        let component_type = accessor.component_type;
+        let component_size = component_type.size();
+
+        let mut result = Vec::new();
+
+        match &accessor.buffer_view {
+            None => result.resize(count * n_components, 0.0),
+            Some(buffer_view) => {
+                let stride = buffer_view
+                    .byte_stride
+                    .map(|x| x as usize)
+                    .unwrap_or(element_size);
+                if stride < element_size {
+                    return Err(IOError::Gltf(GltfError::Stride));
                 }
 
-                Ok(FaceDataReserve::Face([
-                    vid1 as usize + o,
-                    vid2 as usize + o,
-                    vid3 as usize + o,
-                ]))
+                if count > 0 {
+                    let length = stride * (count - 1) + element_size;
+                    let bytes = self.read_buffer_view(buffer_view, accessor.byte_offset, length)?;
+                    result.reserve_exact(count * n_components);
+                    for i in 0..count {
+                        for j in 0..n_components {
+                            result.push(component_type.read(
+                                &bytes[i * stride + j * component_size..],
+                                accessor.normalized,
+                            ));
+                        }
+                    }
+                }
             }
         }
-    }
 
-    #[inline(always)]
-    fn seek_to_points(&mut self) -> IOResult<()> {
-        match &self.p_settings.uri_or_data {
-            None => {
-                self.root_read
-                    .seek(SeekFrom::Start(self.p_settings.seek_start))?;
-                self.p_reader = None;
-            }
-            Some(x) => match x {
-                UriOrDataPointer::DataPointer(x) => {
-                    self.p_reader = Some(Rc::new(RefCell::new(FileOrDataPointer::DataPointer(
-                        self.p_settings.seek_start,
-                        x.clone(),
-                    ))));
+        if let Some(sparse) = &accessor.sparse {
+            let n = sparse.count as usize;
+            let index_type = sparse.indices_component_type;
+            let index_size = index_type.size();
+
+            let indices = self.read_buffer_view(
+                &sparse.indices_buffer_view,
+                sparse.indices_byte_offset,
+                n * index_size,
+            )?;
+            let values = self.read_buffer_view(
+                &sparse.values_buffer_view,
+                sparse.values_byte_offset,
+                n * element_size,
+            )?;
+
+            for k in 0..n {
+                let id = index_type.read(&indices[k * index_size..], false) as usize;
+                if id >= count {
+                    return Err(IOError::Gltf(GltfError::SparseIndex));
                 }
-                UriOrDataPointer::Uri(x) => {
-                    let path = self.folder_path.join(x);
-                    let read = match self.uri_readers.entry(path.clone()) {
-                        Entry::Occupied(x) => x.into_mut(),
-                        Entry::Vacant(x) => {
-                            let entry =
-                                Rc::new(RefCell::new(FileOrDataPointer::File(BufReader::new(
-                                    File::open(path.clone())
-                                        .map_err(|_| IOError::Gltf(GltfError::BufferUriAccess))?,
-                                ))));
-                            x.insert(entry)
-                        }
-                    };
-
-                    read.borrow_mut().seek(self.p_settings.seek_start)?;
-                    self.p_reader = Some(read.clone());
+                for j in 0..n_components {
+                    result[id * n_components + j] = component_type.read(
+                        &values[k * element_size + j * component_size..],
+                        accessor.normalized,
+                    );
                 }
-            },
+            }
         }
 
-        Ok(())
+        Ok(result)
     }
 
-    #[inline(always)]
-    fn seek_to_faces(&mut self) -> IOResult<()> {
-        if let Some(f_settings) = &self.f_settings {
-            match &f_settings.uri_or_data {
-                None => {
-                    self.root_read
-                        .seek(SeekFrom::Start(f_settings.seek_start))?;
-                    self.f_reader = None;
-                }
-                Some(x) => match x {
-                    UriOrDataPointer::DataPointer(x) => {
-                        self.f_reader = Some(Rc::new(RefCell::new(
-                            FileOrDataPointer::DataPointer(f_settings.seek_start, x.clone()),
-                        )));
-                    }
-                    UriOrDataPointer::Uri(x) => {
-                        let path = self.folder_path.join(x);
-                        let read = match self.uri_readers.entry(path.clone()) {
-                            Entry::Occupied(x) => x.into_mut(),
-                            Entry::Vacant(x) => {
-                                let entry =
-                                    Rc::new(RefCell::new(FileOrDataPointer::File(BufReader::new(
-                                        File::open(path.clone()).map_err(|_| {
-                                            IOError::Gltf(GltfError::BufferUriAccess)
-                                        })?,
-                                    ))));
-                                x.insert(entry)
-                            }
-                        };
-
-                        read.borrow_mut().seek(f_settings.seek_start)?;
-                        self.f_reader = Some(read.clone());
-                    }
-                },
+    fn read_buffer_view(
+        &mut self,
+        buffer_view: &BufferView,
+        offset: u64,
+        length: usize,
+    ) -> IOResult<Vec<u8>> {
+        if offset + length as u64 > buffer_view.byte_length {
+            return Err(IOError::Gltf(GltfError::BufferRange));
+        }
+
+        self.read_buffer(
+            &buffer_view.buffer,
+            buffer_view.byte_offset + offset,
+            length,
+        )
+    }
+
+    fn read_buffer(&mut self, buffer: &Buffer, start: u64, length: usize) -> IOResult<Vec<u8>> {
+        if start + length as u64 > buffer.byte_length {
+            return Err(IOError::Gltf(GltfError::BufferRange));
+        }
+
+        match &buffer.uri_or_data {
+            // Binary chunk of .glb
+            None => {
+                let chunk_offset = self
+                    .chunk_offset
+                    .ok_or(IOError::Gltf(GltfError::BinChunk))?;
+                read_exact_at(&mut self.read, chunk_offset + start, length)
+            }
+            Some(UriOrDataPointer::DataPointer(x)) => {
+                let data = x.get()?;
+                let data = data.borrow();
+                data.get(start as usize..start as usize + length)
+                    .map(|x| x.to_vec())
+                    .ok_or(IOError::Gltf(GltfError::BufferRange))
+            }
+            Some(UriOrDataPointer::Uri(x)) => {
+                let mut file = File::open(self.folder_path.join(x))
+                    .map_err(|_| IOError::Gltf(GltfError::BufferUriAccess))?;
+                read_exact_at(&mut file, start, length)
             }
         }
-        Ok(())
     }
 }
 
-impl<P, R, const CHUNK_SIZE: usize> Iterator for PointFaceIterator<P, R, CHUNK_SIZE>
+impl<P, R, const CHUNK_SIZE: usize> Iterator for GltfAttributeIterator<P, R, CHUNK_SIZE>
 where
     P: IsBuildable3D + IsMatrix4Transformable + Default,
     R: Read + Seek,
 {
-    type Item = IOResult<StackVec<FaceDataReserve<P>, CHUNK_SIZE>>;
+    type Item = IOResult<StackVec<FaceDataReserve<(P, GltfVertexAttributes)>, CHUNK_SIZE>>;
+    #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
         if self.is_done {
             return None;
@@ -666,111 +588,43 @@ where
         loop {
             if chunk.is_full() {
                 return Some(Ok(chunk));
-            } else if self.data_faces_to_reserve != [0, 0] {
-                self.data_faces_to_reserve = [0, 0];
+            } else if !self.data.is_reserved {
+                self.data.is_reserved = true;
                 chunk
                     .push(FaceDataReserve::ReserveDataFaces(
-                        self.data_faces_to_reserve[0],
-                        self.data_faces_to_reserve[1],
+                        self.data.n_vertices,
+                        self.data.n_faces,
                     ))
                     .unwrap() // unwrap safe since we only call this if chunk.has_space()
-            } else if self.p_settings.to_fetch != 0 {
-                self.points_pushed += 1;
-                self.p_settings.to_fetch -= 1;
-
-                let result = match &self.p_reader {
-                    None => Self::fetch_point(&mut self.root_read, &self.p_settings),
-                    Some(r) => match &mut *r.borrow_mut() {
-                        FileOrDataPointer::DataPointer(seek, chunk) => {
-                            match chunk.get() {
-                                Err(e) => return Some(Err(e)),
-                                Ok(tmp) => {
-                                    let borrow = tmp.borrow();
-                                    let mut cursor = Cursor::new(&borrow[*seek as usize..]);
-                                    let res = Self::fetch_point(&mut cursor, &self.p_settings);
-                                    *seek = *seek + cursor.seek(SeekFrom::Current(0)).unwrap(); //safe, since not really moving the cursor
-                                    res
-                                }
-                            }
+            } else if self.data.i_vertex < self.data.n_vertices {
+                chunk
+                    .push(FaceDataReserve::Data(self.fetch_vertex()))
+                    .unwrap(); // unwrap safe since we only call this if chunk.has_space()
+                self.data.i_vertex += 1;
+            } else if self.data.i_face < self.data.n_faces {
+                chunk.push(self.fetch_face()).unwrap(); // unwrap safe since we only call this if chunk.has_space()
+                self.data.i_face += 1;
+            } else {
+                match self.next_primitive() {
+                    Ok(true) => (),
+                    Ok(false) => {
+                        self.is_done = true;
+                        if chunk.has_data() {
+                            return Some(Ok(chunk));
                         }
-                        FileOrDataPointer::File(r) => Self::fetch_point(r, &self.p_settings),
-                    },
-                };
-
-                if self.p_settings.to_fetch == 0 {
-                    match self.seek_to_faces() {
-                        Err(e) => return Some(Err(e)),
-                        Ok(_) => match result {
-                            Err(e) => return Some(Err(e)),
-                            Ok(x) => chunk.push(x).unwrap(), // unwrap safe since we only call this if chunk.has_space()
-                        },
+                        return None;
                     }
-                } else {
-                    match result {
-                        Err(e) => return Some(Err(e)),
-                        Ok(x) => chunk.push(x).unwrap(), // unwrap safe since we only call this if chunk.has_space(),
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e));
                     }
                 }
-            } else if let Some(f_settings) = &mut self.f_settings {
-                if f_settings.to_fetch != 0 {
-                    f_settings.to_fetch -= 1;
-
-                    match &self.f_reader {
-                        None => match Self::fetch_face(
-                            self.index_offset,
-                            &mut self.root_read,
-                            f_settings,
-                        ) {
-                            Err(e) => return Some(Err(e)),
-                            Ok(x) => chunk.push(x).unwrap(), // unwrap safe since we only call this if chunk.has_space()
-                        },
-                        Some(r) => match &mut *r.borrow_mut() {
-                            FileOrDataPointer::DataPointer(seek, dp) => {
-                                match dp.get() {
-                                    Err(e) => return Some(Err(e)),
-                                    Ok(tmp) => {
-                                        let borrow = tmp.borrow();
-                                        let mut cursor = Cursor::new(&borrow[*seek as usize..]);
-                                        let res = Self::fetch_face(
-                                            self.index_offset,
-                                            &mut cursor,
-                                            f_settings,
-                                        );
-                                        *seek = *seek + cursor.seek(SeekFrom::Current(0)).unwrap(); //safe, since not really moving the cursor
-                                        match res {
-                                            Err(e) => return Some(Err(e)),
-                                            Ok(x) => chunk.push(x).unwrap(), // unwrap safe since we only call this if chunk.has_space()
-                                        }
-                                    }
-                                }
-                            }
-                            FileOrDataPointer::File(r) => {
-                                match Self::fetch_point(r, &self.p_settings) {
-                                    Err(e) => return Some(Err(e)),
-                                    Ok(x) => chunk.push(x).unwrap(), // unwrap safe since we only call this if chunk.has_space()
-                                }
-                            }
-                        }, //unwrap safe, since inserting in seek
-                    }
-                } else {
-                    self.is_done = true;
-                    if chunk.has_data() {
-                        return Some(Ok(chunk));
-                    }
-                    return None;
-                }
-            } else {
-                self.is_done = true;
-                if chunk.has_data() {
-                    return Some(Ok(chunk));
-                }
-                return None;
             }
         }
     }
 }
 
-impl<P, R, const CHUNK_SIZE: usize> FusedIterator for PointFaceIterator<P, R, CHUNK_SIZE>
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for GltfAttributeIterator<P, R, CHUNK_SIZE>
 where
     P: IsBuildable3D + IsMatrix4Transformable + Default,
     R: Read + Seek,
@@ -779,6 +633,148 @@ where
 
 //------------------------------------------------------------------------------
 
+/// The decoded data of a single primitive
+struct PrimitiveData {
+    n_vertices: usize,
+    n_faces: usize,
+    i_vertex: usize,
+    i_face: usize,
+    is_reserved: bool,
+    transformation: Option<Matrix4>,
+    normal_transformation: Option<Matrix4>,
+    positions: Vec<f64>,
+    indices: Vec<usize>,
+    normals: Option<Vec<f64>>,
+    tex_coords: Option<Vec<f64>>,
+    colors: Option<Vec<f64>>,
+    n_color_components: usize,
+}
+
+impl Default for PrimitiveData {
+    fn default() -> Self {
+        Self {
+            n_vertices: 0,
+            n_faces: 0,
+            i_vertex: 0,
+            i_face: 0,
+            // Nothing to reserve before the first primitive is loaded
+            is_reserved: true,
+            transformation: None,
+            normal_transformation: None,
+            positions: Vec::new(),
+            indices: Vec::new(),
+            normals: None,
+            tex_coords: None,
+            colors: None,
+            n_color_components: 0,
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+fn add_to_mesh<EM, P>(mesh: &mut EM, x: FaceDataReserve<P>) -> IOResult<()>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3>,
+    P: IsBuildable3D,
+{
+    match x {
+        FaceDataReserve::Data(p) => {
+            mesh.add_vertex(p);
+        }
+        FaceDataReserve::Face([a, b, c]) => {
+            mesh.try_add_connection(VId(a), VId(b), VId(c))
+                .or(Err(IOError::InvalidMeshIndices))?;
+        }
+        FaceDataReserve::ReserveDataFaces(n_vertices, n_faces) => {
+            mesh.reserve_vertices(n_vertices);
+            mesh.reserve_faces(n_faces);
+        }
+        FaceDataReserve::ReserveDataFacesExact(n_vertices, n_faces) => {
+            mesh.reserve_vertices_exact(n_vertices);
+            mesh.reserve_faces_exact(n_faces);
+        }
+    }
+
+    Ok(())
+}
+
+fn add_to_mesh_with_attributes<EM, P, IPA>(
+    mesh: &mut EM,
+    attributes: &mut IPA,
+    x: FaceDataReserve<(P, GltfVertexAttributes)>,
+) -> IOResult<()>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3>,
+    P: IsBuildable3D,
+    IPA: IsPushable<GltfVertexAttributes>,
+{
+    match x {
+        FaceDataReserve::Data((p, a)) => {
+            mesh.add_vertex(p);
+            attributes.push(a);
+        }
+        FaceDataReserve::Face(x) => add_to_mesh(mesh, FaceDataReserve::Face(x))?,
+        FaceDataReserve::ReserveDataFaces(n_vertices, n_faces) => {
+            attributes.reserve(n_vertices);
+            add_to_mesh(mesh, FaceDataReserve::ReserveDataFaces(n_vertices, n_faces))?
+        }
+        FaceDataReserve::ReserveDataFacesExact(n_vertices, n_faces) => {
+            attributes.reserve_exact(n_vertices);
+            add_to_mesh(
+                mesh,
+                FaceDataReserve::ReserveDataFacesExact(n_vertices, n_faces),
+            )?
+        }
+    }
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+/// Converts the attributes of the loaded vertices into Attributes
+fn into_attributes(loaded: Vec<GltfVertexAttributes>) -> IOResult<Attributes> {
+    let mut attributes = Attributes::new(loaded.len());
+
+    if loaded.iter().any(|x| x.normal.is_some()) {
+        let normals: Vec<Norm3D> = loaded
+            .iter()
+            .map(|x| x.normal.clone().unwrap_or_else(Norm3D::default_attribute))
+            .collect();
+        attributes
+            .insert("normal", normals)
+            .or(Err(IOError::NormalArrayLength))?;
+    }
+    if loaded.iter().any(|x| x.color.is_some()) {
+        let colors: Vec<Rgb> = loaded
+            .iter()
+            .map(|x| x.color.clone().unwrap_or_else(Rgb::default_attribute))
+            .collect();
+        attributes
+            .insert("color", colors)
+            .or(Err(IOError::ColorArrayLength))?;
+    }
+
+    Ok(attributes)
+}
+
+fn color_component(x: f64) -> u8 {
+    (x.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn read_exact_at<R>(read: &mut R, start: u64, length: usize) -> IOResult<Vec<u8>>
+where
+    R: Read + Seek,
+{
+    read.seek(SeekFrom::Start(start))?;
+    let mut result = vec![0; length];
+    read.read_exact(&mut result)?;
+    Ok(result)
+}
+
+//------------------------------------------------------------------------------
+
 fn read_file_header<R>(read: &mut R) -> IOResult<FileHeader>
 where
     R: Read,
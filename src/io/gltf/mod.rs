@@ -23,6 +23,14 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //! Module for IO operations of the glTF file formats
 
 mod load;
+mod save;
 mod types;
 
-pub use load::{load_glb, load_gltf, GltfIterator};
+pub use load::{
+    load_glb, load_glb_attributed, load_glb_with_attributes, load_gltf, load_gltf_attributed,
+    load_gltf_with_attributes, GltfAttributeIterator, GltfIterator,
+};
+pub use save::{save_glb, save_gltf};
+pub use types::GltfVertexAttributes;
+
+pub(crate) use types::VALID_MAGIC;
@@ -0,0 +1,275 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//! Module for save operations of the glTF file formats
+
+use crate::*;
+
+use std::{convert::TryFrom, io::Write};
+
+use super::{super::types::*, types::*};
+
+use base64::encode;
+use serde_json::json;
+
+//------------------------------------------------------------------------------
+
+const COMPONENT_TYPE_U8: u32 = 5121;
+const COMPONENT_TYPE_U32: u32 = 5125;
+const COMPONENT_TYPE_F32: u32 = 5126;
+
+const TARGET_ARRAY_BUFFER: u32 = 34962;
+const TARGET_ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+//------------------------------------------------------------------------------
+
+/// Saves an IsMesh3D in the .glTF file format, the binary data is embedded as base64
+/// Normals and colors are optional and must have one entry per vertex
+pub fn save_gltf<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    normals: Option<&[Norm3D]>,
+    colors: Option<&[Rgb]>,
+) -> IOResult<()>
+where
+    M: IsMesh3D<P>,
+    P: IsBuildable3D,
+    W: Write,
+{
+    let (mut json, buffer) = to_json_and_buffer(mesh, normals, colors)?;
+
+    json["buffers"] = json!([{
+        "byteLength": buffer.len(),
+        "uri": BASE64_OCTET_STREAM.to_string() + &encode(&buffer)
+    }]);
+
+    serde_json::to_writer(&mut *write, &json)?;
+
+    Ok(())
+}
+
+/// Saves an IsMesh3D in the .glb file format
+/// Normals and colors are optional and must have one entry per vertex
+pub fn save_glb<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    normals: Option<&[Norm3D]>,
+    colors: Option<&[Rgb]>,
+) -> IOResult<()>
+where
+    M: IsMesh3D<P>,
+    P: IsBuildable3D,
+    W: Write,
+{
+    let (mut json, mut buffer) = to_json_and_buffer(mesh, normals, colors)?;
+
+    json["buffers"] = json!([{ "byteLength": buffer.len() }]);
+
+    // Chunks must be aligned to 4 bytes, JSON is padded with spaces and the binary data with zeros
+    let mut json_data = serde_json::to_vec(&json)?;
+    while json_data.len() % 4 != 0 {
+        json_data.push(b' ');
+    }
+    while buffer.len() % 4 != 0 {
+        buffer.push(0);
+    }
+
+    // file header + 2 chunk headers
+    let length = to_u32(12 + 8 + json_data.len() + 8 + buffer.len())?;
+
+    write.write_all(&VALID_MAGIC.to_le_bytes())?;
+    write.write_all(&VALID_VERSION.to_le_bytes())?;
+    write.write_all(&length.to_le_bytes())?;
+
+    write.write_all(&to_u32(json_data.len())?.to_le_bytes())?;
+    write.write_all(&TYPE_JSON.to_le_bytes())?;
+    write.write_all(&json_data)?;
+
+    write.write_all(&to_u32(buffer.len())?.to_le_bytes())?;
+    write.write_all(&TYPE_BIN.to_le_bytes())?;
+    write.write_all(&buffer)?;
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+/// Creates the JSON description (without buffers) and the binary buffer of a mesh
+fn to_json_and_buffer<M, P>(
+    mesh: &M,
+    normals: Option<&[Norm3D]>,
+    colors: Option<&[Rgb]>,
+) -> IOResult<(serde_json::Value, Vec<u8>)>
+where
+    M: IsMesh3D<P>,
+    P: IsBuildable3D,
+{
+    let n_vertices = mesh.num_vertices();
+    let n_faces = mesh.num_faces();
+
+    // Accessors must have a count of at least 1
+    if n_vertices == 0 || n_faces == 0 {
+        return Err(IOError::Gltf(GltfError::EmptyMesh));
+    }
+
+    if let Some(normals) = normals {
+        if normals.len() != n_vertices {
+            return Err(IOError::NormalArrayLength);
+        }
+    }
+
+    if let Some(colors) = colors {
+        if colors.len() != n_vertices {
+            return Err(IOError::ColorArrayLength);
+        }
+    }
+
+    let mut buffer = Vec::with_capacity(
+        n_vertices * (12 + if normals.is_some() { 12 } else { 0 })
+            + n_vertices * if colors.is_some() { 4 } else { 0 }
+            + n_faces * 12,
+    );
+    let mut buffer_views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut attributes = serde_json::Map::new();
+
+    // Positions
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    let start = buffer.len();
+    for i in 0..n_vertices {
+        let v = mesh.vertex(VId(i)).unwrap(); // safe since iterating n_vertices
+        let xyz = [v.x() as f32, v.y() as f32, v.z() as f32];
+        for j in 0..3 {
+            min[j] = min[j].min(xyz[j]);
+            max[j] = max[j].max(xyz[j]);
+            buffer.extend_from_slice(&xyz[j].to_le_bytes());
+        }
+    }
+    attributes.insert("POSITION".to_string(), json!(accessors.len()));
+    accessors.push(json!({
+        "bufferView": buffer_views.len(),
+        "componentType": COMPONENT_TYPE_F32,
+        "count": n_vertices,
+        "type": "VEC3",
+        "min": min,
+        "max": max
+    }));
+    buffer_views.push(buffer_view(start, buffer.len(), TARGET_ARRAY_BUFFER));
+
+    // Normals
+    if let Some(normals) = normals {
+        let start = buffer.len();
+        for n in normals {
+            buffer.extend_from_slice(&(n.x() as f32).to_le_bytes());
+            buffer.extend_from_slice(&(n.y() as f32).to_le_bytes());
+            buffer.extend_from_slice(&(n.z() as f32).to_le_bytes());
+        }
+        attributes.insert("NORMAL".to_string(), json!(accessors.len()));
+        accessors.push(json!({
+            "bufferView": buffer_views.len(),
+            "componentType": COMPONENT_TYPE_F32,
+            "count": n_vertices,
+            "type": "VEC3"
+        }));
+        buffer_views.push(buffer_view(start, buffer.len(), TARGET_ARRAY_BUFFER));
+    }
+
+    // Colors, stored as normalized VEC4 to keep the 4 byte alignment of vertex attributes
+    if let Some(colors) = colors {
+        let start = buffer.len();
+        for c in colors {
+            buffer.extend_from_slice(&[c.r, c.g, c.b, 255]);
+        }
+        attributes.insert("COLOR_0".to_string(), json!(accessors.len()));
+        accessors.push(json!({
+            "bufferView": buffer_views.len(),
+            "componentType": COMPONENT_TYPE_U8,
+            "normalized": true,
+            "count": n_vertices,
+            "type": "VEC4"
+        }));
+        buffer_views.push(buffer_view(start, buffer.len(), TARGET_ARRAY_BUFFER));
+    }
+
+    let mut primitive = json!({
+        "attributes": attributes,
+        "mode": 4
+    });
+
+    // Indices
+    let mut min = u32::MAX;
+    let mut max = 0;
+    let start = buffer.len();
+    for i in 0..n_faces {
+        let f = mesh.face_vertex_ids(FId(i)).unwrap(); // safe since iterating n_faces
+        for vid in [f.a, f.b, f.c].iter() {
+            let id = to_u32(vid.0)?;
+            min = min.min(id);
+            max = max.max(id);
+            buffer.extend_from_slice(&id.to_le_bytes());
+        }
+    }
+    primitive["indices"] = json!(accessors.len());
+    accessors.push(json!({
+        "bufferView": buffer_views.len(),
+        "componentType": COMPONENT_TYPE_U32,
+        "count": 3 * n_faces,
+        "type": "SCALAR",
+        "min": [min],
+        "max": [max]
+    }));
+    buffer_views.push(buffer_view(
+        start,
+        buffer.len(),
+        TARGET_ELEMENT_ARRAY_BUFFER,
+    ));
+
+    let json = json!({
+        "asset": {
+            "version": "2.0",
+            "generator": "rust-3d"
+        },
+        "scene": 0,
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0 }],
+        "meshes": [{ "primitives": [primitive] }],
+        "accessors": accessors,
+        "bufferViews": buffer_views
+    });
+
+    Ok((json, buffer))
+}
+
+fn buffer_view(start: usize, end: usize, target: u32) -> serde_json::Value {
+    json!({
+        "buffer": 0,
+        "byteOffset": start,
+        "byteLength": end - start,
+        "target": target
+    })
+}
+
+/// Indices and chunk lengths are stored as u32, larger values can't be represented
+fn to_u32(x: usize) -> IOResult<u32> {
+    u32::try_from(x).map_err(|_| IOError::Gltf(GltfError::ExceedsU32))
+}
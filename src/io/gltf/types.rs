@@ -24,15 +24,7 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 //------------------------------------------------------------------------------
 
-use std::{
-    cell::RefCell,
-    collections::HashSet,
-    convert::TryFrom,
-    fs::File,
-    io::{BufReader, Seek, SeekFrom},
-    path::PathBuf,
-    rc::Rc,
-};
+use std::{cell::RefCell, collections::HashSet, convert::TryFrom, path::PathBuf, rc::Rc};
 
 use super::super::types::*;
 
@@ -42,9 +34,21 @@ use base64::decode;
 
 //------------------------------------------------------------------------------
 
-const BASE64_OCTET_STREAM: &str = "data:application/octet-stream;base64,";
+pub const BASE64_OCTET_STREAM: &str = "data:application/octet-stream;base64,";
 const BASE64_GLTF_BUFFER: &str = "data:application/gltf-buffer;base64,";
 
+const MAX_NODE_DEPTH: usize = 256;
+
+//------------------------------------------------------------------------------
+
+/// The optional attributes of a vertex of a .glTF/.glb file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GltfVertexAttributes {
+    pub normal: Option<Norm3D>,
+    pub tex_coord: Option<Point2D>,
+    pub color: Option<Rgb>,
+}
+
 //------------------------------------------------------------------------------
 
 #[derive(Debug)]
@@ -62,7 +66,7 @@ impl Root {
         let meshes = val
             .get("meshes")
             .and_then(|x| x.as_array())
-            .ok_or(IOError::Gltf(GltfError::JSONAccessors))?;
+            .ok_or(IOError::Gltf(GltfError::JSONMeshes))?;
 
         let accessors = val
             .get("accessors")
@@ -87,27 +91,62 @@ impl Root {
             buffers,
         };
 
+        let root_ids = Self::scene_node_ids(val).unwrap_or_else(|| Self::unreferenced_ids(nodes));
+
+        let mut root_nodes = Vec::new();
+        let mut path = Vec::new();
+        for id in root_ids {
+            if let Some(n) = Node::new(&arrays, id, &None, &mut path)? {
+                root_nodes.push(n)
+            }
+        }
+
+        Ok(Self { root_nodes })
+    }
+
+    /// Walks the node hierarchy, returning all primitives together with their accumulated transformation
+    pub fn primitives(&self) -> Vec<(Option<Matrix4>, Primitive)> {
+        let mut result = Vec::new();
+        for node in self.root_nodes.iter() {
+            node.collect_primitives(&mut result);
+        }
+        result
+    }
+
+    /// Root nodes of the default scene
+    fn scene_node_ids(val: &serde_json::Value) -> Option<Vec<usize>> {
+        let scene_id = val.get("scene").and_then(|x| x.as_u64()).unwrap_or(0);
+        let scene_nodes = val
+            .get("scenes")
+            .and_then(|x| x.as_array())
+            .and_then(|x| x.get(scene_id as usize))
+            .and_then(|x| x.get("nodes"))
+            .and_then(|x| x.as_array())?;
+
+        Some(
+            scene_nodes
+                .iter()
+                .filter_map(|x| x.as_u64().map(|x| x as usize))
+                .collect(),
+        )
+    }
+
+    /// All nodes that aren't referenced as children, used if no scene is defined
+    fn unreferenced_ids(nodes: &[serde_json::Value]) -> Vec<usize> {
         let mut child_nodes = HashSet::new();
         for node in nodes {
             if let Some(children) = node.get("children").and_then(|x| x.as_array()) {
                 for child in children {
                     if let Some(id) = child.as_u64() {
-                        child_nodes.insert(id);
+                        child_nodes.insert(id as usize);
                     }
                 }
             }
         }
 
-        let mut root_nodes = Vec::new();
-        for (id, node) in nodes.iter().enumerate() {
-            if !child_nodes.contains(&(id as u64)) {
-                if let Some(n) = Node::new(&arrays, node, &None) {
-                    root_nodes.push(n)
-                }
-            }
-        }
-
-        Ok(Self { root_nodes })
+        (0..nodes.len())
+            .filter(|id| !child_nodes.contains(id))
+            .collect()
     }
 }
 
@@ -240,22 +279,6 @@ pub enum MeshOrChildren {
     Children(Vec<Node>),
 }
 
-impl MeshOrChildren {
-    pub fn mesh(&self) -> Option<&Mesh> {
-        match self {
-            MeshOrChildren::Mesh(x) => Some(&x),
-            MeshOrChildren::Children(_) => None,
-        }
-    }
-
-    pub fn children(&self) -> Option<&Vec<Node>> {
-        match self {
-            MeshOrChildren::Mesh(_) => None,
-            MeshOrChildren::Children(x) => Some(&x),
-        }
-    }
-}
-
 //------------------------------------------------------------------------------
 
 #[derive(Debug)]
@@ -265,11 +288,27 @@ pub struct Node {
 }
 
 impl Node {
+    /// Creates the node with the given id, path contains the ids of all nodes from the root to the parent
     pub fn new(
         arrays: &JSONArrays,
-        val: &serde_json::Value,
+        id: usize,
         parent_transformation: &Option<Matrix4>,
-    ) -> Option<Self> {
+        path: &mut Vec<usize>,
+    ) -> IOResult<Option<Self>> {
+        // Malformed files might contain cycles
+        if path.contains(&id) {
+            return Err(IOError::Gltf(GltfError::NodeCycle));
+        }
+
+        if path.len() > MAX_NODE_DEPTH {
+            return Err(IOError::Gltf(GltfError::NodeDepth));
+        }
+
+        let val = match arrays.nodes.get(id) {
+            Some(val) => val,
+            None => return Ok(None),
+        };
+
         let transformations = Transformations::new(val);
 
         let transformation = match (parent_transformation, transformations.transformation()) {
@@ -279,11 +318,14 @@ impl Node {
             (Some(parent), Some(ref this)) => Some(parent * this),
         };
 
-        let mut children = Self::read_children(arrays, val, &transformation);
+        path.push(id);
+        let children = Self::read_children(arrays, val, &transformation, path);
+        path.pop();
+        let mut children = children?;
         let mesh = Self::read_mesh(arrays, val);
 
         // Simplification, just treat the Mesh as another child node
-        match (mesh, children.is_empty()) {
+        Ok(match (mesh, children.is_empty()) {
             (None, true) => None,
             (None, false) => Some(Self {
                 transformation,
@@ -300,7 +342,7 @@ impl Node {
                     mesh_or_children: MeshOrChildren::Children(children),
                 })
             }
-        }
+        })
     }
 
     fn new_from_mesh(mesh: Mesh, transformation: Option<Matrix4>) -> Self {
@@ -310,28 +352,40 @@ impl Node {
         }
     }
 
-    //@todo this could cause endless recursion in case the file is malformed
+    /// Adds all primitives of this node and its children
+    pub fn collect_primitives(&self, result: &mut Vec<(Option<Matrix4>, Primitive)>) {
+        match &self.mesh_or_children {
+            MeshOrChildren::Mesh(mesh) => {
+                for primitive in mesh.primitives.iter() {
+                    result.push((self.transformation.clone(), primitive.clone()))
+                }
+            }
+            MeshOrChildren::Children(children) => {
+                for child in children.iter() {
+                    child.collect_primitives(result)
+                }
+            }
+        }
+    }
+
     fn read_children(
         arrays: &JSONArrays,
         val: &serde_json::Value,
         parent_transformation: &Option<Matrix4>,
-    ) -> Vec<Node> {
+        path: &mut Vec<usize>,
+    ) -> IOResult<Vec<Node>> {
         let mut result = Vec::new();
         if let Some(children) = val.get("children").and_then(|x| x.as_array()) {
             for child in children {
                 if let Some(id) = child.as_u64() {
-                    if let Some(n) = arrays
-                        .nodes
-                        .get(id as usize)
-                        .and_then(|node_val| Node::new(arrays, node_val, parent_transformation))
-                    {
+                    if let Some(n) = Node::new(arrays, id as usize, parent_transformation, path)? {
                         result.push(n)
                     }
                 }
             }
         }
 
-        result
+        Ok(result)
     }
 
     fn read_mesh(arrays: &JSONArrays, val: &serde_json::Value) -> Option<Mesh> {
@@ -363,10 +417,9 @@ impl Mesh {
         let mut primitives = Vec::new();
         for primitive_val in primitives_array.iter() {
             // Ignoring invalid primitives
-            match Primitive::new(arrays, primitive_val) {
-                Ok(x) => primitives.push(x),
-                Err(_) => (),
-            };
+            if let Ok(x) = Primitive::new(arrays, primitive_val) {
+                primitives.push(x)
+            }
         }
 
         Ok(Self { primitives })
@@ -377,34 +430,80 @@ impl Mesh {
 
 #[derive(Debug, Clone)]
 pub struct Primitive {
-    //@todo consider storing normal id as well
-    pub positions: PosAccessor,
-    pub indices: Option<IndexAccessor>,
+    pub positions: Accessor,
+    pub indices: Option<Accessor>,
+    pub normals: Option<Accessor>,
+    pub tex_coords: Option<Accessor>,
+    pub colors: Option<Accessor>,
 }
 
 impl Primitive {
     pub fn new(arrays: &JSONArrays, val: &serde_json::Value) -> IOResult<Self> {
         let mode = val.get("mode").and_then(|x| x.as_u64()).unwrap_or(4);
-        if mode == 4 {
+        if mode != 4 {
             // TRIANGLES
-            let attributes = val
-                .get("attributes")
-                .ok_or(IOError::Gltf(GltfError::JSONAttributes))?;
-            let positions_id = attributes
-                .get("POSITION")
-                .and_then(|x| x.as_u64())
-                .ok_or(IOError::Gltf(GltfError::JSONPosition))?;
-            let indices_id = val.get("indices").and_then(|x| x.as_u64());
-            let positions = Accessor::new(arrays, &arrays.accessors[positions_id as usize])
-                .and_then(|x| PosAccessor::new(x))?;
-            let indices = indices_id
-                .and_then(|x| Accessor::new(arrays, &arrays.accessors[x as usize]).ok())
-                .and_then(|x| IndexAccessor::new(x).ok());
-
-            Ok(Self { positions, indices })
-        } else {
-            Err(IOError::Gltf(GltfError::PrimitiveMode4Only))
+            return Err(IOError::Gltf(GltfError::PrimitiveMode4Only));
+        }
+
+        let attributes = val
+            .get("attributes")
+            .ok_or(IOError::Gltf(GltfError::JSONAttributes))?;
+
+        let positions = attributes
+            .get("POSITION")
+            .and_then(|x| x.as_u64())
+            .ok_or(IOError::Gltf(GltfError::JSONPosition))
+            .and_then(|x| Accessor::new_by_id(arrays, x))?;
+
+        if positions.accessor_type != AccessorType::Vec3 {
+            return Err(IOError::Gltf(GltfError::PosAccessorType));
+        }
+        if positions.component_type != ComponentType::F32 {
+            return Err(IOError::Gltf(GltfError::PosComponentType));
         }
+
+        let indices = match val.get("indices").and_then(|x| x.as_u64()) {
+            None => None,
+            Some(id) => {
+                let indices = Accessor::new_by_id(arrays, id)?;
+                if indices.accessor_type != AccessorType::Scalar {
+                    return Err(IOError::Gltf(GltfError::IndexAccessorType));
+                }
+                IndexComponentType::new(indices.component_type)?;
+                Some(indices)
+            }
+        };
+
+        // Invalid optional attributes are ignored
+        let normals = Self::attribute(arrays, attributes, "NORMAL", &[AccessorType::Vec3]);
+        let tex_coords = Self::attribute(arrays, attributes, "TEXCOORD_0", &[AccessorType::Vec2]);
+        let colors = Self::attribute(
+            arrays,
+            attributes,
+            "COLOR_0",
+            &[AccessorType::Vec3, AccessorType::Vec4],
+        );
+
+        Ok(Self {
+            positions,
+            indices,
+            normals,
+            tex_coords,
+            colors,
+        })
+    }
+
+    fn attribute(
+        arrays: &JSONArrays,
+        attributes: &serde_json::Value,
+        name: &str,
+        valid_types: &[AccessorType],
+    ) -> Option<Accessor> {
+        attributes
+            .get(name)
+            .and_then(|x| x.as_u64())
+            .and_then(|x| Accessor::new_by_id(arrays, x).ok())
+            .filter(|x| valid_types.contains(&x.accessor_type))
     }
 }
 
@@ -430,12 +529,6 @@ impl IndexComponentType {
     }
 }
 
-impl Default for IndexComponentType {
-    fn default() -> Self {
-        Self::U8
-    }
-}
-
 //------------------------------------------------------------------------------
 
 #[derive(Debug, PartialEq, Clone, Copy)]
@@ -460,6 +553,33 @@ impl ComponentType {
             _ => Err(IOError::Gltf(GltfError::ComponentType)),
         }
     }
+
+    /// Size in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::U32 | Self::F32 => 4,
+        }
+    }
+
+    /// Reads a single value, mapping it to [0, 1] or [-1, 1] if normalized
+    pub fn read(&self, bytes: &[u8], normalized: bool) -> f64 {
+        match (self, normalized) {
+            (Self::I8, false) => bytes[0] as i8 as f64,
+            (Self::I8, true) => (bytes[0] as i8 as f64 / 127.0).max(-1.0),
+            (Self::U8, false) => bytes[0] as f64,
+            (Self::U8, true) => bytes[0] as f64 / 255.0,
+            (Self::I16, false) => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            (Self::I16, true) => {
+                (i16::from_le_bytes([bytes[0], bytes[1]]) as f64 / 32767.0).max(-1.0)
+            }
+            (Self::U16, false) => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            (Self::U16, true) => u16::from_le_bytes([bytes[0], bytes[1]]) as f64 / 65535.0,
+            (Self::U32, _) => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            (Self::F32, _) => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+        }
+    }
 }
 
 //------------------------------------------------------------------------------
@@ -488,31 +608,66 @@ impl AccessorType {
             _ => Err(IOError::Gltf(GltfError::AccessorType)),
         }
     }
+
+    /// Number of components per element
+    pub fn n_components(&self) -> usize {
+        match self {
+            Self::Scalar => 1,
+            Self::Vec2 => 2,
+            Self::Vec3 => 3,
+            Self::Vec4 | Self::Mat2 => 4,
+            Self::Mat3 => 9,
+            Self::Mat4 => 16,
+        }
+    }
 }
 
 //------------------------------------------------------------------------------
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Accessor {
-    pub buffer_view: BufferView,
+    /// Index within the accessors of the file
+    pub id: usize,
+    /// Without buffer view all values are zero, unless replaced by sparse values
+    pub buffer_view: Option<BufferView>,
     pub byte_offset: u64,
     pub component_type: ComponentType,
+    pub normalized: bool,
     pub count: u64,
     pub accessor_type: AccessorType,
+    pub sparse: Option<Sparse>,
 }
 
 impl Accessor {
-    pub fn new(arrays: &JSONArrays, val: &serde_json::Value) -> IOResult<Self> {
-        let buffer_view_id = val
-            .get("bufferView")
-            .and_then(|x| x.as_u64())
-            .ok_or(IOError::Gltf(GltfError::JSONBufferView))?;
+    pub fn new_by_id(arrays: &JSONArrays, id: u64) -> IOResult<Self> {
+        let id = id as usize;
+        arrays
+            .accessors
+            .get(id)
+            .ok_or(IOError::Gltf(GltfError::JSONAccessors))
+            .and_then(|x| Self::new(arrays, id, x))
+            .map_err(|e| e.context(IOElement::Accessor, IOLocation::Index(id)))
+    }
+
+    pub fn new(arrays: &JSONArrays, id: usize, val: &serde_json::Value) -> IOResult<Self> {
+        let buffer_view = match val.get("bufferView") {
+            None => None,
+            Some(x) => Some(
+                x.as_u64()
+                    .ok_or(IOError::Gltf(GltfError::JSONBufferView))
+                    .and_then(|x| BufferView::new_by_id(arrays, x))?,
+            ),
+        };
         let byte_offset = val.get("byteOffset").and_then(|x| x.as_u64()).unwrap_or(0);
         let component_type = val
             .get("componentType")
             .and_then(|x| x.as_u64())
             .ok_or(IOError::Gltf(GltfError::JSONComponentType))
-            .and_then(|x| ComponentType::new(x))?;
+            .and_then(ComponentType::new)?;
+        let normalized = val
+            .get("normalized")
+            .and_then(|x| x.as_bool())
+            .unwrap_or(false);
         let count = val
             .get("count")
             .and_then(|x| x.as_u64())
@@ -521,68 +676,88 @@ impl Accessor {
             .get("type")
             .and_then(|x| x.as_str())
             .ok_or(IOError::Gltf(GltfError::JSONAccessorType))
-            .and_then(|x| AccessorType::new(x))?;
-
-        let buffer_view = BufferView::new(arrays, &arrays.buffer_views[buffer_view_id as usize])?;
+            .and_then(AccessorType::new)?;
+        let sparse = match val.get("sparse") {
+            None => None,
+            Some(x) => Some(Sparse::new(arrays, x)?),
+        };
 
         Ok(Self {
+            id,
             buffer_view,
             byte_offset,
             component_type,
+            normalized,
             count,
             accessor_type,
+            sparse,
         })
     }
-}
-
-//------------------------------------------------------------------------------
-
-#[derive(Debug, Clone)]
-pub struct IndexAccessor {
-    pub buffer_view: BufferView,
-    pub byte_offset: u64,
-    pub component_type: IndexComponentType,
-    pub count: u64,
-}
-
-impl IndexAccessor {
-    pub fn new(accessor: Accessor) -> IOResult<Self> {
-        if accessor.accessor_type != AccessorType::Scalar {
-            return Err(IOError::Gltf(GltfError::IndexAccessorType));
-        }
-        let component_type = IndexComponentType::new(accessor.component_type)?;
 
-        Ok(Self {
-            buffer_view: accessor.buffer_view,
-            byte_offset: accessor.byte_offset,
-            component_type,
-            count: accessor.count,
-        })
+    /// Size of a single element in bytes
+    pub fn element_size(&self) -> usize {
+        self.accessor_type.n_components() * self.component_type.size()
     }
 }
 
 //------------------------------------------------------------------------------
 
 #[derive(Debug, Clone)]
-pub struct PosAccessor {
-    pub buffer_view: BufferView,
-    pub byte_offset: u64,
+pub struct Sparse {
     pub count: u64,
+    pub indices_buffer_view: BufferView,
+    pub indices_byte_offset: u64,
+    pub indices_component_type: ComponentType,
+    pub values_buffer_view: BufferView,
+    pub values_byte_offset: u64,
 }
 
-impl PosAccessor {
-    pub fn new(accessor: Accessor) -> IOResult<Self> {
-        if accessor.accessor_type != AccessorType::Vec3 {
-            return Err(IOError::Gltf(GltfError::PosAccessorType));
-        }
-        if accessor.component_type != ComponentType::F32 {
-            return Err(IOError::Gltf(GltfError::PosComponentType));
-        }
+impl Sparse {
+    pub fn new(arrays: &JSONArrays, val: &serde_json::Value) -> IOResult<Self> {
+        let count = val
+            .get("count")
+            .and_then(|x| x.as_u64())
+            .ok_or(IOError::Gltf(GltfError::JSONSparse))?;
+        let indices = val
+            .get("indices")
+            .ok_or(IOError::Gltf(GltfError::JSONSparse))?;
+        let values = val
+            .get("values")
+            .ok_or(IOError::Gltf(GltfError::JSONSparse))?;
+
+        let indices_buffer_view = indices
+            .get("bufferView")
+            .and_then(|x| x.as_u64())
+            .ok_or(IOError::Gltf(GltfError::JSONSparse))
+            .and_then(|x| BufferView::new_by_id(arrays, x))?;
+        let indices_byte_offset = indices
+            .get("byteOffset")
+            .and_then(|x| x.as_u64())
+            .unwrap_or(0);
+        let indices_component_type = indices
+            .get("componentType")
+            .and_then(|x| x.as_u64())
+            .ok_or(IOError::Gltf(GltfError::JSONSparse))
+            .and_then(ComponentType::new)
+            .and_then(|x| IndexComponentType::new(x).map(|_| x))?;
+
+        let values_buffer_view = values
+            .get("bufferView")
+            .and_then(|x| x.as_u64())
+            .ok_or(IOError::Gltf(GltfError::JSONSparse))
+            .and_then(|x| BufferView::new_by_id(arrays, x))?;
+        let values_byte_offset = values
+            .get("byteOffset")
+            .and_then(|x| x.as_u64())
+            .unwrap_or(0);
 
         Ok(Self {
-            buffer_view: accessor.buffer_view,
-            byte_offset: accessor.byte_offset,
-            count: accessor.count,
+            count,
+            indices_buffer_view,
+            indices_byte_offset,
+            indices_component_type,
+            values_buffer_view,
+            values_byte_offset,
         })
     }
 }
@@ -592,13 +767,20 @@ impl PosAccessor {
 #[derive(Debug, Clone)]
 pub struct BufferView {
     pub buffer: Buffer,
-    #[allow(dead_code)]
     pub byte_length: u64,
     pub byte_offset: u64,
     pub byte_stride: Option<u64>,
 }
 
 impl BufferView {
+    pub fn new_by_id(arrays: &JSONArrays, id: u64) -> IOResult<Self> {
+        arrays
+            .buffer_views
+            .get(id as usize)
+            .ok_or(IOError::Gltf(GltfError::JSONBufferViews))
+            .and_then(|x| Self::new(arrays, x))
+    }
+
     pub fn new(arrays: &JSONArrays, val: &serde_json::Value) -> IOResult<Self> {
         let buffer_id = val
             .get("buffer")
@@ -610,7 +792,11 @@ impl BufferView {
             .ok_or(IOError::Gltf(GltfError::JSONByteLength))?;
         let byte_offset = val.get("byteOffset").and_then(|x| x.as_u64()).unwrap_or(0);
         let byte_stride = val.get("byteStride").and_then(|x| x.as_u64());
-        let buffer = Buffer::new(&arrays.buffers[buffer_id as usize])?;
+        let buffer = arrays
+            .buffers
+            .get(buffer_id as usize)
+            .ok_or(IOError::Gltf(GltfError::JSONBuffers))
+            .and_then(Buffer::new)?;
 
         Ok(Self {
             buffer,
@@ -623,25 +809,6 @@ impl BufferView {
 
 //------------------------------------------------------------------------------
 
-pub enum FileOrDataPointer {
-    DataPointer(u64, DataPointer),
-    File(BufReader<File>),
-}
-
-impl FileOrDataPointer {
-    pub fn seek(&mut self, start: u64) -> std::result::Result<u64, std::io::Error> {
-        match self {
-            Self::DataPointer(i, _) => {
-                *i = start;
-                Ok(start)
-            }
-            Self::File(x) => x.seek(SeekFrom::Start(start)),
-        }
-    }
-}
-
-//------------------------------------------------------------------------------
-
 #[derive(Debug, Clone)]
 pub enum UriOrDataPointer {
     Uri(PathBuf),
@@ -697,7 +864,6 @@ impl DataPointer {
 
 #[derive(Debug, Clone)]
 pub struct Buffer {
-    #[allow(dead_code)]
     pub byte_length: u64,
     pub uri_or_data: Option<UriOrDataPointer>,
 }
@@ -815,9 +981,9 @@ impl TryFrom<ChunkHeader> for BinChunkHeader {
 
 //------------------------------------------------------------------------------
 
-const VALID_MAGIC: u32 = 0x46546C67; //"glTF"
-const VALID_VERSION: u32 = 2;
-const TYPE_JSON: u32 = 0x4E4F534A; // "JSON"
-const TYPE_BIN: u32 = 0x004E4942; // "BIN"
+pub const VALID_MAGIC: u32 = 0x46546C67; //"glTF"
+pub const VALID_VERSION: u32 = 2;
+pub const TYPE_JSON: u32 = 0x4E4F534A; // "JSON"
+pub const TYPE_BIN: u32 = 0x004E4942; // "BIN"
 
 //------------------------------------------------------------------------------
@@ -0,0 +1,1834 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//! Module for the decompression of .laz files (LASzip)
+//! Supports the pointwise chunked compressor with the version 2 items, which are used for the point record formats 0 - 3
+//! and the layered chunked compressor with the version 3 items, which are used for the point record formats 6 - 8
+//! Files of other point record formats are rejected with LazError::UnsupportedPointFormat
+
+use std::{
+    convert::TryInto,
+    io::{Read, Seek, SeekFrom},
+};
+
+use super::{types::*, vlr::*};
+
+use super::super::types::*;
+
+//------------------------------------------------------------------------------
+
+const AC_MIN_LENGTH: u32 = 0x0100_0000;
+const AC_MAX_LENGTH: u32 = 0xFFFF_FFFF;
+
+const BM_LENGTH_SHIFT: u32 = 13;
+const BM_MAX_COUNT: u32 = 1 << BM_LENGTH_SHIFT;
+
+const DM_LENGTH_SHIFT: u32 = 15;
+const DM_MAX_COUNT: u32 = 1 << DM_LENGTH_SHIFT;
+
+const COMPRESSOR_POINTWISE_CHUNKED: u16 = 2;
+const COMPRESSOR_LAYERED_CHUNKED: u16 = 3;
+
+const ITEM_BYTE: u16 = 0;
+const ITEM_POINT10: u16 = 6;
+const ITEM_GPSTIME11: u16 = 7;
+const ITEM_RGB12: u16 = 8;
+const ITEM_POINT14: u16 = 10;
+const ITEM_RGB14: u16 = 11;
+const ITEM_RGBNIR14: u16 = 12;
+const ITEM_BYTE14: u16 = 14;
+
+const GPSTIME_MULTI: i32 = 500;
+const GPSTIME_MULTI_MINUS: i32 = -10;
+const GPSTIME_MULTI_UNCHANGED: u32 = (GPSTIME_MULTI - GPSTIME_MULTI_MINUS + 1) as u32;
+const GPSTIME_MULTI_CODE_FULL: u32 = (GPSTIME_MULTI - GPSTIME_MULTI_MINUS + 2) as u32;
+const GPSTIME_MULTI_TOTAL: u32 = (GPSTIME_MULTI - GPSTIME_MULTI_MINUS + 6) as u32;
+
+const NUMBER_RETURN_MAP: [[u8; 8]; 8] = [
+    [15, 14, 13, 12, 11, 10, 9, 8],
+    [14, 0, 1, 3, 6, 10, 10, 9],
+    [13, 1, 2, 4, 7, 11, 11, 10],
+    [12, 3, 4, 5, 8, 12, 12, 11],
+    [11, 6, 7, 8, 9, 13, 13, 12],
+    [10, 10, 11, 12, 13, 14, 14, 13],
+    [9, 10, 11, 12, 13, 14, 15, 14],
+    [8, 9, 10, 11, 12, 13, 14, 15],
+];
+
+const NUMBER_RETURN_MAP_6CTX: [[u8; 16]; 16] = [
+    [0, 1, 2, 3, 4, 5, 3, 4, 4, 5, 5, 5, 5, 5, 5, 5],
+    [1, 0, 1, 3, 4, 5, 3, 4, 4, 5, 5, 5, 5, 5, 5, 5],
+    [2, 1, 2, 4, 4, 5, 4, 4, 4, 5, 5, 5, 5, 5, 5, 5],
+    [3, 3, 4, 5, 4, 5, 4, 4, 4, 5, 5, 5, 5, 5, 5, 5],
+    [4, 4, 4, 4, 5, 5, 4, 4, 4, 5, 5, 5, 5, 5, 5, 5],
+    [5, 5, 5, 5, 5, 5, 5, 4, 4, 5, 5, 5, 5, 5, 5, 5],
+    [3, 3, 4, 4, 4, 5, 4, 4, 4, 5, 5, 5, 5, 5, 5, 5],
+    [4, 4, 4, 4, 4, 4, 4, 4, 4, 5, 5, 5, 5, 5, 5, 5],
+    [4, 4, 4, 4, 4, 4, 4, 4, 4, 5, 5, 5, 5, 5, 5, 5],
+    [5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
+    [5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
+    [5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
+    [5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
+    [5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
+    [5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
+    [5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5, 5],
+];
+
+const NUMBER_RETURN_LEVEL: [[u8; 8]; 8] = [
+    [0, 1, 2, 3, 4, 5, 6, 7],
+    [1, 0, 1, 2, 3, 4, 5, 6],
+    [2, 1, 0, 1, 2, 3, 4, 5],
+    [3, 2, 1, 0, 1, 2, 3, 4],
+    [4, 3, 2, 1, 0, 1, 2, 3],
+    [5, 4, 3, 2, 1, 0, 1, 2],
+    [6, 5, 4, 3, 2, 1, 0, 1],
+    [7, 6, 5, 4, 3, 2, 1, 0],
+];
+
+//------------------------------------------------------------------------------
+
+/// Content of the LASzip variable length record
+#[derive(Debug, Clone, PartialEq)]
+pub struct LazVlr {
+    pub compressor: u16,
+    pub coder: u16,
+    pub version_major: u8,
+    pub version_minor: u8,
+    pub version_revision: u16,
+    pub options: u32,
+    pub chunk_size: u32,
+    pub n_special_evlrs: i64,
+    pub offset_special_evlrs: i64,
+    pub items: Vec<LazItem>,
+}
+
+/// A compressed item of a point record
+#[derive(Debug, Clone, PartialEq)]
+pub struct LazItem {
+    pub item_type: u16,
+    pub size: u16,
+    pub version: u16,
+}
+
+impl LazVlr {
+    /// Returns true if the LasVlr is the LASzip record
+    pub fn is_laz_vlr(vlr: &LasVlr) -> bool {
+        vlr.user_id == "laszip encoded" && vlr.record_id == 22204
+    }
+
+    /// Parses the LASzip record from the data of a LasVlr
+    pub fn from_bytes(buffer: &[u8]) -> IOResult<Self> {
+        if buffer.len() < 34 {
+            return Err(IOError::Laz(LazError::InvalidVlr));
+        }
+
+        let n_items = u16::from_le_bytes(buffer[32..34].try_into()?) as usize;
+        if buffer.len() < 34 + 6 * n_items {
+            return Err(IOError::Laz(LazError::InvalidVlr));
+        }
+
+        let items = buffer[34..34 + 6 * n_items]
+            .chunks_exact(6)
+            .map(|x| -> IOResult<LazItem> {
+                Ok(LazItem {
+                    item_type: u16::from_le_bytes(x[0..2].try_into()?),
+                    size: u16::from_le_bytes(x[2..4].try_into()?),
+                    version: u16::from_le_bytes(x[4..6].try_into()?),
+                })
+            })
+            .collect::<IOResult<_>>()?;
+
+        Ok(Self {
+            compressor: u16::from_le_bytes(buffer[0..2].try_into()?),
+            coder: u16::from_le_bytes(buffer[2..4].try_into()?),
+            version_major: buffer[4],
+            version_minor: buffer[5],
+            version_revision: u16::from_le_bytes(buffer[6..8].try_into()?),
+            options: u32::from_le_bytes(buffer[8..12].try_into()?),
+            chunk_size: u32::from_le_bytes(buffer[12..16].try_into()?),
+            n_special_evlrs: i64::from_le_bytes(buffer[16..24].try_into()?),
+            offset_special_evlrs: i64::from_le_bytes(buffer[24..32].try_into()?),
+            items,
+        })
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Decompresses the point records of a .laz file chunk by chunk
+pub struct LazDecompressor {
+    items: Vec<LazItem>,
+    /// Start position and number of points of each chunk
+    chunks: Vec<(u64, u64)>,
+    i_chunk: usize,
+    n_read_chunk: u64,
+    is_layered: bool,
+    /// Scanner channel of the last point, only used by the layered compression
+    context: usize,
+    decoder: ArithmeticDecoder,
+    item_decompressors: Vec<ItemDecompressor>,
+}
+
+impl LazDecompressor {
+    /// Creates a new LazDecompressor, read must be positioned at the start of the point data
+    pub fn new<R>(read: &mut R, laz_vlr: &LazVlr, header: &Header) -> IOResult<Self>
+    where
+        R: Read + Seek,
+    {
+        let is_layered = match header.point_record_format {
+            0..=3 => false,
+            6..=8 => true,
+            x => return Err(IOError::Laz(LazError::UnsupportedPointFormat(x))),
+        };
+
+        let (compressor, version) = if is_layered {
+            (COMPRESSOR_LAYERED_CHUNKED, 3)
+        } else {
+            (COMPRESSOR_POINTWISE_CHUNKED, 2)
+        };
+
+        if laz_vlr.compressor != compressor {
+            return Err(IOError::Laz(LazError::UnsupportedCompressor(
+                laz_vlr.compressor,
+            )));
+        }
+
+        let mut record_length = 0;
+        for item in laz_vlr.items.iter() {
+            let expected_size = match (is_layered, item.item_type) {
+                (false, ITEM_POINT10) => 20,
+                (false, ITEM_GPSTIME11) => 8,
+                (false, ITEM_RGB12) => 6,
+                (false, ITEM_BYTE) => item.size,
+                (true, ITEM_POINT14) => 30,
+                (true, ITEM_RGB14) => 6,
+                (true, ITEM_RGBNIR14) => 8,
+                (true, ITEM_BYTE14) => item.size,
+                _ => 0,
+            };
+
+            if expected_size == 0 || item.version != version || item.size != expected_size {
+                return Err(IOError::Laz(LazError::UnsupportedItem(
+                    item.item_type,
+                    item.version,
+                )));
+            }
+            record_length += item.size;
+        }
+
+        if record_length != header.point_record_length {
+            return Err(IOError::Laz(LazError::InvalidVlr));
+        }
+
+        // The scanner channel of POINT14 is the context of all other layered items
+        if is_layered && laz_vlr.items.first().map(|x| x.item_type) != Some(ITEM_POINT14) {
+            return Err(IOError::Laz(LazError::InvalidVlr));
+        }
+
+        let chunks = read_chunk_table(read, laz_vlr.chunk_size, header.n_point_records)?;
+
+        Ok(Self {
+            items: laz_vlr.items.clone(),
+            chunks,
+            i_chunk: 0,
+            n_read_chunk: 0,
+            is_layered,
+            context: 0,
+            decoder: ArithmeticDecoder::default(),
+            item_decompressors: Vec::new(),
+        })
+    }
+
+    /// Decompresses the next point record into record
+    pub fn decompress<R>(&mut self, read: &mut R, record: &mut [u8]) -> IOResult<()>
+    where
+        R: Read + Seek,
+    {
+        if self.item_decompressors.is_empty() || self.n_read_chunk >= self.chunks[self.i_chunk].1 {
+            if !self.item_decompressors.is_empty() {
+                self.i_chunk += 1;
+            }
+
+            return self.start_chunk(read, record);
+        }
+
+        let mut start = 0;
+        for (item, decompressor) in self.items.iter().zip(self.item_decompressors.iter_mut()) {
+            let end = start + item.size as usize;
+            decompressor.decompress(
+                &mut self.decoder,
+                &mut self.context,
+                &mut record[start..end],
+            );
+            start = end;
+        }
+        self.n_read_chunk += 1;
+
+        Ok(())
+    }
+
+    /// Reads the chunk into memory and its first, uncompressed point record into record
+    fn start_chunk<R>(&mut self, read: &mut R, record: &mut [u8]) -> IOResult<()>
+    where
+        R: Read + Seek,
+    {
+        let (start, _) = *self
+            .chunks
+            .get(self.i_chunk)
+            .ok_or(IOError::Laz(LazError::ChunkTable))?;
+
+        let n_bytes = self
+            .chunks
+            .get(self.i_chunk + 1)
+            .map(|(next, _)| next.saturating_sub(start))
+            .ok_or(IOError::Laz(LazError::ChunkTable))?;
+
+        if (n_bytes as usize) < record.len() {
+            return Err(IOError::Laz(LazError::ChunkTable));
+        }
+
+        read.seek(SeekFrom::Start(start))?;
+        read.read_exact(record)?;
+
+        let mut data = Vec::new();
+        read.take(n_bytes - record.len() as u64)
+            .read_to_end(&mut data)?;
+
+        self.item_decompressors.clear();
+        if self.is_layered {
+            self.start_layered_chunk(&data, record)?;
+        } else {
+            self.decoder = ArithmeticDecoder::new(data);
+            let mut start = 0;
+            for item in self.items.iter() {
+                let end = start + item.size as usize;
+                self.item_decompressors
+                    .push(ItemDecompressor::new(item, &record[start..end]));
+                start = end;
+            }
+        }
+        self.n_read_chunk = 1;
+
+        Ok(())
+    }
+
+    /// Layered chunks continue with the number of points, the sizes of the layers of all items and the layers themselves
+    /// Each layer is decoded separately, layers of size 0 are unchanged within the chunk
+    fn start_layered_chunk(&mut self, data: &[u8], first: &[u8]) -> IOResult<()> {
+        // The number of points is already known from the chunk table
+        let mut position = 4;
+        let mut sizes = Vec::with_capacity(self.items.len());
+        for item in self.items.iter() {
+            let n_layers = match item.item_type {
+                ITEM_POINT14 => 9,
+                ITEM_RGB14 => 1,
+                ITEM_RGBNIR14 => 2,
+                _ => item.size as usize,
+            };
+
+            let mut item_sizes = Vec::with_capacity(n_layers);
+            for _ in 0..n_layers {
+                let size = data
+                    .get(position..position + 4)
+                    .ok_or(IOError::Laz(LazError::LayerSize))?;
+                item_sizes.push(u32::from_le_bytes(size.try_into()?) as usize);
+                position += 4;
+            }
+            sizes.push(item_sizes);
+        }
+
+        self.context = ((first[15] >> 4) & 0b0000_0011) as usize;
+
+        let mut start = 0;
+        for (item, item_sizes) in self.items.iter().zip(sizes) {
+            let mut layers = Vec::with_capacity(item_sizes.len());
+            for size in item_sizes {
+                let layer = data
+                    .get(position..position + size)
+                    .ok_or(IOError::Laz(LazError::LayerSize))?;
+                layers.push(if size == 0 {
+                    None
+                } else {
+                    Some(ArithmeticDecoder::new(layer.to_vec()))
+                });
+                position += size;
+            }
+
+            let end = start + item.size as usize;
+            self.item_decompressors.push(ItemDecompressor::new_layered(
+                item,
+                &first[start..end],
+                self.context,
+                layers,
+            ));
+            start = end;
+        }
+
+        Ok(())
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Reads the chunk table, returning the start position and number of points of each chunk
+/// An additional entry marks the end of the last chunk
+fn read_chunk_table<R>(read: &mut R, chunk_size: u32, n_points: u64) -> IOResult<Vec<(u64, u64)>>
+where
+    R: Read + Seek,
+{
+    let mut buffer = [0u8; 8];
+    read.read_exact(&mut buffer)?;
+    let mut chunk_table_start = i64::from_le_bytes(buffer);
+    let chunks_start = read.stream_position()?;
+
+    // Table offset is stored at the end of the file, if it was unknown during writing
+    if chunk_table_start == -1 {
+        read.seek(SeekFrom::End(-8))?;
+        read.read_exact(&mut buffer)?;
+        chunk_table_start = i64::from_le_bytes(buffer);
+    }
+
+    if chunk_table_start < chunks_start as i64 {
+        return Err(IOError::Laz(LazError::ChunkTable));
+    }
+
+    read.seek(SeekFrom::Start(chunk_table_start as u64))?;
+
+    read.read_exact(&mut buffer)?;
+    let version = u32::from_le_bytes(buffer[0..4].try_into()?);
+    let n_chunks = u32::from_le_bytes(buffer[4..8].try_into()?) as u64;
+
+    if version != 0 {
+        return Err(IOError::Laz(LazError::ChunkTable));
+    }
+
+    // Limit the amount of data read, since the table might be followed by further data
+    let mut data = Vec::new();
+    read.take(16 * (n_chunks + 1)).read_to_end(&mut data)?;
+
+    let mut decoder = ArithmeticDecoder::new(data);
+    let mut ic = IntegerDecompressor::new(32, 2, 8, 0);
+
+    let is_variable = chunk_size == u32::MAX;
+    let mut result = Vec::with_capacity(n_chunks as usize + 1);
+    let mut start = chunks_start;
+    let mut n_remaining = n_points;
+    let mut last_count = 0;
+    let mut last_size = 0;
+
+    for _ in 0..n_chunks {
+        let count = if is_variable {
+            last_count = ic.decompress(&mut decoder, last_count, 0);
+            last_count as u32 as u64
+        } else {
+            n_remaining.min(chunk_size as u64)
+        };
+        last_size = ic.decompress(&mut decoder, last_size, 1);
+
+        result.push((start, count));
+        start += last_size as u32 as u64;
+        n_remaining = n_remaining.saturating_sub(count);
+    }
+    result.push((start, 0));
+
+    Ok(result)
+}
+
+//------------------------------------------------------------------------------
+
+enum ItemDecompressor {
+    Point10(Box<Point10Decompressor>),
+    GpsTime11(Box<GpsTime11Decompressor>),
+    Rgb12(Box<Rgb12Decompressor>),
+    Byte(ByteDecompressor),
+    Point14(Box<Point14Decompressor>),
+    Rgb14(Box<Rgb14Decompressor>),
+    RgbNir14(Box<RgbNir14Decompressor>),
+    Byte14(Box<Byte14Decompressor>),
+}
+
+impl ItemDecompressor {
+    fn new(item: &LazItem, first: &[u8]) -> Self {
+        match item.item_type {
+            ITEM_POINT10 => Self::Point10(Box::new(Point10Decompressor::new(first))),
+            ITEM_GPSTIME11 => Self::GpsTime11(Box::new(GpsTime11Decompressor::new(first))),
+            ITEM_RGB12 => Self::Rgb12(Box::new(Rgb12Decompressor::new(first))),
+            _ => Self::Byte(ByteDecompressor::new(first)),
+        }
+    }
+
+    fn new_layered(
+        item: &LazItem,
+        first: &[u8],
+        context: usize,
+        mut layers: Vec<Option<ArithmeticDecoder>>,
+    ) -> Self {
+        match item.item_type {
+            ITEM_POINT14 => Self::Point14(Box::new(Point14Decompressor::new(first, layers))),
+            ITEM_RGB14 => Self::Rgb14(Box::new(Rgb14Decompressor::new(
+                first,
+                context,
+                layers.remove(0),
+            ))),
+            ITEM_RGBNIR14 => Self::RgbNir14(Box::new(RgbNir14Decompressor {
+                rgb: Rgb14Decompressor::new(&first[0..6], context, layers.remove(0)),
+                nir: Nir14Decompressor::new(&first[6..8], context, layers.remove(0)),
+            })),
+            _ => Self::Byte14(Box::new(Byte14Decompressor::new(first, context, layers))),
+        }
+    }
+
+    /// Decompresses the next item, layered items use their own decoders and the scanner channel as context instead
+    fn decompress(
+        &mut self,
+        decoder: &mut ArithmeticDecoder,
+        context: &mut usize,
+        item: &mut [u8],
+    ) {
+        match self {
+            Self::Point10(x) => x.decompress(decoder, item),
+            Self::GpsTime11(x) => x.decompress(decoder, item),
+            Self::Rgb12(x) => x.decompress(decoder, item),
+            Self::Byte(x) => x.decompress(decoder, item),
+            Self::Point14(x) => x.decompress(context, item),
+            Self::Rgb14(x) => x.decompress(*context, item),
+            Self::RgbNir14(x) => {
+                x.rgb.decompress(*context, &mut item[0..6]);
+                x.nir.decompress(*context, &mut item[6..8]);
+            }
+            Self::Byte14(x) => x.decompress(*context, item),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+struct Point10Decompressor {
+    last_item: [u8; 20],
+    last_intensity: [u16; 16],
+    last_x_diff_median5: [StreamingMedian5; 16],
+    last_y_diff_median5: [StreamingMedian5; 16],
+    last_height: [i32; 8],
+    m_changed_values: ArithmeticModel,
+    m_scan_angle_rank: [ArithmeticModel; 2],
+    m_bit_byte: Vec<Option<ArithmeticModel>>,
+    m_classification: Vec<Option<ArithmeticModel>>,
+    m_user_data: Vec<Option<ArithmeticModel>>,
+    ic_intensity: IntegerDecompressor,
+    ic_point_source_id: IntegerDecompressor,
+    ic_dx: IntegerDecompressor,
+    ic_dy: IntegerDecompressor,
+    ic_z: IntegerDecompressor,
+}
+
+impl Point10Decompressor {
+    fn new(first: &[u8]) -> Self {
+        let mut last_item = [0u8; 20];
+        last_item.copy_from_slice(first);
+        // The intensity starts at zero
+        last_item[12] = 0;
+        last_item[13] = 0;
+
+        Self {
+            last_item,
+            last_intensity: [0; 16],
+            last_x_diff_median5: Default::default(),
+            last_y_diff_median5: Default::default(),
+            last_height: [0; 8],
+            m_changed_values: ArithmeticModel::new(64),
+            m_scan_angle_rank: [ArithmeticModel::new(256), ArithmeticModel::new(256)],
+            m_bit_byte: vec![None; 256],
+            m_classification: vec![None; 256],
+            m_user_data: vec![None; 256],
+            ic_intensity: IntegerDecompressor::new(16, 4, 8, 0),
+            ic_point_source_id: IntegerDecompressor::new(16, 1, 8, 0),
+            ic_dx: IntegerDecompressor::new(32, 2, 8, 0),
+            ic_dy: IntegerDecompressor::new(32, 22, 8, 0),
+            ic_z: IntegerDecompressor::new(32, 20, 8, 0),
+        }
+    }
+
+    fn decompress(&mut self, decoder: &mut ArithmeticDecoder, item: &mut [u8]) {
+        let changed_values = decoder.decode_symbol(&mut self.m_changed_values);
+
+        let mut r;
+        let mut n;
+        let mut m;
+
+        if changed_values != 0 {
+            if changed_values & 32 != 0 {
+                let model = lazy_model(&mut self.m_bit_byte, self.last_item[14], 256);
+                self.last_item[14] = decoder.decode_symbol(model) as u8;
+            }
+
+            r = (self.last_item[14] & 0b0000_0111) as usize;
+            n = ((self.last_item[14] >> 3) & 0b0000_0111) as usize;
+            m = NUMBER_RETURN_MAP[n][r] as usize;
+
+            if changed_values & 16 != 0 {
+                let intensity = self.ic_intensity.decompress(
+                    decoder,
+                    self.last_intensity[m] as i32,
+                    m.min(3) as u32,
+                ) as u16;
+                self.last_intensity[m] = intensity;
+            }
+            self.last_item[12..14].copy_from_slice(&self.last_intensity[m].to_le_bytes());
+
+            if changed_values & 8 != 0 {
+                let model = lazy_model(&mut self.m_classification, self.last_item[15], 256);
+                self.last_item[15] = decoder.decode_symbol(model) as u8;
+            }
+
+            if changed_values & 4 != 0 {
+                let scan_direction_flag = ((self.last_item[14] >> 6) & 1) as usize;
+                let value = decoder.decode_symbol(&mut self.m_scan_angle_rank[scan_direction_flag]);
+                self.last_item[16] = u8_fold(value as i32 + self.last_item[16] as i32);
+            }
+
+            if changed_values & 2 != 0 {
+                let model = lazy_model(&mut self.m_user_data, self.last_item[17], 256);
+                self.last_item[17] = decoder.decode_symbol(model) as u8;
+            }
+
+            if changed_values & 1 != 0 {
+                let last = u16::from_le_bytes([self.last_item[18], self.last_item[19]]);
+                let point_source_id =
+                    self.ic_point_source_id.decompress(decoder, last as i32, 0) as u16;
+                self.last_item[18..20].copy_from_slice(&point_source_id.to_le_bytes());
+            }
+        } else {
+            r = (self.last_item[14] & 0b0000_0111) as usize;
+            n = ((self.last_item[14] >> 3) & 0b0000_0111) as usize;
+            m = NUMBER_RETURN_MAP[n][r] as usize;
+            self.last_item[12..14].copy_from_slice(&self.last_intensity[m].to_le_bytes());
+        }
+        r = r.min(7);
+        n = n.min(7);
+        m = m.min(15);
+        let l = NUMBER_RETURN_LEVEL[n][r] as usize;
+
+        let x = i32::from_le_bytes(self.last_item[0..4].try_into().unwrap()); // unwrap safe since fixed size
+        let median = self.last_x_diff_median5[m].get();
+        let diff = self.ic_dx.decompress(decoder, median, (n == 1) as u32);
+        self.last_item[0..4].copy_from_slice(&x.wrapping_add(diff).to_le_bytes());
+        self.last_x_diff_median5[m].add(diff);
+
+        let y = i32::from_le_bytes(self.last_item[4..8].try_into().unwrap()); // unwrap safe since fixed size
+        let median = self.last_y_diff_median5[m].get();
+        let k_bits = self.ic_dx.k();
+        let diff = self.ic_dy.decompress(
+            decoder,
+            median,
+            (n == 1) as u32 + if k_bits < 20 { k_bits & !1 } else { 20 },
+        );
+        self.last_item[4..8].copy_from_slice(&y.wrapping_add(diff).to_le_bytes());
+        self.last_y_diff_median5[m].add(diff);
+
+        let k_bits = (self.ic_dx.k() + self.ic_dy.k()) / 2;
+        let z = self.ic_z.decompress(
+            decoder,
+            self.last_height[l],
+            (n == 1) as u32 + if k_bits < 18 { k_bits & !1 } else { 18 },
+        );
+        self.last_item[8..12].copy_from_slice(&z.to_le_bytes());
+        self.last_height[l] = z;
+
+        item.copy_from_slice(&self.last_item);
+    }
+}
+
+//------------------------------------------------------------------------------
+
+struct GpsTime11Decompressor {
+    /// The layered compression signals unchanged values via POINT14, lacking the unchanged symbols
+    is_layered: bool,
+    last: usize,
+    next: usize,
+    last_gps_time: [i64; 4],
+    last_gps_time_diff: [i32; 4],
+    multi_extreme_counter: [i32; 4],
+    m_gps_time_multi: ArithmeticModel,
+    m_gps_time_0diff: ArithmeticModel,
+    ic_gps_time: IntegerDecompressor,
+}
+
+impl GpsTime11Decompressor {
+    fn new(first: &[u8]) -> Self {
+        Self::with_layered(first, false)
+    }
+
+    fn with_layered(first: &[u8], is_layered: bool) -> Self {
+        let n_removed = is_layered as u32;
+        Self {
+            is_layered,
+            last: 0,
+            next: 0,
+            last_gps_time: [
+                i64::from_le_bytes(first.try_into().unwrap()), // unwrap safe since item size is checked
+                0,
+                0,
+                0,
+            ],
+            last_gps_time_diff: [0; 4],
+            multi_extreme_counter: [0; 4],
+            m_gps_time_multi: ArithmeticModel::new(GPSTIME_MULTI_TOTAL - n_removed),
+            m_gps_time_0diff: ArithmeticModel::new(6 - n_removed),
+            ic_gps_time: IntegerDecompressor::new(32, 9, 8, 0),
+        }
+    }
+
+    fn decompress(&mut self, decoder: &mut ArithmeticDecoder, item: &mut [u8]) {
+        // Switching to another sequence requires reading again
+        loop {
+            let last = self.last;
+            if self.last_gps_time_diff[last] == 0 {
+                // Without the unchanged symbol 0, the remaining symbols are shifted down by one
+                let multi =
+                    decoder.decode_symbol(&mut self.m_gps_time_0diff) + self.is_layered as u32;
+                if multi == 1 {
+                    // Difference fits into 32 bits
+                    let diff = self.ic_gps_time.decompress(decoder, 0, 0);
+                    self.last_gps_time_diff[last] = diff;
+                    self.last_gps_time[last] = self.last_gps_time[last].wrapping_add(diff as i64);
+                    self.multi_extreme_counter[last] = 0;
+                } else if multi == 2 {
+                    self.read_full(decoder);
+                } else if multi > 2 {
+                    self.last = (self.last + multi as usize - 2) & 3;
+                    continue;
+                }
+            } else {
+                let mut multi = decoder.decode_symbol(&mut self.m_gps_time_multi);
+                if self.is_layered && multi >= GPSTIME_MULTI_UNCHANGED {
+                    multi += 1;
+                }
+                if multi == 1 {
+                    let diff =
+                        self.ic_gps_time
+                            .decompress(decoder, self.last_gps_time_diff[last], 1);
+                    self.last_gps_time[last] = self.last_gps_time[last].wrapping_add(diff as i64);
+                    self.multi_extreme_counter[last] = 0;
+                } else if multi < GPSTIME_MULTI_UNCHANGED {
+                    let diff;
+                    let last_diff = self.last_gps_time_diff[last];
+                    if multi == 0 {
+                        diff = self.ic_gps_time.decompress(decoder, 0, 7);
+                        self.count_extreme(diff);
+                    } else if (multi as i32) < GPSTIME_MULTI {
+                        let context = if multi < 10 { 2 } else { 3 };
+                        diff = self.ic_gps_time.decompress(
+                            decoder,
+                            (multi as i32).wrapping_mul(last_diff),
+                            context,
+                        );
+                    } else if multi as i32 == GPSTIME_MULTI {
+                        diff = self.ic_gps_time.decompress(
+                            decoder,
+                            GPSTIME_MULTI.wrapping_mul(last_diff),
+                            4,
+                        );
+                        self.count_extreme(diff);
+                    } else {
+                        let multi = GPSTIME_MULTI - multi as i32;
+                        if multi > GPSTIME_MULTI_MINUS {
+                            diff = self.ic_gps_time.decompress(
+                                decoder,
+                                multi.wrapping_mul(last_diff),
+                                5,
+                            );
+                        } else {
+                            diff = self.ic_gps_time.decompress(
+                                decoder,
+                                GPSTIME_MULTI_MINUS.wrapping_mul(last_diff),
+                                6,
+                            );
+                            self.count_extreme(diff);
+                        }
+                    }
+                    self.last_gps_time[last] = self.last_gps_time[last].wrapping_add(diff as i64);
+                } else if multi == GPSTIME_MULTI_CODE_FULL {
+                    self.read_full(decoder);
+                } else if multi > GPSTIME_MULTI_CODE_FULL {
+                    self.last = (self.last + (multi - GPSTIME_MULTI_CODE_FULL) as usize) & 3;
+                    continue;
+                }
+            }
+            break;
+        }
+
+        item.copy_from_slice(&self.last_gps_time[self.last].to_le_bytes());
+    }
+
+    /// Counts diffs outside of the predicted range, using them as new prediction if they occur often
+    fn count_extreme(&mut self, diff: i32) {
+        let last = self.last;
+        self.multi_extreme_counter[last] += 1;
+        if self.multi_extreme_counter[last] > 3 {
+            self.last_gps_time_diff[last] = diff;
+            self.multi_extreme_counter[last] = 0;
+        }
+    }
+
+    /// Reads a full 64 bit value as start of a new sequence
+    fn read_full(&mut self, decoder: &mut ArithmeticDecoder) {
+        let last = self.last;
+        self.next = (self.next + 1) & 3;
+        let high =
+            self.ic_gps_time
+                .decompress(decoder, (self.last_gps_time[last] as u64 >> 32) as i32, 8)
+                as u32 as u64;
+        let low = decoder.read_int() as u64;
+        self.last_gps_time[self.next] = ((high << 32) | low) as i64;
+        self.last = self.next;
+        self.last_gps_time_diff[self.last] = 0;
+        self.multi_extreme_counter[self.last] = 0;
+    }
+}
+
+//------------------------------------------------------------------------------
+
+struct Rgb12Decompressor {
+    last_item: [u16; 3],
+    m_byte_used: ArithmeticModel,
+    m_rgb_diff: [ArithmeticModel; 6],
+}
+
+impl Rgb12Decompressor {
+    fn new(first: &[u8]) -> Self {
+        Self {
+            last_item: [
+                u16::from_le_bytes([first[0], first[1]]),
+                u16::from_le_bytes([first[2], first[3]]),
+                u16::from_le_bytes([first[4], first[5]]),
+            ],
+            m_byte_used: ArithmeticModel::new(128),
+            m_rgb_diff: [
+                ArithmeticModel::new(256),
+                ArithmeticModel::new(256),
+                ArithmeticModel::new(256),
+                ArithmeticModel::new(256),
+                ArithmeticModel::new(256),
+                ArithmeticModel::new(256),
+            ],
+        }
+    }
+
+    fn decompress(&mut self, decoder: &mut ArithmeticDecoder, item: &mut [u8]) {
+        let last = self.last_item;
+        let mut rgb = [0u16; 3];
+
+        let sym = decoder.decode_symbol(&mut self.m_byte_used);
+
+        let mut decode = |i: usize, prediction: i32| -> u16 {
+            let corr = decoder.decode_symbol(&mut self.m_rgb_diff[i]) as i32;
+            u8_fold(corr + prediction) as u16
+        };
+
+        rgb[0] = if sym & (1 << 0) != 0 {
+            decode(0, (last[0] & 0xFF) as i32)
+        } else {
+            last[0] & 0xFF
+        };
+
+        rgb[0] |= if sym & (1 << 1) != 0 {
+            decode(1, (last[0] >> 8) as i32) << 8
+        } else {
+            last[0] & 0xFF00
+        };
+
+        if sym & (1 << 6) != 0 {
+            let mut diff = (rgb[0] & 0xFF) as i32 - (last[0] & 0xFF) as i32;
+
+            rgb[1] = if sym & (1 << 2) != 0 {
+                decode(2, u8_clamp(diff + (last[1] & 0xFF) as i32))
+            } else {
+                last[1] & 0xFF
+            };
+
+            rgb[2] = if sym & (1 << 4) != 0 {
+                let diff = (diff + ((rgb[1] & 0xFF) as i32 - (last[1] & 0xFF) as i32)) / 2;
+                decode(4, u8_clamp(diff + (last[2] & 0xFF) as i32))
+            } else {
+                last[2] & 0xFF
+            };
+
+            diff = (rgb[0] >> 8) as i32 - (last[0] >> 8) as i32;
+
+            rgb[1] |= if sym & (1 << 3) != 0 {
+                decode(3, u8_clamp(diff + (last[1] >> 8) as i32)) << 8
+            } else {
+                last[1] & 0xFF00
+            };
+
+            rgb[2] |= if sym & (1 << 5) != 0 {
+                let diff = (diff + ((rgb[1] >> 8) as i32 - (last[1] >> 8) as i32)) / 2;
+                decode(5, u8_clamp(diff + (last[2] >> 8) as i32)) << 8
+            } else {
+                last[2] & 0xFF00
+            };
+        } else {
+            rgb[1] = rgb[0];
+            rgb[2] = rgb[0];
+        }
+
+        self.last_item = rgb;
+        item.copy_from_slice(&self.last_bytes());
+    }
+
+    fn last_bytes(&self) -> [u8; 6] {
+        let [r, g, b] = self.last_item;
+        let mut result = [0u8; 6];
+        result[0..2].copy_from_slice(&r.to_le_bytes());
+        result[2..4].copy_from_slice(&g.to_le_bytes());
+        result[4..6].copy_from_slice(&b.to_le_bytes());
+        result
+    }
+}
+
+//------------------------------------------------------------------------------
+
+struct ByteDecompressor {
+    last_item: Vec<u8>,
+    m_byte: Vec<ArithmeticModel>,
+}
+
+impl ByteDecompressor {
+    fn new(first: &[u8]) -> Self {
+        Self {
+            last_item: first.to_vec(),
+            m_byte: vec![ArithmeticModel::new(256); first.len()],
+        }
+    }
+
+    fn decompress(&mut self, decoder: &mut ArithmeticDecoder, item: &mut [u8]) {
+        for (i, x) in item.iter_mut().enumerate() {
+            let value =
+                self.last_item[i] as i32 + decoder.decode_symbol(&mut self.m_byte[i]) as i32;
+            *x = u8_fold(value);
+        }
+        self.last_item.copy_from_slice(item);
+    }
+
+    /// Decompresses each byte from its own layer, bytes without a layer are unchanged
+    fn decompress_layered(&mut self, layers: &mut [Option<ArithmeticDecoder>], item: &mut [u8]) {
+        for (i, (x, layer)) in item.iter_mut().zip(layers.iter_mut()).enumerate() {
+            if let Some(decoder) = layer {
+                let value =
+                    self.last_item[i] as i32 + decoder.decode_symbol(&mut self.m_byte[i]) as i32;
+                self.last_item[i] = u8_fold(value);
+            }
+            *x = self.last_item[i];
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// States of a layered item for each of the four scanner channels
+/// A channel is created on first use, starting with the last item of the channel used before
+struct ChannelContexts<T> {
+    contexts: [Option<T>; 4],
+    current: usize,
+}
+
+impl<T> ChannelContexts<T> {
+    fn new(current: usize, first: T) -> Self {
+        let mut contexts = [None, None, None, None];
+        contexts[current] = Some(first);
+        Self { contexts, current }
+    }
+
+    fn current(&mut self) -> &mut T {
+        self.contexts[self.current].as_mut().unwrap() // unwrap safe since the current context is always created
+    }
+
+    fn switch<F>(&mut self, channel: usize, create: F) -> &mut T
+    where
+        F: FnOnce(&T) -> T,
+    {
+        if channel != self.current {
+            if self.contexts[channel].is_none() {
+                let created = create(self.current());
+                self.contexts[channel] = Some(created);
+            }
+            self.current = channel;
+        }
+        self.current()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+struct Point14Layers {
+    channel_returns_xy: ArithmeticDecoder,
+    z: Option<ArithmeticDecoder>,
+    classification: Option<ArithmeticDecoder>,
+    flags: Option<ArithmeticDecoder>,
+    intensity: Option<ArithmeticDecoder>,
+    scan_angle: Option<ArithmeticDecoder>,
+    user_data: Option<ArithmeticDecoder>,
+    point_source: Option<ArithmeticDecoder>,
+    gps_time: Option<ArithmeticDecoder>,
+}
+
+struct Point14Context {
+    last_item: [u8; 30],
+    last_gps_time_change: bool,
+    last_intensity: [u16; 8],
+    last_x_diff_median5: [StreamingMedian5; 12],
+    last_y_diff_median5: [StreamingMedian5; 12],
+    last_z: [i32; 8],
+    m_changed_values: Vec<ArithmeticModel>,
+    m_scanner_channel: ArithmeticModel,
+    m_number_of_returns: Vec<Option<ArithmeticModel>>,
+    m_return_number: Vec<Option<ArithmeticModel>>,
+    m_return_number_gps_same: ArithmeticModel,
+    m_classification: Vec<Option<ArithmeticModel>>,
+    m_flags: Vec<Option<ArithmeticModel>>,
+    m_user_data: Vec<Option<ArithmeticModel>>,
+    ic_dx: IntegerDecompressor,
+    ic_dy: IntegerDecompressor,
+    ic_z: IntegerDecompressor,
+    ic_intensity: IntegerDecompressor,
+    ic_scan_angle: IntegerDecompressor,
+    ic_point_source_id: IntegerDecompressor,
+    gps_time: GpsTime11Decompressor,
+}
+
+impl Point14Context {
+    fn new(first: &[u8]) -> Self {
+        let mut last_item = [0u8; 30];
+        last_item.copy_from_slice(first);
+        let intensity = u16::from_le_bytes([first[12], first[13]]);
+        let z = i32::from_le_bytes(first[8..12].try_into().unwrap()); // unwrap safe since fixed size
+
+        Self {
+            last_item,
+            last_gps_time_change: false,
+            last_intensity: [intensity; 8],
+            last_x_diff_median5: Default::default(),
+            last_y_diff_median5: Default::default(),
+            last_z: [z; 8],
+            m_changed_values: vec![ArithmeticModel::new(128); 8],
+            m_scanner_channel: ArithmeticModel::new(3),
+            m_number_of_returns: vec![None; 16],
+            m_return_number: vec![None; 16],
+            m_return_number_gps_same: ArithmeticModel::new(13),
+            m_classification: vec![None; 64],
+            m_flags: vec![None; 64],
+            m_user_data: vec![None; 64],
+            ic_dx: IntegerDecompressor::new(32, 2, 8, 0),
+            ic_dy: IntegerDecompressor::new(32, 22, 8, 0),
+            ic_z: IntegerDecompressor::new(32, 20, 8, 0),
+            ic_intensity: IntegerDecompressor::new(16, 4, 8, 0),
+            ic_scan_angle: IntegerDecompressor::new(16, 2, 8, 0),
+            ic_point_source_id: IntegerDecompressor::new(16, 1, 8, 0),
+            gps_time: GpsTime11Decompressor::with_layered(&first[22..30], true),
+        }
+    }
+}
+
+struct Point14Decompressor {
+    layers: Point14Layers,
+    contexts: ChannelContexts<Point14Context>,
+}
+
+impl Point14Decompressor {
+    fn new(first: &[u8], layers: Vec<Option<ArithmeticDecoder>>) -> Self {
+        let mut layers = layers.into_iter();
+        let mut next = || layers.next().flatten();
+
+        Self {
+            layers: Point14Layers {
+                channel_returns_xy: next().unwrap_or_default(),
+                z: next(),
+                classification: next(),
+                flags: next(),
+                intensity: next(),
+                scan_angle: next(),
+                user_data: next(),
+                point_source: next(),
+                gps_time: next(),
+            },
+            contexts: ChannelContexts::new(
+                ((first[15] >> 4) & 0b0000_0011) as usize,
+                Point14Context::new(first),
+            ),
+        }
+    }
+
+    fn decompress(&mut self, context: &mut usize, item: &mut [u8]) {
+        let layers = &mut self.layers;
+        let decoder = &mut layers.channel_returns_xy;
+
+        let changed_values = {
+            let c = self.contexts.current();
+            let last_r = c.last_item[14] & 0b0000_1111;
+            let last_n = c.last_item[14] >> 4;
+            let lpr = (last_r == 1) as usize
+                + 2 * (last_r >= last_n) as usize
+                + 4 * c.last_gps_time_change as usize;
+            decoder.decode_symbol(&mut c.m_changed_values[lpr])
+        };
+
+        if changed_values & (1 << 6) != 0 {
+            let diff = decoder.decode_symbol(&mut self.contexts.current().m_scanner_channel);
+            let channel = (self.contexts.current + diff as usize + 1) % 4;
+            let c = self
+                .contexts
+                .switch(channel, |x| Point14Context::new(&x.last_item));
+            c.last_item[15] = (c.last_item[15] & 0b1100_1111) | ((channel as u8) << 4);
+            *context = channel;
+        }
+
+        let c = self.contexts.current();
+
+        let point_source_change = changed_values & (1 << 5) != 0;
+        let gps_time_change = changed_values & (1 << 4) != 0;
+        let scan_angle_change = changed_values & (1 << 3) != 0;
+
+        let last_r = (c.last_item[14] & 0b0000_1111) as usize;
+        let last_n = (c.last_item[14] >> 4) as usize;
+
+        let n = if changed_values & (1 << 2) != 0 {
+            let model = lazy_model(&mut c.m_number_of_returns, last_n as u8, 16);
+            decoder.decode_symbol(model) as usize
+        } else {
+            last_n
+        };
+
+        let r = match changed_values & 0b0000_0011 {
+            0 => last_r,
+            1 => (last_r + 1) % 16,
+            2 => (last_r + 15) % 16,
+            _ => {
+                if gps_time_change {
+                    let model = lazy_model(&mut c.m_return_number, last_r as u8, 16);
+                    decoder.decode_symbol(model) as usize
+                } else {
+                    let sym = decoder.decode_symbol(&mut c.m_return_number_gps_same) as usize;
+                    (last_r + sym + 2) % 16
+                }
+            }
+        };
+        c.last_item[14] = (r | (n << 4)) as u8;
+
+        let m = NUMBER_RETURN_MAP_6CTX[n][r] as usize;
+        // Distance between return number and number of returns, limited to 8 contexts
+        let l = (n as i32 - r as i32).unsigned_abs().min(7) as usize;
+        // single (3), first (2), last (1) or intermediate (0) return
+        let cpr = 2 * (r == 1) as usize + (r >= n) as usize;
+        let gps = gps_time_change as usize;
+
+        let x = i32::from_le_bytes(c.last_item[0..4].try_into().unwrap()); // unwrap safe since fixed size
+        let median = c.last_x_diff_median5[(m << 1) | gps].get();
+        let diff = c.ic_dx.decompress(decoder, median, (n == 1) as u32);
+        c.last_item[0..4].copy_from_slice(&x.wrapping_add(diff).to_le_bytes());
+        c.last_x_diff_median5[(m << 1) | gps].add(diff);
+
+        let y = i32::from_le_bytes(c.last_item[4..8].try_into().unwrap()); // unwrap safe since fixed size
+        let median = c.last_y_diff_median5[(m << 1) | gps].get();
+        let k_bits = c.ic_dx.k();
+        let diff = c.ic_dy.decompress(
+            decoder,
+            median,
+            (n == 1) as u32 + if k_bits < 20 { k_bits & !1 } else { 20 },
+        );
+        c.last_item[4..8].copy_from_slice(&y.wrapping_add(diff).to_le_bytes());
+        c.last_y_diff_median5[(m << 1) | gps].add(diff);
+
+        if let Some(decoder) = layers.z.as_mut() {
+            let k_bits = (c.ic_dx.k() + c.ic_dy.k()) / 2;
+            let z = c.ic_z.decompress(
+                decoder,
+                c.last_z[l],
+                (n == 1) as u32 + if k_bits < 18 { k_bits & !1 } else { 18 },
+            );
+            c.last_item[8..12].copy_from_slice(&z.to_le_bytes());
+            c.last_z[l] = z;
+        }
+
+        if let Some(decoder) = layers.classification.as_mut() {
+            let last = ((c.last_item[16] & 0b0001_1111) << 1) + (cpr == 3) as u8;
+            let model = lazy_model(&mut c.m_classification, last, 256);
+            c.last_item[16] = decoder.decode_symbol(model) as u8;
+        }
+
+        // Flags are stored as edge of flight line (bit 5), scan direction (bit 4) and classification flags (bits 0 - 3)
+        if let Some(decoder) = layers.flags.as_mut() {
+            let byte = c.last_item[15];
+            let last = ((byte >> 7) << 5) | (((byte >> 6) & 1) << 4) | (byte & 0b0000_1111);
+            let model = lazy_model(&mut c.m_flags, last, 64);
+            let flags = decoder.decode_symbol(model) as u8;
+            c.last_item[15] = (byte & 0b0011_0000)
+                | (flags & 0b0000_1111)
+                | (((flags >> 4) & 1) << 6)
+                | (((flags >> 5) & 1) << 7);
+        }
+
+        if let Some(decoder) = layers.intensity.as_mut() {
+            let i = (cpr << 1) | gps;
+            let intensity =
+                c.ic_intensity
+                    .decompress(decoder, c.last_intensity[i] as i32, cpr as u32)
+                    as u16;
+            c.last_intensity[i] = intensity;
+            c.last_item[12..14].copy_from_slice(&intensity.to_le_bytes());
+        }
+
+        if let (true, Some(decoder)) = (scan_angle_change, layers.scan_angle.as_mut()) {
+            let last = i16::from_le_bytes([c.last_item[18], c.last_item[19]]);
+            let scan_angle = c.ic_scan_angle.decompress(decoder, last as i32, gps as u32) as i16;
+            c.last_item[18..20].copy_from_slice(&scan_angle.to_le_bytes());
+        }
+
+        if let Some(decoder) = layers.user_data.as_mut() {
+            let model = lazy_model(&mut c.m_user_data, c.last_item[17] / 4, 256);
+            c.last_item[17] = decoder.decode_symbol(model) as u8;
+        }
+
+        if let (true, Some(decoder)) = (point_source_change, layers.point_source.as_mut()) {
+            let last = u16::from_le_bytes([c.last_item[20], c.last_item[21]]);
+            let point_source_id = c.ic_point_source_id.decompress(decoder, last as i32, 0) as u16;
+            c.last_item[20..22].copy_from_slice(&point_source_id.to_le_bytes());
+        }
+
+        if let (true, Some(decoder)) = (gps_time_change, layers.gps_time.as_mut()) {
+            c.gps_time.decompress(decoder, &mut c.last_item[22..30]);
+        }
+
+        item.copy_from_slice(&c.last_item);
+        c.last_gps_time_change = gps_time_change;
+    }
+}
+
+//------------------------------------------------------------------------------
+
+struct Rgb14Decompressor {
+    layer: Option<ArithmeticDecoder>,
+    contexts: ChannelContexts<Rgb12Decompressor>,
+}
+
+impl Rgb14Decompressor {
+    fn new(first: &[u8], context: usize, layer: Option<ArithmeticDecoder>) -> Self {
+        Self {
+            layer,
+            contexts: ChannelContexts::new(context, Rgb12Decompressor::new(first)),
+        }
+    }
+
+    fn decompress(&mut self, context: usize, item: &mut [u8]) {
+        let rgb = self
+            .contexts
+            .switch(context, |x| Rgb12Decompressor::new(&x.last_bytes()));
+
+        match self.layer.as_mut() {
+            Some(decoder) => rgb.decompress(decoder, item),
+            None => item.copy_from_slice(&rgb.last_bytes()),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+struct RgbNir14Decompressor {
+    rgb: Rgb14Decompressor,
+    nir: Nir14Decompressor,
+}
+
+struct Nir14Context {
+    last: u16,
+    m_byte_used: ArithmeticModel,
+    m_nir_diff: [ArithmeticModel; 2],
+}
+
+impl Nir14Context {
+    fn new(last: u16) -> Self {
+        Self {
+            last,
+            m_byte_used: ArithmeticModel::new(4),
+            m_nir_diff: [ArithmeticModel::new(256), ArithmeticModel::new(256)],
+        }
+    }
+}
+
+struct Nir14Decompressor {
+    layer: Option<ArithmeticDecoder>,
+    contexts: ChannelContexts<Nir14Context>,
+}
+
+impl Nir14Decompressor {
+    fn new(first: &[u8], context: usize, layer: Option<ArithmeticDecoder>) -> Self {
+        Self {
+            layer,
+            contexts: ChannelContexts::new(
+                context,
+                Nir14Context::new(u16::from_le_bytes([first[0], first[1]])),
+            ),
+        }
+    }
+
+    fn decompress(&mut self, context: usize, item: &mut [u8]) {
+        let c = self.contexts.switch(context, |x| Nir14Context::new(x.last));
+
+        if let Some(decoder) = self.layer.as_mut() {
+            let last = c.last;
+            let sym = decoder.decode_symbol(&mut c.m_byte_used);
+
+            let low = if sym & (1 << 0) != 0 {
+                let corr = decoder.decode_symbol(&mut c.m_nir_diff[0]) as i32;
+                u8_fold(corr + (last & 0xFF) as i32) as u16
+            } else {
+                last & 0xFF
+            };
+
+            let high = if sym & (1 << 1) != 0 {
+                let corr = decoder.decode_symbol(&mut c.m_nir_diff[1]) as i32;
+                (u8_fold(corr + (last >> 8) as i32) as u16) << 8
+            } else {
+                last & 0xFF00
+            };
+
+            c.last = low | high;
+        }
+
+        item.copy_from_slice(&c.last.to_le_bytes());
+    }
+}
+
+//------------------------------------------------------------------------------
+
+struct Byte14Decompressor {
+    layers: Vec<Option<ArithmeticDecoder>>,
+    contexts: ChannelContexts<ByteDecompressor>,
+}
+
+impl Byte14Decompressor {
+    fn new(first: &[u8], context: usize, layers: Vec<Option<ArithmeticDecoder>>) -> Self {
+        Self {
+            layers,
+            contexts: ChannelContexts::new(context, ByteDecompressor::new(first)),
+        }
+    }
+
+    fn decompress(&mut self, context: usize, item: &mut [u8]) {
+        self.contexts
+            .switch(context, |x| ByteDecompressor::new(&x.last_item))
+            .decompress_layered(&mut self.layers, item);
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Default, Clone, Copy)]
+struct StreamingMedian5 {
+    values: [i32; 5],
+    is_low: bool,
+}
+
+impl StreamingMedian5 {
+    fn add(&mut self, v: i32) {
+        let values = &mut self.values;
+        if !self.is_low {
+            if v < values[2] {
+                values[4] = values[3];
+                values[3] = values[2];
+                if v < values[0] {
+                    values[2] = values[1];
+                    values[1] = values[0];
+                    values[0] = v;
+                } else if v < values[1] {
+                    values[2] = values[1];
+                    values[1] = v;
+                } else {
+                    values[2] = v;
+                }
+            } else {
+                if v < values[3] {
+                    values[4] = values[3];
+                    values[3] = v;
+                } else {
+                    values[4] = v;
+                }
+                self.is_low = true;
+            }
+        } else if values[2] < v {
+            values[0] = values[1];
+            values[1] = values[2];
+            if values[4] < v {
+                values[2] = values[3];
+                values[3] = values[4];
+                values[4] = v;
+            } else if values[3] < v {
+                values[2] = values[3];
+                values[3] = v;
+            } else {
+                values[2] = v;
+            }
+        } else {
+            if values[1] < v {
+                values[0] = values[1];
+                values[1] = v;
+            } else {
+                values[0] = v;
+            }
+            self.is_low = false;
+        }
+    }
+
+    fn get(&self) -> i32 {
+        self.values[2]
+    }
+}
+
+//------------------------------------------------------------------------------
+
+struct IntegerDecompressor {
+    k: u32,
+    corr_bits: u32,
+    corr_range: u32,
+    corr_min: i32,
+    bits_high: u32,
+    m_bits: Vec<ArithmeticModel>,
+    m_corrector_0: ArithmeticBitModel,
+    m_corrector: Vec<ArithmeticModel>,
+}
+
+impl IntegerDecompressor {
+    fn new(bits: u32, contexts: u32, bits_high: u32, range: u32) -> Self {
+        let (corr_bits, corr_range, corr_min) = if range != 0 {
+            let mut corr_bits = 0;
+            let mut x = range;
+            while x != 0 {
+                x >>= 1;
+                corr_bits += 1;
+            }
+            if range == 1 << (corr_bits - 1) {
+                corr_bits -= 1;
+            }
+            (corr_bits, range, -((range / 2) as i32))
+        } else if bits != 0 && bits < 32 {
+            let corr_range = 1u32 << bits;
+            (bits, corr_range, -((corr_range / 2) as i32))
+        } else {
+            (32, 0, i32::MIN)
+        };
+
+        let m_bits = vec![ArithmeticModel::new(corr_bits + 1); contexts as usize];
+        let m_corrector = (0..=corr_bits)
+            .map(|i| {
+                if i == 0 {
+                    // Unused, index 0 is handled by m_corrector_0
+                    ArithmeticModel::new(1)
+                } else if i <= bits_high {
+                    ArithmeticModel::new(1 << i)
+                } else {
+                    ArithmeticModel::new(1 << bits_high)
+                }
+            })
+            .collect();
+
+        Self {
+            k: 0,
+            corr_bits,
+            corr_range,
+            corr_min,
+            bits_high,
+            m_bits,
+            m_corrector_0: ArithmeticBitModel::new(),
+            m_corrector,
+        }
+    }
+
+    fn k(&self) -> u32 {
+        self.k
+    }
+
+    fn decompress(&mut self, decoder: &mut ArithmeticDecoder, pred: i32, context: u32) -> i32 {
+        let corr = self.read_corrector(decoder, context as usize);
+        let mut real = pred.wrapping_add(corr);
+        if real < 0 {
+            real = real.wrapping_add(self.corr_range as i32);
+        } else if real as u32 >= self.corr_range {
+            real = real.wrapping_sub(self.corr_range as i32);
+        }
+        real
+    }
+
+    fn read_corrector(&mut self, decoder: &mut ArithmeticDecoder, context: usize) -> i32 {
+        self.k = decoder.decode_symbol(&mut self.m_bits[context]);
+
+        let k = self.k;
+        if k == 0 {
+            return decoder.decode_bit(&mut self.m_corrector_0) as i32;
+        }
+
+        if k >= 32 || k > self.corr_bits {
+            return self.corr_min;
+        }
+
+        let c = if k <= self.bits_high {
+            decoder.decode_symbol(&mut self.m_corrector[k as usize]) as i32
+        } else {
+            let k1 = k - self.bits_high;
+            let c = decoder.decode_symbol(&mut self.m_corrector[k as usize]) as i32;
+            let c1 = decoder.read_bits(k1) as i32;
+            (c << k1) | c1
+        };
+
+        if c >= (1 << (k - 1)) {
+            c.wrapping_add(1)
+        } else {
+            c.wrapping_sub(((1u64 << k) - 1) as i32)
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Clone)]
+struct ArithmeticBitModel {
+    bit_0_count: u32,
+    bit_count: u32,
+    bit_0_prob: u32,
+    update_cycle: u32,
+    bits_until_update: u32,
+}
+
+impl ArithmeticBitModel {
+    fn new() -> Self {
+        Self {
+            bit_0_count: 1,
+            bit_count: 2,
+            bit_0_prob: 1 << (BM_LENGTH_SHIFT - 1),
+            update_cycle: 4,
+            bits_until_update: 4,
+        }
+    }
+
+    fn update(&mut self) {
+        self.bit_count += self.update_cycle;
+        if self.bit_count > BM_MAX_COUNT {
+            self.bit_count = (self.bit_count + 1) >> 1;
+            self.bit_0_count = (self.bit_0_count + 1) >> 1;
+            if self.bit_0_count == self.bit_count {
+                self.bit_count += 1;
+            }
+        }
+
+        let scale = 0x8000_0000u32 / self.bit_count;
+        self.bit_0_prob = (self.bit_0_count * scale) >> (31 - BM_LENGTH_SHIFT);
+
+        self.update_cycle = ((5 * self.update_cycle) >> 2).min(64);
+        self.bits_until_update = self.update_cycle;
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Clone)]
+struct ArithmeticModel {
+    symbols: u32,
+    last_symbol: u32,
+    table_size: u32,
+    table_shift: u32,
+    total_count: u32,
+    update_cycle: u32,
+    symbols_until_update: u32,
+    distribution: Vec<u32>,
+    symbol_count: Vec<u32>,
+    decoder_table: Vec<u32>,
+}
+
+impl ArithmeticModel {
+    fn new(symbols: u32) -> Self {
+        let (table_size, table_shift) = if symbols > 16 {
+            let mut table_bits = 3;
+            while symbols > (1 << (table_bits + 2)) {
+                table_bits += 1;
+            }
+            (1 << table_bits, DM_LENGTH_SHIFT - table_bits)
+        } else {
+            (0, 0)
+        };
+
+        let mut result = Self {
+            symbols,
+            last_symbol: symbols - 1,
+            table_size,
+            table_shift,
+            total_count: 0,
+            update_cycle: symbols,
+            symbols_until_update: 0,
+            distribution: vec![0; symbols as usize],
+            symbol_count: vec![1; symbols as usize],
+            decoder_table: if table_size > 0 {
+                vec![0; table_size as usize + 2]
+            } else {
+                Vec::new()
+            },
+        };
+
+        result.update();
+        result.update_cycle = (symbols + 6) >> 1;
+        result.symbols_until_update = result.update_cycle;
+        result
+    }
+
+    fn update(&mut self) {
+        self.total_count += self.update_cycle;
+        if self.total_count > DM_MAX_COUNT {
+            self.total_count = 0;
+            for count in self.symbol_count.iter_mut() {
+                *count = (*count + 1) >> 1;
+                self.total_count += *count;
+            }
+        }
+
+        let scale = 0x8000_0000u32 / self.total_count;
+        let mut sum = 0u32;
+
+        if self.table_size == 0 {
+            for k in 0..self.symbols as usize {
+                self.distribution[k] = (scale.wrapping_mul(sum)) >> (31 - DM_LENGTH_SHIFT);
+                sum += self.symbol_count[k];
+            }
+        } else {
+            let mut s = 0usize;
+            for k in 0..self.symbols as usize {
+                self.distribution[k] = (scale.wrapping_mul(sum)) >> (31 - DM_LENGTH_SHIFT);
+                sum += self.symbol_count[k];
+                let w = (self.distribution[k] >> self.table_shift) as usize;
+                while s < w {
+                    s += 1;
+                    self.decoder_table[s] = k as u32 - 1;
+                }
+            }
+            self.decoder_table[0] = 0;
+            while s <= self.table_size as usize {
+                s += 1;
+                self.decoder_table[s] = self.symbols - 1;
+            }
+        }
+
+        self.update_cycle = ((5 * self.update_cycle) >> 2).min((self.symbols + 6) << 3);
+        self.symbols_until_update = self.update_cycle;
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Default)]
+struct ArithmeticDecoder {
+    data: Vec<u8>,
+    position: usize,
+    value: u32,
+    length: u32,
+}
+
+impl ArithmeticDecoder {
+    fn new(data: Vec<u8>) -> Self {
+        let mut result = Self {
+            data,
+            position: 0,
+            value: 0,
+            length: AC_MAX_LENGTH,
+        };
+
+        for _ in 0..4 {
+            result.value = (result.value << 8) | result.next_byte() as u32;
+        }
+
+        result
+    }
+
+    /// Returns the next byte of the data, 0 if its end was reached
+    #[inline(always)]
+    fn next_byte(&mut self) -> u8 {
+        let result = self.data.get(self.position).cloned().unwrap_or(0);
+        self.position += 1;
+        result
+    }
+
+    #[inline(always)]
+    fn renorm(&mut self) {
+        loop {
+            self.value = (self.value << 8) | self.next_byte() as u32;
+            self.length <<= 8;
+            if self.length >= AC_MIN_LENGTH {
+                break;
+            }
+        }
+    }
+
+    fn decode_bit(&mut self, m: &mut ArithmeticBitModel) -> u32 {
+        let x = m.bit_0_prob * (self.length >> BM_LENGTH_SHIFT);
+        let sym = if self.value < x {
+            self.length = x;
+            m.bit_0_count += 1;
+            0
+        } else {
+            self.value -= x;
+            self.length -= x;
+            1
+        };
+
+        if self.length < AC_MIN_LENGTH {
+            self.renorm();
+        }
+
+        m.bits_until_update -= 1;
+        if m.bits_until_update == 0 {
+            m.update();
+        }
+
+        sym
+    }
+
+    fn decode_symbol(&mut self, m: &mut ArithmeticModel) -> u32 {
+        let mut sym;
+        let x;
+        let mut y = self.length;
+
+        if !m.decoder_table.is_empty() {
+            self.length >>= DM_LENGTH_SHIFT;
+            let dv = self.value / self.length;
+            // Corrupt data could otherwise exceed the table
+            let t = ((dv >> m.table_shift) as usize).min(m.table_size as usize);
+
+            sym = m.decoder_table[t];
+            let mut n = m.decoder_table[t + 1] + 1;
+
+            while n > sym + 1 {
+                let k = (sym + n) >> 1;
+                if m.distribution[k as usize] > dv {
+                    n = k;
+                } else {
+                    sym = k;
+                }
+            }
+
+            x = m.distribution[sym as usize].wrapping_mul(self.length);
+            if sym != m.last_symbol {
+                y = m.distribution[sym as usize + 1].wrapping_mul(self.length);
+            }
+        } else {
+            let mut x_tmp = 0;
+            sym = 0;
+            self.length >>= DM_LENGTH_SHIFT;
+            let mut n = m.symbols;
+            let mut k = n >> 1;
+
+            loop {
+                let z = self.length.wrapping_mul(m.distribution[k as usize]);
+                if z > self.value {
+                    n = k;
+                    y = z;
+                } else {
+                    sym = k;
+                    x_tmp = z;
+                }
+                k = (sym + n) >> 1;
+                if k == sym {
+                    break;
+                }
+            }
+            x = x_tmp;
+        }
+
+        self.value = self.value.wrapping_sub(x);
+        // Non-zero to guarantee the termination of renorm, even for corrupt data
+        self.length = y.wrapping_sub(x).max(1);
+
+        if self.length < AC_MIN_LENGTH {
+            self.renorm();
+        }
+
+        m.symbol_count[sym as usize] += 1;
+        m.symbols_until_update -= 1;
+        if m.symbols_until_update == 0 {
+            m.update();
+        }
+
+        sym
+    }
+
+    fn read_bits(&mut self, bits: u32) -> u32 {
+        if bits > 19 {
+            let low = self.read_short() as u32;
+            let high = self.read_bits(bits - 16);
+            return (high << 16) | low;
+        }
+
+        self.length >>= bits;
+        let sym = self.value / self.length;
+        self.value -= self.length * sym;
+
+        if self.length < AC_MIN_LENGTH {
+            self.renorm();
+        }
+
+        sym
+    }
+
+    fn read_short(&mut self) -> u16 {
+        self.length >>= 16;
+        let sym = self.value / self.length;
+        self.value -= self.length * sym;
+
+        if self.length < AC_MIN_LENGTH {
+            self.renorm();
+        }
+
+        sym as u16
+    }
+
+    fn read_int(&mut self) -> u32 {
+        let low = self.read_short() as u32;
+        let high = self.read_short() as u32;
+        (high << 16) | low
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[inline(always)]
+fn lazy_model(models: &mut [Option<ArithmeticModel>], i: u8, symbols: u32) -> &mut ArithmeticModel {
+    models[i as usize].get_or_insert_with(|| ArithmeticModel::new(symbols))
+}
+
+#[inline(always)]
+fn u8_fold(x: i32) -> u8 {
+    if x < 0 {
+        (x + 256) as u8
+    } else if x > 255 {
+        (x - 256) as u8
+    } else {
+        x as u8
+    }
+}
+
+#[inline(always)]
+fn u8_clamp(x: i32) -> i32 {
+    x.clamp(0, 255)
+}
@@ -24,7 +24,7 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 use crate::*;
 
-use super::types::*;
+use super::{laz::*, types::*, vlr::*};
 
 use std::{
     convert::{TryFrom, TryInto},
@@ -39,6 +39,59 @@ use super::super::{from_bytes::*, types::*};
 
 /// Iterator to incrementally load a .las file
 pub struct LasIterator<P, R, const CHUNK_SIZE: usize>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead + Seek,
+{
+    inner: LasPointIterator<P, R, CHUNK_SIZE>,
+}
+
+impl<P, R, const CHUNK_SIZE: usize> LasIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead + Seek,
+{
+    pub fn new(read: R) -> IOResult<Self> {
+        Ok(Self {
+            inner: LasPointIterator::new(read)?,
+        })
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> Iterator for LasIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead + Seek,
+{
+    type Item = IOResult<StackVec<DataReserve<P>, CHUNK_SIZE>>;
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|chunk| {
+            let mut result = StackVec::default();
+            for x in chunk? {
+                let x = match x {
+                    DataReserve::Data((p, _)) => DataReserve::Data(p),
+                    DataReserve::Reserve(x) => DataReserve::Reserve(x),
+                    DataReserve::ReserveExact(x) => DataReserve::ReserveExact(x),
+                };
+                result.push(x).unwrap(); // unwrap safe since both have the same size
+            }
+            Ok(result)
+        })
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for LasIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead + Seek,
+{
+}
+
+//------------------------------------------------------------------------------
+
+/// Iterator to incrementally load a .las file together with the attributes of each point record
+pub struct LasPointIterator<P, R, const CHUNK_SIZE: usize>
 where
     P: IsBuildable3D + Default,
     R: BufRead + Seek,
@@ -47,11 +100,12 @@ where
     is_done: bool,
     current: usize,
     header: Option<Header>,
+    laz: Option<LazDecompressor>,
     buffer: Vec<u8>,
     phantom_p: PhantomData<P>,
 }
 
-impl<P, R, const CHUNK_SIZE: usize> LasIterator<P, R, CHUNK_SIZE>
+impl<P, R, const CHUNK_SIZE: usize> LasPointIterator<P, R, CHUNK_SIZE>
 where
     P: IsBuildable3D + Default,
     R: BufRead + Seek,
@@ -62,15 +116,19 @@ where
             is_done: false,
             current: 0,
             header: None,
+            laz: None,
             buffer: Vec::new(),
             phantom_p: PhantomData,
         })
     }
 
     #[inline(always)]
-    fn fetch_one(&mut self) -> IOResult<P> {
+    fn fetch_one(&mut self) -> IOResult<(P, LasPoint)> {
         if let Some(ref header) = self.header {
-            self.read.read_exact(&mut self.buffer)?;
+            match self.laz {
+                Some(ref mut laz) => laz.decompress(&mut self.read, &mut self.buffer)?,
+                None => self.read.read_exact(&mut self.buffer)?,
+            }
 
             let pd = PointData::from_bytes(self.buffer[0..12].try_into()?);
 
@@ -78,19 +136,21 @@ where
             let y = header.offset_y + (pd.y as f64 * header.scale_factor_y);
             let z = header.offset_z + (pd.z as f64 * header.scale_factor_z);
 
-            Ok(P::new(x, y, z))
+            let las_point = LasPoint::from_bytes(&self.buffer, header.point_record_format)?;
+
+            Ok((P::new(x, y, z), las_point))
         } else {
             Err(IOError::Header)
         }
     }
 }
 
-impl<P, R, const CHUNK_SIZE: usize> Iterator for LasIterator<P, R, CHUNK_SIZE>
+impl<P, R, const CHUNK_SIZE: usize> Iterator for LasPointIterator<P, R, CHUNK_SIZE>
 where
     P: IsBuildable3D + Default,
     R: BufRead + Seek,
 {
-    type Item = IOResult<StackVec<DataReserve<P>, CHUNK_SIZE>>;
+    type Item = IOResult<StackVec<DataReserve<(P, LasPoint)>, CHUNK_SIZE>>;
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
         if self.is_done {
@@ -103,23 +163,25 @@ where
             if chunk.is_full() {
                 return Some(Ok(chunk));
             } else if self.header.is_none() {
-                if let Ok(header) = load_header(&mut self.read).and_then(|x| Header::try_from(x)) {
-                    if let Ok(_) = self
-                        .read
-                        .seek(SeekFrom::Start(header.offset_point_data as u64))
-                    {
-                        self.buffer = vec![0u8; header.point_record_length as usize];
-                        let n = header.n_point_records;
-                        self.header = Some(header);
-                        chunk.push(DataReserve::ReserveExact(n as usize)).unwrap()
-                    // unwrap safe since we only call this if chunk.has_space()
-                    } else {
+                match load_header(&mut self.read).and_then(Header::try_from) {
+                    Ok(header) => match start_point_data(&mut self.read, &header) {
+                        Ok(laz) => {
+                            self.laz = laz;
+                            self.buffer = vec![0u8; header.point_record_length as usize];
+                            let n = header.n_point_records;
+                            self.header = Some(header);
+                            chunk.push(DataReserve::ReserveExact(n as usize)).unwrap()
+                            // unwrap safe since we only call this if chunk.has_space()
+                        }
+                        Err(e) => {
+                            self.is_done = true;
+                            return Some(Err(e));
+                        }
+                    },
+                    Err(e) => {
                         self.is_done = true;
-                        return Some(Err(IOError::BinaryData));
+                        return Some(Err(e.context(IOElement::Header, IOLocation::Byte(0))));
                     }
-                } else {
-                    self.is_done = true;
-                    return Some(Err(IOError::Header));
                 }
             }
             // unwrap safe since header is always assigned
@@ -129,7 +191,13 @@ where
                 match self.fetch_one() {
                     Err(e) => {
                         self.is_done = true;
-                        return Some(Err(e));
+                        // unwrap safe since header is always assigned
+                        let location = point_location(
+                            self.header.as_ref().unwrap(),
+                            self.laz.is_some(),
+                            self.current - 1,
+                        );
+                        return Some(Err(e.context(IOElement::Point, location)));
                     }
                     Ok(x) => chunk.push(DataReserve::Data(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
                 }
@@ -144,7 +212,7 @@ where
     }
 }
 
-impl<P, R, const CHUNK_SIZE: usize> FusedIterator for LasIterator<P, R, CHUNK_SIZE>
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for LasPointIterator<P, R, CHUNK_SIZE>
 where
     P: IsBuildable3D + Default,
     R: BufRead + Seek,
@@ -153,7 +221,7 @@ where
 
 //------------------------------------------------------------------------------
 
-/// Loads points from .las file into IsPushable<IsBuildable3D>
+/// Loads points from .las or .laz file into IsPushable<IsBuildable3D>
 pub fn load_las<IP, P, R, const CHUNK_SIZE: usize>(read: R, ip: &mut IP) -> IOResult<()>
 where
     IP: IsPushable<P>,
@@ -175,14 +243,151 @@ where
     Ok(())
 }
 
+/// Loads points from .las file into IsPushable<IsBuildable3D>
+/// The attributes of each point record are pushed in the same order into las_points
+pub fn load_las_points<IP, P, R, IPL, const CHUNK_SIZE: usize>(
+    read: R,
+    ip: &mut IP,
+    las_points: &mut IPL,
+) -> IOResult<()>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D + Default,
+    R: BufRead + Seek,
+    IPL: IsPushable<LasPoint>,
+{
+    let iterator = LasPointIterator::<_, _, CHUNK_SIZE>::new(read)?;
+
+    for chunk in iterator {
+        for x in chunk? {
+            match x {
+                DataReserve::Data((p, las_point)) => {
+                    ip.push(p);
+                    las_points.push(las_point);
+                }
+                DataReserve::Reserve(x) => {
+                    ip.reserve(x);
+                    las_points.reserve(x);
+                }
+                DataReserve::ReserveExact(x) => {
+                    ip.reserve_exact(x);
+                    las_points.reserve_exact(x);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads the variable length records and extended variable length records of a .las file
+/// Note that read is in an undefined state afterwards
+pub fn load_las_vlrs<R>(mut read: R) -> IOResult<Vec<LasVlr>>
+where
+    R: Read + Seek,
+{
+    let header = Header::try_from(load_header(&mut read)?)?;
+
+    load_vlrs_of(&mut read, &header)
+}
+
+//------------------------------------------------------------------------------
+
+/// Location of the i-th point record, compressed records can only be referred to by index
+fn point_location(header: &Header, is_compressed: bool, i: usize) -> IOLocation {
+    if is_compressed {
+        IOLocation::Index(i)
+    } else {
+        IOLocation::Byte(
+            header.offset_point_data as u64 + i as u64 * header.point_record_length as u64,
+        )
+    }
+}
+
+/// Positions read at the start of the point data
+/// For compressed files the returned LazDecompressor must be used to read the point records
+fn start_point_data<R>(read: &mut R, header: &Header) -> IOResult<Option<LazDecompressor>>
+where
+    R: Read + Seek,
+{
+    if !header.is_compressed {
+        read.seek(SeekFrom::Start(header.offset_point_data as u64))?;
+        return Ok(None);
+    }
+
+    let laz_vlr = load_vlrs_of(read, header)?
+        .iter()
+        .find(|x| LazVlr::is_laz_vlr(x))
+        .ok_or(IOError::Laz(LazError::MissingVlr))
+        .and_then(|x| LazVlr::from_bytes(&x.data))?;
+
+    read.seek(SeekFrom::Start(header.offset_point_data as u64))?;
+
+    Ok(Some(LazDecompressor::new(read, &laz_vlr, header)?))
+}
+
+fn load_vlrs_of<R>(read: &mut R, header: &Header) -> IOResult<Vec<LasVlr>>
+where
+    R: Read + Seek,
+{
+    let mut result = Vec::with_capacity(
+        (header.n_variable_length_records + header.n_extended_variable_length) as usize,
+    );
+
+    read.seek(SeekFrom::Start(header.header_size as u64))?;
+    for _ in 0..header.n_variable_length_records {
+        result.push(load_vlr(read, false)?);
+    }
+
+    if header.n_extended_variable_length > 0 {
+        read.seek(SeekFrom::Start(header.start_extended_variable_length))?;
+        for _ in 0..header.n_extended_variable_length {
+            result.push(load_vlr(read, true)?);
+        }
+    }
+
+    Ok(result)
+}
+
+//------------------------------------------------------------------------------
+
+fn load_vlr<R>(read: &mut R, is_extended: bool) -> IOResult<LasVlr>
+where
+    R: Read,
+{
+    let mut buffer = [0u8; EVLR_HEADER_SIZE];
+    let header_size = if is_extended {
+        EVLR_HEADER_SIZE
+    } else {
+        VLR_HEADER_SIZE
+    };
+    read.read_exact(&mut buffer[..header_size])?;
+
+    let (mut vlr, n_data) = LasVlr::from_header_bytes(&buffer[..header_size], is_extended)?;
+
+    // Don't trust n_data for the allocation, since it might be corrupt
+    read.take(n_data).read_to_end(&mut vlr.data)?;
+    if vlr.data.len() as u64 != n_data {
+        return Err(IOError::EndReached);
+    }
+
+    Ok(vlr)
+}
+
 //------------------------------------------------------------------------------
 
 fn load_header<R>(mut read: R) -> IOResult<HeaderRaw>
 where
     R: Read,
 {
-    let mut buffer = [0u8; 375];
-    read.read_exact(&mut buffer)?;
+    // Headers prior to 1.4 are shorter, the remaining fields are zero in that case
+    let mut buffer = [0u8; HEADER_SIZE_1_4 as usize];
+    read.read_exact(&mut buffer[..HEADER_SIZE_1_2 as usize])?;
+
+    let header_size = u16::from_le_bytes(buffer[94..96].try_into()?);
+    let n_remaining =
+        (header_size.clamp(HEADER_SIZE_1_2, HEADER_SIZE_1_4) - HEADER_SIZE_1_2) as usize;
+    read.read_exact(&mut buffer[HEADER_SIZE_1_2 as usize..HEADER_SIZE_1_2 as usize + n_remaining])?;
 
     Ok(HeaderRaw {
         file_signature: array_from_bytes_le!(u8, 4, &buffer[0..4])?, //4 4
@@ -22,8 +22,14 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 //! Module for IO operations of the las file format
 
+mod laz;
 mod load;
+mod save;
 mod types;
+mod vlr;
 
+pub use laz::*;
 pub use load::*;
+pub use save::*;
 pub use types::*;
+pub use vlr::*;
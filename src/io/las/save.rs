@@ -0,0 +1,307 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//! Module for save functions of the .las file format
+
+use crate::*;
+
+use super::{types::*, vlr::*};
+
+use std::{
+    io::Write,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use super::super::types::*;
+
+//------------------------------------------------------------------------------
+
+/// Saves an IsRandomAccessible<Is3D> in the .las file format
+/// Supports the point record formats 0 - 3, colors are only written for the formats 2 and 3
+/// Scale and offset are calculated from the bounding box, using the finest decimal scale that still fits all values
+/// The vlrs are written as (extended) variable length records, extended records are written as regular ones for version 1.2
+/// Records describing extra bytes are skipped, since no extra bytes are written
+pub fn save_las<RA, P, W>(
+    write: &mut W,
+    ra: &RA,
+    version: LasVersion,
+    point_format: u8,
+    colors: Option<&[Rgb]>,
+    vlrs: &[LasVlr],
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P>,
+    P: Is3D,
+    W: Write,
+{
+    let n = ra.len();
+
+    if point_format > 3 {
+        return Err(IOError::UnknownPointFormat);
+    }
+
+    if let Some(colors) = colors {
+        if colors.len() != n {
+            return Err(IOError::ColorArrayLength);
+        }
+    }
+
+    if version == LasVersion::V1_2 && n > u32::MAX as usize {
+        return Err(IOError::VertexCount(None));
+    }
+
+    let mut min = [f64::MAX; 3];
+    let mut max = [f64::MIN; 3];
+    for i in 0..n {
+        let p = &ra[i];
+        for (dim, value) in [p.x(), p.y(), p.z()].iter().enumerate() {
+            min[dim] = min64(min[dim], *value);
+            max[dim] = max64(max[dim], *value);
+        }
+    }
+
+    if n == 0 {
+        min = [0.0; 3];
+        max = [0.0; 3];
+    }
+
+    let offset = min;
+    let scale = [
+        scale_for_range(max[0] - min[0]),
+        scale_for_range(max[1] - min[1]),
+        scale_for_range(max[2] - min[2]),
+    ];
+
+    let (header_size, version_minor) = match version {
+        LasVersion::V1_2 => (HEADER_SIZE_1_2, 2),
+        LasVersion::V1_4 => (HEADER_SIZE_1_4, 4),
+    };
+
+    let point_record_length = min_point_record_length(point_format);
+
+    let mut vlrs_bytes = Vec::new();
+    let mut evlrs_bytes = Vec::new();
+    let mut n_vlrs = 0u32;
+    let mut n_evlrs = 0u32;
+    let mut global_encoding = 0u16;
+
+    for vlr in vlrs.iter().filter(|x| !x.is_extra_bytes()) {
+        if vlr.is_extended && version == LasVersion::V1_4 {
+            evlrs_bytes.extend_from_slice(&vlr.to_bytes(true)?);
+            n_evlrs += 1;
+        } else {
+            vlrs_bytes.extend_from_slice(&vlr.to_bytes(false)?);
+            n_vlrs += 1;
+        }
+
+        if vlr.is_ogc_wkt() && version == LasVersion::V1_4 {
+            // WKT bit
+            global_encoding |= 0b0001_0000;
+        }
+    }
+
+    let offset_point_data = header_size as u32 + vlrs_bytes.len() as u32;
+    let start_extended_variable_length = if n_evlrs > 0 {
+        offset_point_data as u64 + n as u64 * point_record_length as u64
+    } else {
+        0
+    };
+
+    // All points are considered to be the first of a single return
+    let legacy_n_point_records = if n > u32::MAX as usize { 0 } else { n as u32 };
+    let mut legacy_n_point_return = [0u32; 5];
+    legacy_n_point_return[0] = legacy_n_point_records;
+    let mut n_points_return = [0u64; 15];
+    n_points_return[0] = n as u64;
+
+    let (file_creation_day, file_creation_year) = creation_day_year();
+
+    let mut system_identifier = [0u8; 32];
+    system_identifier[..5].copy_from_slice(b"OTHER");
+    let mut generating_software = [0u8; 32];
+    generating_software[..7].copy_from_slice(b"rust-3d");
+
+    let header = HeaderRaw {
+        file_signature: *b"LASF",
+        file_source_id: 0,
+        global_encoding,
+        guid1: 0,
+        guid2: 0,
+        guid3: 0,
+        guid4: [0; 8],
+        version_major: 1,
+        version_minor,
+        system_identifier,
+        generating_software,
+        file_creation_day,
+        file_creation_year,
+        header_size,
+        offset_point_data,
+        n_variable_length_records: n_vlrs,
+        point_record_format: point_format,
+        point_record_length,
+        legacy_n_point_records,
+        legacy_n_point_return,
+        scale_factor_x: scale[0],
+        scale_factor_y: scale[1],
+        scale_factor_z: scale[2],
+        offset_x: offset[0],
+        offset_y: offset[1],
+        offset_z: offset[2],
+        max_x: max[0],
+        min_x: min[0],
+        max_y: max[1],
+        min_y: min[1],
+        max_z: max[2],
+        min_z: min[2],
+        start_wavefront_data: 0,
+        start_extended_variable_length,
+        n_extended_variable_length: n_evlrs,
+        n_point_records: n as u64,
+        n_points_return,
+    };
+
+    write.write_all(&header_to_bytes(&header)[..header_size as usize])?;
+    write.write_all(&vlrs_bytes)?;
+
+    let mut buffer = vec![0u8; point_record_length as usize];
+
+    for i in 0..n {
+        let p = &ra[i];
+
+        let x = ((p.x() - offset[0]) / scale[0]).round() as i32;
+        let y = ((p.y() - offset[1]) / scale[1]).round() as i32;
+        let z = ((p.z() - offset[2]) / scale[2]).round() as i32;
+
+        buffer[0..4].copy_from_slice(&x.to_le_bytes());
+        buffer[4..8].copy_from_slice(&y.to_le_bytes());
+        buffer[8..12].copy_from_slice(&z.to_le_bytes());
+        // intensity
+        buffer[12..14].copy_from_slice(&0u16.to_le_bytes());
+        // return number 1 of 1 returns
+        buffer[14] = 0b0000_1001;
+        // classification, scan angle rank, user data and point source id remain 0
+
+        // GPS time of formats 1 and 3 remains 0
+
+        if point_format == 2 || point_format == 3 {
+            let start = if point_format == 2 { 20 } else { 28 };
+            if let Some(colors) = colors {
+                let c = &colors[i];
+                buffer[start..start + 2].copy_from_slice(&(c.r as u16 * 257).to_le_bytes());
+                buffer[start + 2..start + 4].copy_from_slice(&(c.g as u16 * 257).to_le_bytes());
+                buffer[start + 4..start + 6].copy_from_slice(&(c.b as u16 * 257).to_le_bytes());
+            }
+        }
+
+        write.write_all(&buffer)?;
+    }
+
+    write.write_all(&evlrs_bytes)?;
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+fn header_to_bytes(x: &HeaderRaw) -> Vec<u8> {
+    let mut result = Vec::with_capacity(HEADER_SIZE_1_4 as usize);
+
+    result.extend_from_slice(&x.file_signature); //4 4
+    result.extend_from_slice(&x.file_source_id.to_le_bytes()); //2 6
+    result.extend_from_slice(&x.global_encoding.to_le_bytes()); //2 8
+    result.extend_from_slice(&x.guid1.to_le_bytes()); //4 12
+    result.extend_from_slice(&x.guid2.to_le_bytes()); //2 14
+    result.extend_from_slice(&x.guid3.to_le_bytes()); //2 16
+    result.extend_from_slice(&x.guid4); //8 24
+    result.push(x.version_major); //1 25
+    result.push(x.version_minor); //1 26
+    result.extend_from_slice(&x.system_identifier); //32 58
+    result.extend_from_slice(&x.generating_software); //32 90
+    result.extend_from_slice(&x.file_creation_day.to_le_bytes()); //2 92
+    result.extend_from_slice(&x.file_creation_year.to_le_bytes()); //2 94
+    result.extend_from_slice(&x.header_size.to_le_bytes()); //2 96
+    result.extend_from_slice(&x.offset_point_data.to_le_bytes()); //4 100
+    result.extend_from_slice(&x.n_variable_length_records.to_le_bytes()); //4 104
+    result.push(x.point_record_format); //1 105
+    result.extend_from_slice(&x.point_record_length.to_le_bytes()); //2 107
+    result.extend_from_slice(&x.legacy_n_point_records.to_le_bytes()); //4 111
+    for n in x.legacy_n_point_return.iter() {
+        result.extend_from_slice(&n.to_le_bytes()); //20 131
+    }
+    result.extend_from_slice(&x.scale_factor_x.to_le_bytes()); //8 139
+    result.extend_from_slice(&x.scale_factor_y.to_le_bytes()); //8 147
+    result.extend_from_slice(&x.scale_factor_z.to_le_bytes()); //8 155
+    result.extend_from_slice(&x.offset_x.to_le_bytes()); //8 163
+    result.extend_from_slice(&x.offset_y.to_le_bytes()); //8 171
+    result.extend_from_slice(&x.offset_z.to_le_bytes()); //8 179
+    result.extend_from_slice(&x.max_x.to_le_bytes()); //8 187
+    result.extend_from_slice(&x.min_x.to_le_bytes()); //8 195
+    result.extend_from_slice(&x.max_y.to_le_bytes()); //8 203
+    result.extend_from_slice(&x.min_y.to_le_bytes()); //8 211
+    result.extend_from_slice(&x.max_z.to_le_bytes()); //8 219
+    result.extend_from_slice(&x.min_z.to_le_bytes()); //8 227
+    result.extend_from_slice(&x.start_wavefront_data.to_le_bytes()); //8 235
+    result.extend_from_slice(&x.start_extended_variable_length.to_le_bytes()); //8 243
+    result.extend_from_slice(&x.n_extended_variable_length.to_le_bytes()); //4 247
+    result.extend_from_slice(&x.n_point_records.to_le_bytes()); //8 255
+    for n in x.n_points_return.iter() {
+        result.extend_from_slice(&n.to_le_bytes()); //120 375
+    }
+
+    result
+}
+
+//------------------------------------------------------------------------------
+
+/// Finest decimal scale which still allows storing the range within an i32
+fn scale_for_range(range: f64) -> f64 {
+    if range <= 0.0 {
+        return 0.001;
+    }
+
+    let exponent = (range / (i32::MAX as f64)).log10().ceil() as i32;
+    // Prefer at most nanometer precision, finer scales aren't supported by most readers
+    10f64.powi(exponent.max(-9))
+}
+
+//------------------------------------------------------------------------------
+
+/// Day of year and year of the current date, (0, 0) if unknown
+fn creation_day_year() -> (u16, u16) {
+    let mut days = match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(x) => x.as_secs() / 86400,
+        Err(_) => return (0, 0),
+    };
+
+    let mut year = 1970;
+    loop {
+        let is_leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+        let days_year = if is_leap { 366 } else { 365 };
+        if days < days_year {
+            // Day of year is 1 based
+            return ((days + 1) as u16, year as u16);
+        }
+        days -= days_year;
+        year += 1;
+    }
+}
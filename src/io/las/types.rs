@@ -22,23 +22,49 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 //! Module for types for the .las file format
 
+use crate::*;
+
 use std::convert::{TryFrom, TryInto};
 
 use super::super::types::*;
 
 //------------------------------------------------------------------------------
 
+/// Size of the header of .las files of version 1.2
+pub const HEADER_SIZE_1_2: u16 = 227;
+
+/// Size of the header of .las files of version 1.4
+pub const HEADER_SIZE_1_4: u16 = 375;
+
+//------------------------------------------------------------------------------
+
+/// Version of the .las file format
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LasVersion {
+    V1_2,
+    #[default]
+    V1_4,
+}
+
+//------------------------------------------------------------------------------
+
 #[derive(Debug)]
 pub struct Header {
+    pub header_size: u16,
     pub offset_point_data: u32,
+    pub n_variable_length_records: u32,
+    pub point_record_format: u8,
     pub point_record_length: u16,
     pub n_point_records: u64,
+    pub is_compressed: bool,
     pub scale_factor_x: f64,
     pub scale_factor_y: f64,
     pub scale_factor_z: f64,
     pub offset_x: f64,
     pub offset_y: f64,
     pub offset_z: f64,
+    pub start_extended_variable_length: u64,
+    pub n_extended_variable_length: u32,
 }
 
 impl TryFrom<HeaderRaw> for Header {
@@ -56,20 +82,34 @@ impl TryFrom<HeaderRaw> for Header {
             x.legacy_n_point_records as u64
         };
 
-        if x.point_record_format > 10 {
+        // LASzip marks compressed files by setting the upper bits of the format
+        let is_compressed = x.point_record_format & 0xC0 != 0;
+        let point_record_format = x.point_record_format & 0x3F;
+
+        if point_record_format > 10 {
             return Err(IOError::UnknownPointFormat);
         }
 
+        if x.point_record_length < min_point_record_length(point_record_format) {
+            return Err(IOError::Header);
+        }
+
         Ok(Header {
+            header_size: x.header_size,
             offset_point_data: x.offset_point_data,
+            n_variable_length_records: x.n_variable_length_records,
+            point_record_format,
             point_record_length: x.point_record_length,
             n_point_records,
+            is_compressed,
             scale_factor_x: x.scale_factor_x,
             scale_factor_y: x.scale_factor_y,
             scale_factor_z: x.scale_factor_z,
             offset_x: x.offset_x,
             offset_y: x.offset_y,
             offset_z: x.offset_z,
+            start_extended_variable_length: x.start_extended_variable_length,
+            n_extended_variable_length: x.n_extended_variable_length,
         })
     }
 }
@@ -138,3 +178,124 @@ impl PointData {
         }
     }
 }
+
+//------------------------------------------------------------------------------
+
+/// All attributes of a point record of a .las file, except for its position
+/// Fields which aren't part of the point record format are None
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LasPoint {
+    pub intensity: u16,
+    pub return_number: u8,
+    pub number_of_returns: u8,
+    pub scan_direction_flag: bool,
+    pub edge_of_flight_line: bool,
+    pub classification: u8,
+    /// Synthetic, key-point, withheld and overlap (formats 6 - 10 only) bits
+    pub classification_flags: u8,
+    /// Always 0 for the formats 0 - 5
+    pub scanner_channel: u8,
+    /// Scan angle in degrees
+    pub scan_angle: f32,
+    pub user_data: u8,
+    pub point_source_id: u16,
+    pub gps_time: Option<f64>,
+    pub rgb: Option<[u16; 3]>,
+    pub nir: Option<u16>,
+}
+
+impl LasPoint {
+    /// Creates a LasPoint from the bytes of a point record of the given format
+    pub fn from_bytes(buffer: &[u8], point_format: u8) -> IOResult<Self> {
+        if point_format > 10 {
+            return Err(IOError::UnknownPointFormat);
+        }
+
+        if buffer.len() < min_point_record_length(point_format) as usize {
+            return Err(IOError::BinaryData);
+        }
+
+        let mut result = Self {
+            intensity: u16::from_le_bytes(buffer[12..14].try_into()?),
+            ..Self::default()
+        };
+
+        if point_format <= 5 {
+            result.return_number = buffer[14] & 0b0000_0111;
+            result.number_of_returns = (buffer[14] >> 3) & 0b0000_0111;
+            result.scan_direction_flag = buffer[14] & 0b0100_0000 != 0;
+            result.edge_of_flight_line = buffer[14] & 0b1000_0000 != 0;
+            result.classification = buffer[15] & 0b0001_1111;
+            result.classification_flags = buffer[15] >> 5;
+            result.scan_angle = i8::from_le_bytes([buffer[16]]) as f32;
+            result.user_data = buffer[17];
+            result.point_source_id = u16::from_le_bytes(buffer[18..20].try_into()?);
+
+            if point_format == 1 || point_format >= 3 {
+                result.gps_time = Some(f64::from_le_bytes(buffer[20..28].try_into()?));
+            }
+
+            match point_format {
+                2 => result.rgb = Some(rgb_from_bytes(&buffer[20..26])?),
+                3 | 5 => result.rgb = Some(rgb_from_bytes(&buffer[28..34])?),
+                _ => (),
+            }
+        } else {
+            result.return_number = buffer[14] & 0b0000_1111;
+            result.number_of_returns = buffer[14] >> 4;
+            result.classification_flags = buffer[15] & 0b0000_1111;
+            result.scanner_channel = (buffer[15] >> 4) & 0b0000_0011;
+            result.scan_direction_flag = buffer[15] & 0b0100_0000 != 0;
+            result.edge_of_flight_line = buffer[15] & 0b1000_0000 != 0;
+            result.classification = buffer[16];
+            result.user_data = buffer[17];
+            // Scan angle is stored in increments of 0.006 degrees
+            result.scan_angle = i16::from_le_bytes(buffer[18..20].try_into()?) as f32 * 0.006;
+            result.point_source_id = u16::from_le_bytes(buffer[20..22].try_into()?);
+            result.gps_time = Some(f64::from_le_bytes(buffer[22..30].try_into()?));
+
+            if point_format == 7 || point_format == 8 || point_format == 10 {
+                result.rgb = Some(rgb_from_bytes(&buffer[30..36])?);
+            }
+
+            if point_format == 8 || point_format == 10 {
+                result.nir = Some(u16::from_le_bytes(buffer[36..38].try_into()?));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Returns the color reduced to 8 bits per channel
+    pub fn rgb8(&self) -> Option<Rgb> {
+        self.rgb
+            .map(|[r, g, b]| Rgb::new((r >> 8) as u8, (g >> 8) as u8, (b >> 8) as u8))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Minimum length of a point record of the given format
+pub fn min_point_record_length(point_format: u8) -> u16 {
+    match point_format {
+        0 => 20,
+        1 => 28,
+        2 => 26,
+        3 => 34,
+        4 => 57,
+        5 => 63,
+        6 => 30,
+        7 => 36,
+        8 => 38,
+        9 => 59,
+        _ => 67,
+    }
+}
+
+fn rgb_from_bytes(buffer: &[u8]) -> IOResult<[u16; 3]> {
+    Ok([
+        u16::from_le_bytes(buffer[0..2].try_into()?),
+        u16::from_le_bytes(buffer[2..4].try_into()?),
+        u16::from_le_bytes(buffer[4..6].try_into()?),
+    ])
+}
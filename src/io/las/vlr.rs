@@ -0,0 +1,337 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//! Module for the (extended) variable length records of the .las file format
+
+use std::convert::TryInto;
+
+use super::super::types::*;
+
+//------------------------------------------------------------------------------
+
+/// Size of the header of a variable length record
+pub const VLR_HEADER_SIZE: usize = 54;
+
+/// Size of the header of an extended variable length record
+pub const EVLR_HEADER_SIZE: usize = 60;
+
+/// Size of a single extra bytes descriptor
+pub const EXTRA_BYTES_DESCRIPTOR_SIZE: usize = 192;
+
+//------------------------------------------------------------------------------
+
+/// A (extended) variable length record of a .las file
+/// The raw data is kept, so records can be written unchanged
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LasVlr {
+    pub user_id: String,
+    pub record_id: u16,
+    pub description: String,
+    /// Whether this record was / shall be stored as extended variable length record
+    pub is_extended: bool,
+    pub data: Vec<u8>,
+}
+
+impl LasVlr {
+    /// Creates a LasVlr holding a OGC WKT coordinate system
+    pub fn ogc_wkt(wkt: &str) -> Self {
+        let mut data = wkt.as_bytes().to_vec();
+        data.push(0);
+        Self {
+            user_id: "LASF_Projection".to_string(),
+            record_id: 2112,
+            description: "OGC coordinate system WKT".to_string(),
+            is_extended: false,
+            data,
+        }
+    }
+
+    /// Decodes the data of known record types
+    pub fn content(&self) -> IOResult<LasVlrContent> {
+        Ok(match (self.user_id.as_str(), self.record_id) {
+            ("LASF_Projection", 34735) => {
+                LasVlrContent::GeoKeyDirectory(GeoKeyDirectory::from_bytes(&self.data)?)
+            }
+            ("LASF_Projection", 34736) => LasVlrContent::GeoDoubleParams(
+                self.data
+                    .chunks_exact(8)
+                    .map(|x| f64::from_le_bytes(x.try_into().unwrap())) // unwrap safe since chunks_exact
+                    .collect(),
+            ),
+            ("LASF_Projection", 34737) => {
+                LasVlrContent::GeoAsciiParams(string_from_bytes(&self.data))
+            }
+            ("LASF_Projection", 2111) => {
+                LasVlrContent::OgcMathTransformWkt(string_from_bytes(&self.data))
+            }
+            ("LASF_Projection", 2112) => {
+                LasVlrContent::OgcCoordinateSystemWkt(string_from_bytes(&self.data))
+            }
+            ("LASF_Spec", 4) => {
+                let chunks = self.data.chunks_exact(EXTRA_BYTES_DESCRIPTOR_SIZE);
+                if !chunks.remainder().is_empty() {
+                    return Err(IOError::BinaryData);
+                }
+                LasVlrContent::ExtraBytes(
+                    chunks
+                        .map(ExtraBytesDescriptor::from_bytes)
+                        .collect::<IOResult<_>>()?,
+                )
+            }
+            _ => LasVlrContent::Unknown,
+        })
+    }
+
+    /// Returns true if this is a record describing extra bytes of the point records
+    pub fn is_extra_bytes(&self) -> bool {
+        self.user_id == "LASF_Spec" && self.record_id == 4
+    }
+
+    /// Returns true if this is a record holding the coordinate system as OGC WKT
+    pub fn is_ogc_wkt(&self) -> bool {
+        self.user_id == "LASF_Projection" && self.record_id == 2112
+    }
+
+    /// Parses the record header and returns the record with empty data and the length of the data
+    pub fn from_header_bytes(buffer: &[u8], is_extended: bool) -> IOResult<(Self, u64)> {
+        let header_size = if is_extended {
+            EVLR_HEADER_SIZE
+        } else {
+            VLR_HEADER_SIZE
+        };
+
+        if buffer.len() != header_size {
+            return Err(IOError::BinaryData);
+        }
+
+        let user_id = string_from_bytes(&buffer[2..18]);
+        let record_id = u16::from_le_bytes(buffer[18..20].try_into()?);
+        let (n_data, description) = if is_extended {
+            (
+                u64::from_le_bytes(buffer[20..28].try_into()?),
+                string_from_bytes(&buffer[28..60]),
+            )
+        } else {
+            (
+                u16::from_le_bytes(buffer[20..22].try_into()?) as u64,
+                string_from_bytes(&buffer[22..54]),
+            )
+        };
+
+        Ok((
+            Self {
+                user_id,
+                record_id,
+                description,
+                is_extended,
+                data: Vec::new(),
+            },
+            n_data,
+        ))
+    }
+
+    /// Converts the record to bytes, including its header
+    pub fn to_bytes(&self, as_extended: bool) -> IOResult<Vec<u8>> {
+        let mut result = Vec::with_capacity(EVLR_HEADER_SIZE + self.data.len());
+
+        result.extend_from_slice(&0u16.to_le_bytes());
+        result.extend_from_slice(&string_to_bytes::<16>(&self.user_id));
+        result.extend_from_slice(&self.record_id.to_le_bytes());
+        if as_extended {
+            result.extend_from_slice(&(self.data.len() as u64).to_le_bytes());
+        } else {
+            if self.data.len() > u16::MAX as usize {
+                return Err(IOError::BinaryData);
+            }
+            result.extend_from_slice(&(self.data.len() as u16).to_le_bytes());
+        }
+        result.extend_from_slice(&string_to_bytes::<32>(&self.description));
+        result.extend_from_slice(&self.data);
+
+        Ok(result)
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Decoded content of a LasVlr
+#[derive(Debug, Clone, PartialEq)]
+pub enum LasVlrContent {
+    GeoKeyDirectory(GeoKeyDirectory),
+    GeoDoubleParams(Vec<f64>),
+    GeoAsciiParams(String),
+    OgcMathTransformWkt(String),
+    OgcCoordinateSystemWkt(String),
+    ExtraBytes(Vec<ExtraBytesDescriptor>),
+    Unknown,
+}
+
+//------------------------------------------------------------------------------
+
+/// GeoTIFF key directory, defining the coordinate reference system
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GeoKeyDirectory {
+    pub key_directory_version: u16,
+    pub key_revision: u16,
+    pub minor_revision: u16,
+    pub keys: Vec<GeoKeyEntry>,
+}
+
+impl GeoKeyDirectory {
+    /// Parses the GeoKeyDirectory from the data of its record
+    pub fn from_bytes(buffer: &[u8]) -> IOResult<Self> {
+        if buffer.len() < 8 {
+            return Err(IOError::BinaryData);
+        }
+
+        let n_keys = u16::from_le_bytes(buffer[6..8].try_into()?) as usize;
+        if buffer.len() < 8 * (n_keys + 1) {
+            return Err(IOError::BinaryData);
+        }
+
+        let keys = buffer[8..8 * (n_keys + 1)]
+            .chunks_exact(8)
+            .map(|x| -> IOResult<GeoKeyEntry> {
+                Ok(GeoKeyEntry {
+                    key_id: u16::from_le_bytes(x[0..2].try_into()?),
+                    tiff_tag_location: u16::from_le_bytes(x[2..4].try_into()?),
+                    count: u16::from_le_bytes(x[4..6].try_into()?),
+                    value_offset: u16::from_le_bytes(x[6..8].try_into()?),
+                })
+            })
+            .collect::<IOResult<_>>()?;
+
+        Ok(Self {
+            key_directory_version: u16::from_le_bytes(buffer[0..2].try_into()?),
+            key_revision: u16::from_le_bytes(buffer[2..4].try_into()?),
+            minor_revision: u16::from_le_bytes(buffer[4..6].try_into()?),
+            keys,
+        })
+    }
+
+    /// Returns the value of the given key, if it is stored directly within the directory
+    pub fn value_of(&self, key_id: u16) -> Option<u16> {
+        self.keys
+            .iter()
+            .find(|x| x.key_id == key_id && x.tiff_tag_location == 0)
+            .map(|x| x.value_offset)
+    }
+
+    /// Returns the EPSG code of the projected or geographic coordinate system, if defined
+    pub fn epsg(&self) -> Option<u16> {
+        // ProjectedCSTypeGeoKey, GeographicTypeGeoKey
+        self.value_of(3072).or_else(|| self.value_of(2048))
+    }
+}
+
+/// A single entry of the GeoKeyDirectory
+/// If tiff_tag_location is 0, value_offset holds the value itself
+/// Otherwise it's the index into the GeoDoubleParams (34736) or GeoAsciiParams (34737) records
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GeoKeyEntry {
+    pub key_id: u16,
+    pub tiff_tag_location: u16,
+    pub count: u16,
+    pub value_offset: u16,
+}
+
+//------------------------------------------------------------------------------
+
+/// Descriptor of additional bytes appended to each point record
+/// no_data, min and max are stored as raw bytes, since their type depends on data_type
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtraBytesDescriptor {
+    pub data_type: u8,
+    pub options: u8,
+    pub name: String,
+    pub no_data: [u8; 24],
+    pub min: [u8; 24],
+    pub max: [u8; 24],
+    pub scale: [f64; 3],
+    pub offset: [f64; 3],
+    pub description: String,
+}
+
+impl ExtraBytesDescriptor {
+    /// Parses a single descriptor
+    pub fn from_bytes(buffer: &[u8]) -> IOResult<Self> {
+        if buffer.len() != EXTRA_BYTES_DESCRIPTOR_SIZE {
+            return Err(IOError::BinaryData);
+        }
+
+        Ok(Self {
+            data_type: buffer[2],
+            options: buffer[3],
+            name: string_from_bytes(&buffer[4..36]),
+            no_data: buffer[40..64].try_into()?,
+            min: buffer[64..88].try_into()?,
+            max: buffer[88..112].try_into()?,
+            scale: [
+                f64::from_le_bytes(buffer[112..120].try_into()?),
+                f64::from_le_bytes(buffer[120..128].try_into()?),
+                f64::from_le_bytes(buffer[128..136].try_into()?),
+            ],
+            offset: [
+                f64::from_le_bytes(buffer[136..144].try_into()?),
+                f64::from_le_bytes(buffer[144..152].try_into()?),
+                f64::from_le_bytes(buffer[152..160].try_into()?),
+            ],
+            description: string_from_bytes(&buffer[160..192]),
+        })
+    }
+
+    /// Number of bytes used per point record, None if data_type is 0 (undocumented extra bytes)
+    pub fn size(&self) -> Option<usize> {
+        let (base, n) = match self.data_type {
+            0 => return None,
+            x if x <= 10 => (x, 1),
+            x if x <= 20 => (x - 10, 2),
+            x if x <= 30 => (x - 20, 3),
+            _ => return None,
+        };
+
+        let size = match base {
+            1 | 2 => 1,
+            3 | 4 => 2,
+            5 | 6 | 9 => 4,
+            _ => 8,
+        };
+
+        Some(size * n)
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Converts null terminated / padded bytes to a String
+fn string_from_bytes(buffer: &[u8]) -> String {
+    let end = buffer.iter().position(|x| *x == 0).unwrap_or(buffer.len());
+    String::from_utf8_lossy(&buffer[..end]).to_string()
+}
+
+/// Converts a String to null padded bytes, truncating if necessary
+fn string_to_bytes<const SIZE: usize>(x: &str) -> [u8; SIZE] {
+    let mut result = [0u8; SIZE];
+    let n = x.len().min(SIZE);
+    result[..n].copy_from_slice(&x.as_bytes()[..n]);
+    result
+}
@@ -58,10 +58,30 @@ pub use self::gcode::*;
 mod gltf;
 pub use self::gltf::*;
 
+mod e57;
+pub use self::e57::*;
+
+mod pcd;
+pub use self::pcd::*;
+
+mod vtk;
+pub use self::vtk::*;
+
+mod dxf;
+pub use self::dxf::*;
+
+mod svg;
+pub use self::svg::*;
+
+mod auto;
+pub use self::auto::*;
+
 mod types;
 pub use self::types::*;
 
 mod utils;
 
+pub use crate::triangulation::Triangulation;
+
 mod byte_reader;
 mod from_bytes;
@@ -24,7 +24,11 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 use crate::*;
 
-use std::{io::BufRead, iter::FusedIterator, marker::PhantomData};
+use std::{
+    io::{BufRead, Write},
+    iter::FusedIterator,
+    marker::PhantomData,
+};
 
 use super::{types::*, utils::*};
 
@@ -76,7 +80,22 @@ where
         loop {
             if chunk.is_full() {
                 return Some(Ok(chunk));
-            } else if let Ok(line) = fetch_line(&mut self.read, &mut self.line_buffer) {
+            } else {
+                let line = match fetch_line_or_none(&mut self.read, &mut self.line_buffer) {
+                    Ok(Some(line)) => line,
+                    Ok(None) => {
+                        self.is_done = true;
+                        if chunk.has_data() {
+                            return Some(Ok(chunk));
+                        }
+                        return None;
+                    }
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e.into()));
+                    }
+                };
+
                 self.i_line += 1;
 
                 if line.starts_with(b"v ") {
@@ -90,12 +109,6 @@ where
                         }
                     }
                 }
-            } else {
-                self.is_done = true;
-                if chunk.has_data() {
-                    return Some(Ok(chunk));
-                }
-                return None;
             }
         }
     }
@@ -120,6 +133,9 @@ where
     is_done: bool,
     i_line: usize,
     line_buffer: Vec<u8>,
+    n_vertices: usize,
+    face_buffer: Vec<usize>,
+    triangulator: FaceTriangulator,
     phantom_p: PhantomData<P>,
 }
 
@@ -128,12 +144,21 @@ where
     P: IsBuildable3D + Default,
     R: BufRead,
 {
+    /// Creates a new iterator, polygonal faces are split via Triangulation::Fan
     pub fn new(read: R) -> Self {
+        Self::with_triangulation(read, Triangulation::default())
+    }
+
+    /// Creates a new iterator, polygonal faces are split via triangulation
+    pub fn with_triangulation(read: R, triangulation: Triangulation) -> Self {
         Self {
             read,
             is_done: false,
             i_line: 0,
             line_buffer: Vec::new(),
+            n_vertices: 0,
+            face_buffer: Vec::new(),
+            triangulator: FaceTriangulator::new(triangulation),
             phantom_p: PhantomData,
         }
     }
@@ -156,32 +181,50 @@ where
         loop {
             if chunk.is_full() {
                 return Some(Ok(chunk));
-            } else if let Ok(line) = fetch_line(&mut self.read, &mut self.line_buffer) {
+            } else if let Some(x) = self.triangulator.next_face() {
+                chunk.push(FaceDataReserve::Face(x)).unwrap() // unwrap safe since we only call this if chunk.has_space()
+            } else {
+                let line = match fetch_line_or_none(&mut self.read, &mut self.line_buffer) {
+                    Ok(Some(line)) => line,
+                    Ok(None) => {
+                        self.is_done = true;
+                        if chunk.has_data() {
+                            return Some(Ok(chunk));
+                        }
+                        return None;
+                    }
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e.into()));
+                    }
+                };
+
                 self.i_line += 1;
 
                 if line.starts_with(b"v ") {
-                    match fetch_vertex(self.i_line, line) {
+                    match fetch_vertex::<P>(self.i_line, line) {
                         Err(e) => {
                             self.is_done = true;
                             return Some(Err(e));
                         }
-                        Ok(x) => chunk.push(FaceDataReserve::Data(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
+                        Ok(x) => {
+                            self.n_vertices += 1;
+                            self.triangulator.add_vertex(&x);
+                            chunk.push(FaceDataReserve::Data(x)).unwrap() // unwrap safe since we only call this if chunk.has_space()
+                        }
                     }
                 } else if line.starts_with(b"f ") {
-                    match fetch_face(self.i_line, line) {
-                        Err(e) => {
-                            self.is_done = true;
-                            return Some(Err(e));
-                        }
-                        Ok(x) => chunk.push(FaceDataReserve::Face(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
+                    if let Err(e) =
+                        fetch_face(self.i_line, line, self.n_vertices, &mut self.face_buffer)
+                    {
+                        self.is_done = true;
+                        return Some(Err(e));
+                    }
+                    if !self.triangulator.add_face(&self.face_buffer) {
+                        self.is_done = true;
+                        return Some(Err(IOError::Face(Some(self.i_line))));
                     }
                 }
-            } else {
-                self.is_done = true;
-                if chunk.has_data() {
-                    return Some(Ok(chunk));
-                }
-                return None;
             }
         }
     }
@@ -203,7 +246,21 @@ where
     P: IsBuildable3D + Clone + Default,
     R: BufRead,
 {
-    let iterator = ObjMeshIterator::<_, _, CHUNK_SIZE>::new(read);
+    load_obj_mesh_with_triangulation::<_, _, _, CHUNK_SIZE>(read, mesh, Triangulation::default())
+}
+
+/// Loads an IsMesh3D from the .obj file format, splitting polygonal faces via triangulation
+pub fn load_obj_mesh_with_triangulation<EM, P, R, const CHUNK_SIZE: usize>(
+    read: R,
+    mesh: &mut EM,
+    triangulation: Triangulation,
+) -> IOResult<()>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3>,
+    P: IsBuildable3D + Clone + Default,
+    R: BufRead,
+{
+    let iterator = ObjMeshIterator::<_, _, CHUNK_SIZE>::with_triangulation(read, triangulation);
 
     for chunk in iterator {
         for x in chunk? {
@@ -230,6 +287,120 @@ where
     Ok(())
 }
 
+/// Loads an IsEditablePolygonMesh from the .obj file format, keeping polygonal faces
+pub fn load_obj_polygon_mesh<EM, P, R>(mut read: R, mesh: &mut EM) -> IOResult<()>
+where
+    EM: IsEditablePolygonMesh<P>,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let mut line_buffer = Vec::new();
+    let mut face_buffer = Vec::new();
+    let mut vids = Vec::new();
+    let mut i_line = 0;
+    let mut n_vertices = 0;
+
+    while let Some(line) = fetch_line_or_none(&mut read, &mut line_buffer)? {
+        i_line += 1;
+
+        if line.starts_with(b"v ") {
+            mesh.add_vertex(fetch_vertex(i_line, line)?);
+            n_vertices += 1;
+        } else if line.starts_with(b"f ") {
+            fetch_face(i_line, line, n_vertices, &mut face_buffer)?;
+            if face_buffer.len() < 3 {
+                return Err(IOError::Face(Some(i_line)));
+            }
+
+            vids.clear();
+            vids.extend(face_buffer.iter().map(VId));
+            mesh.try_add_polygon(&vids).map_err(|_| {
+                IOError::InvalidMeshIndices.context(IOElement::Face, IOLocation::Line(i_line))
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads an IsMesh3D from the .obj file format, storing the normals ('vn') referenced by the faces as "normal" Attributes of their vertices
+/// Vertices referenced with different normals keep the last one, vertices without any get the default normal
+/// Polygonal faces are split via triangulation
+pub fn load_obj_mesh_attributed<EM, P, R>(
+    mut read: R,
+    mesh: &mut EM,
+    triangulation: Triangulation,
+) -> IOResult<()>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3> + HasVertexAttributes,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let n_before = mesh.vertex_attributes_mut().len();
+
+    let mut line_buffer = Vec::new();
+    let mut face_buffer = Vec::new();
+    let mut normal_ids = Vec::new();
+    let mut triangulator = FaceTriangulator::new(triangulation);
+    let mut i_line = 0;
+    let mut n_vertices = 0;
+
+    let mut normals = Vec::new();
+    let mut vertex_normals: Vec<Option<usize>> = Vec::new();
+
+    while let Some(line) = fetch_line_or_none(&mut read, &mut line_buffer)? {
+        i_line += 1;
+
+        if line.starts_with(b"v ") {
+            let p: P = fetch_vertex(i_line, line)?;
+            triangulator.add_vertex(&p);
+            mesh.add_vertex(p);
+            n_vertices += 1;
+        } else if line.starts_with(b"vn ") {
+            let n: Point3D = fetch_vertex(i_line, line)?;
+            normals.push(Norm3D::new(n).unwrap_or_else(|_| Norm3D::default_attribute()));
+        } else if line.starts_with(b"f ") {
+            fetch_face(i_line, line, n_vertices, &mut face_buffer)?;
+            fetch_face_normals(i_line, line, normals.len(), &mut normal_ids)?;
+
+            vertex_normals.resize(n_vertices, None);
+            // invalid vertex ids are rejected when adding the face
+            for (vid, nid) in face_buffer[..].iter().zip(&normal_ids) {
+                if let (Some(x), Some(_)) = (vertex_normals.get_mut(*vid), nid) {
+                    *x = *nid;
+                }
+            }
+
+            if !triangulator.add_face(&face_buffer) {
+                return Err(IOError::Face(Some(i_line)));
+            }
+            while let Some([a, b, c]) = triangulator.next_face() {
+                mesh.try_add_connection(VId(a), VId(b), VId(c))
+                    .map_err(|_| {
+                        IOError::InvalidMeshIndices
+                            .context(IOElement::Face, IOLocation::Line(i_line))
+                    })?;
+            }
+        }
+    }
+
+    if vertex_normals.iter().any(|x| x.is_some()) {
+        vertex_normals.resize(n_vertices, None);
+        let values: Vec<Norm3D> = vertex_normals
+            .into_iter()
+            .map(|x| x.map_or_else(Norm3D::default_attribute, |i| normals[i].clone()))
+            .collect();
+
+        let mut attributes = Attributes::new(n_vertices);
+        attributes
+            .insert("normal", values)
+            .or(Err(IOError::NormalArrayLength))?;
+        mesh.vertex_attributes_mut().write_at(n_before, attributes);
+    }
+
+    Ok(())
+}
+
 /// Loads IsPushable<Is3D> from the .obj file format
 pub fn load_obj_points<IP, P, R, const CHUNK_SIZE: usize>(read: R, ip: &mut IP) -> IOResult<()>
 where
@@ -252,6 +423,133 @@ where
     Ok(())
 }
 
+/// Saves an IsMesh3D in the .obj file format
+/// Optional per vertex normals are written as 'vn', optional per vertex texture coordinates as 'vt'
+/// The faces then reference these as 'f v/vt/vn', 'f v//vn' or 'f v/vt'
+/// precision defines the number of decimal places, None writes the shortest lossless representation
+pub fn save_obj_mesh<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    normals: Option<&[Norm3D]>,
+    tex_coords: Option<&[Point2D]>,
+    precision: Option<usize>,
+) -> IOResult<()>
+where
+    M: IsMesh3D<P>,
+    P: IsBuildable3D,
+    W: Write,
+{
+    let n_vertices = mesh.num_vertices();
+
+    if let Some(normals) = normals {
+        if normals.len() != n_vertices {
+            return Err(IOError::NormalArrayLength);
+        }
+    }
+
+    if let Some(tex_coords) = tex_coords {
+        if tex_coords.len() != n_vertices {
+            return Err(IOError::TexCoordArrayLength);
+        }
+    }
+
+    write.write_all(b"# Created by rust-3d\n")?;
+
+    for i in 0..n_vertices {
+        let v = mesh.vertex(VId(i)).unwrap(); // safe since iterating n_vertices
+        write.write_all(line_3d("v", &v, precision).as_bytes())?;
+    }
+
+    if let Some(tex_coords) = tex_coords {
+        for uv in tex_coords {
+            write.write_all(
+                format!(
+                    "vt {} {}\n",
+                    str_precision(uv.x(), precision),
+                    str_precision(uv.y(), precision)
+                )
+                .as_bytes(),
+            )?;
+        }
+    }
+
+    if let Some(normals) = normals {
+        for n in normals {
+            write.write_all(line_3d("vn", n, precision).as_bytes())?;
+        }
+    }
+
+    for i in 0..mesh.num_faces() {
+        let face = mesh.face_vertex_ids(FId(i)).unwrap(); // safe since iterating num_faces
+                                                          // obj indexing starts at 1
+        let [a, b, c] = [face.a.0 + 1, face.b.0 + 1, face.c.0 + 1];
+        let buffer = match (tex_coords.is_some(), normals.is_some()) {
+            (false, false) => format!("f {} {} {}\n", a, b, c),
+            (false, true) => format!("f {0}//{0} {1}//{1} {2}//{2}\n", a, b, c),
+            (true, false) => format!("f {0}/{0} {1}/{1} {2}/{2}\n", a, b, c),
+            (true, true) => format!("f {0}/{0}/{0} {1}/{1}/{1} {2}/{2}/{2}\n", a, b, c),
+        };
+        write.write_all(buffer.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Saves an IsPolygonMesh in the .obj file format, keeping polygonal faces
+/// precision defines the number of decimal places, None writes the shortest lossless representation
+pub fn save_obj_polygon_mesh<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    precision: Option<usize>,
+) -> IOResult<()>
+where
+    M: IsPolygonMesh<P>,
+    P: Is3D,
+    W: Write,
+{
+    write.write_all(b"# Created by rust-3d\n")?;
+
+    for i in 0..mesh.num_vertices() {
+        let v = mesh.vertex(VId(i)).unwrap(); // safe since iterating num_vertices
+        write.write_all(line_3d("v", &v, precision).as_bytes())?;
+    }
+
+    let mut vids = Vec::new();
+
+    for i in 0..mesh.num_faces() {
+        vids.clear();
+        mesh.face_vertex_ids(FId(i), &mut vids).unwrap(); // safe since iterating num_faces
+
+        // obj indexing starts at 1
+        let mut buffer = "f".to_string();
+        for vid in &vids {
+            buffer += &format!(" {}", vid.0 + 1);
+        }
+        buffer += "\n";
+        write.write_all(buffer.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Saves an IsRandomAccessible<Is3D> as vertices in the .obj file format
+/// precision defines the number of decimal places, None writes the shortest lossless representation
+pub fn save_obj_points<RA, P, W>(write: &mut W, ra: &RA, precision: Option<usize>) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P>,
+    P: Is3D,
+    W: Write,
+{
+    write.write_all(b"# Created by rust-3d\n")?;
+
+    let n = ra.len();
+    for i in 0..n {
+        write.write_all(line_3d("v", &ra[i], precision).as_bytes())?;
+    }
+
+    Ok(())
+}
+
 //------------------------------------------------------------------------------
 
 #[inline(always)]
@@ -282,23 +580,77 @@ where
     Ok(P::new(x, y, z))
 }
 
+/// Reads the vertex indices of a face, supporting 'v', 'v/vt', 'v//vn' and 'v/vt/vn' as well as negative, relative indices
 #[inline(always)]
-fn fetch_face(i_line: usize, line: &[u8]) -> IOResult<[usize; 3]> {
-    let mut words = to_words_skip_empty(line);
+fn fetch_face(i_line: usize, line: &[u8], n_vertices: usize, ids: &mut Vec<usize>) -> IOResult<()> {
+    ids.clear();
 
     // skip "f"
-    words.next().ok_or(IOError::Face(Some(i_line)))?;
+    for word in to_words_skip_empty(line).skip(1) {
+        let id = from_ascii(until_bytes(word, b'/'))
+            .and_then(|x| resolve_index(x, n_vertices))
+            .ok_or(IOError::Face(Some(i_line)))?;
+        ids.push(id);
+    }
 
-    let mut tmp = words.next().ok_or(IOError::Face(Some(i_line)))?;
-    let a: usize = from_ascii(until_bytes(tmp, b'/')).ok_or(IOError::Face(Some(i_line)))?;
+    Ok(())
+}
 
-    tmp = words.next().ok_or(IOError::Face(Some(i_line)))?;
-    let b: usize = from_ascii(until_bytes(tmp, b'/')).ok_or(IOError::Face(Some(i_line)))?;
+/// Reads the normal indices of a face, None for vertices without a normal ('v' or 'v/vt')
+#[inline(always)]
+fn fetch_face_normals(
+    i_line: usize,
+    line: &[u8],
+    n_normals: usize,
+    ids: &mut Vec<Option<usize>>,
+) -> IOResult<()> {
+    ids.clear();
 
-    tmp = words.next().ok_or(IOError::Face(Some(i_line)))?;
-    let c: usize = from_ascii(until_bytes(tmp, b'/')).ok_or(IOError::Face(Some(i_line)))?;
+    // skip "f"
+    for word in to_words_skip_empty(line).skip(1) {
+        let id = match word.split(|x| *x == b'/').nth(2) {
+            Some(x) if !x.is_empty() => Some(
+                from_ascii(x)
+                    .and_then(|x| resolve_index(x, n_normals))
+                    .filter(|x| *x < n_normals)
+                    .ok_or(IOError::Face(Some(i_line)))?,
+            ),
+            _ => None,
+        };
+        ids.push(id);
+    }
+
+    Ok(())
+}
+
+/// obj indexing starts at 1, negative values are relative to the current end
+#[inline(always)]
+fn resolve_index(id: i64, n: usize) -> Option<usize> {
+    match id {
+        x if x > 0 => Some(x as usize - 1),
+        x if x < 0 && x.unsigned_abs() as usize <= n => Some(n - x.unsigned_abs() as usize),
+        _ => None,
+    }
+}
 
-    //@todo could fail if 0 in file
-    //obj indexing starts at 1
-    Ok([a - 1, b - 1, c - 1])
+#[inline(always)]
+fn line_3d<P>(prefix: &str, p: &P, precision: Option<usize>) -> String
+where
+    P: Is3D,
+{
+    format!(
+        "{} {} {} {}\n",
+        prefix,
+        str_precision(p.x(), precision),
+        str_precision(p.y(), precision),
+        str_precision(p.z(), precision)
+    )
+}
+
+#[inline(always)]
+fn str_precision(x: f64, precision: Option<usize>) -> String {
+    match precision {
+        Some(precision) => format!("{:.*}", precision, x),
+        None => x.to_string(),
+    }
 }
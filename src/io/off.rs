@@ -24,7 +24,11 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 use crate::*;
 
-use std::{io::BufRead, iter::FusedIterator, marker::PhantomData};
+use std::{
+    io::{BufRead, Write},
+    iter::FusedIterator,
+    marker::PhantomData,
+};
 
 use super::{types::*, utils::*};
 
@@ -82,10 +86,25 @@ where
         loop {
             if chunk.is_full() {
                 return Some(Ok(chunk));
-            } else if let Ok(line) = fetch_line(&mut self.read, &mut self.line_buffer) {
+            } else {
+                let line = match fetch_line_or_none(&mut self.read, &mut self.line_buffer) {
+                    Ok(Some(line)) => line,
+                    Ok(None) => {
+                        self.is_done = true;
+                        if chunk.has_data() {
+                            return Some(Ok(chunk));
+                        }
+                        return None;
+                    }
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e.into()));
+                    }
+                };
+
                 self.i_line += 1;
 
-                if !self.off_seen && line.starts_with(b"OFF") {
+                if !self.off_seen && is_off_keyword(line) {
                     self.off_seen = true;
                     continue;
                 } else if line.is_empty() || line.starts_with(b"#") {
@@ -120,12 +139,6 @@ where
                         Ok(x) => chunk.push(DataReserve::Data(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
                     }
                 }
-            } else {
-                self.is_done = true;
-                if chunk.has_data() {
-                    return Some(Ok(chunk));
-                }
-                return None;
             }
         }
     }
@@ -178,7 +191,9 @@ where
     fn fetch_face(i_line: usize, line: &[u8]) -> IOResult<[usize; 3]> {
         let mut words = to_words_skip_empty(line);
 
-        let count_face = words.next().ok_or(IOError::FaceVertexCount)?;
+        let count_face = words.next().ok_or_else(|| {
+            IOError::FaceVertexCount.context(IOElement::Face, IOLocation::Line(i_line))
+        })?;
 
         if count_face == b"3" {
             let a = words
@@ -198,24 +213,9 @@ where
 
             Ok([a, b, c])
         } else {
-            Err(IOError::FaceVertexCount)
+            Err(IOError::FaceVertexCount.context(IOElement::Face, IOLocation::Line(i_line)))
         }
     }
-
-    #[inline(always)]
-    fn fetch_counts(i_line: usize, line: &[u8]) -> IOResult<[usize; 2]> {
-        let mut words = to_words_skip_empty(line);
-        let n_vertices = words
-            .next()
-            .and_then(|word| from_ascii(word))
-            .ok_or(IOError::VertexCount(Some(i_line)))?;
-        let n_faces = words
-            .next()
-            .and_then(|word| from_ascii(word))
-            .ok_or(IOError::FaceCount(Some(i_line)))?;
-
-        Ok([n_vertices, n_faces])
-    }
 }
 
 impl<P, R, const CHUNK_SIZE: usize> Iterator for OffMeshIterator<P, R, CHUNK_SIZE>
@@ -235,16 +235,31 @@ where
         loop {
             if chunk.is_full() {
                 return Some(Ok(chunk));
-            } else if let Ok(line) = fetch_line(&mut self.read, &mut self.line_buffer) {
+            } else {
+                let line = match fetch_line_or_none(&mut self.read, &mut self.line_buffer) {
+                    Ok(Some(line)) => line,
+                    Ok(None) => {
+                        self.is_done = true;
+                        if chunk.has_data() {
+                            return Some(Ok(chunk));
+                        }
+                        return None;
+                    }
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e.into()));
+                    }
+                };
+
                 self.i_line += 1;
 
-                if !self.off_seen && line.starts_with(b"OFF") {
+                if !self.off_seen && is_off_keyword(line) {
                     self.off_seen = true;
                     continue;
                 } else if line.is_empty() || line.starts_with(b"#") {
                     continue;
                 } else if self.counts.is_none() {
-                    match Self::fetch_counts(self.i_line, line) {
+                    match fetch_counts(self.i_line, line) {
                         Ok(counts) => {
                             self.counts = Some(counts);
                             chunk
@@ -276,12 +291,6 @@ where
                         Ok(x) => chunk.push(FaceDataReserve::Face(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
                     }
                 }
-            } else {
-                self.is_done = true;
-                if chunk.has_data() {
-                    return Some(Ok(chunk));
-                }
-                return None;
             }
         }
     }
@@ -330,6 +339,135 @@ where
     Ok(())
 }
 
+/// Loads an IsEditablePolygonMesh from the off file format, keeping polygonal faces
+pub fn load_off_polygon_mesh<EM, P, R>(mut read: R, mesh: &mut EM) -> IOResult<()>
+where
+    EM: IsEditablePolygonMesh<P>,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let mut line_buffer = Vec::new();
+    let mut vids = Vec::new();
+    let mut i_line = 0;
+    let mut off_seen = false;
+    let mut n_vertices = None;
+    let mut n_vertices_added = 0;
+
+    while let Some(line) = fetch_line_or_none(&mut read, &mut line_buffer)? {
+        i_line += 1;
+
+        if !off_seen && is_off_keyword(line) {
+            off_seen = true;
+        } else if line.is_empty() || line.starts_with(b"#") {
+            continue;
+        } else if let Some(n_vertices) = n_vertices {
+            if n_vertices_added < n_vertices {
+                n_vertices_added += 1;
+                mesh.add_vertex(fetch_vertex(i_line, line)?);
+            } else {
+                fetch_polygon(i_line, line, &mut vids)?;
+                mesh.try_add_polygon(&vids).map_err(|_| {
+                    IOError::InvalidMeshIndices.context(IOElement::Face, IOLocation::Line(i_line))
+                })?;
+            }
+        } else {
+            let [n, n_faces] = fetch_counts(i_line, line)?;
+            mesh.reserve_vertices(n);
+            mesh.reserve_faces(n_faces);
+            n_vertices = Some(n);
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads an IsMesh3D from the off file format, storing the vertex normals of NOFF and colors of COFF as "normal" and "color" Attributes
+/// Polygonal faces are split via triangulation
+pub fn load_off_mesh_attributed<EM, P, R>(
+    mut read: R,
+    mesh: &mut EM,
+    triangulation: Triangulation,
+) -> IOResult<()>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3> + HasVertexAttributes,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let n_before = mesh.vertex_attributes_mut().len();
+
+    let mut line_buffer = Vec::new();
+    let mut vids = Vec::new();
+    let mut face_buffer = Vec::new();
+    let mut triangulator = FaceTriangulator::new(triangulation);
+    let mut i_line = 0;
+    let mut off_seen = false;
+    let mut has_colors = false;
+    let mut has_normals = false;
+    let mut n_vertices = None;
+    let mut n_vertices_added = 0;
+
+    let mut colors = Vec::new();
+    let mut normals = Vec::new();
+
+    while let Some(line) = fetch_line_or_none(&mut read, &mut line_buffer)? {
+        i_line += 1;
+
+        if !off_seen && is_off_keyword(line) {
+            off_seen = true;
+            has_colors = line.starts_with(b"C");
+            has_normals = line.starts_with(b"NOFF") || line.starts_with(b"CNOFF");
+        } else if line.is_empty() || line.starts_with(b"#") {
+            continue;
+        } else if let Some(n_vertices) = n_vertices {
+            if n_vertices_added < n_vertices {
+                n_vertices_added += 1;
+                let p: P = fetch_vertex(i_line, line)?;
+                let (normal, color) =
+                    fetch_vertex_attributes(i_line, line, has_normals, has_colors)?;
+                normals.extend(normal);
+                colors.extend(color);
+                triangulator.add_vertex(&p);
+                mesh.add_vertex(p);
+            } else {
+                fetch_polygon(i_line, line, &mut vids)?;
+                face_buffer.clear();
+                face_buffer.extend(vids.iter().map(|x| x.0));
+
+                if !triangulator.add_face(&face_buffer) {
+                    return Err(IOError::Face(Some(i_line)));
+                }
+                while let Some([a, b, c]) = triangulator.next_face() {
+                    mesh.try_add_connection(VId(a), VId(b), VId(c))
+                        .map_err(|_| {
+                            IOError::InvalidMeshIndices
+                                .context(IOElement::Face, IOLocation::Line(i_line))
+                        })?;
+                }
+            }
+        } else {
+            let [n, n_faces] = fetch_counts(i_line, line)?;
+            mesh.reserve_vertices(n);
+            mesh.reserve_faces(n_faces);
+            n_vertices = Some(n);
+        }
+    }
+
+    let mut attributes = Attributes::new(n_vertices_added);
+    if has_colors {
+        attributes
+            .insert("color", colors)
+            .or(Err(IOError::ColorArrayLength))?;
+    }
+    if has_normals {
+        attributes
+            .insert("normal", normals)
+            .or(Err(IOError::NormalArrayLength))?;
+    }
+    mesh.vertex_attributes_mut().write_at(n_before, attributes);
+
+    Ok(())
+}
+
 /// Loads IsPushable<Is3D> from the .off file format
 pub fn load_off_points<IP, P, R, const CHUNK_SIZE: usize>(read: R, ip: &mut IP) -> IOResult<()>
 where
@@ -352,6 +490,267 @@ where
     Ok(())
 }
 
+/// Saves an IsMesh3D in the .off file format
+/// If colors and / or normals are provided, the COFF, NOFF or CNOFF variant is written
+pub fn save_off_mesh<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    colors: Option<&[Rgb]>,
+    normals: Option<&[Norm3D]>,
+) -> IOResult<()>
+where
+    M: IsMesh3D<P>,
+    P: IsBuildable3D,
+    W: Write,
+{
+    let n_vertices = mesh.num_vertices();
+    let n_faces = mesh.num_faces();
+
+    write_off_header(write, n_vertices, n_faces, colors, normals)?;
+
+    for i in 0..n_vertices {
+        let v = mesh.vertex(VId(i)).unwrap(); // safe since iterating n_vertices
+        write_off_vertex(write, i, &v, colors, normals)?;
+    }
+
+    for i in 0..n_faces {
+        let face = mesh.face_vertex_ids(FId(i)).unwrap(); // safe since iterating n_faces
+        write.write_all(format!("3 {} {} {}\n", face.a.0, face.b.0, face.c.0).as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Saves an IsPolygonMesh in the .off file format, keeping polygonal faces
+/// If colors and / or normals are provided, the COFF, NOFF or CNOFF variant is written
+pub fn save_off_polygon_mesh<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    colors: Option<&[Rgb]>,
+    normals: Option<&[Norm3D]>,
+) -> IOResult<()>
+where
+    M: IsPolygonMesh<P>,
+    P: Is3D,
+    W: Write,
+{
+    let n_vertices = mesh.num_vertices();
+    let n_faces = mesh.num_faces();
+
+    write_off_header(write, n_vertices, n_faces, colors, normals)?;
+
+    for i in 0..n_vertices {
+        let v = mesh.vertex(VId(i)).unwrap(); // safe since iterating n_vertices
+        write_off_vertex(write, i, &v, colors, normals)?;
+    }
+
+    let mut vids = Vec::new();
+
+    for i in 0..n_faces {
+        vids.clear();
+        mesh.face_vertex_ids(FId(i), &mut vids).unwrap(); // safe since iterating n_faces
+
+        let mut buffer = vids.len().to_string();
+        for vid in &vids {
+            buffer += &format!(" {}", vid.0);
+        }
+        buffer += "\n";
+        write.write_all(buffer.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+/// Saves an IsRandomAccessible<Is3D> in the .off file format
+/// If colors and / or normals are provided, the COFF, NOFF or CNOFF variant is written
+pub fn save_off_points<RA, P, W>(
+    write: &mut W,
+    ra: &RA,
+    colors: Option<&[Rgb]>,
+    normals: Option<&[Norm3D]>,
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P>,
+    P: Is3D,
+    W: Write,
+{
+    let n = ra.len();
+
+    write_off_header(write, n, 0, colors, normals)?;
+
+    for i in 0..n {
+        write_off_vertex(write, i, &ra[i], colors, normals)?;
+    }
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+#[inline(always)]
+fn is_off_keyword(line: &[u8]) -> bool {
+    line.starts_with(b"OFF")
+        || line.starts_with(b"COFF")
+        || line.starts_with(b"NOFF")
+        || line.starts_with(b"CNOFF")
+}
+
+#[inline(always)]
+fn fetch_counts(i_line: usize, line: &[u8]) -> IOResult<[usize; 2]> {
+    let mut words = to_words_skip_empty(line);
+    let n_vertices = words
+        .next()
+        .and_then(|word| from_ascii(word))
+        .ok_or(IOError::VertexCount(Some(i_line)))?;
+    let n_faces = words
+        .next()
+        .and_then(|word| from_ascii(word))
+        .ok_or(IOError::FaceCount(Some(i_line)))?;
+
+    Ok([n_vertices, n_faces])
+}
+
+/// Reads the vertex ids of a face, ignoring optional trailing color values
+#[inline(always)]
+fn fetch_polygon(i_line: usize, line: &[u8], vids: &mut Vec<VId>) -> IOResult<()> {
+    vids.clear();
+
+    let mut words = to_words_skip_empty(line);
+
+    let n: usize = words.next().and_then(from_ascii).ok_or_else(|| {
+        IOError::FaceVertexCount.context(IOElement::Face, IOLocation::Line(i_line))
+    })?;
+
+    if n < 3 {
+        return Err(IOError::FaceVertexCount.context(IOElement::Face, IOLocation::Line(i_line)));
+    }
+
+    for _ in 0..n {
+        let id = words
+            .next()
+            .and_then(from_ascii)
+            .ok_or(IOError::Face(Some(i_line)))?;
+        vids.push(VId(id));
+    }
+
+    Ok(())
+}
+
+/// Reads the optional normal and color following the position of a vertex
+/// Colors are either integers within [0, 255] or floating point values within [0, 1], an optional alpha value is ignored
+#[inline(always)]
+fn fetch_vertex_attributes(
+    i_line: usize,
+    line: &[u8],
+    has_normals: bool,
+    has_colors: bool,
+) -> IOResult<(Option<Norm3D>, Option<Rgb>)> {
+    let mut words = to_words_skip_empty(line).skip(3);
+
+    let normal = if has_normals {
+        let mut next = || -> IOResult<f64> {
+            words
+                .next()
+                .and_then(from_ascii)
+                .ok_or(IOError::Vertex(Some(i_line)))
+        };
+        let p = Point3D::new(next()?, next()?, next()?);
+        Some(Norm3D::new(p).unwrap_or_else(|_| Norm3D::default_attribute()))
+    } else {
+        None
+    };
+
+    let color = if has_colors {
+        let rgb: Vec<&[u8]> = words.take(3).collect();
+        if rgb.len() != 3 {
+            return Err(IOError::Vertex(Some(i_line)));
+        }
+        let is_float = rgb.iter().any(|w| w.contains(&b'.'));
+        let component = |w: &[u8]| -> IOResult<u8> {
+            if is_float {
+                from_ascii::<f64>(w).map(|x| (x.clamp(0.0, 1.0) * 255.0).round() as u8)
+            } else {
+                from_ascii::<u8>(w)
+            }
+            .ok_or(IOError::Vertex(Some(i_line)))
+        };
+        Some(Rgb::new(
+            component(rgb[0])?,
+            component(rgb[1])?,
+            component(rgb[2])?,
+        ))
+    } else {
+        None
+    };
+
+    Ok((normal, color))
+}
+
+fn write_off_header<W>(
+    write: &mut W,
+    n_vertices: usize,
+    n_faces: usize,
+    colors: Option<&[Rgb]>,
+    normals: Option<&[Norm3D]>,
+) -> IOResult<()>
+where
+    W: Write,
+{
+    if let Some(colors) = colors {
+        if colors.len() != n_vertices {
+            return Err(IOError::ColorArrayLength);
+        }
+    }
+
+    if let Some(normals) = normals {
+        if normals.len() != n_vertices {
+            return Err(IOError::NormalArrayLength);
+        }
+    }
+
+    let keyword = match (colors.is_some(), normals.is_some()) {
+        (false, false) => "OFF",
+        (true, false) => "COFF",
+        (false, true) => "NOFF",
+        (true, true) => "CNOFF",
+    };
+
+    write.write_all(format!("{}\n{} {} 0\n", keyword, n_vertices, n_faces).as_bytes())?;
+
+    Ok(())
+}
+
+#[inline(always)]
+fn write_off_vertex<P, W>(
+    write: &mut W,
+    i: usize,
+    p: &P,
+    colors: Option<&[Rgb]>,
+    normals: Option<&[Norm3D]>,
+) -> IOResult<()>
+where
+    P: Is3D,
+    W: Write,
+{
+    let mut buffer = format!("{} {} {}", p.x(), p.y(), p.z());
+
+    // Normals must be written before colors
+    if let Some(normals) = normals {
+        let n = &normals[i];
+        buffer += &format!(" {} {} {}", n.x(), n.y(), n.z());
+    }
+
+    if let Some(colors) = colors {
+        let c = &colors[i];
+        buffer += &format!(" {} {} {} 255", c.r, c.g, c.b);
+    }
+
+    buffer += "\n";
+    write.write_all(buffer.as_bytes())?;
+
+    Ok(())
+}
+
 //------------------------------------------------------------------------------
 
 #[inline(always)]
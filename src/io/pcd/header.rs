@@ -0,0 +1,204 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for the header of the pcd file format
+
+use crate::*;
+
+use std::io::BufRead;
+
+use super::{
+    super::{types::*, utils::*},
+    types::*,
+};
+
+//------------------------------------------------------------------------------
+
+/// Reads the header up to and including the DATA line
+pub fn read_header<R>(read: &mut R, line_buffer: &mut Vec<u8>) -> IOResult<Header>
+where
+    R: BufRead,
+{
+    let mut i_line = 0;
+    let mut names: Option<Vec<String>> = None;
+    let mut sizes: Option<Vec<usize>> = None;
+    let mut types: Option<Vec<FieldType>> = None;
+    let mut counts: Option<Vec<usize>> = None;
+    let mut width: Option<usize> = None;
+    let mut height = 1;
+    let mut n_points: Option<usize> = None;
+    let mut viewpoint = PcdViewpoint::default();
+
+    let format = loop {
+        let line = fetch_line(read, line_buffer).map_err(|e| {
+            IOError::from(e).context(IOElement::Header, IOLocation::Line(i_line + 1))
+        })?;
+        i_line += 1;
+
+        let line = trim_start(line);
+        if line.is_empty() || line.starts_with(b"#") {
+            continue;
+        }
+
+        let error = || IOError::Pcd(PcdError::Header(i_line));
+        let mut words = to_words_skip_empty(line);
+
+        match words.next().ok_or_else(error)? {
+            b"VERSION" => match words.next() {
+                Some(b"0.7") | Some(b".7") => (),
+                _ => return Err(IOError::UnsupportedVersion),
+            },
+            b"FIELDS" | b"COLUMNS" => {
+                names = Some(
+                    words
+                        .map(|w| String::from_utf8_lossy(w).into_owned())
+                        .collect(),
+                )
+            }
+            b"SIZE" => sizes = Some(parse_all(words).ok_or_else(error)?),
+            b"COUNT" => counts = Some(parse_all(words).ok_or_else(error)?),
+            b"TYPE" => {
+                types = Some(
+                    words
+                        .map(|w| match w {
+                            b"I" => Some(FieldType::I),
+                            b"U" => Some(FieldType::U),
+                            b"F" => Some(FieldType::F),
+                            _ => None,
+                        })
+                        .collect::<Option<_>>()
+                        .ok_or_else(error)?,
+                )
+            }
+            b"WIDTH" => width = Some(parse_one(words).ok_or_else(error)?),
+            b"HEIGHT" => height = parse_one(words).ok_or_else(error)?,
+            b"POINTS" => n_points = Some(parse_one(words).ok_or_else(error)?),
+            b"VIEWPOINT" => {
+                let values: Vec<f64> = parse_all(words).ok_or_else(error)?;
+                if values.len() != 7 {
+                    return Err(error());
+                }
+                viewpoint = PcdViewpoint {
+                    translation: [values[0], values[1], values[2]],
+                    orientation: [values[3], values[4], values[5], values[6]],
+                };
+            }
+            b"DATA" => match words.next() {
+                Some(b"ascii") => break PcdDataFormat::Ascii,
+                Some(b"binary") => break PcdDataFormat::Binary,
+                Some(b"binary_compressed") => break PcdDataFormat::BinaryCompressed,
+                _ => return Err(error()),
+            },
+            _ => return Err(error()),
+        }
+    };
+
+    let names = names.ok_or(IOError::Pcd(PcdError::Fields))?;
+    let sizes = sizes.ok_or(IOError::Pcd(PcdError::Fields))?;
+    let types = types.ok_or(IOError::Pcd(PcdError::Fields))?;
+    let counts = counts.unwrap_or_else(|| vec![1; names.len()]);
+
+    if sizes.len() != names.len() || types.len() != names.len() || counts.len() != names.len() {
+        return Err(IOError::Pcd(PcdError::Fields));
+    }
+
+    let mut fields = Vec::with_capacity(names.len());
+    let mut offset = 0;
+    for (((name, size), t), count) in names.into_iter().zip(sizes).zip(types).zip(counts) {
+        let is_valid_size = match t {
+            FieldType::F => size == 4 || size == 8,
+            FieldType::I | FieldType::U => [1, 2, 4, 8].contains(&size),
+        };
+        if !is_valid_size || count == 0 {
+            return Err(IOError::Pcd(PcdError::Fields));
+        }
+
+        fields.push(Field {
+            name,
+            size,
+            t,
+            count,
+            offset,
+        });
+        offset += size * count;
+    }
+
+    let width = width.ok_or(IOError::Pcd(PcdError::Layout))?;
+    let n_points = n_points.unwrap_or(width * height);
+    if width.checked_mul(height) != Some(n_points) {
+        return Err(IOError::Pcd(PcdError::Layout));
+    }
+
+    Ok(Header {
+        ids: field_ids(&fields)?,
+        fields,
+        layout: PcdLayout {
+            width,
+            height,
+            viewpoint,
+        },
+        n_points,
+        format,
+        record_size: offset,
+        n_lines: i_line,
+    })
+}
+
+//------------------------------------------------------------------------------
+
+fn field_ids(fields: &[Field]) -> IOResult<FieldIds> {
+    let find = |name: &str| fields.iter().position(|x| x.name == name);
+
+    let position = match (find("x"), find("y"), find("z")) {
+        (Some(x), Some(y), Some(z)) => [x, y, z],
+        _ => return Err(IOError::Pcd(PcdError::MissingPosition)),
+    };
+
+    let normal = match (find("normal_x"), find("normal_y"), find("normal_z")) {
+        (Some(x), Some(y), Some(z)) => Some([x, y, z]),
+        _ => None,
+    };
+
+    Ok(FieldIds {
+        position,
+        color: find("rgb")
+            .or_else(|| find("rgba"))
+            .filter(|i| fields[*i].size == 4),
+        normal,
+        intensity: find("intensity"),
+    })
+}
+
+fn parse_all<'a, T, I>(words: I) -> Option<Vec<T>>
+where
+    T: std::str::FromStr,
+    I: Iterator<Item = &'a [u8]>,
+{
+    words.map(from_ascii).collect()
+}
+
+fn parse_one<'a, T, I>(mut words: I) -> Option<T>
+where
+    T: std::str::FromStr,
+    I: Iterator<Item = &'a [u8]>,
+{
+    words.next().and_then(from_ascii)
+}
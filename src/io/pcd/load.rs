@@ -0,0 +1,407 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for load functions of the pcd file format
+//! The VIEWPOINT is provided via the layout, points are not transformed by it
+
+use crate::*;
+
+use std::{
+    io::{BufRead, Read},
+    iter::FusedIterator,
+    marker::PhantomData,
+};
+
+use super::{
+    super::{types::*, utils::*},
+    header::*,
+    lzf,
+    types::*,
+};
+
+//------------------------------------------------------------------------------
+
+/// Iterator to incrementally load a .pcd file
+pub struct PcdIterator<P, R, const CHUNK_SIZE: usize>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    inner: PcdPointIterator<P, R, CHUNK_SIZE>,
+}
+
+impl<P, R, const CHUNK_SIZE: usize> PcdIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    pub fn new(read: R) -> IOResult<Self> {
+        Ok(Self {
+            inner: PcdPointIterator::new(read)?,
+        })
+    }
+
+    /// Dimensions and viewpoint of the cloud
+    pub fn layout(&self) -> &PcdLayout {
+        self.inner.layout()
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> Iterator for PcdIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    type Item = IOResult<StackVec<DataReserve<P>, CHUNK_SIZE>>;
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|chunk| {
+            let mut result = StackVec::default();
+            for x in chunk? {
+                let x = match x {
+                    DataReserve::Data((p, _)) => DataReserve::Data(p),
+                    DataReserve::Reserve(x) => DataReserve::Reserve(x),
+                    DataReserve::ReserveExact(x) => DataReserve::ReserveExact(x),
+                };
+                result.push(x).unwrap(); // unwrap safe since both have the same size
+            }
+            Ok(result)
+        })
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for PcdIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+}
+
+//------------------------------------------------------------------------------
+
+/// Iterator to incrementally load a .pcd file together with the attributes of each point
+pub struct PcdPointIterator<P, R, const CHUNK_SIZE: usize>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    read: R,
+    header: Header,
+    is_done: bool,
+    is_reserved: bool,
+    i_line: usize,
+    line_buffer: Vec<u8>,
+    record: Vec<u8>,
+    /// Content of binary_compressed files, stored field by field
+    decompressed: Vec<u8>,
+    n_fetched: usize,
+    phantom_p: PhantomData<P>,
+}
+
+impl<P, R, const CHUNK_SIZE: usize> PcdPointIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    /// Reads the header, points are read while iterating
+    pub fn new(mut read: R) -> IOResult<Self> {
+        let mut line_buffer = Vec::new();
+        let header = read_header(&mut read, &mut line_buffer)?;
+
+        let decompressed = match header.format {
+            PcdDataFormat::BinaryCompressed => read_compressed(&mut read, &header)?,
+            _ => Vec::new(),
+        };
+
+        Ok(Self {
+            read,
+            i_line: header.n_lines,
+            record: vec![0; header.record_size],
+            header,
+            is_done: false,
+            is_reserved: false,
+            line_buffer,
+            decompressed,
+            n_fetched: 0,
+            phantom_p: PhantomData,
+        })
+    }
+
+    /// Dimensions and viewpoint of the cloud
+    pub fn layout(&self) -> &PcdLayout {
+        &self.header.layout
+    }
+
+    /// Fills the record of the next point with little endian data
+    #[inline(always)]
+    fn fetch_record(&mut self) -> IOResult<()> {
+        match self.header.format {
+            PcdDataFormat::Ascii => loop {
+                let line = fetch_line(&mut self.read, &mut self.line_buffer)?;
+                self.i_line += 1;
+
+                if line.is_empty() {
+                    continue;
+                }
+
+                return parse_ascii_record(&self.header, line, &mut self.record)
+                    .ok_or(IOError::Vertex(Some(self.i_line)));
+            },
+            PcdDataFormat::Binary => Ok(self.read.read_exact(&mut self.record)?),
+            PcdDataFormat::BinaryCompressed => {
+                let n_points = self.header.n_points;
+                for field in &self.header.fields {
+                    let n = field.n_bytes();
+                    let start = field.offset * n_points + self.n_fetched * n;
+                    self.record[field.offset..field.offset + n]
+                        .copy_from_slice(&self.decompressed[start..start + n]);
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> Iterator for PcdPointIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    type Item = IOResult<StackVec<DataReserve<(P, PcdPoint)>, CHUNK_SIZE>>;
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+
+        let mut chunk = StackVec::default();
+
+        if !self.is_reserved {
+            self.is_reserved = true;
+            // unwrap safe since the chunk is empty
+            chunk
+                .push(DataReserve::ReserveExact(self.header.n_points))
+                .unwrap();
+        }
+
+        while chunk.has_space() {
+            if self.n_fetched >= self.header.n_points {
+                self.is_done = true;
+                break;
+            }
+
+            if let Err(e) = self.fetch_record() {
+                self.is_done = true;
+                let location = match self.header.format {
+                    PcdDataFormat::Ascii => IOLocation::Line(self.i_line),
+                    _ => IOLocation::Index(self.n_fetched),
+                };
+                return Some(Err(e.context(IOElement::Point, location)));
+            }
+
+            let x = make_point(&self.header, &self.record, self.n_fetched);
+            self.n_fetched += 1;
+            // unwrap safe since we only call this if chunk.has_space()
+            chunk.push(DataReserve::Data(x)).unwrap();
+        }
+
+        if chunk.has_data() {
+            Some(Ok(chunk))
+        } else {
+            None
+        }
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for PcdPointIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+}
+
+//------------------------------------------------------------------------------
+
+/// Loads points from .pcd file into IsPushable<Is3D>
+pub fn load_pcd<IP, P, R, const CHUNK_SIZE: usize>(read: R, ip: &mut IP) -> IOResult<()>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let iterator = PcdIterator::<_, _, CHUNK_SIZE>::new(read)?;
+
+    for chunk in iterator {
+        for x in chunk? {
+            match x {
+                DataReserve::Data(x) => ip.push(x),
+                DataReserve::Reserve(x) => ip.reserve(x),
+                DataReserve::ReserveExact(x) => ip.reserve_exact(x),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Loads points from .pcd file into IsPushable<Is3D> and their attributes into IsPushable<PcdPoint>
+/// Returns the layout of the cloud
+pub fn load_pcd_points<IP, P, R, IPP, const CHUNK_SIZE: usize>(
+    read: R,
+    ip: &mut IP,
+    pcd_points: &mut IPP,
+) -> IOResult<PcdLayout>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+    IPP: IsPushable<PcdPoint>,
+{
+    let mut iterator = PcdPointIterator::<_, _, CHUNK_SIZE>::new(read)?;
+    let layout = iterator.layout().clone();
+
+    for chunk in &mut iterator {
+        for x in chunk? {
+            match x {
+                DataReserve::Data((p, pcd_point)) => {
+                    ip.push(p);
+                    pcd_points.push(pcd_point);
+                }
+                DataReserve::Reserve(x) => {
+                    ip.reserve(x);
+                    pcd_points.reserve(x);
+                }
+                DataReserve::ReserveExact(x) => {
+                    ip.reserve_exact(x);
+                    pcd_points.reserve_exact(x);
+                }
+            }
+        }
+    }
+
+    Ok(layout)
+}
+
+//------------------------------------------------------------------------------
+
+fn read_compressed<R>(read: &mut R, header: &Header) -> IOResult<Vec<u8>>
+where
+    R: BufRead,
+{
+    let mut sizes = [0u8; 8];
+    read.read_exact(&mut sizes)?;
+    let n_compressed = u32::from_le_bytes([sizes[0], sizes[1], sizes[2], sizes[3]]) as u64;
+    let n_raw = u32::from_le_bytes([sizes[4], sizes[5], sizes[6], sizes[7]]) as usize;
+
+    if Some(n_raw) != header.record_size.checked_mul(header.n_points) {
+        return Err(IOError::Pcd(PcdError::Compression));
+    }
+
+    let mut compressed = Vec::new();
+    read.take(n_compressed).read_to_end(&mut compressed)?;
+    if compressed.len() as u64 != n_compressed {
+        return Err(IOError::EndReached);
+    }
+
+    lzf::decompress(&compressed, n_raw).ok_or(IOError::Pcd(PcdError::Compression))
+}
+
+/// Converts an ascii line into the binary record
+fn parse_ascii_record(header: &Header, line: &[u8], record: &mut [u8]) -> Option<()> {
+    let mut words = to_words_skip_empty(line);
+
+    for (i, field) in header.fields.iter().enumerate() {
+        for k in 0..field.count {
+            let word = words.next()?;
+            let start = field.offset + k * field.size;
+            let bytes = &mut record[start..start + field.size];
+
+            // packed colors are written as integers, but some writers use their float representation
+            if header.ids.color == Some(i) {
+                let packed = match from_ascii::<u32>(word) {
+                    Some(x) => x,
+                    None => from_ascii::<f32>(word)?.to_bits(),
+                };
+                bytes.copy_from_slice(&packed.to_le_bytes());
+                continue;
+            }
+
+            match (field.t, field.size) {
+                (FieldType::F, 4) => bytes.copy_from_slice(&from_ascii::<f32>(word)?.to_le_bytes()),
+                (FieldType::F, _) => bytes.copy_from_slice(&from_ascii::<f64>(word)?.to_le_bytes()),
+                (FieldType::U, n) => {
+                    bytes.copy_from_slice(&from_ascii::<u64>(word)?.to_le_bytes()[..n])
+                }
+                (FieldType::I, n) => {
+                    bytes.copy_from_slice(&from_ascii::<i64>(word)?.to_le_bytes()[..n])
+                }
+            }
+        }
+    }
+
+    Some(())
+}
+
+fn make_point<P>(header: &Header, record: &[u8], index: usize) -> (P, PcdPoint)
+where
+    P: IsBuildable3D,
+{
+    let fields = &header.fields;
+    let ids = &header.ids;
+    let value = |i: usize| fields[i].value(record);
+
+    let [x, y, z] = ids.position;
+    let p = P::new(value(x), value(y), value(z));
+
+    let color = ids.color.map(|i| {
+        let offset = fields[i].offset;
+        let packed = u32::from_le_bytes([
+            record[offset],
+            record[offset + 1],
+            record[offset + 2],
+            record[offset + 3],
+        ]);
+        Rgb::new((packed >> 16) as u8, (packed >> 8) as u8, packed as u8)
+    });
+
+    // organized clouds use NaN for missing normals
+    let normal = ids.normal.and_then(|[x, y, z]| {
+        let n = Point3D::new(value(x), value(y), value(z));
+        if n.x().is_finite() && n.y().is_finite() && n.z().is_finite() {
+            Norm3D::new(n).ok()
+        } else {
+            None
+        }
+    });
+
+    let width = header.layout.width;
+
+    (
+        p,
+        PcdPoint {
+            row: index / width,
+            column: index % width,
+            color,
+            normal,
+            intensity: ids.intensity.map(value),
+        },
+    )
+}
@@ -0,0 +1,134 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for the LZF compression used by binary_compressed .pcd files
+
+//------------------------------------------------------------------------------
+
+const MAX_LITERAL: usize = 32;
+const MAX_OFFSET: usize = 1 << 13;
+const MAX_MATCH: usize = 7 + 255 + 2;
+const HASH_BITS: u32 = 14;
+
+//------------------------------------------------------------------------------
+
+/// Decompresses LZF data into exactly n_out bytes
+pub fn decompress(input: &[u8], n_out: usize) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(n_out);
+    let mut i = 0;
+
+    while i < input.len() {
+        let control = input[i] as usize;
+        i += 1;
+
+        if control < MAX_LITERAL {
+            let n = control + 1;
+            out.extend_from_slice(input.get(i..i + n)?);
+            i += n;
+        } else {
+            let mut n = control >> 5;
+            if n == 7 {
+                n += *input.get(i)? as usize;
+                i += 1;
+            }
+            n += 2;
+
+            let back = ((control & 0x1F) << 8) + *input.get(i)? as usize + 1;
+            i += 1;
+
+            let start = out.len().checked_sub(back)?;
+            // copied one by one, since the ranges may overlap
+            for k in start..start + n {
+                out.push(out[k]);
+            }
+        }
+
+        if out.len() > n_out {
+            return None;
+        }
+    }
+
+    if out.len() == n_out {
+        Some(out)
+    } else {
+        None
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Compresses data with LZF
+pub fn compress(input: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(input.len() / 2 + MAX_LITERAL);
+    let mut table = vec![usize::MAX; 1 << HASH_BITS];
+    let mut literal_start = 0;
+    let mut i = 0;
+
+    while i + 2 < input.len() {
+        let h = hash(&input[i..i + 3]);
+        let candidate = table[h];
+        table[h] = i;
+
+        if candidate != usize::MAX
+            && i - candidate <= MAX_OFFSET
+            && input[candidate..candidate + 3] == input[i..i + 3]
+        {
+            let max = MAX_MATCH.min(input.len() - i);
+            let mut n = 3;
+            while n < max && input[candidate + n] == input[i + n] {
+                n += 1;
+            }
+
+            push_literals(&mut out, &input[literal_start..i]);
+
+            let offset = i - candidate - 1;
+            let len = n - 2;
+            if len < 7 {
+                out.push(((len << 5) | (offset >> 8)) as u8);
+            } else {
+                out.push(((7 << 5) | (offset >> 8)) as u8);
+                out.push((len - 7) as u8);
+            }
+            out.push(offset as u8);
+
+            i += n;
+            literal_start = i;
+        } else {
+            i += 1;
+        }
+    }
+
+    push_literals(&mut out, &input[literal_start..]);
+
+    out
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    let x = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+    (x.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+}
+
+fn push_literals(out: &mut Vec<u8>, literals: &[u8]) {
+    for run in literals.chunks(MAX_LITERAL) {
+        out.push((run.len() - 1) as u8);
+        out.extend_from_slice(run);
+    }
+}
@@ -0,0 +1,186 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for save functions of the pcd file format
+
+use crate::*;
+
+use std::io::Write;
+
+use super::{super::types::*, lzf, types::*};
+
+//------------------------------------------------------------------------------
+
+/// Saves an IsRandomAccessible<Is3D> as unorganized .pcd file
+pub fn save_pcd<RA, P, W>(write: &mut W, points: &RA, format: &PcdDataFormat) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P>,
+    P: Is3D,
+    W: Write,
+{
+    save_pcd_points(
+        write,
+        points,
+        format,
+        &PcdLayout::unorganized(points.len()),
+        None,
+        None,
+        None,
+    )
+}
+
+/// Saves an IsRandomAccessible<Is3D> as .pcd file with optional colors, normals and intensities
+/// Values are written with single precision
+pub fn save_pcd_points<RA, P, W>(
+    write: &mut W,
+    points: &RA,
+    format: &PcdDataFormat,
+    layout: &PcdLayout,
+    colors: Option<&[Rgb]>,
+    normals: Option<&[Norm3D]>,
+    intensities: Option<&[f64]>,
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P>,
+    P: Is3D,
+    W: Write,
+{
+    let n = points.len();
+
+    if layout.width.checked_mul(layout.height) != Some(n) {
+        return Err(IOError::Pcd(PcdError::Layout));
+    }
+    if colors.map(|x| x.len() != n).unwrap_or(false) {
+        return Err(IOError::ColorArrayLength);
+    }
+    if normals.map(|x| x.len() != n).unwrap_or(false) {
+        return Err(IOError::NormalArrayLength);
+    }
+    if intensities.map(|x| x.len() != n).unwrap_or(false) {
+        return Err(IOError::ScalarArrayLength);
+    }
+
+    let mut names = vec!["x", "y", "z"];
+    if colors.is_some() {
+        names.push("rgb");
+    }
+    if normals.is_some() {
+        names.extend(&["normal_x", "normal_y", "normal_z"]);
+    }
+    if intensities.is_some() {
+        names.push("intensity");
+    }
+
+    let v = &layout.viewpoint;
+    let repeat = |x: &str| vec![x; names.len()].join(" ");
+    let header = "# .PCD v0.7 - Point Cloud Data file format\n".to_string()
+        + "VERSION 0.7\n"
+        + &format!("FIELDS {}\n", names.join(" "))
+        + &format!("SIZE {}\n", repeat("4"))
+        + &format!("TYPE {}\n", repeat("F"))
+        + &format!("COUNT {}\n", repeat("1"))
+        + &format!("WIDTH {}\n", layout.width)
+        + &format!("HEIGHT {}\n", layout.height)
+        + &format!(
+            "VIEWPOINT {} {} {} {} {} {} {}\n",
+            v.translation[0],
+            v.translation[1],
+            v.translation[2],
+            v.orientation[0],
+            v.orientation[1],
+            v.orientation[2],
+            v.orientation[3]
+        )
+        + &format!("POINTS {}\n", n)
+        + &format!("DATA {}\n", format.name());
+    write.write_all(header.as_bytes())?;
+
+    let packed = |c: &Rgb| (c.r as u32) << 16 | (c.g as u32) << 8 | c.b as u32;
+
+    // all fields are 4 bytes wide
+    let record = |i: usize| -> Vec<[u8; 4]> {
+        let p = &points[i];
+        let mut record = vec![
+            (p.x() as f32).to_le_bytes(),
+            (p.y() as f32).to_le_bytes(),
+            (p.z() as f32).to_le_bytes(),
+        ];
+        if let Some(colors) = colors {
+            record.push(packed(&colors[i]).to_le_bytes());
+        }
+        if let Some(normals) = normals {
+            let x = &normals[i];
+            record.push((x.x() as f32).to_le_bytes());
+            record.push((x.y() as f32).to_le_bytes());
+            record.push((x.z() as f32).to_le_bytes());
+        }
+        if let Some(intensities) = intensities {
+            record.push((intensities[i] as f32).to_le_bytes());
+        }
+        record
+    };
+
+    match format {
+        PcdDataFormat::Ascii => {
+            for i in 0..n {
+                let p = &points[i];
+                let mut line = format!("{} {} {}", p.x() as f32, p.y() as f32, p.z() as f32);
+                if let Some(colors) = colors {
+                    line += &format!(" {}", packed(&colors[i]));
+                }
+                if let Some(normals) = normals {
+                    let x = &normals[i];
+                    line += &format!(" {} {} {}", x.x() as f32, x.y() as f32, x.z() as f32);
+                }
+                if let Some(intensities) = intensities {
+                    line += &format!(" {}", intensities[i] as f32);
+                }
+                line += "\n";
+                write.write_all(line.as_bytes())?;
+            }
+        }
+        PcdDataFormat::Binary => {
+            for i in 0..n {
+                for x in record(i) {
+                    write.write_all(&x)?;
+                }
+            }
+        }
+        PcdDataFormat::BinaryCompressed => {
+            // stored field by field
+            let n_fields = names.len();
+            let mut data = vec![0u8; 4 * n_fields * n];
+            for i in 0..n {
+                for (j, x) in record(i).iter().enumerate() {
+                    let start = 4 * (j * n + i);
+                    data[start..start + 4].copy_from_slice(x);
+                }
+            }
+
+            let compressed = lzf::compress(&data);
+            write.write_all(&(compressed.len() as u32).to_le_bytes())?;
+            write.write_all(&(data.len() as u32).to_le_bytes())?;
+            write.write_all(&compressed)?;
+        }
+    }
+
+    Ok(())
+}
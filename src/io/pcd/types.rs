@@ -0,0 +1,175 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for types of the pcd file format
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// Storage of the data section of a .pcd file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PcdDataFormat {
+    Ascii,
+    Binary,
+    /// LZF compressed, with the fields stored one after another
+    BinaryCompressed,
+}
+
+impl PcdDataFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ascii => "ascii",
+            Self::Binary => "binary",
+            Self::BinaryCompressed => "binary_compressed",
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Acquisition viewpoint of a .pcd file
+#[derive(Debug, Clone, PartialEq)]
+pub struct PcdViewpoint {
+    pub translation: [f64; 3],
+    /// Quaternion in the order w x y z
+    pub orientation: [f64; 4],
+}
+
+impl Default for PcdViewpoint {
+    fn default() -> Self {
+        Self {
+            translation: [0.0; 3],
+            orientation: [1.0, 0.0, 0.0, 0.0],
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Dimensions and viewpoint of a .pcd point cloud
+/// Organized clouds have a height greater than 1, with width * height points stored row by row
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PcdLayout {
+    pub width: usize,
+    pub height: usize,
+    pub viewpoint: PcdViewpoint,
+}
+
+impl PcdLayout {
+    /// Layout of an unorganized cloud of n points
+    pub fn unorganized(n: usize) -> Self {
+        Self {
+            width: n,
+            height: 1,
+            viewpoint: PcdViewpoint::default(),
+        }
+    }
+
+    pub fn is_organized(&self) -> bool {
+        self.height > 1
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// All attributes of a point of a .pcd file, except for its position
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PcdPoint {
+    /// Position within the layout
+    pub row: usize,
+    /// Position within the layout
+    pub column: usize,
+    pub color: Option<Rgb>,
+    /// None if missing or not normalizable
+    pub normal: Option<Norm3D>,
+    pub intensity: Option<f64>,
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    I,
+    U,
+    F,
+}
+
+#[derive(Debug, Clone)]
+pub struct Field {
+    pub name: String,
+    pub size: usize,
+    pub t: FieldType,
+    pub count: usize,
+    /// Offset in bytes within a record
+    pub offset: usize,
+}
+
+impl Field {
+    /// Byte size of all elements of the field within a record
+    pub fn n_bytes(&self) -> usize {
+        self.size * self.count
+    }
+
+    /// Reads the little endian value of the first element
+    pub fn value(&self, record: &[u8]) -> f64 {
+        let bytes = &record[self.offset..self.offset + self.size];
+        let mut buffer = [0u8; 8];
+        buffer[..self.size].copy_from_slice(bytes);
+
+        match (self.t, self.size) {
+            (FieldType::F, 4) => {
+                f32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64
+            }
+            (FieldType::F, _) => f64::from_le_bytes(buffer),
+            (FieldType::U, _) => u64::from_le_bytes(buffer) as f64,
+            (FieldType::I, _) => {
+                // sign extend
+                let shift = 64 - 8 * self.size as u32;
+                ((i64::from_le_bytes(buffer) << shift) >> shift) as f64
+            }
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Ids within Header::fields of the used fields
+#[derive(Debug, Clone)]
+pub struct FieldIds {
+    pub position: [usize; 3],
+    /// Packed rgb or rgba
+    pub color: Option<usize>,
+    pub normal: Option<[usize; 3]>,
+    pub intensity: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Header {
+    pub fields: Vec<Field>,
+    pub ids: FieldIds,
+    pub layout: PcdLayout,
+    pub n_points: usize,
+    pub format: PcdDataFormat,
+    pub record_size: usize,
+    /// Number of lines including the DATA line
+    pub n_lines: usize,
+}
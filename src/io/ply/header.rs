@@ -32,6 +32,42 @@ use super::types::*;
 
 //------------------------------------------------------------------------------
 
+/// Whether the data starts with a .ply header, checked the same way as within load_header
+pub(crate) fn starts_with_header(mut head: &[u8]) -> bool {
+    let mut line_buffer = Vec::new();
+    while let Ok(line) = fetch_line(&mut head, &mut line_buffer) {
+        if line.starts_with(b"comment") || line.starts_with(b"obj_info") {
+            continue;
+        }
+        return is_start(line);
+    }
+    false
+}
+
+/// Whether the line is the first line of a .ply header
+#[inline(always)]
+fn is_start(line: &[u8]) -> bool {
+    line == b"ply"
+}
+
+//------------------------------------------------------------------------------
+
+/// Loading a .ply header, additionally returning its size in bytes
+pub fn load_header_with_offset<R>(
+    read: &mut R,
+    line_buffer: &mut Vec<u8>,
+    i_line: &mut usize,
+) -> IOResult<(Header, u64)>
+where
+    R: BufRead,
+{
+    let mut counter = ByteCounter::new(read);
+    let header = load_header(&mut counter, line_buffer, i_line)?;
+    Ok((header, counter.offset()))
+}
+
+//------------------------------------------------------------------------------
+
 /// Loading a .ply header
 pub fn load_header<R>(
     read: &mut R,
@@ -64,7 +100,10 @@ where
     let mut face_before = BytesWords::default();
     let mut face_after = BytesWords::default();
 
-    while let Ok(line) = fetch_line(read, line_buffer) {
+    let mut vertex_properties = Vec::new();
+    let mut face_properties = Vec::new();
+
+    while let Some(line) = fetch_line_or_none(read, line_buffer)? {
         *i_line += 1;
 
         if line.starts_with(b"comment") {
@@ -76,7 +115,7 @@ where
         }
 
         if !ply_found {
-            if line == b"ply" {
+            if is_start(line) {
                 ply_found = true;
                 continue;
             }
@@ -140,6 +179,7 @@ where
                             Type::try_from(w).map_err(|s| IOError::InvalidPlyType(s, *i_line))
                         })?;
                     let id = words.next().ok_or(IOError::Property(*i_line))?;
+                    vertex_properties.push(Property::new(id, PropertyType::Scalar(t)));
                     if id == b"x" {
                         opt_fst_type = Some(
                             VertexType::try_from(t)
@@ -180,32 +220,34 @@ where
                 }
                 HeaderReadState::Face => {
                     if line.starts_with(b"property list") {
-                        if contains(line, b"vertex_indices") || contains(line, b"vertex_index") {
-                            let mut words = to_words_skip_empty(line);
-                            skip_n(&mut words, 2); // skip "property" and "list"
+                        let mut words = to_words_skip_empty(line);
+                        skip_n(&mut words, 2); // skip "property" and "list"
 
-                            let t_count = words
-                                .next()
-                                .ok_or(IOError::Property(*i_line))
-                                .and_then(|x| {
-                                    Type::try_from(x)
-                                        .map_err(|s| IOError::InvalidPlyType(s, *i_line))
-                                })
-                                .and_then(|x| {
-                                    FaceType::try_from(x)
-                                        .map_err(|t| IOError::InvalidPlyFaceType(t, *i_line))
-                                })?;
-                            let t_index = words
+                        let t_count = words
+                            .next()
+                            .ok_or(IOError::Property(*i_line))
+                            .and_then(|x| {
+                                Type::try_from(x).map_err(|s| IOError::InvalidPlyType(s, *i_line))
+                            })
+                            .and_then(|x| {
+                                FaceType::try_from(x)
+                                    .map_err(|t| IOError::InvalidPlyFaceType(t, *i_line))
+                            })?;
+                        let t_item =
+                            words
                                 .next()
                                 .ok_or(IOError::Property(*i_line))
                                 .and_then(|x| {
                                     Type::try_from(x)
                                         .map_err(|s| IOError::InvalidPlyType(s, *i_line))
-                                })
-                                .and_then(|x| {
-                                    FaceType::try_from(x)
-                                        .map_err(|t| IOError::InvalidPlyFaceType(t, *i_line))
                                 })?;
+                        let id = words.next().ok_or(IOError::Property(*i_line))?;
+                        face_properties
+                            .push(Property::new(id, PropertyType::List(t_count, t_item)));
+
+                        if id == b"vertex_indices" || id == b"vertex_index" {
+                            let t_index = FaceType::try_from(t_item)
+                                .map_err(|t| IOError::InvalidPlyFaceType(t, *i_line))?;
 
                             opt_face_count_type = Some(t_count);
                             opt_face_index_type = Some(t_index);
@@ -219,6 +261,8 @@ where
                             .and_then(|x| {
                                 Type::try_from(x).map_err(|s| IOError::InvalidPlyType(s, *i_line))
                             })?;
+                        let id = words.next().ok_or(IOError::Property(*i_line))?;
+                        face_properties.push(Property::new(id, PropertyType::Scalar(t)));
                         if opt_face_count_type.is_some() {
                             face_after.bytes += t.size_bytes();
                             face_after.words += 1;
@@ -247,7 +291,8 @@ where
                 let vertex_data = VertexData {
                     count: n_vertices,
                     format: VertexFormat {
-                        order: VertexOrder::try_from(vertex_order)?, //@todo line info
+                        order: VertexOrder::try_from(vertex_order)
+                            .map_err(|e| e.context(IOElement::Header, IOLocation::Line(*i_line)))?,
                         first: x_type,
                         snd: y_type,
                         third: z_type,
@@ -256,6 +301,7 @@ where
                         between_snd_third: vertex_between_snd_third,
                         after,
                     },
+                    properties: vertex_properties,
                 };
 
                 if let (Some(n_faces), Some(face_count_type), Some(face_index_type)) =
@@ -272,6 +318,7 @@ where
                                 count: face_count_type,
                                 index: face_index_type,
                             },
+                            properties: face_properties,
                         },
                     }));
                 } else {
@@ -283,10 +330,10 @@ where
             }
         }
 
-        return Err(IOError::Header);
+        return Err(IOError::Header.context(IOElement::Header, IOLocation::Line(*i_line)));
     }
 
-    Err(IOError::Header)
+    Err(IOError::Header.context(IOElement::Header, IOLocation::Line(*i_line)))
 }
 
 //------------------------------------------------------------------------------
@@ -47,22 +47,30 @@ where
     P: IsBuildable3D + Default,
     R: BufRead,
 {
-    pub fn new(mut read: R) -> IOResult<Self> {
+    /// Creates a new iterator, polygonal faces are split via Triangulation::Fan
+    pub fn new(read: R) -> IOResult<Self> {
+        Self::with_triangulation(read, Triangulation::default())
+    }
+
+    /// Creates a new iterator, polygonal faces are split via triangulation
+    pub fn with_triangulation(mut read: R, triangulation: Triangulation) -> IOResult<Self> {
         let mut line_buffer = Vec::new();
         let mut i_line = 0;
 
-        if let Header::Full(header) = load_header(&mut read, &mut line_buffer, &mut i_line)? {
+        let (header, offset) = load_header_with_offset(&mut read, &mut line_buffer, &mut i_line)?;
+
+        if let Header::Full(header) = header {
             let to_reserve_data_faces = Some((header.vertex.count, header.face.count));
 
             let inner = match header.format {
                 Format::Ascii => BinaryOrAsciiPlyMeshInteralIterator::Ascii(
-                    PlyAsciiMeshIterator::new(read, header, i_line),
+                    PlyAsciiMeshIterator::new(read, header, i_line, triangulation),
                 ),
                 Format::LittleEndian => BinaryOrAsciiPlyMeshInteralIterator::BinaryLittle(
-                    PlyBinaryMeshIterator::new(read, header),
+                    PlyBinaryMeshIterator::new(read, header, triangulation, offset),
                 ),
                 Format::BigEndian => BinaryOrAsciiPlyMeshInteralIterator::BinaryBig(
-                    PlyBinaryMeshIterator::new(read, header),
+                    PlyBinaryMeshIterator::new(read, header, triangulation, offset),
                 ),
             };
 
@@ -128,7 +136,8 @@ where
         let mut line_buffer = Vec::new();
         let mut i_line = 0;
 
-        let header: PartialHeader = load_header(&mut read, &mut line_buffer, &mut i_line)?.into();
+        let (header, offset) = load_header_with_offset(&mut read, &mut line_buffer, &mut i_line)?;
+        let header: PartialHeader = header.into();
         let to_reserve = Some(header.vertex.count);
 
         let inner = match header.format {
@@ -136,10 +145,10 @@ where
                 PlyAsciiPointsIterator::new(read, header, i_line),
             ),
             Format::LittleEndian => BinaryOrAsciiPlyPointsInteralIterator::BinaryLittle(
-                PlyBinaryPointsIterator::new(read, header),
+                PlyBinaryPointsIterator::new(read, header, offset),
             ),
             Format::BigEndian => BinaryOrAsciiPlyPointsInteralIterator::BinaryBig(
-                PlyBinaryPointsIterator::new(read, header),
+                PlyBinaryPointsIterator::new(read, header, offset),
             ),
         };
 
@@ -42,6 +42,7 @@ where
     R: BufRead,
 {
     header: FullHeader,
+    triangulator: Option<FaceTriangulator>,
     p_iter: Option<PlyAsciiPointsIterator<P, R, CHUNK_SIZE>>,
     f_iter: Option<PlyAsciiFacesIterator<R, P, CHUNK_SIZE>>, //@todo order type args
 }
@@ -51,10 +52,11 @@ where
     P: IsBuildable3D + Default,
     R: BufRead,
 {
-    pub fn new(read: R, header: FullHeader, i_line: usize) -> Self {
+    pub fn new(read: R, header: FullHeader, i_line: usize, triangulation: Triangulation) -> Self {
         let partial_header: PartialHeader = header.clone().into();
         Self {
             header,
+            triangulator: Some(FaceTriangulator::new(triangulation)),
             p_iter: Some(PlyAsciiPointsIterator::new(read, partial_header, i_line)),
             f_iter: None,
         }
@@ -71,7 +73,7 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(ref mut p_iter) = self.p_iter {
             match p_iter.next() {
-                Some(x) => return Some(x.map(|x| x.convert())),
+                Some(x) => return Some(x.map(|x| add_vertices(&mut self.triangulator, x))),
                 None => {
                     // point iteration done, switch to face iteration
                     // unwrap safe, since in if let Some()
@@ -81,6 +83,7 @@ where
                         read,
                         self.header.clone(),
                         i_line,
+                        self.triangulator.take().unwrap(), // unwrap safe, only taken once
                     ));
                 }
             }
@@ -110,6 +113,7 @@ where
     BR: IsByteReader,
 {
     header: FullHeader,
+    triangulator: Option<FaceTriangulator>,
     p_iter: Option<PlyBinaryPointsIterator<BR, P, R, CHUNK_SIZE>>,
     f_iter: Option<PlyBinaryFacesIterator<BR, P, R, CHUNK_SIZE>>,
 }
@@ -120,11 +124,13 @@ where
     R: Read,
     BR: IsByteReader,
 {
-    pub fn new(read: R, header: FullHeader) -> Self {
+    /// offset is the size of the already consumed header in bytes
+    pub fn new(read: R, header: FullHeader, triangulation: Triangulation, offset: u64) -> Self {
         let partial_header: PartialHeader = header.clone().into();
         Self {
             header,
-            p_iter: Some(PlyBinaryPointsIterator::new(read, partial_header)),
+            triangulator: Some(FaceTriangulator::new(triangulation)),
+            p_iter: Some(PlyBinaryPointsIterator::new(read, partial_header, offset)),
             f_iter: None,
         }
     }
@@ -141,13 +147,18 @@ where
     fn next(&mut self) -> Option<Self::Item> {
         if let Some(ref mut p_iter) = self.p_iter {
             match p_iter.next() {
-                Some(x) => return Some(x.map(|x| x.convert())),
+                Some(x) => return Some(x.map(|x| add_vertices(&mut self.triangulator, x))),
                 None => {
                     // point iteration done, switch to face iteration
                     // unwrap safe, since in if let Some()
                     let p_iter = self.p_iter.take().unwrap();
-                    let read = p_iter.destruct();
-                    self.f_iter = Some(PlyBinaryFacesIterator::new(read, self.header.clone()));
+                    let (read, offset) = p_iter.destruct();
+                    self.f_iter = Some(PlyBinaryFacesIterator::new(
+                        read,
+                        self.header.clone(),
+                        self.triangulator.take().unwrap(), // unwrap safe, only taken once
+                        offset,
+                    ));
                 }
             }
         }
@@ -201,7 +212,7 @@ where
     R: Read,
     BR: IsByteReader,
 {
-    read: R,
+    read: ByteCounter<R>,
     is_done: bool,
     header: PartialHeader,
     current: usize,
@@ -215,9 +226,10 @@ where
     R: Read,
     BR: IsByteReader,
 {
-    pub fn new(read: R, header: PartialHeader) -> Self {
+    /// offset is the size of the already consumed header in bytes
+    pub fn new(read: R, header: PartialHeader, offset: u64) -> Self {
         Self {
-            read,
+            read: ByteCounter::with_offset(read, offset),
             is_done: false,
             header,
             current: 0,
@@ -226,8 +238,10 @@ where
         }
     }
 
-    pub fn destruct(self) -> R {
-        self.read
+    /// Returns the reader and the offset of the data following the vertices
+    pub fn destruct(self) -> (R, u64) {
+        let offset = self.read.offset();
+        (self.read.into_inner(), offset)
     }
 
     #[inline(always)]
@@ -281,10 +295,11 @@ where
                 return Some(Ok(chunk));
             } else if self.current < self.header.vertex.count {
                 self.current += 1;
+                let offset = self.read.offset();
                 match self.fetch_one() {
                     Err(e) => {
                         self.is_done = true;
-                        return Some(Err(e));
+                        return Some(Err(e.context(IOElement::Vertex, IOLocation::Byte(offset))));
                     }
                     Ok(x) => chunk.push(DataReserve::Data(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
                 }
@@ -402,15 +417,20 @@ where
                 //@todo error handling here might now diverge from previous version, double check
                 //@todo outer else should already cause failure?
                 self.current += 1;
-                if let Ok(line) = fetch_line(&mut self.read, &mut self.line_buffer) {
-                    self.i_line += 1;
-                    match Self::fetch_one(&self.header, self.i_line, line) {
-                        Err(e) => {
-                            self.is_done = true;
-                            return Some(Err(e));
-                        }
-                        Ok(x) => chunk.push(DataReserve::Data(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
+                let line = match fetch_line(&mut self.read, &mut self.line_buffer) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e.into()));
                     }
+                };
+                self.i_line += 1;
+                match Self::fetch_one(&self.header, self.i_line, line) {
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e));
+                    }
+                    Ok(x) => chunk.push(DataReserve::Data(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
                 }
             } else {
                 self.is_done = true;
@@ -447,6 +467,8 @@ where
     current: usize,
     i_line: usize,
     line_buffer: Vec<u8>,
+    face_buffer: Vec<usize>,
+    triangulator: FaceTriangulator,
     phantom: PhantomData<P>,
 }
 
@@ -454,7 +476,7 @@ impl<R, P, const CHUNK_SIZE: usize> PlyAsciiFacesIterator<R, P, CHUNK_SIZE>
 where
     R: BufRead,
 {
-    pub fn new(read: R, header: FullHeader, i_line: usize) -> Self {
+    pub fn new(read: R, header: FullHeader, i_line: usize, triangulator: FaceTriangulator) -> Self {
         Self {
             read,
             is_done: false,
@@ -462,6 +484,8 @@ where
             current: 0,
             i_line,
             line_buffer: Vec::new(),
+            face_buffer: Vec::new(),
+            triangulator,
             phantom: PhantomData::default(),
         }
     }
@@ -483,20 +507,26 @@ where
         loop {
             if chunk.is_full() {
                 return Some(Ok(chunk));
+            } else if let Some(x) = self.triangulator.next_face() {
+                chunk.push(crate::io::FaceData::Face(x)).unwrap() // unwrap safe since we only call this if chunk.has_space()
             } else if self.current < self.header.face.count {
                 //@todo error handling here might now diverge from previous version, double check
                 //@todo outer else should already cause failure?
                 self.current += 1;
-                if let Ok(line) = fetch_line(&mut self.read, &mut self.line_buffer) {
-                    self.i_line += 1;
-
-                    match collect_index_line(&line) {
-                        None => {
-                            self.is_done = true;
-                            return Some(Err(IOError::Face(Some(self.i_line))));
-                        }
-                        Some(x) => chunk.push(crate::io::FaceData::Face(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
+                let line = match fetch_line(&mut self.read, &mut self.line_buffer) {
+                    Ok(line) => line,
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e.into()));
                     }
+                };
+                self.i_line += 1;
+
+                if collect_index_line(&line, &mut self.face_buffer).is_none()
+                    || !self.triangulator.add_face(&self.face_buffer)
+                {
+                    self.is_done = true;
+                    return Some(Err(IOError::Face(Some(self.i_line))));
                 }
             } else {
                 self.is_done = true;
@@ -522,10 +552,12 @@ where
     R: Read,
     BR: IsByteReader,
 {
-    read: R,
+    read: ByteCounter<R>,
     is_done: bool,
     header: FullHeader,
     current: usize,
+    face_buffer: Vec<usize>,
+    triangulator: FaceTriangulator,
     phantom_br: PhantomData<BR>,
     phantom_p: PhantomData<P>,
 }
@@ -535,34 +567,41 @@ where
     R: Read,
     BR: IsByteReader,
 {
-    pub fn new(read: R, header: FullHeader) -> Self {
+    /// offset is the position of the first face in bytes
+    pub fn new(read: R, header: FullHeader, triangulator: FaceTriangulator, offset: u64) -> Self {
         Self {
-            read,
+            read: ByteCounter::with_offset(read, offset),
             is_done: false,
             header,
             current: 0,
+            face_buffer: Vec::new(),
+            triangulator,
             phantom_br: PhantomData,
             phantom_p: PhantomData,
         }
     }
 
     #[inline(always)]
-    fn fetch_one(&mut self) -> IOResult<[usize; 3]> {
+    fn fetch_one(&mut self) -> IOResult<()> {
         skip_bytes(&mut self.read, self.header.face.format.before.bytes)?;
 
         let element_count = read_face_type::<BR, _>(&mut self.read, self.header.face.format.count)?;
 
-        if element_count != 3 {
-            return Err(IOError::Face(None));
+        self.face_buffer.clear();
+        for _ in 0..element_count {
+            self.face_buffer.push(read_face_type::<BR, _>(
+                &mut self.read,
+                self.header.face.format.index,
+            )?);
         }
 
-        let a = read_face_type::<BR, _>(&mut self.read, self.header.face.format.index)?;
-        let b = read_face_type::<BR, _>(&mut self.read, self.header.face.format.index)?;
-        let c = read_face_type::<BR, _>(&mut self.read, self.header.face.format.index)?;
-
         skip_bytes(&mut self.read, self.header.face.format.after.bytes)?;
 
-        Ok([a, b, c])
+        if !self.triangulator.add_face(&self.face_buffer) {
+            return Err(IOError::Face(None));
+        }
+
+        Ok(())
     }
 }
 
@@ -583,14 +622,14 @@ where
         loop {
             if chunk.is_full() {
                 return Some(Ok(chunk));
+            } else if let Some(x) = self.triangulator.next_face() {
+                chunk.push(crate::io::FaceData::Face(x)).unwrap() // unwrap safe since we only call this if chunk.has_space()
             } else if self.current < self.header.face.count {
                 self.current += 1;
-                match self.fetch_one() {
-                    Err(e) => {
-                        self.is_done = true;
-                        return Some(Err(e));
-                    }
-                    Ok(x) => chunk.push(crate::io::FaceData::Face(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
+                let offset = self.read.offset();
+                if let Err(e) = self.fetch_one() {
+                    self.is_done = true;
+                    return Some(Err(e.context(IOElement::Face, IOLocation::Byte(offset))));
                 }
             } else {
                 self.is_done = true;
@@ -610,3 +649,25 @@ where
     BR: IsByteReader,
 {
 }
+
+//------------------------------------------------------------------------------
+
+/// Passes the positions to the triangulator, converting the chunk
+#[inline(always)]
+fn add_vertices<P, const CHUNK_SIZE: usize>(
+    triangulator: &mut Option<FaceTriangulator>,
+    chunk: StackVec<DataReserve<P>, CHUNK_SIZE>,
+) -> StackVec<FaceDataReserve<P>, CHUNK_SIZE>
+where
+    P: Is3D,
+{
+    if let Some(triangulator) = triangulator {
+        for x in chunk.data() {
+            if let DataReserve::Data(p) = x {
+                triangulator.add_vertex(p)
+            }
+        }
+    }
+
+    chunk.convert()
+}
@@ -28,7 +28,7 @@ use std::io::{BufRead, Read};
 
 use super::super::{byte_reader::*, types::*};
 
-use super::{header::*, iterators::*, iterators_internal::*, types::*};
+use super::{header::*, iterators::*, iterators_internal::*, properties::*, types::*};
 
 //------------------------------------------------------------------------------
 
@@ -47,7 +47,9 @@ where
     let mut line_buffer = Vec::new();
     let mut i_line = 0;
 
-    match load_header(&mut read, &mut line_buffer, &mut i_line)? {
+    let (header, offset) = load_header_with_offset(&mut read, &mut line_buffer, &mut i_line)?;
+
+    match header {
         Header::Full(header) => {
             mesh.reserve_vertices(header.vertex.count);
             mesh.reserve_faces(header.face.count);
@@ -56,12 +58,12 @@ where
                 Format::Ascii => {
                     load_mesh_ascii::<_, _, _, CHUNK_SIZE>(&mut read, mesh, header, &mut i_line)
                 }
-                Format::LittleEndian => {
-                    load_mesh_binary::<LittleReader, _, _, _, CHUNK_SIZE>(&mut read, mesh, header)
-                }
-                Format::BigEndian => {
-                    load_mesh_binary::<BigReader, _, _, _, CHUNK_SIZE>(&mut read, mesh, header)
-                }
+                Format::LittleEndian => load_mesh_binary::<LittleReader, _, _, _, CHUNK_SIZE>(
+                    &mut read, mesh, header, offset,
+                ),
+                Format::BigEndian => load_mesh_binary::<BigReader, _, _, _, CHUNK_SIZE>(
+                    &mut read, mesh, header, offset,
+                ),
             }?;
 
             Ok(MeshOrPoints::Mesh)
@@ -73,12 +75,12 @@ where
                 Format::Ascii => {
                     load_points_ascii::<_, _, _, CHUNK_SIZE>(&mut read, ip, header, i_line)
                 }
-                Format::LittleEndian => {
-                    load_points_binary::<LittleReader, _, _, _, CHUNK_SIZE>(&mut read, ip, header)
-                }
-                Format::BigEndian => {
-                    load_points_binary::<BigReader, _, _, _, CHUNK_SIZE>(&mut read, ip, header)
-                }
+                Format::LittleEndian => load_points_binary::<LittleReader, _, _, _, CHUNK_SIZE>(
+                    &mut read, ip, header, offset,
+                ),
+                Format::BigEndian => load_points_binary::<BigReader, _, _, _, CHUNK_SIZE>(
+                    &mut read, ip, header, offset,
+                ),
             }?;
 
             Ok(MeshOrPoints::Points)
@@ -93,7 +95,21 @@ where
     P: IsBuildable3D + Default,
     R: BufRead,
 {
-    let iterator = PlyMeshIterator::<_, _, CHUNK_SIZE>::new(read)?;
+    load_ply_mesh_with_triangulation::<_, _, _, CHUNK_SIZE>(read, mesh, Triangulation::default())
+}
+
+/// Loads an IsMesh3D from the .ply file format, splitting polygonal faces via triangulation
+pub fn load_ply_mesh_with_triangulation<EM, P, R, const CHUNK_SIZE: usize>(
+    read: R,
+    mesh: &mut EM,
+    triangulation: Triangulation,
+) -> IOResult<()>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3>,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let iterator = PlyMeshIterator::<_, _, CHUNK_SIZE>::with_triangulation(read, triangulation)?;
 
     for data in iterator {
         for x in data? {
@@ -144,6 +160,230 @@ where
     Ok(())
 }
 
+//------------------------------------------------------------------------------
+
+/// Loads an IsMesh3D from the .ply file format, returning the properties of all vertices and faces
+/// Polygonal faces are split via triangulation, each resulting triangle has the properties of its polygon
+pub fn load_ply_mesh_with_attributes<EM, P, R>(
+    mut read: R,
+    mesh: &mut EM,
+    triangulation: Triangulation,
+) -> IOResult<PlyMeshAttributes>
+where
+    EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3>,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let mut line_buffer = Vec::new();
+    let mut i_line = 0;
+
+    let (header, offset) = load_header_with_offset(&mut read, &mut line_buffer, &mut i_line)?;
+    let header = match header {
+        Header::Full(x) => x,
+        Header::Partial(_) => return Err(IOError::Header),
+    };
+
+    mesh.reserve_vertices(header.vertex.count);
+    mesh.reserve_faces(header.face.count);
+
+    let mut reader = ElementReader::new(header.format, i_line, offset);
+
+    let mut triangulator = FaceTriangulator::new(triangulation);
+
+    let vertex = read_vertices(&mut read, &mut reader, &header.vertex, |p: P| {
+        triangulator.add_vertex(&p);
+        mesh.add_vertex(p);
+    })?;
+
+    let properties = &header.face.properties;
+    let mut collector = AttributeCollector::new(properties, header.face.count, &[]);
+
+    for _ in 0..header.face.count {
+        reader.read(&mut read, properties, IOElement::Face)?;
+
+        if !triangulator.add_face(&reader.indices) {
+            return Err(IOError::Face(None).context(IOElement::Face, reader.location()));
+        }
+
+        while let Some([a, b, c]) = triangulator.next_face() {
+            mesh.try_add_connection(VId(a), VId(b), VId(c))
+                .map_err(|_| {
+                    IOError::InvalidMeshIndices.context(IOElement::Face, reader.location())
+                })?;
+            collector.push(&reader.values);
+        }
+    }
+
+    Ok(PlyMeshAttributes {
+        vertex,
+        face: collector.into_attributes(),
+    })
+}
+
+/// Loads an IsMesh3D from the .ply file format, storing the properties of all vertices and faces as their Attributes
+/// Colors are stored as "color", normals as "normal" and all other properties as scalars of their name
+/// Polygonal faces are split via triangulation, each resulting triangle has the properties of its polygon
+pub fn load_ply_mesh_attributed<EM, P, R>(
+    read: R,
+    mesh: &mut EM,
+    triangulation: Triangulation,
+) -> IOResult<()>
+where
+    EM: IsFaceEditableMesh<P, Face3>
+        + IsVertexEditableMesh<P, Face3>
+        + HasVertexAttributes
+        + HasFaceAttributes,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let n_vertices = mesh.vertex_attributes_mut().len();
+    let n_faces = mesh.face_attributes_mut().len();
+
+    let attributes = load_ply_mesh_with_attributes(read, mesh, triangulation)?;
+
+    let vertex = attributes
+        .vertex
+        .into_attributes(mesh.num_vertices() - n_vertices)?;
+    let face = attributes
+        .face
+        .into_attributes(mesh.num_faces() - n_faces)?;
+
+    mesh.vertex_attributes_mut().write_at(n_vertices, vertex);
+    mesh.face_attributes_mut().write_at(n_faces, face);
+
+    Ok(())
+}
+
+/// Loads an IsEditablePolygonMesh from the .ply file format, keeping polygonal faces
+pub fn load_ply_polygon_mesh<EM, P, R>(read: R, mesh: &mut EM) -> IOResult<()>
+where
+    EM: IsEditablePolygonMesh<P>,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    load_ply_polygon_mesh_with_attributes(read, mesh).map(|_| ())
+}
+
+/// Loads an IsEditablePolygonMesh from the .ply file format, keeping polygonal faces and returning the properties of all vertices and faces
+pub fn load_ply_polygon_mesh_with_attributes<EM, P, R>(
+    mut read: R,
+    mesh: &mut EM,
+) -> IOResult<PlyMeshAttributes>
+where
+    EM: IsEditablePolygonMesh<P>,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let mut line_buffer = Vec::new();
+    let mut i_line = 0;
+
+    let (header, offset) = load_header_with_offset(&mut read, &mut line_buffer, &mut i_line)?;
+    let header = match header {
+        Header::Full(x) => x,
+        Header::Partial(_) => return Err(IOError::Header),
+    };
+
+    mesh.reserve_vertices(header.vertex.count);
+    mesh.reserve_faces(header.face.count);
+
+    let mut reader = ElementReader::new(header.format, i_line, offset);
+
+    let vertex = read_vertices(&mut read, &mut reader, &header.vertex, |p: P| {
+        mesh.add_vertex(p);
+    })?;
+
+    let properties = &header.face.properties;
+    let mut collector = AttributeCollector::new(properties, header.face.count, &[]);
+    let mut vids = Vec::new();
+
+    for _ in 0..header.face.count {
+        reader.read(&mut read, properties, IOElement::Face)?;
+
+        if reader.indices.len() < 3 {
+            return Err(IOError::Face(None).context(IOElement::Face, reader.location()));
+        }
+
+        vids.clear();
+        vids.extend(reader.indices.iter().map(VId));
+        mesh.try_add_polygon(&vids)
+            .map_err(|_| IOError::InvalidMeshIndices.context(IOElement::Face, reader.location()))?;
+        collector.push(&reader.values);
+    }
+
+    Ok(PlyMeshAttributes {
+        vertex,
+        face: collector.into_attributes(),
+    })
+}
+
+/// Loads an IsEditablePolygonMesh from the .ply file format, keeping polygonal faces and storing the properties of all vertices and faces as their Attributes
+/// Colors are stored as "color", normals as "normal" and all other properties as scalars of their name
+pub fn load_ply_polygon_mesh_attributed<EM, P, R>(read: R, mesh: &mut EM) -> IOResult<()>
+where
+    EM: IsEditablePolygonMesh<P> + HasVertexAttributes + HasFaceAttributes,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let n_vertices = mesh.vertex_attributes_mut().len();
+    let n_faces = mesh.face_attributes_mut().len();
+
+    let attributes = load_ply_polygon_mesh_with_attributes(read, mesh)?;
+
+    let vertex = attributes
+        .vertex
+        .into_attributes(mesh.num_vertices() - n_vertices)?;
+    let face = attributes
+        .face
+        .into_attributes(mesh.num_faces() - n_faces)?;
+
+    mesh.vertex_attributes_mut().write_at(n_vertices, vertex);
+    mesh.face_attributes_mut().write_at(n_faces, face);
+
+    Ok(())
+}
+
+/// Loads the points from the .ply file into IsPushable<Is3D>, returning the properties of all vertices
+pub fn load_ply_points_with_attributes<IP, P, R>(
+    mut read: R,
+    ip: &mut IP,
+) -> IOResult<PlyAttributes>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let mut line_buffer = Vec::new();
+    let mut i_line = 0;
+
+    let (header, offset) = load_header_with_offset(&mut read, &mut line_buffer, &mut i_line)?;
+    let header: PartialHeader = header.into();
+
+    ip.reserve_exact(header.vertex.count);
+
+    let mut reader = ElementReader::new(header.format, i_line, offset);
+
+    read_vertices(&mut read, &mut reader, &header.vertex, |p| ip.push(p))
+}
+
+/// Loads the points from the .ply file into IsPushable<Is3D>, storing the properties of all vertices as their Attributes
+/// Colors are stored as "color", normals as "normal" and all other properties as scalars of their name
+pub fn load_ply_points_attributed<IP, P, R>(read: R, ip: &mut IP) -> IOResult<()>
+where
+    IP: IsPushable<P> + HasVertexAttributes,
+    P: IsBuildable3D + Default,
+    R: BufRead,
+{
+    let n = ip.vertex_attributes_mut().len();
+
+    let attributes = load_ply_points_with_attributes(read, ip)?;
+
+    let n_loaded = ip.vertex_attributes_mut().len() - n;
+    let attributes = attributes.into_attributes(n_loaded)?;
+    ip.vertex_attributes_mut().write_at(n, attributes);
+
+    Ok(())
+}
+
 //------------------------------------------------------------------------------
 //------------------------------------------------------------------------------
 //------------------------------------------------------------------------------
@@ -154,6 +394,7 @@ fn load_points_binary<BR, IP, P, R, const CHUNK_SIZE: usize>(
     read: &mut R,
     ip: &mut IP,
     header: PartialHeader,
+    offset: u64,
 ) -> IOResult<()>
 where
     IP: IsPushable<P>,
@@ -161,7 +402,7 @@ where
     R: Read,
     BR: IsByteReader,
 {
-    let iterator = PlyBinaryPointsIterator::<BR, _, _, CHUNK_SIZE>::new(read, header);
+    let iterator = PlyBinaryPointsIterator::<BR, _, _, CHUNK_SIZE>::new(read, header, offset);
 
     for data in iterator {
         for x in data? {
@@ -210,6 +451,7 @@ fn load_mesh_binary<BR, EM, P, R, const CHUNK_SIZE: usize>(
     read: &mut R,
     mesh: &mut EM,
     header: FullHeader,
+    offset: u64,
 ) -> IOResult<()>
 where
     EM: IsFaceEditableMesh<P, Face3> + IsVertexEditableMesh<P, Face3>,
@@ -217,7 +459,12 @@ where
     R: Read,
     BR: IsByteReader,
 {
-    let iterator = PlyBinaryMeshIterator::<BR, _, _, CHUNK_SIZE>::new(read, header);
+    let iterator = PlyBinaryMeshIterator::<BR, _, _, CHUNK_SIZE>::new(
+        read,
+        header,
+        Triangulation::default(),
+        offset,
+    );
 
     for data in iterator {
         for x in data? {
@@ -257,7 +504,12 @@ where
     P: IsBuildable3D + Default,
     R: BufRead,
 {
-    let iterator = PlyAsciiMeshIterator::<_, _, CHUNK_SIZE>::new(read, header, *i_line);
+    let iterator = PlyAsciiMeshIterator::<_, _, CHUNK_SIZE>::new(
+        read,
+        header,
+        *i_line,
+        Triangulation::default(),
+    );
 
     for data in iterator {
         for x in data? {
@@ -283,3 +535,30 @@ where
 
     Ok(())
 }
+
+//------------------------------------------------------------------------------
+
+fn read_vertices<P, R, F>(
+    read: &mut R,
+    reader: &mut ElementReader,
+    vertex: &VertexData,
+    mut f: F,
+) -> IOResult<PlyAttributes>
+where
+    P: IsBuildable3D,
+    R: BufRead,
+    F: FnMut(P),
+{
+    let properties = &vertex.properties;
+    let [x, y, z] = position_ids(properties)?;
+    let mut collector = AttributeCollector::new(properties, vertex.count, &["x", "y", "z"]);
+
+    for _ in 0..vertex.count {
+        reader.read(read, properties, IOElement::Vertex)?;
+        let values = &reader.values;
+        f(P::new(values[x], values[y], values[z]));
+        collector.push(values);
+    }
+
+    Ok(collector.into_attributes())
+}
@@ -26,12 +26,14 @@ mod header;
 mod iterators;
 mod iterators_internal;
 mod load;
+mod properties;
 mod save;
 mod types;
 mod utils;
 
 pub use header::load_header; //@todo rename load_ply_header?
+pub(crate) use header::starts_with_header;
 pub use iterators::*;
 pub use load::*;
 pub use save::*;
-pub use types::{Header, MeshOrPoints, Type}; //@todo rename PlyHeader?
+pub use types::{Header, MeshOrPoints, PlyAttributes, PlyMeshAttributes, PlyScalarColumn, Type}; //@todo rename PlyHeader?
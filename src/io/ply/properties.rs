@@ -0,0 +1,320 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for reading all properties of the elements of the ply file format
+
+use crate::*;
+
+use std::{convert::TryFrom, io::BufRead};
+
+use super::{
+    super::{byte_reader::*, types::*, utils::*},
+    types::*,
+    utils::*,
+};
+
+//------------------------------------------------------------------------------
+
+/// Reads single elements, storing the values of all their properties
+pub struct ElementReader {
+    format: Format,
+    i_line: usize,
+    /// Offset of the next element in binary data
+    offset: u64,
+    /// Offset of the previous element in binary data
+    last_offset: u64,
+    line_buffer: Vec<u8>,
+    /// One value per property, NaN for lists
+    pub values: Vec<f64>,
+    /// The entries of the vertex index list
+    pub indices: Vec<usize>,
+}
+
+impl ElementReader {
+    pub fn new(format: Format, i_line: usize, offset: u64) -> Self {
+        Self {
+            format,
+            i_line,
+            offset,
+            last_offset: offset,
+            line_buffer: Vec::new(),
+            values: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    /// Location of the previously read element
+    pub fn location(&self) -> IOLocation {
+        match self.format {
+            Format::Ascii => IOLocation::Line(self.i_line),
+            _ => IOLocation::Byte(self.last_offset),
+        }
+    }
+
+    /// Reads the next element of the given kind
+    pub fn read<R>(
+        &mut self,
+        read: &mut R,
+        properties: &[Property],
+        element: IOElement,
+    ) -> IOResult<()>
+    where
+        R: BufRead,
+    {
+        self.values.clear();
+        self.indices.clear();
+
+        let error = match element {
+            IOElement::Face => IOError::Face,
+            _ => IOError::Vertex,
+        };
+
+        match self.format {
+            Format::Ascii => {
+                let i_line = self.i_line;
+                let line = fetch_line(read, &mut self.line_buffer).map_err(|e| match e.0 {
+                    Some(e) => IOError::AccessFile(e),
+                    None => error(Some(i_line)),
+                })?;
+                self.i_line += 1;
+                read_ascii(line, properties, &mut self.values, &mut self.indices)
+                    .ok_or_else(|| error(Some(self.i_line)))
+            }
+            _ => {
+                self.last_offset = self.offset;
+                let mut counter = ByteCounter::with_offset(read, self.offset);
+                let result = match self.format {
+                    Format::LittleEndian => read_binary::<LittleReader, _>(
+                        &mut counter,
+                        properties,
+                        &mut self.values,
+                        &mut self.indices,
+                    ),
+                    _ => read_binary::<BigReader, _>(
+                        &mut counter,
+                        properties,
+                        &mut self.values,
+                        &mut self.indices,
+                    ),
+                };
+                self.offset = counter.offset();
+                result.map_err(|e| e.context(element, IOLocation::Byte(self.last_offset)))
+            }
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Collects the values of elements into PlyAttributes
+pub struct AttributeCollector {
+    color_ids: Option<[usize; 3]>,
+    is_color_float: bool,
+    normal_ids: Option<[usize; 3]>,
+    scalar_ids: Vec<usize>,
+    attributes: PlyAttributes,
+}
+
+impl AttributeCollector {
+    /// Creates a new collector, ignoring the properties named in skip
+    pub fn new(properties: &[Property], count: usize, skip: &[&str]) -> Self {
+        let color_ids = find_scalars(properties, ["red", "green", "blue"]);
+        let normal_ids = find_scalars(properties, ["nx", "ny", "nz"]);
+
+        let is_color_float = color_ids
+            .map(|[r, _, _]| {
+                matches!(
+                    properties[r].t,
+                    PropertyType::Scalar(Type::Float) | PropertyType::Scalar(Type::Double)
+                )
+            })
+            .unwrap_or(false);
+
+        let scalar_ids: Vec<usize> = properties
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| {
+                matches!(p.t, PropertyType::Scalar(_))
+                    && !skip.contains(&p.name.as_str())
+                    && !color_ids.map(|x| x.contains(i)).unwrap_or(false)
+                    && !normal_ids.map(|x| x.contains(i)).unwrap_or(false)
+            })
+            .map(|(i, _)| i)
+            .collect();
+
+        let attributes = PlyAttributes {
+            colors: color_ids.map(|_| Vec::with_capacity(count)),
+            normals: normal_ids.map(|_| Vec::with_capacity(count)),
+            scalars: scalar_ids
+                .iter()
+                .map(|i| PlyScalarColumn {
+                    name: properties[i].name.clone(),
+                    values: Vec::with_capacity(count),
+                })
+                .collect(),
+        };
+
+        Self {
+            color_ids,
+            is_color_float,
+            normal_ids,
+            scalar_ids,
+            attributes,
+        }
+    }
+
+    /// Adds the values of a single element
+    pub fn push(&mut self, values: &[f64]) {
+        if let (Some([r, g, b]), Some(colors)) = (self.color_ids, &mut self.attributes.colors) {
+            let is_float = self.is_color_float;
+            colors.push(Rgb::new(
+                color_component(values[r], is_float),
+                color_component(values[g], is_float),
+                color_component(values[b], is_float),
+            ));
+        }
+
+        if let (Some([x, y, z]), Some(normals)) = (self.normal_ids, &mut self.attributes.normals) {
+            normals.push(Norm3D::new(Point3D::new(values[x], values[y], values[z])).ok());
+        }
+
+        for (id, column) in self
+            .scalar_ids
+            .iter()
+            .zip(self.attributes.scalars.iter_mut())
+        {
+            column.values.push(values[id]);
+        }
+    }
+
+    pub fn into_attributes(self) -> PlyAttributes {
+        self.attributes
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Positions of the x, y and z properties
+pub fn position_ids(properties: &[Property]) -> IOResult<[usize; 3]> {
+    find_scalars(properties, ["x", "y", "z"]).ok_or(IOError::InvalidPlyVertexDimensionDefinition)
+}
+
+//------------------------------------------------------------------------------
+
+fn find_scalars(properties: &[Property], names: [&str; 3]) -> Option<[usize; 3]> {
+    let find = |name: &str| {
+        properties
+            .iter()
+            .position(|p| p.name == name && matches!(p.t, PropertyType::Scalar(_)))
+    };
+
+    Some([find(names[0])?, find(names[1])?, find(names[2])?])
+}
+
+fn is_index_list(property: &Property) -> bool {
+    property.name == "vertex_indices" || property.name == "vertex_index"
+}
+
+/// Colors stored as floating point are within [0, 1]
+fn color_component(x: f64, is_float: bool) -> u8 {
+    if is_float {
+        (x.clamp(0.0, 1.0) * 255.0).round() as u8
+    } else {
+        x.clamp(0.0, 255.0) as u8
+    }
+}
+
+#[inline(always)]
+fn read_type<BR, R>(read: &mut R, t: Type) -> IOResult<f64>
+where
+    BR: IsByteReader,
+    R: BufRead,
+{
+    Ok(match t {
+        Type::Char => BR::read_i8(read)? as f64,
+        Type::UChar => BR::read_u8(read)? as f64,
+        Type::Short => BR::read_i16(read)? as f64,
+        Type::UShort => BR::read_u16(read)? as f64,
+        Type::Int => BR::read_i32(read)? as f64,
+        Type::UInt => BR::read_u32(read)? as f64,
+        Type::Float => BR::read_f32(read)? as f64,
+        Type::Double => BR::read_f64(read)?,
+    })
+}
+
+fn read_binary<BR, R>(
+    read: &mut R,
+    properties: &[Property],
+    values: &mut Vec<f64>,
+    indices: &mut Vec<usize>,
+) -> IOResult<()>
+where
+    BR: IsByteReader,
+    R: BufRead,
+{
+    for property in properties {
+        match property.t {
+            PropertyType::Scalar(t) => values.push(read_type::<BR, _>(read, t)?),
+            PropertyType::List(t_count, t_item) => {
+                let n = read_face_type::<BR, _>(read, t_count)?;
+                match FaceType::try_from(t_item) {
+                    Ok(t_index) if is_index_list(property) => {
+                        for _ in 0..n {
+                            indices.push(read_face_type::<BR, _>(read, t_index)?);
+                        }
+                    }
+                    _ => skip_bytes(read, n * t_item.size_bytes())?,
+                }
+                values.push(f64::NAN);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn read_ascii(
+    line: &[u8],
+    properties: &[Property],
+    values: &mut Vec<f64>,
+    indices: &mut Vec<usize>,
+) -> Option<()> {
+    let mut words = to_words_skip_empty(line);
+
+    for property in properties {
+        match property.t {
+            PropertyType::Scalar(_) => values.push(from_ascii(words.next()?)?),
+            PropertyType::List(_, _) => {
+                let n: usize = from_ascii(words.next()?)?;
+                if is_index_list(property) {
+                    for _ in 0..n {
+                        indices.push(from_ascii(words.next()?)?);
+                    }
+                } else {
+                    skip_n(&mut words, n);
+                }
+                values.push(f64::NAN);
+            }
+        }
+    }
+
+    Some(())
+}
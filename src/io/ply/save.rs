@@ -24,7 +24,7 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 use crate::*;
 
-use super::super::types::*;
+use super::{super::types::*, types::*};
 
 use std::io::Write;
 
@@ -321,3 +321,574 @@ where
 
     Ok(())
 }
+
+//------------------------------------------------------------------------------
+
+/// Saves an IsRandomAccessible<Is3D> in the ASCII .ply file format with optional colors, normals and scalar properties
+pub fn save_ply_points_ascii<RA, P, W>(
+    write: &mut W,
+    points: &RA,
+    colors: Option<&[Rgb]>,
+    normals: Option<&[Norm3D]>,
+    scalars: &[PlyScalarColumn],
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P>,
+    P: Is3D,
+    W: Write,
+{
+    let n = points.len();
+    check_point_attributes(n, colors, normals, scalars)?;
+
+    let header = points_header("ascii", "float", n, colors, normals, scalars);
+    write.write_all(header.as_bytes())?;
+
+    for i in 0..n {
+        let p = &points[i];
+        let mut line = p.to_str();
+        if let Some(colors) = colors {
+            let c = &colors[i];
+            line += &format!(" {} {} {}", c.r, c.g, c.b);
+        }
+        if let Some(normals) = normals {
+            let n = &normals[i];
+            line += &format!(" {} {} {}", n.x(), n.y(), n.z());
+        }
+        for column in scalars {
+            line += &format!(" {}", column.values[i]);
+        }
+        line += "\n";
+        write.write_all(line.as_bytes())?;
+    }
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+/// Saves an IsRandomAccessible<Is3D> in the binary .ply file format with optional colors, normals and scalar properties
+pub fn save_ply_points_binary<RA, P, W>(
+    write: &mut W,
+    points: &RA,
+    precision: &Precision,
+    colors: Option<&[Rgb]>,
+    normals: Option<&[Norm3D]>,
+    scalars: &[PlyScalarColumn],
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P>,
+    P: Is3D,
+    W: Write,
+{
+    let n = points.len();
+    check_point_attributes(n, colors, normals, scalars)?;
+
+    let t = match precision {
+        Precision::P32 => "float",
+        Precision::P64 => "double",
+    };
+
+    let header = points_header("binary_big_endian", t, n, colors, normals, scalars);
+    write.write_all(header.as_bytes())?;
+
+    let write_value = |write: &mut W, x: f64| -> IOResult<()> {
+        match precision {
+            Precision::P32 => write.write_all(&(x as f32).to_be_bytes())?,
+            Precision::P64 => write.write_all(&x.to_be_bytes())?,
+        }
+        Ok(())
+    };
+
+    for i in 0..n {
+        let p = &points[i];
+        write_value(write, p.x())?;
+        write_value(write, p.y())?;
+        write_value(write, p.z())?;
+        if let Some(colors) = colors {
+            let c = &colors[i];
+            write.write_all(&[c.r, c.g, c.b])?;
+        }
+        if let Some(normals) = normals {
+            let n = &normals[i];
+            write_value(write, n.x())?;
+            write_value(write, n.y())?;
+            write_value(write, n.z())?;
+        }
+        for column in scalars {
+            write_value(write, column.values[i])?;
+        }
+    }
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+/// Saves an IsMesh3D in the ASCII .ply file format, together with the Attributes of its vertices and faces
+/// Channels named "color" and "normal" are written as red, green, blue and nx, ny, nz, other color / normal channels get their name as prefix
+pub fn save_ply_ascii_attributed<M, P, W>(write: &mut W, mesh: &M) -> IOResult<()>
+where
+    M: IsMesh<P, Face3> + HasVertexAttributes + HasFaceAttributes,
+    P: IsBuildable3D,
+    W: Write,
+{
+    save_mesh_attributed(write, mesh, None)
+}
+
+/// Saves an IsMesh3D in the binary .ply file format, together with the Attributes of its vertices and faces
+/// Channels named "color" and "normal" are written as red, green, blue and nx, ny, nz, other color / normal channels get their name as prefix
+pub fn save_ply_binary_attributed<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    precision: &Precision,
+) -> IOResult<()>
+where
+    M: IsMesh<P, Face3> + HasVertexAttributes + HasFaceAttributes,
+    P: IsBuildable3D,
+    W: Write,
+{
+    save_mesh_attributed(write, mesh, Some(precision))
+}
+
+/// Saves an IsPolygonMesh in the ASCII .ply file format, keeping polygonal faces
+pub fn save_ply_polygon_ascii<M, P, W>(write: &mut W, mesh: &M) -> IOResult<()>
+where
+    M: IsPolygonMesh<P>,
+    P: Is3D,
+    W: Write,
+{
+    let empty = Attributes::default();
+    save_polygon_mesh_attributed(write, mesh, &empty, &empty, None)
+}
+
+/// Saves an IsPolygonMesh in the binary .ply file format, keeping polygonal faces
+pub fn save_ply_polygon_binary<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    precision: &Precision,
+) -> IOResult<()>
+where
+    M: IsPolygonMesh<P>,
+    P: Is3D,
+    W: Write,
+{
+    let empty = Attributes::default();
+    save_polygon_mesh_attributed(write, mesh, &empty, &empty, Some(precision))
+}
+
+/// Saves an IsPolygonMesh in the ASCII .ply file format, keeping polygonal faces, together with the Attributes of its vertices and faces
+/// Channels named "color" and "normal" are written as red, green, blue and nx, ny, nz, other color / normal channels get their name as prefix
+pub fn save_ply_polygon_ascii_attributed<M, P, W>(write: &mut W, mesh: &M) -> IOResult<()>
+where
+    M: IsPolygonMesh<P> + HasVertexAttributes + HasFaceAttributes,
+    P: Is3D,
+    W: Write,
+{
+    save_polygon_mesh_attributed(
+        write,
+        mesh,
+        mesh.vertex_attributes(),
+        mesh.face_attributes(),
+        None,
+    )
+}
+
+/// Saves an IsPolygonMesh in the binary .ply file format, keeping polygonal faces, together with the Attributes of its vertices and faces
+/// Channels named "color" and "normal" are written as red, green, blue and nx, ny, nz, other color / normal channels get their name as prefix
+pub fn save_ply_polygon_binary_attributed<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    precision: &Precision,
+) -> IOResult<()>
+where
+    M: IsPolygonMesh<P> + HasVertexAttributes + HasFaceAttributes,
+    P: Is3D,
+    W: Write,
+{
+    save_polygon_mesh_attributed(
+        write,
+        mesh,
+        mesh.vertex_attributes(),
+        mesh.face_attributes(),
+        Some(precision),
+    )
+}
+
+/// Saves an IsRandomAccessible<Is3D> in the ASCII .ply file format, together with the Attributes of its points
+/// Channels named "color" and "normal" are written as red, green, blue and nx, ny, nz, other color / normal channels get their name as prefix
+pub fn save_ply_points_ascii_attributed<RA, P, W>(write: &mut W, points: &RA) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P> + HasVertexAttributes,
+    P: Is3D,
+    W: Write,
+{
+    save_points_attributed(write, points, None)
+}
+
+/// Saves an IsRandomAccessible<Is3D> in the binary .ply file format, together with the Attributes of its points
+/// Channels named "color" and "normal" are written as red, green, blue and nx, ny, nz, other color / normal channels get their name as prefix
+pub fn save_ply_points_binary_attributed<RA, P, W>(
+    write: &mut W,
+    points: &RA,
+    precision: &Precision,
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P> + HasVertexAttributes,
+    P: Is3D,
+    W: Write,
+{
+    save_points_attributed(write, points, Some(precision))
+}
+
+//------------------------------------------------------------------------------
+
+fn check_point_attributes(
+    n: usize,
+    colors: Option<&[Rgb]>,
+    normals: Option<&[Norm3D]>,
+    scalars: &[PlyScalarColumn],
+) -> IOResult<()> {
+    if colors.map(|x| x.len() != n).unwrap_or(false) {
+        return Err(IOError::ColorArrayLength);
+    }
+    if normals.map(|x| x.len() != n).unwrap_or(false) {
+        return Err(IOError::NormalArrayLength);
+    }
+    if scalars.iter().any(|x| x.values.len() != n) {
+        return Err(IOError::ScalarArrayLength);
+    }
+    // Names are written as words of the header lines
+    if let Some(x) = scalars
+        .iter()
+        .find(|x| x.name.is_empty() || x.name.contains(char::is_whitespace))
+    {
+        return Err(IOError::InvalidPlyPropertyName(x.name.clone()));
+    }
+
+    Ok(())
+}
+
+fn points_header(
+    format: &str,
+    t: &str,
+    n: usize,
+    colors: Option<&[Rgb]>,
+    normals: Option<&[Norm3D]>,
+    scalars: &[PlyScalarColumn],
+) -> String {
+    let mut header = "ply\n".to_string()
+        + "format "
+        + format
+        + " 1.0\n"
+        + "comment Created by rust-3d\n"
+        + "element vertex "
+        + &n.to_string()
+        + "\n";
+
+    for name in ["x", "y", "z"].iter() {
+        header += &format!("property {} {}\n", t, name);
+    }
+    if colors.is_some() {
+        header += "property uchar red\n";
+        header += "property uchar green\n";
+        header += "property uchar blue\n";
+    }
+    if normals.is_some() {
+        for name in ["nx", "ny", "nz"].iter() {
+            header += &format!("property {} {}\n", t, name);
+        }
+    }
+    for column in scalars {
+        header += &format!("property {} {}\n", t, column.name);
+    }
+
+    header + "end_header\n"
+}
+
+//------------------------------------------------------------------------------
+
+/// A single value of an element, written as float / double, uchar or uint
+enum Value {
+    Float(f64),
+    UChar(u8),
+    UInt(u32),
+}
+
+/// Writes an element to ASCII if precision is None, otherwise as binary big endian
+fn write_values<W>(write: &mut W, values: &[Value], precision: Option<&Precision>) -> IOResult<()>
+where
+    W: Write,
+{
+    match precision {
+        None => {
+            let words: Vec<String> = values
+                .iter()
+                .map(|x| match x {
+                    Value::Float(x) => x.to_string(),
+                    Value::UChar(x) => x.to_string(),
+                    Value::UInt(x) => x.to_string(),
+                })
+                .collect();
+            write.write_all((words.join(" ") + "\n").as_bytes())?;
+        }
+        Some(precision) => {
+            for x in values {
+                match (x, precision) {
+                    (Value::Float(x), Precision::P32) => {
+                        write.write_all(&(*x as f32).to_be_bytes())?
+                    }
+                    (Value::Float(x), Precision::P64) => write.write_all(&x.to_be_bytes())?,
+                    (Value::UChar(x), _) => write.write_all(&[*x])?,
+                    (Value::UInt(x), _) => write.write_all(&x.to_be_bytes())?,
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Attributes without channels are valid for any number of elements
+fn is_attribute_length_valid(attributes: &Attributes, n: usize) -> bool {
+    !attributes.has_channels() || attributes.len() == n
+}
+
+fn push_attribute_values(values: &mut Vec<Value>, attributes: &Attributes, i: usize) {
+    for channel in attributes.channels() {
+        match &channel.values {
+            AttributeValues::Rgb(x) => {
+                let c = &x[i];
+                values.push(Value::UChar(c.r));
+                values.push(Value::UChar(c.g));
+                values.push(Value::UChar(c.b));
+            }
+            AttributeValues::Norm3D(x) => {
+                let n = &x[i];
+                values.push(Value::Float(n.x()));
+                values.push(Value::Float(n.y()));
+                values.push(Value::Float(n.z()));
+            }
+            AttributeValues::Scalar(x) => values.push(Value::Float(x[i])),
+            AttributeValues::Label(x) => values.push(Value::UInt(x[i])),
+        }
+    }
+}
+
+fn attribute_properties(attributes: &Attributes, t: &str) -> String {
+    let prefixed = |name: &str, default: &str, suffix: &str| {
+        if name == default {
+            suffix.to_string()
+        } else {
+            format!("{}_{}", name, suffix)
+        }
+    };
+
+    let mut result = String::new();
+    for channel in attributes.channels() {
+        let name = &channel.name;
+        match &channel.values {
+            AttributeValues::Rgb(_) => {
+                for c in ["red", "green", "blue"].iter() {
+                    result += &format!("property uchar {}\n", prefixed(name, "color", c));
+                }
+            }
+            AttributeValues::Norm3D(_) => {
+                for c in ["nx", "ny", "nz"].iter() {
+                    result += &format!("property {} {}\n", t, prefixed(name, "normal", c));
+                }
+            }
+            AttributeValues::Scalar(_) => result += &format!("property {} {}\n", t, name),
+            AttributeValues::Label(_) => result += &format!("property uint {}\n", name),
+        }
+    }
+    result
+}
+
+fn attributed_header(
+    precision: Option<&Precision>,
+    n_vertices: usize,
+    vertex_attributes: &Attributes,
+    faces: Option<(usize, &Attributes)>,
+) -> String {
+    let (format, t) = match precision {
+        None => ("ascii", "float"),
+        Some(Precision::P32) => ("binary_big_endian", "float"),
+        Some(Precision::P64) => ("binary_big_endian", "double"),
+    };
+
+    let mut header = "ply\n".to_string()
+        + "format "
+        + format
+        + " 1.0\n"
+        + "comment Created by rust-3d\n"
+        + "element vertex "
+        + &n_vertices.to_string()
+        + "\n";
+
+    for name in ["x", "y", "z"].iter() {
+        header += &format!("property {} {}\n", t, name);
+    }
+    header += &attribute_properties(vertex_attributes, t);
+
+    if let Some((n_faces, face_attributes)) = faces {
+        header += &format!("element face {}\n", n_faces);
+        header += "property list uchar uint vertex_indices\n";
+        header += &attribute_properties(face_attributes, t);
+    }
+
+    header + "end_header\n"
+}
+
+fn save_mesh_attributed<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    precision: Option<&Precision>,
+) -> IOResult<()>
+where
+    M: IsMesh<P, Face3> + HasVertexAttributes + HasFaceAttributes,
+    P: IsBuildable3D,
+    W: Write,
+{
+    let n_vertices = mesh.num_vertices();
+    let n_faces = mesh.num_faces();
+    let vertex_attributes = mesh.vertex_attributes();
+    let face_attributes = mesh.face_attributes();
+
+    if !is_attribute_length_valid(vertex_attributes, n_vertices)
+        || !is_attribute_length_valid(face_attributes, n_faces)
+    {
+        return Err(IOError::AttributeLength);
+    }
+
+    let header = attributed_header(
+        precision,
+        n_vertices,
+        vertex_attributes,
+        Some((n_faces, face_attributes)),
+    );
+    write.write_all(header.as_bytes())?;
+
+    let mut values = Vec::new();
+
+    for i in 0..n_vertices {
+        let p = mesh.vertex(VId(i)).unwrap(); // safe since iterating n_vertices
+        values.clear();
+        values.push(Value::Float(p.x()));
+        values.push(Value::Float(p.y()));
+        values.push(Value::Float(p.z()));
+        push_attribute_values(&mut values, vertex_attributes, i);
+        write_values(write, &values, precision)?;
+    }
+
+    for i in 0..n_faces {
+        let face = mesh.face_vertex_ids(FId(i)).unwrap(); // safe since iterating n_faces
+        values.clear();
+        values.push(Value::UChar(3));
+        values.push(Value::UInt(face.a.0 as u32));
+        values.push(Value::UInt(face.b.0 as u32));
+        values.push(Value::UInt(face.c.0 as u32));
+        push_attribute_values(&mut values, face_attributes, i);
+        write_values(write, &values, precision)?;
+    }
+
+    Ok(())
+}
+
+fn save_polygon_mesh_attributed<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    vertex_attributes: &Attributes,
+    face_attributes: &Attributes,
+    precision: Option<&Precision>,
+) -> IOResult<()>
+where
+    M: IsPolygonMesh<P>,
+    P: Is3D,
+    W: Write,
+{
+    let n_vertices = mesh.num_vertices();
+    let n_faces = mesh.num_faces();
+
+    if !is_attribute_length_valid(vertex_attributes, n_vertices)
+        || !is_attribute_length_valid(face_attributes, n_faces)
+    {
+        return Err(IOError::AttributeLength);
+    }
+
+    let header = attributed_header(
+        precision,
+        n_vertices,
+        vertex_attributes,
+        Some((n_faces, face_attributes)),
+    );
+    write.write_all(header.as_bytes())?;
+
+    let mut values = Vec::new();
+
+    for i in 0..n_vertices {
+        let p = mesh.vertex(VId(i)).unwrap(); // safe since iterating n_vertices
+        values.clear();
+        values.push(Value::Float(p.x()));
+        values.push(Value::Float(p.y()));
+        values.push(Value::Float(p.z()));
+        push_attribute_values(&mut values, vertex_attributes, i);
+        write_values(write, &values, precision)?;
+    }
+
+    let mut vids = Vec::new();
+
+    for i in 0..n_faces {
+        vids.clear();
+        mesh.face_vertex_ids(FId(i), &mut vids).unwrap(); // safe since iterating n_faces
+
+        // the number of vertices is written as uchar
+        if vids.len() > u8::MAX as usize {
+            return Err(IOError::FaceVertexCount);
+        }
+
+        values.clear();
+        values.push(Value::UChar(vids.len() as u8));
+        for vid in &vids {
+            values.push(Value::UInt(vid.0 as u32));
+        }
+        push_attribute_values(&mut values, face_attributes, i);
+        write_values(write, &values, precision)?;
+    }
+
+    Ok(())
+}
+
+fn save_points_attributed<RA, P, W>(
+    write: &mut W,
+    points: &RA,
+    precision: Option<&Precision>,
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P> + HasVertexAttributes,
+    P: Is3D,
+    W: Write,
+{
+    let n = points.len();
+    let attributes = points.vertex_attributes();
+
+    if !is_attribute_length_valid(attributes, n) {
+        return Err(IOError::AttributeLength);
+    }
+
+    let header = attributed_header(precision, n, attributes, None);
+    write.write_all(header.as_bytes())?;
+
+    let mut values = Vec::new();
+
+    for i in 0..n {
+        let p = &points[i];
+        values.clear();
+        values.push(Value::Float(p.x()));
+        values.push(Value::Float(p.y()));
+        values.push(Value::Float(p.z()));
+        push_attribute_values(&mut values, attributes, i);
+        write_values(write, &values, precision)?;
+    }
+
+    Ok(())
+}
@@ -26,6 +26,8 @@ use core::convert::TryFrom;
 
 use std::fmt;
 
+use crate::*;
+
 use super::super::types::*;
 
 //------------------------------------------------------------------------------
@@ -91,6 +93,70 @@ pub enum MeshOrPoints {
 
 //------------------------------------------------------------------------------
 
+/// The values of a single scalar property of a .ply file
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlyScalarColumn {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+/// The properties of all vertices or faces of a .ply file, one entry per element
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlyAttributes {
+    /// Built from the red, green and blue properties
+    pub colors: Option<Vec<Rgb>>,
+    /// Built from the nx, ny and nz properties, None for normals of zero length
+    pub normals: Option<Vec<Option<Norm3D>>>,
+    /// All other scalar properties in order of appearance
+    pub scalars: Vec<PlyScalarColumn>,
+}
+
+impl PlyAttributes {
+    /// Returns the values of the scalar property with the given name
+    pub fn scalar(&self, name: &str) -> Option<&[f64]> {
+        self.scalars
+            .iter()
+            .find(|x| x.name == name)
+            .map(|x| x.values.as_slice())
+    }
+    /// Converts into Attributes of n elements, with the channels "color", "normal" and one per scalar property
+    /// Normals of zero length are replaced by the default normal
+    pub fn into_attributes(self, n: usize) -> IOResult<Attributes> {
+        let mut attributes = Attributes::new(n);
+
+        if let Some(colors) = self.colors {
+            attributes
+                .insert("color", colors)
+                .or(Err(IOError::ColorArrayLength))?;
+        }
+        if let Some(normals) = self.normals {
+            let normals = normals
+                .into_iter()
+                .map(|x| x.unwrap_or_else(Norm3D::default_attribute))
+                .collect::<Vec<_>>();
+            attributes
+                .insert("normal", normals)
+                .or(Err(IOError::NormalArrayLength))?;
+        }
+        for column in self.scalars {
+            attributes
+                .insert(&column.name, column.values)
+                .or(Err(IOError::ScalarArrayLength))?;
+        }
+
+        Ok(attributes)
+    }
+}
+
+/// The properties of the vertices and faces of a .ply mesh
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlyMeshAttributes {
+    pub vertex: PlyAttributes,
+    pub face: PlyAttributes,
+}
+
+//------------------------------------------------------------------------------
+
 #[derive(Copy, Clone, Debug)]
 pub enum Xyz {
     X,
@@ -226,16 +292,44 @@ pub enum HeaderReadState {
 
 //------------------------------------------------------------------------------
 
+#[derive(Debug, Clone)]
+pub enum PropertyType {
+    Scalar(Type),
+    /// Type of the count and type of the elements
+    List(FaceType, Type),
+}
+
+#[derive(Debug, Clone)]
+pub struct Property {
+    pub name: String,
+    pub t: PropertyType,
+}
+
+impl Property {
+    pub fn new(name: &[u8], t: PropertyType) -> Self {
+        Self {
+            name: String::from_utf8_lossy(name).to_string(),
+            t,
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
 #[derive(Debug, Clone)]
 pub struct VertexData {
     pub count: usize,
     pub format: VertexFormat,
+    /// All properties in order of appearance
+    pub properties: Vec<Property>,
 }
 
 #[derive(Debug, Clone)]
 pub struct FaceData {
     pub count: usize,
     pub format: FaceFormat,
+    /// All properties in order of appearance
+    pub properties: Vec<Property>,
 }
 
 //------------------------------------------------------------------------------
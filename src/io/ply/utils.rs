@@ -34,17 +34,17 @@ use std::io::Read;
 //------------------------------------------------------------------------------
 
 #[inline(always)]
-pub fn collect_index_line(line: &[u8]) -> Option<[usize; 3]> {
+pub fn collect_index_line(line: &[u8], ids: &mut Vec<usize>) -> Option<()> {
+    ids.clear();
+
     let mut words = to_words_skip_empty(line);
-    if words.next()? != b"3" {
-        return None;
-    }
+    let n: usize = from_ascii(words.next()?)?;
 
-    let a = from_ascii(words.next()?)?;
-    let b = from_ascii(words.next()?)?;
-    let c = from_ascii(words.next()?)?;
+    for _ in 0..n {
+        ids.push(from_ascii(words.next()?)?);
+    }
 
-    Some([a, b, c])
+    Some(())
 }
 
 //------------------------------------------------------------------------------
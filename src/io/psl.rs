@@ -62,7 +62,8 @@ where
     }
     #[inline(always)]
     fn initialize(&mut self) -> IOResult<()> {
-        self.n_passes_left = fetch_header_return_n_passes(&mut self.read)?;
+        self.n_passes_left = fetch_header_return_n_passes(&mut self.read)
+            .map_err(|e| e.context(IOElement::Header, IOLocation::Byte(0)))?;
         Ok(())
     }
     #[inline(always)]
@@ -102,7 +102,21 @@ where
         loop {
             if chunk.is_full() {
                 return Some(Ok(chunk));
-            } else if let Ok(line) = fetch_line(&mut self.read, &mut self.line_buffer) {
+            } else {
+                let line = match fetch_line_or_none(&mut self.read, &mut self.line_buffer) {
+                    Ok(Some(line)) => line,
+                    Ok(None) => {
+                        if chunk.has_data() {
+                            return Some(Ok(chunk));
+                        }
+                        return None;
+                    }
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e.into()));
+                    }
+                };
+
                 self.i_line += 1;
 
                 if line.is_empty() {
@@ -160,11 +174,6 @@ where
                         }
                     }
                 }
-            } else {
-                if chunk.has_data() {
-                    return Some(Ok(chunk));
-                }
-                return None;
             }
         }
     }
@@ -21,6 +21,8 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 */
 
 //! Module for IO operations of the ptx file format
+//! Files may contain several concatenated scans, each with its own header
+//! Points are transformed into world coordinates by the registration matrix of their scan
 
 use crate::*;
 
@@ -30,8 +32,76 @@ use super::{types::*, utils::*};
 
 //------------------------------------------------------------------------------
 
+/// All attributes of a point of a .ptx file, except for its position
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PtxPoint {
+    /// Index of the scan within the file
+    pub scan: usize,
+    /// Position within the grid of the scan
+    pub row: usize,
+    /// Position within the grid of the scan
+    pub column: usize,
+    pub intensity: Option<f64>,
+    pub color: Option<Rgb>,
+}
+
+//------------------------------------------------------------------------------
+
 /// Iterator to incrementally load a .ptx file
 pub struct PtxIterator<P, R, const CHUNK_SIZE: usize>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: BufRead,
+{
+    inner: PtxPointIterator<P, R, CHUNK_SIZE>,
+}
+
+impl<P, R, const CHUNK_SIZE: usize> PtxIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: BufRead,
+{
+    pub fn new(read: R) -> Self {
+        Self {
+            inner: PtxPointIterator::new(read),
+        }
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> Iterator for PtxIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: BufRead,
+{
+    type Item = IOResult<StackVec<DataReserve<P>, CHUNK_SIZE>>;
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|chunk| {
+            let mut result = StackVec::default();
+            for x in chunk? {
+                let x = match x {
+                    DataReserve::Data((p, _)) => DataReserve::Data(p),
+                    DataReserve::Reserve(x) => DataReserve::Reserve(x),
+                    DataReserve::ReserveExact(x) => DataReserve::ReserveExact(x),
+                };
+                result.push(x).unwrap(); // unwrap safe since both have the same size
+            }
+            Ok(result)
+        })
+    }
+}
+
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for PtxIterator<P, R, CHUNK_SIZE>
+where
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: BufRead,
+{
+}
+
+//------------------------------------------------------------------------------
+
+/// Iterator to incrementally load a .ptx file together with the attributes of each point
+pub struct PtxPointIterator<P, R, const CHUNK_SIZE: usize>
 where
     P: IsBuildable3D + IsMatrix4Transformable + Default,
     R: BufRead,
@@ -40,13 +110,17 @@ where
     is_done: bool,
     i_line: usize,
     line_buffer: Vec<u8>,
+    /// Index of the current scan, starting at 1 once the first header was read
+    n_scans: usize,
+    rows: usize,
+    n_points_fetched: usize,
     n_points_to_fetch: usize,
     must_transform: bool,
     transformation: Matrix4,
     phantom_p: PhantomData<P>,
 }
 
-impl<P, R, const CHUNK_SIZE: usize> PtxIterator<P, R, CHUNK_SIZE>
+impl<P, R, const CHUNK_SIZE: usize> PtxPointIterator<P, R, CHUNK_SIZE>
 where
     P: IsBuildable3D + IsMatrix4Transformable + Default,
     R: BufRead,
@@ -57,6 +131,9 @@ where
             is_done: false,
             i_line: 0,
             line_buffer: Vec::new(),
+            n_scans: 0,
+            rows: 0,
+            n_points_fetched: 0,
             n_points_to_fetch: 0,
             must_transform: false,
             transformation: Matrix4::identity(),
@@ -64,13 +141,14 @@ where
         }
     }
 
+    /// Fetches a single point, its position within the scan is set by the caller
     #[inline(always)]
     fn fetch_one(
         i_line: usize,
         line: &[u8],
         must_transform: bool,
         transformation: &Matrix4,
-    ) -> IOResult<P> {
+    ) -> IOResult<(P, PtxPoint)> {
         let mut words = to_words_skip_empty(line);
 
         let x = words
@@ -92,7 +170,32 @@ where
             p.transform(transformation)
         }
 
-        Ok(p)
+        let intensity = match words.next() {
+            None => None,
+            Some(w) => Some(from_ascii(w).ok_or(IOError::Vertex(Some(i_line)))?),
+        };
+
+        let color = match words.next() {
+            None => None,
+            Some(r) => {
+                let component =
+                    |w: Option<&[u8]>| w.and_then(from_ascii).ok_or(IOError::Vertex(Some(i_line)));
+                Some(Rgb::new(
+                    component(Some(r))?,
+                    component(words.next())?,
+                    component(words.next())?,
+                ))
+            }
+        };
+
+        Ok((
+            p,
+            PtxPoint {
+                intensity,
+                color,
+                ..PtxPoint::default()
+            },
+        ))
     }
 
     #[inline(always)]
@@ -134,6 +237,7 @@ where
         self.i_line += 1;
         let [m41, m42, m43, m44] = read_matrix_row(line).ok_or(IOError::Matrix(self.i_line))?;
 
+        // .ptx stores the matrix for row vectors, with the translation in the last row
         self.transformation = Matrix4 {
             data: [
                 [m11, m12, m13, m14],
@@ -141,22 +245,26 @@ where
                 [m31, m32, m33, m34],
                 [m41, m42, m43, m44],
             ],
-        };
+        }
+        .transposed();
 
         self.must_transform = self.transformation != Matrix4::identity();
 
+        self.n_scans += 1;
+        self.rows = rows;
+        self.n_points_fetched = 0;
         self.n_points_to_fetch = rows * columns;
 
         Ok(())
     }
 }
 
-impl<P, R, const CHUNK_SIZE: usize> Iterator for PtxIterator<P, R, CHUNK_SIZE>
+impl<P, R, const CHUNK_SIZE: usize> Iterator for PtxPointIterator<P, R, CHUNK_SIZE>
 where
     P: IsBuildable3D + IsMatrix4Transformable + Default,
     R: BufRead,
 {
-    type Item = IOResult<StackVec<DataReserve<P>, CHUNK_SIZE>>;
+    type Item = IOResult<StackVec<DataReserve<(P, PtxPoint)>, CHUNK_SIZE>>;
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
         if self.is_done {
@@ -169,20 +277,26 @@ where
             if chunk.is_full() {
                 return Some(Ok(chunk));
             } else if self.n_points_to_fetch == 0 {
-                let first_line = fetch_line(&mut self.read, &mut self.line_buffer);
-                if first_line.is_err() {
-                    self.is_done = true;
-                    if chunk.has_data() {
-                        return Some(Ok(chunk));
+                let first_line = match fetch_line_or_none(&mut self.read, &mut self.line_buffer) {
+                    Ok(Some(line)) => line,
+                    Ok(None) => {
+                        self.is_done = true;
+                        if chunk.has_data() {
+                            return Some(Ok(chunk));
+                        }
+                        return None;
                     }
-                    return None;
-                }
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e.into()));
+                    }
+                };
 
                 self.i_line += 1;
-                // unwrap safe, is_err() checked above
-                match from_ascii(first_line.as_ref().unwrap())
+                match from_ascii(first_line)
                     .ok_or(IOError::Columns(self.i_line))
                     .and_then(|columns| self.fetch_header(columns))
+                    .map_err(|e| e.context(IOElement::Header, IOLocation::Line(self.i_line)))
                 {
                     Ok(()) => {
                         chunk
@@ -194,7 +308,7 @@ where
                         return Some(Err(e));
                     }
                 }
-            } else if self.n_points_to_fetch > 0 {
+            } else {
                 self.n_points_to_fetch -= 1;
                 match fetch_line(&mut self.read, &mut self.line_buffer) {
                     Ok(line) => {
@@ -209,7 +323,16 @@ where
                                 self.is_done = true;
                                 return Some(Err(e));
                             }
-                            Ok(x) => chunk.push(DataReserve::Data(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
+                            Ok((p, mut ptx_point)) => {
+                                // Points are stored column by column
+                                let index = self.n_points_fetched;
+                                self.n_points_fetched += 1;
+                                ptx_point.scan = self.n_scans - 1;
+                                ptx_point.row = index % self.rows;
+                                ptx_point.column = index / self.rows;
+                                chunk.push(DataReserve::Data((p, ptx_point))).unwrap()
+                                // unwrap safe since we only call this if chunk.has_space()
+                            }
                         }
                     }
                     Err(e) => {
@@ -217,18 +340,12 @@ where
                         return Some(Err(e.into()));
                     }
                 }
-            } else {
-                self.is_done = true;
-                if chunk.has_data() {
-                    return Some(Ok(chunk));
-                }
-                return None;
             }
         }
     }
 }
 
-impl<P, R, const CHUNK_SIZE: usize> FusedIterator for PtxIterator<P, R, CHUNK_SIZE>
+impl<P, R, const CHUNK_SIZE: usize> FusedIterator for PtxPointIterator<P, R, CHUNK_SIZE>
 where
     P: IsBuildable3D + IsMatrix4Transformable + Default,
     R: BufRead,
@@ -259,6 +376,42 @@ where
     Ok(())
 }
 
+/// Loads points from .ptx file into IsPushable<Is3D> and their attributes into IsPushable<PtxPoint>
+pub fn load_ptx_points<IP, P, R, IPP, const CHUNK_SIZE: usize>(
+    read: R,
+    ip: &mut IP,
+    ptx_points: &mut IPP,
+) -> IOResult<()>
+where
+    IP: IsPushable<P>,
+    P: IsBuildable3D + IsMatrix4Transformable + Default,
+    R: BufRead,
+    IPP: IsPushable<PtxPoint>,
+{
+    let iterator = PtxPointIterator::<_, _, CHUNK_SIZE>::new(read);
+
+    for chunk in iterator {
+        for x in chunk? {
+            match x {
+                DataReserve::Data((p, ptx_point)) => {
+                    ip.push(p);
+                    ptx_points.push(ptx_point);
+                }
+                DataReserve::Reserve(x) => {
+                    ip.reserve(x);
+                    ptx_points.reserve(x);
+                }
+                DataReserve::ReserveExact(x) => {
+                    ip.reserve_exact(x);
+                    ptx_points.reserve_exact(x);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
 //------------------------------------------------------------------------------
 
 #[inline(always)]
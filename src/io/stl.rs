@@ -70,7 +70,6 @@ where
 
 //------------------------------------------------------------------------------
 
-//@todo implement binary write
 /// Saves an IsMesh3D in the ASCII .stl file format
 pub fn save_stl_ascii<M, P, W>(write: &mut W, mesh: &M) -> IOResult<()>
 where
@@ -106,6 +105,56 @@ where
 
 //------------------------------------------------------------------------------
 
+/// Saves an IsMesh3D in the binary .stl file format
+/// The header is zero padded to 80 bytes, longer headers or headers starting with 'solid' are rejected,
+/// since the latter would be detected as ASCII by StlFormat::Auto
+/// The face normals are calculated via normal_of_face, attribute_byte_count is written for every face
+pub fn save_stl_binary<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    header: &[u8],
+    attribute_byte_count: u16,
+) -> IOResult<()>
+where
+    M: IsMesh3D<P>,
+    P: IsBuildable3D,
+    W: Write,
+{
+    if header.len() > 80 || header.starts_with(b"solid") {
+        return Err(IOError::Header);
+    }
+
+    let n_faces = mesh.num_faces();
+    if n_faces > MAX_TRIANGLES_BINARY as usize {
+        return Err(IOError::FaceCount(None));
+    }
+
+    let mut header_buffer = [0u8; 80];
+    header_buffer[..header.len()].copy_from_slice(header);
+    write.write_all(&header_buffer)?;
+    write.write_all(&(n_faces as u32).to_le_bytes())?;
+
+    // size for StlTriangle + u16 attribute byte count
+    let mut buffer = [0u8; 50];
+    buffer[48..50].copy_from_slice(&attribute_byte_count.to_le_bytes());
+
+    for i in 0..n_faces {
+        let [v1, v2, v3] = mesh.face_vertices(FId(i)).unwrap(); // safe since iterating n_faces
+        let n = normal_of_face(&v1, &v2, &v3);
+
+        write_f32_triplet_le(&mut buffer[0..12], &n);
+        write_f32_triplet_le(&mut buffer[12..24], &v1);
+        write_f32_triplet_le(&mut buffer[24..36], &v2);
+        write_f32_triplet_le(&mut buffer[36..48], &v3);
+
+        write.write_all(&buffer)?;
+    }
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
 /// Iterator to incrementally load a .stl file
 pub struct StlIterator<P, N, R, const CHUNK_SIZE: usize>
 where
@@ -123,7 +172,9 @@ where
     R: BufRead,
 {
     pub fn new(mut read: R, format: StlFormat) -> IOResult<Self> {
-        if is_ascii(&mut read, format)? {
+        if is_ascii(&mut read, format)
+            .map_err(|e| e.context(IOElement::Header, IOLocation::Byte(0)))?
+        {
             Ok(Self {
                 inner: BinaryOrAsciiIterator::Ascii(StlAsciiIterator::new(read)),
             })
@@ -232,19 +283,24 @@ where
                     let mut buffer = [0u8; 75];
                     if let Err(e) = self.read.read_exact(&mut buffer) {
                         self.is_done = true;
-                        return Some(Err(e.into()));
+                        return Some(Err(
+                            IOError::from(e).context(IOElement::Header, IOLocation::Byte(0))
+                        ));
                     }
                 }
 
                 match LittleReader::read_u32(&mut self.read) {
                     Err(e) => {
                         self.is_done = true;
-                        return Some(Err(e.into()));
+                        return Some(Err(
+                            IOError::from(e).context(IOElement::Header, IOLocation::Byte(80))
+                        ));
                     }
                     Ok(n_triangles) => {
                         if n_triangles > MAX_TRIANGLES_BINARY {
                             self.is_done = true;
-                            return Some(Err(IOError::FaceCount(None)));
+                            return Some(Err(IOError::FaceCount(None)
+                                .context(IOElement::Header, IOLocation::Byte(80))));
                         }
 
                         self.n_triangles = n_triangles as usize;
@@ -259,7 +315,9 @@ where
                 match read_stl_triangle(&mut self.read) {
                     Err(e) => {
                         self.is_done = true;
-                        return Some(Err(e));
+                        // 80 bytes header, 4 bytes triangle count and 50 bytes per triangle
+                        let offset = 84 + 50 * (self.current as u64 - 1);
+                        return Some(Err(e.context(IOElement::Face, IOLocation::Byte(offset))));
                     }
                     Ok(t) => {
                         let n = N::new(t.n[0] as f64, t.n[1] as f64, t.n[2] as f64);
@@ -369,7 +427,9 @@ where
                 }
                 Err(x) => {
                     self.is_done = true;
-                    return Some(Err(x));
+                    return Some(Err(
+                        x.context(IOElement::Face, IOLocation::Line(self.i_line))
+                    ));
                 }
             }
         }
@@ -606,6 +666,18 @@ where
 
 //------------------------------------------------------------------------------
 
+#[inline(always)]
+fn write_f32_triplet_le<P>(buffer: &mut [u8], p: &P)
+where
+    P: Is3D,
+{
+    buffer[0..4].copy_from_slice(&(p.x() as f32).to_le_bytes());
+    buffer[4..8].copy_from_slice(&(p.y() as f32).to_le_bytes());
+    buffer[8..12].copy_from_slice(&(p.z() as f32).to_le_bytes());
+}
+
+//------------------------------------------------------------------------------
+
 #[inline(always)]
 fn read_stl_facet<P, N, R>(
     read: &mut R,
@@ -0,0 +1,239 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for save functions of the svg file format
+
+use std::io::Write;
+
+use super::{super::types::*, types::*};
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// Saves styled 2D elements as svg, drawn in the given order
+/// The viewBox is the bounding box of all elements plus SvgConfig::margin
+/// Fails with IOError::NonFiniteCoordinates for infinite or NaN coordinates
+pub fn save_svg<W>(write: &mut W, elements: &[SvgElement], config: &SvgConfig) -> IOResult<()>
+where
+    W: Write,
+{
+    let bb = bounding_box(elements)?;
+    let margin = config.margin * bb.size_x().max(*bb.size_y());
+    let min = bb.min_p();
+    let width = *bb.size_x() + 2.0 * margin;
+    let height = *bb.size_y() + 2.0 * margin;
+
+    let writer = SvgWriter {
+        precision: config.precision,
+        // Mirrors y within the bounding box, keeping the viewBox the same
+        flip: if config.flip_y {
+            Some(2.0 * min.y + *bb.size_y())
+        } else {
+            None
+        },
+    };
+
+    write.write_all(b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n")?;
+    write.write_all(b"<!-- Created by rust-3d -->\n")?;
+
+    let size = match config.width {
+        Some(w) => format!(
+            "width=\"{}\" height=\"{}\" ",
+            w,
+            (w as f64 * height / width).round()
+        ),
+        None => String::new(),
+    };
+
+    write.write_all(
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" version=\"1.1\" {}viewBox=\"{} {} {} {}\">\n",
+            size,
+            writer.number(min.x - margin),
+            writer.number(min.y - margin),
+            writer.number(width),
+            writer.number(height)
+        )
+        .as_bytes(),
+    )?;
+
+    for element in elements {
+        writer.element(write, element)?;
+    }
+
+    write.write_all(b"</svg>\n")?;
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+struct SvgWriter {
+    precision: Option<usize>,
+    /// Sum of min and max y if flipping
+    flip: Option<f64>,
+}
+
+impl SvgWriter {
+    fn element<W>(&self, write: &mut W, element: &SvgElement) -> IOResult<()>
+    where
+        W: Write,
+    {
+        let style = &element.style;
+
+        let line = match &element.shape {
+            SvgShape::Points(ps) => {
+                let fill = style.stroke.as_ref().or(style.fill.as_ref());
+                let mut line = format!("<g {}>\n", self.style(None, fill, style));
+                for p in ps {
+                    line += &format!(
+                        "  <circle cx=\"{}\" cy=\"{}\" r=\"{}\"/>\n",
+                        self.number(p.x),
+                        self.y(p.y),
+                        self.number(style.point_radius)
+                    );
+                }
+                line + "</g>\n"
+            }
+            SvgShape::Polyline(ps) => format!(
+                "<polyline points=\"{}\" {}/>\n",
+                self.points(ps),
+                self.style(style.stroke.as_ref(), None, style)
+            ),
+            SvgShape::Polygon(ps) => format!(
+                "<polygon points=\"{}\" {}/>\n",
+                self.points(ps),
+                self.style(style.stroke.as_ref(), style.fill.as_ref(), style)
+            ),
+            SvgShape::Line(start, end) => format!(
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" {}/>\n",
+                self.number(start.x),
+                self.y(start.y),
+                self.number(end.x),
+                self.y(end.y),
+                self.style(style.stroke.as_ref(), None, style)
+            ),
+            SvgShape::Circle(center, radius) => format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" {}/>\n",
+                self.number(center.x),
+                self.y(center.y),
+                self.number(**radius),
+                self.style(style.stroke.as_ref(), style.fill.as_ref(), style)
+            ),
+        };
+
+        write.write_all(line.as_bytes())?;
+
+        Ok(())
+    }
+
+    fn style(&self, stroke: Option<&Rgb>, fill: Option<&Rgb>, style: &SvgStyle) -> String {
+        let mut result = match stroke {
+            Some(stroke) => format!(
+                "stroke=\"{}\" stroke-width=\"{}\"",
+                color(stroke),
+                self.number(style.stroke_width)
+            ),
+            None => "stroke=\"none\"".to_string(),
+        };
+
+        result += &match fill {
+            Some(fill) => format!(" fill=\"{}\"", color(fill)),
+            None => " fill=\"none\"".to_string(),
+        };
+
+        if style.opacity < 1.0 {
+            result += &format!(" opacity=\"{}\"", style.opacity.max(0.0));
+        }
+
+        result
+    }
+
+    fn points(&self, ps: &[Point2D]) -> String {
+        ps.iter()
+            .map(|p| format!("{},{}", self.number(p.x), self.y(p.y)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    fn y(&self, y: f64) -> String {
+        match self.flip {
+            Some(sum) => self.number(sum - y),
+            None => self.number(y),
+        }
+    }
+
+    fn number(&self, x: f64) -> String {
+        match self.precision {
+            Some(precision) => format!("{:.*}", precision, x),
+            None => x.to_string(),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Bounding box of all elements, degenerated dimensions are extended to a size of at least 1.0
+fn bounding_box(elements: &[SvgElement]) -> IOResult<BoundingBox2D> {
+    let mut min = Point2D::new(f64::INFINITY, f64::INFINITY);
+    let mut max = Point2D::new(f64::NEG_INFINITY, f64::NEG_INFINITY);
+
+    for element in elements {
+        for p in element.shape.extrema() {
+            if !p.x.is_finite() || !p.y.is_finite() {
+                return Err(IOError::NonFiniteCoordinates);
+            }
+            min.x = min.x.min(p.x);
+            min.y = min.y.min(p.y);
+            max.x = max.x.max(p.x);
+            max.y = max.y.max(p.y);
+        }
+    }
+
+    if min.x > max.x {
+        min = Point2D::new(0.0, 0.0);
+        max = Point2D::new(1.0, 1.0);
+    }
+
+    widen(&mut min.x, &mut max.x);
+    widen(&mut min.y, &mut max.y);
+
+    // The size might still overflow for coordinates close to f64::MAX
+    if !(max.x - min.x).is_finite() || !(max.y - min.y).is_finite() {
+        return Err(IOError::NonFiniteCoordinates);
+    }
+
+    BoundingBox2D::new(&min, &max).map_err(|_| IOError::NonFiniteCoordinates)
+}
+
+/// Extends a degenerated dimension by an amount which is still representable at the magnitude of its value
+fn widen(min: &mut f64, max: &mut f64) {
+    if *min == *max {
+        let half = (min.abs() * f64::EPSILON * 4.0).max(0.5);
+        *min -= half;
+        *max += half;
+    }
+}
+
+fn color(x: &Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", x.r, x.g, x.b)
+}
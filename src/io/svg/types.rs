@@ -0,0 +1,176 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for types of the svg file format
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// Geometry which can be drawn as svg
+#[derive(Debug, Clone, PartialEq)]
+pub enum SvgShape {
+    /// Drawn as a filled circle of SvgStyle::point_radius per point
+    Points(Vec<Point2D>),
+    /// Open path
+    Polyline(Vec<Point2D>),
+    /// Closed path
+    Polygon(Vec<Point2D>),
+    Line(Point2D, Point2D),
+    Circle(Point2D, Positive),
+}
+
+impl SvgShape {
+    /// Creates Points from any IsRandomAccessible<Is2D>, e.g. a PointCloud2D
+    pub fn points<RA, P>(ra: &RA) -> Self
+    where
+        RA: IsRandomAccessible<P>,
+        P: Is2D,
+    {
+        SvgShape::Points(collect(ra))
+    }
+
+    /// Creates a Polyline from any IsRandomAccessible<Is2D>, e.g. the result of douglas_peucker_2d
+    pub fn polyline<RA, P>(ra: &RA) -> Self
+    where
+        RA: IsRandomAccessible<P>,
+        P: Is2D,
+    {
+        SvgShape::Polyline(collect(ra))
+    }
+
+    /// Creates a Polygon from any IsPolygon<Is2D>, e.g. a Polygon2D
+    pub fn polygon<PG, P>(pg: &PG) -> Self
+    where
+        PG: IsPolygon<P>,
+        P: Is2D,
+    {
+        SvgShape::Polygon(
+            (0..pg.num_vertices())
+                .filter_map(|i| pg.vertex(VId(i)))
+                .map(|p| Point2D::new(p.x(), p.y()))
+                .collect(),
+        )
+    }
+
+    /// Positions which are required to be visible
+    pub(super) fn extrema(&self) -> Vec<Point2D> {
+        match self {
+            SvgShape::Points(ps) | SvgShape::Polyline(ps) | SvgShape::Polygon(ps) => ps.clone(),
+            SvgShape::Line(start, end) => vec![start.clone(), end.clone()],
+            SvgShape::Circle(center, radius) => vec![
+                Point2D::new(center.x - **radius, center.y - **radius),
+                Point2D::new(center.x + **radius, center.y + **radius),
+            ],
+        }
+    }
+}
+
+impl From<&LineSegment2D> for SvgShape {
+    fn from(x: &LineSegment2D) -> Self {
+        SvgShape::Line(x.start.clone(), x.end.clone())
+    }
+}
+
+impl From<&Circle> for SvgShape {
+    fn from(x: &Circle) -> Self {
+        SvgShape::Circle(x.center.clone(), x.radius)
+    }
+}
+
+fn collect<RA, P>(ra: &RA) -> Vec<Point2D>
+where
+    RA: IsRandomAccessible<P>,
+    P: Is2D,
+{
+    (0..ra.len())
+        .map(|i| Point2D::new(ra[i].x(), ra[i].y()))
+        .collect()
+}
+
+//------------------------------------------------------------------------------
+
+/// Appearance of an SvgElement, sizes are in the units of the geometry
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgStyle {
+    /// None to disable outlines
+    pub stroke: Option<Rgb>,
+    pub stroke_width: f64,
+    /// None to disable filling, ignored for Points and Polyline
+    pub fill: Option<Rgb>,
+    /// In [0.0, 1.0]
+    pub opacity: f64,
+    /// Radius of the circles drawn for Points
+    pub point_radius: f64,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        Self {
+            stroke: Some(Rgb::new(0, 0, 0)),
+            stroke_width: 0.1,
+            fill: None,
+            opacity: 1.0,
+            point_radius: 0.1,
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// A styled SvgShape
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgElement {
+    pub shape: SvgShape,
+    pub style: SvgStyle,
+}
+
+impl SvgElement {
+    pub fn new(shape: SvgShape, style: SvgStyle) -> Self {
+        Self { shape, style }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Settings for writing svg files
+#[derive(Debug, Clone, PartialEq)]
+pub struct SvgConfig {
+    /// Width of the image in pixels, the height is derived from the bounding box. None to let the viewer decide
+    pub width: Option<usize>,
+    /// Space added around the bounding box, relative to its larger size
+    pub margin: f64,
+    /// Whether y shall point upwards, as common for geometry. svg's y axis points downwards
+    pub flip_y: bool,
+    /// Number of decimal places of coordinates, None for the shortest exact representation
+    pub precision: Option<usize>,
+}
+
+impl Default for SvgConfig {
+    fn default() -> Self {
+        Self {
+            width: Some(800),
+            margin: 0.05,
+            flip_y: true,
+            precision: None,
+        }
+    }
+}
@@ -22,11 +22,11 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 //! Module for types used for IO actions
 
-use std::{mem::MaybeUninit, result::Result};
+use std::{mem::MaybeUninit, path::PathBuf, result::Result};
 
 use super::from_bytes::FromBytesError;
 
-use super::ply::Type;
+use super::{dxf::MIN_MAX_ANGLE, ply::Type};
 
 //------------------------------------------------------------------------------
 
@@ -209,9 +209,48 @@ impl<T> From<FaceData<T>> for FaceDataReserve<T> {
 
 //------------------------------------------------------------------------------
 
+/// File formats which can be detected and loaded automatically
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FileFormat {
+    Ply,
+    StlAscii,
+    StlBinary,
+    Obj,
+    Off,
+    Gltf,
+    Glb,
+    /// Either .las or .laz
+    Las,
+    E57,
+    Pcd,
+    Ptx,
+    Pts,
+    Psl,
+    Xyz,
+    Gcode,
+}
+
+impl FileFormat {
+    /// Whether the format can contain meshes
+    pub fn is_mesh(&self) -> bool {
+        matches!(
+            self,
+            Self::Ply
+                | Self::StlAscii
+                | Self::StlBinary
+                | Self::Obj
+                | Self::Off
+                | Self::Gltf
+                | Self::Glb
+        )
+    }
+}
+
+//------------------------------------------------------------------------------
+
 //@todo consider split into load/save
 pub enum IOError {
-    AccessFile,
+    AccessFile(std::io::Error),
     Header,
     UnsupportedVersion,
     UnknownPointFormat,
@@ -221,10 +260,16 @@ pub enum IOError {
     FaceVertexCount,
     InvalidMeshIndices,
     ColorArrayLength,
+    NormalArrayLength,
+    TexCoordArrayLength,
+    ScalarArrayLength,
+    AttributeLength,
+    VectorArrayLength,
     InvalidPlyType(String, usize),
     InvalidPlyVertexType(Type, usize),
     InvalidPlyFaceType(Type, usize),
     InvalidPlyVertexDimensionDefinition,
+    InvalidPlyPropertyName(String),
     Vertex(Option<usize>),
     Face(Option<usize>),
     Property(usize),
@@ -238,9 +283,17 @@ pub enum IOError {
     Matrix(usize),
     Loop(usize),
     EndLoop(usize),
-    InvalidJSON,
+    InvalidJSON(serde_json::Error),
     EstimateDelimiter,
     Gltf(GltfError),
+    Laz(LazError),
+    E57(E57Error),
+    Pcd(PcdError),
+    Dxf(DxfError),
+    NonFiniteCoordinates,
+    UnknownFileFormat,
+    NoMeshFormat(FileFormat),
+    Context(Box<IOErrorContext>),
 }
 
 pub enum GltfError {
@@ -276,13 +329,55 @@ pub enum GltfError {
     BufferUriNotSupported,
     BufferUriAccess,
     Base64Decode,
+    JSONSparse,
+    SparseIndex,
+    BufferRange,
+    EmptyMesh,
+    NodeCycle,
+    NodeDepth,
+    ExceedsU32,
+}
+
+pub enum LazError {
+    MissingVlr,
+    InvalidVlr,
+    UnsupportedCompressor(u16),
+    UnsupportedItem(u16, u16),
+    UnsupportedPointFormat(u8),
+    ChunkTable,
+    LayerSize,
+}
+
+pub enum E57Error {
+    Header,
+    Checksum(u64),
+    Xml,
+    Scan,
+    Section,
+    Packet,
+    UnsupportedField(String),
+    UnsupportedCodec,
+}
+
+pub enum PcdError {
+    Header(usize),
+    Fields,
+    Layout,
+    MissingPosition,
+    Compression,
+}
+
+pub enum DxfError {
+    GroupCode(usize),
+    Entity(usize),
+    MaxAngle,
 }
 
 pub type IOResult<T> = Result<T, IOError>; //@todo rename
 
 impl From<std::io::Error> for IOError {
-    fn from(_error: std::io::Error) -> Self {
-        IOError::AccessFile
+    fn from(error: std::io::Error) -> Self {
+        IOError::AccessFile(error)
     }
 }
 
@@ -301,7 +396,7 @@ impl From<FromBytesError> for IOError {
 impl std::fmt::Debug for IOError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            Self::AccessFile => write!(f, "Unable to access file"),
+            Self::AccessFile(x) => write!(f, "Unable to access file: {}", x),
             Self::LineParse(x) => write!(f, "Unable to parse line {}", x),
             Self::BinaryData => write!(f, "Unable to parse binary data"),
             Self::UnknownPointFormat => write!(f, "Unknown point format"),
@@ -318,6 +413,21 @@ impl std::fmt::Debug for IOError {
             Self::VertexCount(None) => write!(f, "Vertex count does not match"),
             Self::VertexCount(Some(x)) => write!(f, "Vertex count does not match on line {}", x),
             Self::ColorArrayLength => write!(f, "Length of color array does not match others"),
+            Self::NormalArrayLength => write!(f, "Length of normal array does not match others"),
+            Self::TexCoordArrayLength => {
+                write!(
+                    f,
+                    "Length of texture coordinate array does not match others"
+                )
+            }
+            Self::ScalarArrayLength => write!(f, "Length of scalar array does not match others"),
+            Self::AttributeLength => {
+                write!(
+                    f,
+                    "Number of attribute values does not match the number of elements"
+                )
+            }
+            Self::VectorArrayLength => write!(f, "Length of vector array does not match others"),
             Self::InvalidPlyType(s, x) => write!(f, "Invalid type '{}' in header '{}'", s, x),
             Self::InvalidPlyVertexType(t, x) => {
                 write!(f, "Invalid vertex type '{}' in header {}", t, x)
@@ -329,6 +439,13 @@ impl std::fmt::Debug for IOError {
             Self::InvalidPlyVertexDimensionDefinition => {
                 write!(f, "Invalid order / definition of vertex dimension order")
             }
+            Self::InvalidPlyPropertyName(s) => {
+                write!(
+                    f,
+                    "Invalid property name '{}', must be non-empty without whitespace",
+                    s
+                )
+            }
             Self::FaceCount(None) => write!(f, "Unable to parse face count"),
             Self::FaceCount(Some(x)) => write!(f, "Unable to parse face count on line {}", x),
             Self::FaceVertexCount => write!(f, "Unable to parse vertex count of face"),
@@ -339,28 +456,211 @@ impl std::fmt::Debug for IOError {
             Self::Loop(x) => write!(f, "Unable to parse loop on line {}", x),
             Self::EndLoop(x) => write!(f, "Unable to parse endloop on line {}", x),
             Self::EstimateDelimiter => write!(f, "Unable to estimate delimiter"),
-            Self::InvalidJSON => write!(f, "Unable to parse JSON format"),
+            Self::InvalidJSON(x) => write!(f, "Unable to parse JSON format: {}", x),
             Self::Gltf(x) => write!(f, "{:?}", x),
+            Self::Laz(x) => write!(f, "{:?}", x),
+            Self::E57(x) => write!(f, "{:?}", x),
+            Self::Pcd(x) => write!(f, "{:?}", x),
+            Self::Dxf(x) => write!(f, "{:?}", x),
+            Self::NonFiniteCoordinates => {
+                write!(
+                    f,
+                    "Coordinates or the extent of all coordinates are not finite"
+                )
+            }
+            Self::UnknownFileFormat => write!(f, "Unable to detect the file format"),
+            Self::NoMeshFormat(x) => write!(f, "The {:?} format does not contain meshes", x),
+            Self::Context(x) => write!(f, "{}: {:?}", x, x.error),
         }
     }
 }
 
 impl std::fmt::Display for IOError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        // Causes are only reachable via source(), so error chains don't print them twice
+        match self {
+            Self::AccessFile(_) => write!(f, "Unable to access file"),
+            Self::InvalidJSON(_) => write!(f, "Unable to parse JSON format"),
+            Self::Context(x) => write!(f, "{}", x),
+            _ => write!(f, "{:?}", self),
+        }
+    }
+}
+
+impl std::error::Error for IOError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::AccessFile(x) => Some(x),
+            Self::InvalidJSON(x) => Some(x),
+            Self::Context(x) => Some(&x.error),
+            _ => None,
+        }
+    }
+}
+
+impl IOError {
+    /// Adds the element and location at which the error occurred
+    /// Already known context is kept, errors which already state the same line are returned unchanged
+    pub fn context(self, element: IOElement, location: IOLocation) -> Self {
+        match self {
+            Self::Context(mut x) => {
+                x.element = x.element.or(Some(element));
+                x.location = x.location.or(Some(location));
+                Self::Context(x)
+            }
+            _ if matches!(location, IOLocation::Line(x) if self.line() == Some(x)) => self,
+            _ => Self::Context(Box::new(IOErrorContext {
+                file: None,
+                element: Some(element),
+                location: Some(location),
+                error: self,
+            })),
+        }
+    }
+
+    /// The line the error refers to, if any
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Self::LineParse(x)
+            | Self::InvalidProperty(x)
+            | Self::MissingStart(x)
+            | Self::UnkownFormat(x)
+            | Self::Property(x)
+            | Self::Columns(x)
+            | Self::Rows(x)
+            | Self::Matrix(x)
+            | Self::Loop(x)
+            | Self::EndLoop(x)
+            | Self::InvalidPlyType(_, x)
+            | Self::InvalidPlyVertexType(_, x)
+            | Self::InvalidPlyFaceType(_, x)
+            | Self::Vertex(Some(x))
+            | Self::Face(Some(x))
+            | Self::VertexCount(Some(x))
+            | Self::FaceCount(Some(x)) => Some(*x),
+            Self::Pcd(PcdError::Header(x)) => Some(*x),
+            Self::Dxf(DxfError::GroupCode(x)) | Self::Dxf(DxfError::Entity(x)) => Some(*x),
+            Self::Context(x) => match x.location {
+                Some(IOLocation::Line(line)) => Some(line),
+                _ => x.error.line(),
+            },
+            _ => None,
+        }
+    }
+
+    /// Adds the file in which the error occurred
+    pub fn in_file<P>(self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        match self {
+            Self::Context(mut x) => {
+                x.file = x.file.or_else(|| Some(path.into()));
+                Self::Context(x)
+            }
+            _ => Self::Context(Box::new(IOErrorContext {
+                file: Some(path.into()),
+                element: None,
+                location: None,
+                error: self,
+            })),
+        }
+    }
+
+    /// The underlying error, without any context
+    pub fn root(&self) -> &IOError {
+        match self {
+            Self::Context(x) => x.error.root(),
+            _ => self,
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Element which was read when an error occurred
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IOElement {
+    Header,
+    Vertex,
+    Face,
+    Point,
+    Accessor,
+}
+
+impl std::fmt::Display for IOElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Header => write!(f, "header"),
+            Self::Vertex => write!(f, "vertex"),
+            Self::Face => write!(f, "face"),
+            Self::Point => write!(f, "point"),
+            Self::Accessor => write!(f, "accessor"),
+        }
+    }
+}
+
+/// Location within a file at which an error occurred
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IOLocation {
+    /// Line of text formats, starting at 1
+    Line(usize),
+    /// Offset of binary formats in bytes
+    Byte(u64),
+    /// Index of the element, if neither line nor offset are known
+    Index(usize),
+}
+
+impl std::fmt::Display for IOLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Line(x) => write!(f, "on line {}", x),
+            Self::Byte(x) => write!(f, "at byte {}", x),
+            Self::Index(x) => write!(f, "with index {}", x),
+        }
+    }
+}
+
+/// An IOError together with where it occurred
+/// Displays only where it occurred, the IOError itself is the source
+pub struct IOErrorContext {
+    pub file: Option<PathBuf>,
+    pub element: Option<IOElement>,
+    pub location: Option<IOLocation>,
+    pub error: IOError,
+}
+
+impl std::fmt::Display for IOErrorContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Unable to read")?;
+        if let Some(element) = self.element {
+            write!(f, " {}", element)?;
+        }
+        if let Some(location) = self.location {
+            write!(f, " {}", location)?;
+        }
+        if let Some(file) = &self.file {
+            if self.element.is_some() || self.location.is_some() {
+                write!(f, " of")?;
+            }
+            write!(f, " '{}'", file.display())?;
+        }
+        Ok(())
     }
 }
 
 impl From<super::utils::FetchLineError> for IOError {
-    fn from(_error: super::utils::FetchLineError) -> Self {
-        IOError::EndReached
+    fn from(error: super::utils::FetchLineError) -> Self {
+        match error.0 {
+            Some(e) => IOError::AccessFile(e),
+            None => IOError::EndReached,
+        }
     }
 }
 
 impl From<serde_json::error::Error> for IOError {
-    fn from(_error: serde_json::error::Error) -> Self {
-        //@todo use more information
-        IOError::InvalidJSON
+    fn from(error: serde_json::error::Error) -> Self {
+        IOError::InvalidJSON(error)
     }
 }
 
@@ -429,6 +729,19 @@ impl std::fmt::Debug for GltfError {
             }
             Self::BufferUriAccess => write!(f, "Could not access buffer file defined via uri"),
             Self::Base64Decode => write!(f, "Unable to decode base64 data in .glTF/.glb file"),
+            Self::JSONSparse => write!(f, "JSON sparse of .glTF/.glb file could not be parsed"),
+            Self::SparseIndex => write!(f, "Invalid sparse index in .glTF/.glb file"),
+            Self::BufferRange => write!(f, "Data exceeds the buffer in .glTF/.glb file"),
+            Self::NodeCycle => write!(f, "Node hierarchy of .glTF/.glb file contains a cycle"),
+            Self::NodeDepth => write!(f, "Node hierarchy of .glTF/.glb file is too deep"),
+            Self::ExceedsU32 => write!(
+                f,
+                "Indices or sizes exceed the 32 bit limit of the .glTF/.glb file format"
+            ),
+            Self::EmptyMesh => write!(
+                f,
+                "Meshes without vertices or faces can't be saved as .glTF/.glb"
+            ),
         }
     }
 }
@@ -438,3 +751,112 @@ impl std::fmt::Display for GltfError {
         write!(f, "{:?}", self)
     }
 }
+
+impl std::error::Error for GltfError {}
+
+//------------------------------------------------------------------------------
+
+impl std::fmt::Debug for LazError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::MissingVlr => write!(f, "LASzip record of .laz file is missing"),
+            Self::InvalidVlr => write!(f, "LASzip record of .laz file is invalid"),
+            Self::UnsupportedCompressor(x) => {
+                write!(f, "Compressor {} of .laz file is not supported", x)
+            }
+            Self::UnsupportedItem(t, v) => write!(
+                f,
+                "Item type {} with version {} of .laz file is not supported",
+                t, v
+            ),
+            Self::UnsupportedPointFormat(x) => write!(
+                f,
+                "Point record format {} of .laz file is not supported, only the formats 0 - 3 can be decompressed",
+                x
+            ),
+            Self::ChunkTable => write!(f, "Chunk table of .laz file is invalid"),
+            Self::LayerSize => write!(f, "Layer sizes of .laz file exceed their chunk"),
+        }
+    }
+}
+
+impl std::fmt::Display for LazError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for LazError {}
+
+//------------------------------------------------------------------------------
+
+impl std::fmt::Debug for E57Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Header => write!(f, "Invalid header of .e57 file"),
+            Self::Checksum(x) => write!(f, "Checksum of page {} of .e57 file does not match", x),
+            Self::Xml => write!(f, "XML section of .e57 file could not be parsed"),
+            Self::Scan => write!(f, "Scan of .e57 file could not be parsed"),
+            Self::Section => write!(f, "Invalid binary section in .e57 file"),
+            Self::Packet => write!(f, "Invalid data packet in .e57 file"),
+            Self::UnsupportedField(x) => {
+                write!(f, "Field '{}' of .e57 file has an unsupported type", x)
+            }
+            Self::UnsupportedCodec => write!(f, "Only the bitPackCodec of .e57 is supported"),
+        }
+    }
+}
+
+impl std::fmt::Display for E57Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for E57Error {}
+
+//------------------------------------------------------------------------------
+
+impl std::fmt::Debug for PcdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Header(x) => write!(f, "Unable to parse .pcd header on line {}", x),
+            Self::Fields => write!(f, "FIELDS, SIZE, TYPE and COUNT of .pcd file do not match"),
+            Self::Layout => write!(f, "WIDTH * HEIGHT does not match the point count"),
+            Self::MissingPosition => write!(f, "The .pcd file is missing the x, y or z field"),
+            Self::Compression => write!(f, "Unable to decompress .pcd data"),
+        }
+    }
+}
+
+impl std::fmt::Display for PcdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for PcdError {}
+
+//------------------------------------------------------------------------------
+
+impl std::fmt::Debug for DxfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::GroupCode(x) => write!(f, "Invalid group code of .dxf file on line {}", x),
+            Self::Entity(x) => write!(f, "Unable to parse .dxf entity starting on line {}", x),
+            Self::MaxAngle => write!(
+                f,
+                "max_angle for the tessellation of .dxf arcs must be at least {} radians",
+                MIN_MAX_ANGLE
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for DxfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl std::error::Error for DxfError {}
@@ -97,7 +97,7 @@ where
     line_buffer.clear();
     let n_read = read.read_until(b'\n', line_buffer)?;
     if n_read == 0 {
-        return Err(FetchLineError);
+        return Err(FetchLineError(None));
     }
 
     // We must drop the '\n' we read_until for sure
@@ -114,22 +114,95 @@ where
     Ok(&line_buffer[0..line_buffer.len() - ignore_end])
 }
 
+/// Fetch a single line, None if the end was reached
+#[inline(always)]
+pub fn fetch_line_or_none<'a, R>(
+    read: &mut R,
+    line_buffer: &'a mut Vec<u8>,
+) -> std::io::Result<Option<&'a [u8]>>
+where
+    R: BufRead,
+{
+    match fetch_line(read, line_buffer) {
+        Ok(line) => Ok(Some(line)),
+        Err(FetchLineError(None)) => Ok(None),
+        Err(FetchLineError(Some(e))) => Err(e),
+    }
+}
+
 //------------------------------------------------------------------------------
 
 /// Error type for the fetch_line function
-pub struct FetchLineError;
+/// Holds the error of the reader, None if its end was reached
+pub struct FetchLineError(pub Option<ioError>);
 
 /// Result type for the fetch_line function
 pub type FetchLineResult<T> = std::result::Result<T, FetchLineError>;
 
 impl fmt::Debug for FetchLineError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Unable to fetch line")
+        match &self.0 {
+            Some(e) => write!(f, "Unable to fetch line: {}", e),
+            None => write!(f, "Unable to fetch line, end reached"),
+        }
     }
 }
 
 impl From<ioError> for FetchLineError {
-    fn from(_error: ioError) -> Self {
-        FetchLineError {}
+    fn from(error: ioError) -> Self {
+        FetchLineError(Some(error))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Wraps a Read / BufRead, keeping track of the number of consumed bytes
+pub struct ByteCounter<R> {
+    read: R,
+    offset: u64,
+}
+
+impl<R> ByteCounter<R> {
+    pub fn new(read: R) -> Self {
+        Self::with_offset(read, 0)
+    }
+
+    /// Starts counting at offset, e.g. if a header has already been consumed
+    pub fn with_offset(read: R, offset: u64) -> Self {
+        Self { read, offset }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.read
+    }
+
+    /// Number of bytes consumed so far
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+}
+
+impl<R> Read for ByteCounter<R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.read.read(buf)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R> BufRead for ByteCounter<R>
+where
+    R: BufRead,
+{
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.read.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.offset += amt as u64;
+        self.read.consume(amt)
     }
 }
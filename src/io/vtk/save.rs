@@ -0,0 +1,540 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for save functions of the vtk file formats
+
+use crate::*;
+
+use std::{fmt::Display, io::Write};
+
+use super::{super::types::*, types::*};
+
+use base64::encode;
+
+//------------------------------------------------------------------------------
+
+/// Saves an IsMesh3D as legacy .vtk POLYDATA with data arrays per vertex and per face
+pub fn save_vtk_mesh<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    format: VtkFormat,
+    point_data: &[VtkArray],
+    cell_data: &[VtkArray],
+) -> IOResult<()>
+where
+    M: IsMesh3D<P>,
+    P: IsBuildable3D,
+    W: Write,
+{
+    let geometry = Geometry::from_mesh(mesh);
+    geometry.check(point_data, cell_data)?;
+    save_legacy(write, &geometry, format, point_data, cell_data)
+}
+
+/// Saves an IsRandomAccessible<Is3D> as legacy .vtk POLYDATA with data arrays per point
+pub fn save_vtk_points<RA, P, W>(
+    write: &mut W,
+    ra: &RA,
+    format: VtkFormat,
+    point_data: &[VtkArray],
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P>,
+    P: Is3D,
+    W: Write,
+{
+    let geometry = Geometry::from_points(ra);
+    geometry.check(point_data, &[])?;
+    save_legacy(write, &geometry, format, point_data, &[])
+}
+
+/// Saves an IsMesh3D as XML .vtu UnstructuredGrid with data arrays per vertex and per face
+pub fn save_vtu_mesh<M, P, W>(
+    write: &mut W,
+    mesh: &M,
+    format: VtkFormat,
+    point_data: &[VtkArray],
+    cell_data: &[VtkArray],
+) -> IOResult<()>
+where
+    M: IsMesh3D<P>,
+    P: IsBuildable3D,
+    W: Write,
+{
+    let geometry = Geometry::from_mesh(mesh);
+    geometry.check(point_data, cell_data)?;
+    save_vtu(write, &geometry, format, point_data, cell_data)
+}
+
+/// Saves an IsRandomAccessible<Is3D> as XML .vtu UnstructuredGrid with data arrays per point
+pub fn save_vtu_points<RA, P, W>(
+    write: &mut W,
+    ra: &RA,
+    format: VtkFormat,
+    point_data: &[VtkArray],
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P>,
+    P: Is3D,
+    W: Write,
+{
+    let geometry = Geometry::from_points(ra);
+    geometry.check(point_data, &[])?;
+    save_vtu(write, &geometry, format, point_data, &[])
+}
+
+/// Saves an IsMesh3D as legacy .vtk POLYDATA, writing its vertex and face Attributes as data arrays (see VtkArray::from_attributes)
+pub fn save_vtk_mesh_attributed<M, P, W>(write: &mut W, mesh: &M, format: VtkFormat) -> IOResult<()>
+where
+    M: IsMesh3D<P> + HasVertexAttributes + HasFaceAttributes,
+    P: IsBuildable3D,
+    W: Write,
+{
+    let point_data = VtkArray::from_attributes(mesh.vertex_attributes());
+    let cell_data = VtkArray::from_attributes(mesh.face_attributes());
+    save_vtk_mesh(write, mesh, format, &point_data, &cell_data)
+}
+
+/// Saves an IsRandomAccessible<Is3D> as legacy .vtk POLYDATA, writing its Attributes as data arrays (see VtkArray::from_attributes)
+pub fn save_vtk_points_attributed<RA, P, W>(
+    write: &mut W,
+    ra: &RA,
+    format: VtkFormat,
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P> + HasVertexAttributes,
+    P: Is3D,
+    W: Write,
+{
+    let point_data = VtkArray::from_attributes(ra.vertex_attributes());
+    save_vtk_points(write, ra, format, &point_data)
+}
+
+/// Saves an IsMesh3D as XML .vtu UnstructuredGrid, writing its vertex and face Attributes as data arrays (see VtkArray::from_attributes)
+pub fn save_vtu_mesh_attributed<M, P, W>(write: &mut W, mesh: &M, format: VtkFormat) -> IOResult<()>
+where
+    M: IsMesh3D<P> + HasVertexAttributes + HasFaceAttributes,
+    P: IsBuildable3D,
+    W: Write,
+{
+    let point_data = VtkArray::from_attributes(mesh.vertex_attributes());
+    let cell_data = VtkArray::from_attributes(mesh.face_attributes());
+    save_vtu_mesh(write, mesh, format, &point_data, &cell_data)
+}
+
+/// Saves an IsRandomAccessible<Is3D> as XML .vtu UnstructuredGrid, writing its Attributes as data arrays (see VtkArray::from_attributes)
+pub fn save_vtu_points_attributed<RA, P, W>(
+    write: &mut W,
+    ra: &RA,
+    format: VtkFormat,
+) -> IOResult<()>
+where
+    RA: IsRandomAccessible<P> + HasVertexAttributes,
+    P: Is3D,
+    W: Write,
+{
+    let point_data = VtkArray::from_attributes(ra.vertex_attributes());
+    save_vtu_points(write, ra, format, &point_data)
+}
+
+//------------------------------------------------------------------------------
+
+/// VTK cell types
+const VTK_VERTEX: u8 = 1;
+const VTK_TRIANGLE: u8 = 5;
+const VTK_POLYGON: u8 = 7;
+
+/// Positions and cells, either one vertex cell per point or polygons
+struct Geometry {
+    points: Vec<f64>,
+    cells: Vec<Vec<usize>>,
+    is_mesh: bool,
+}
+
+impl Geometry {
+    fn from_mesh<M, P>(mesh: &M) -> Self
+    where
+        M: IsMesh3D<P>,
+        P: IsBuildable3D,
+    {
+        let mut points = Vec::with_capacity(3 * mesh.num_vertices());
+        for i in 0..mesh.num_vertices() {
+            let p = mesh.vertex(VId(i)).unwrap(); // safe since iterating num_vertices
+            points.extend_from_slice(&[p.x(), p.y(), p.z()]);
+        }
+
+        let cells = (0..mesh.num_faces())
+            .map(|i| {
+                let f = mesh.face_vertex_ids(FId(i)).unwrap(); // safe since iterating num_faces
+                vec![f.a.0, f.b.0, f.c.0]
+            })
+            .collect();
+
+        Self {
+            points,
+            cells,
+            is_mesh: true,
+        }
+    }
+
+    fn from_points<RA, P>(ra: &RA) -> Self
+    where
+        RA: IsRandomAccessible<P>,
+        P: Is3D,
+    {
+        let n = ra.len();
+        let mut points = Vec::with_capacity(3 * n);
+        for i in 0..n {
+            points.extend_from_slice(&[ra[i].x(), ra[i].y(), ra[i].z()]);
+        }
+
+        Self {
+            points,
+            cells: (0..n).map(|i| vec![i]).collect(),
+            is_mesh: false,
+        }
+    }
+
+    fn n_points(&self) -> usize {
+        self.points.len() / 3
+    }
+
+    fn check(&self, point_data: &[VtkArray], cell_data: &[VtkArray]) -> IOResult<()> {
+        let n_points = self.n_points();
+        let n_cells = self.cells.len();
+        for (array, n) in point_data
+            .iter()
+            .map(|x| (x, n_points))
+            .chain(cell_data.iter().map(|x| (x, n_cells)))
+        {
+            match &array.values {
+                VtkValues::Scalars(x) if x.len() != n => return Err(IOError::ScalarArrayLength),
+                VtkValues::Vectors(x) if x.len() != n => return Err(IOError::VectorArrayLength),
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn cell_type(&self, cell: &[usize]) -> u8 {
+        match (self.is_mesh, cell.len()) {
+            (false, _) => VTK_VERTEX,
+            (true, 3) => VTK_TRIANGLE,
+            (true, _) => VTK_POLYGON,
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+fn save_legacy<W>(
+    write: &mut W,
+    geometry: &Geometry,
+    format: VtkFormat,
+    point_data: &[VtkArray],
+    cell_data: &[VtkArray],
+) -> IOResult<()>
+where
+    W: Write,
+{
+    let n_points = geometry.n_points();
+    let n_cells = geometry.cells.len();
+
+    write.write_all(b"# vtk DataFile Version 3.0\n")?;
+    write.write_all(b"Created by rust-3d\n")?;
+    match format {
+        VtkFormat::Ascii => write.write_all(b"ASCII\n")?,
+        VtkFormat::Binary => write.write_all(b"BINARY\n")?,
+    }
+    write.write_all(b"DATASET POLYDATA\n")?;
+
+    write.write_all(format!("POINTS {} double\n", n_points).as_bytes())?;
+    legacy_values(write, format, &geometry.points, 3)?;
+
+    let size: usize = geometry.cells.iter().map(|x| x.len() + 1).sum();
+    let keyword = if geometry.is_mesh {
+        "POLYGONS"
+    } else {
+        "VERTICES"
+    };
+    write.write_all(format!("{} {} {}\n", keyword, n_cells, size).as_bytes())?;
+    match format {
+        VtkFormat::Ascii => {
+            for cell in &geometry.cells {
+                let line = std::iter::once(cell.len())
+                    .chain(cell.iter())
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                write.write_all((line + "\n").as_bytes())?;
+            }
+        }
+        VtkFormat::Binary => {
+            let mut bytes = Vec::with_capacity(4 * size);
+            for cell in &geometry.cells {
+                bytes.extend_from_slice(&(cell.len() as i32).to_be_bytes());
+                for i in cell {
+                    bytes.extend_from_slice(&(*i as i32).to_be_bytes());
+                }
+            }
+            bytes.push(b'\n');
+            write.write_all(&bytes)?;
+        }
+    }
+
+    if !point_data.is_empty() {
+        write.write_all(format!("POINT_DATA {}\n", n_points).as_bytes())?;
+        for array in point_data {
+            legacy_array(write, format, array)?;
+        }
+    }
+
+    if !cell_data.is_empty() {
+        write.write_all(format!("CELL_DATA {}\n", n_cells).as_bytes())?;
+        for array in cell_data {
+            legacy_array(write, format, array)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn legacy_array<W>(write: &mut W, format: VtkFormat, array: &VtkArray) -> IOResult<()>
+where
+    W: Write,
+{
+    let name: String = array
+        .name
+        .chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect();
+
+    match &array.values {
+        VtkValues::Scalars(values) => {
+            write.write_all(format!("SCALARS {} double 1\n", name).as_bytes())?;
+            write.write_all(b"LOOKUP_TABLE default\n")?;
+            legacy_values(write, format, values, 1)
+        }
+        VtkValues::Vectors(values) => {
+            write.write_all(format!("VECTORS {} double\n", name).as_bytes())?;
+            let flat: Vec<f64> = values.iter().flatten().cloned().collect();
+            legacy_values(write, format, &flat, 3)
+        }
+    }
+}
+
+/// Writes values with n_components per line or as big endian binary
+fn legacy_values<W>(
+    write: &mut W,
+    format: VtkFormat,
+    values: &[f64],
+    n_components: usize,
+) -> IOResult<()>
+where
+    W: Write,
+{
+    match format {
+        VtkFormat::Ascii => {
+            for chunk in values.chunks(n_components) {
+                write.write_all((join(chunk) + "\n").as_bytes())?;
+            }
+        }
+        VtkFormat::Binary => {
+            let mut bytes = Vec::with_capacity(8 * values.len() + 1);
+            for x in values {
+                bytes.extend_from_slice(&x.to_be_bytes());
+            }
+            bytes.push(b'\n');
+            write.write_all(&bytes)?;
+        }
+    }
+
+    Ok(())
+}
+
+//------------------------------------------------------------------------------
+
+fn save_vtu<W>(
+    write: &mut W,
+    geometry: &Geometry,
+    format: VtkFormat,
+    point_data: &[VtkArray],
+    cell_data: &[VtkArray],
+) -> IOResult<()>
+where
+    W: Write,
+{
+    let mut connectivity = Vec::new();
+    let mut offsets = Vec::with_capacity(geometry.cells.len());
+    let mut types = Vec::with_capacity(geometry.cells.len());
+    for cell in &geometry.cells {
+        connectivity.extend(cell.iter().map(|x| x as i64));
+        offsets.push(connectivity.len() as i64);
+        types.push(geometry.cell_type(cell));
+    }
+
+    write.write_all(b"<?xml version=\"1.0\"?>\n")?;
+    write.write_all(b"<!-- Created by rust-3d -->\n")?;
+    write.write_all(
+        b"<VTKFile type=\"UnstructuredGrid\" version=\"0.1\" byte_order=\"LittleEndian\" header_type=\"UInt32\">\n",
+    )?;
+    write.write_all(b"  <UnstructuredGrid>\n")?;
+    write.write_all(
+        format!(
+            "    <Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">\n",
+            geometry.n_points(),
+            geometry.cells.len()
+        )
+        .as_bytes(),
+    )?;
+
+    for (tag, arrays) in &[("PointData", point_data), ("CellData", cell_data)] {
+        write.write_all(format!("      <{}>\n", tag).as_bytes())?;
+        for array in arrays.iter() {
+            match &array.values {
+                VtkValues::Scalars(values) => {
+                    vtu_array(write, format, Some(&array.name), 1, values)?
+                }
+                VtkValues::Vectors(values) => {
+                    let flat: Vec<f64> = values.iter().flatten().cloned().collect();
+                    vtu_array(write, format, Some(&array.name), 3, &flat)?
+                }
+            }
+        }
+        write.write_all(format!("      </{}>\n", tag).as_bytes())?;
+    }
+
+    write.write_all(b"      <Points>\n")?;
+    vtu_array(write, format, None, 3, &geometry.points)?;
+    write.write_all(b"      </Points>\n")?;
+
+    write.write_all(b"      <Cells>\n")?;
+    vtu_array(write, format, Some("connectivity"), 1, &connectivity)?;
+    vtu_array(write, format, Some("offsets"), 1, &offsets)?;
+    vtu_array(write, format, Some("types"), 1, &types)?;
+    write.write_all(b"      </Cells>\n")?;
+
+    write.write_all(b"    </Piece>\n")?;
+    write.write_all(b"  </UnstructuredGrid>\n")?;
+    write.write_all(b"</VTKFile>\n")?;
+
+    Ok(())
+}
+
+/// Value types of .vtu DataArrays
+trait VtuValue: Display {
+    const NAME: &'static str;
+    fn extend_le(&self, bytes: &mut Vec<u8>);
+}
+
+impl VtuValue for f64 {
+    const NAME: &'static str = "Float64";
+    fn extend_le(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes())
+    }
+}
+
+impl VtuValue for i64 {
+    const NAME: &'static str = "Int64";
+    fn extend_le(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_le_bytes())
+    }
+}
+
+impl VtuValue for u8 {
+    const NAME: &'static str = "UInt8";
+    fn extend_le(&self, bytes: &mut Vec<u8>) {
+        bytes.push(*self)
+    }
+}
+
+fn vtu_array<W, T>(
+    write: &mut W,
+    format: VtkFormat,
+    name: Option<&str>,
+    n_components: usize,
+    values: &[T],
+) -> IOResult<()>
+where
+    W: Write,
+    T: VtuValue,
+{
+    let name = match name {
+        Some(name) => format!(" Name=\"{}\"", escape(name)),
+        None => String::new(),
+    };
+    let format_name = match format {
+        VtkFormat::Ascii => "ascii",
+        VtkFormat::Binary => "binary",
+    };
+
+    write.write_all(
+        format!(
+            "        <DataArray type=\"{}\"{} NumberOfComponents=\"{}\" format=\"{}\">\n",
+            T::NAME,
+            name,
+            n_components,
+            format_name
+        )
+        .as_bytes(),
+    )?;
+
+    match format {
+        VtkFormat::Ascii => {
+            for chunk in values.chunks(n_components) {
+                write.write_all(format!("          {}\n", join(chunk)).as_bytes())?;
+            }
+        }
+        VtkFormat::Binary => {
+            // Inline binary data is prefixed by its size in bytes
+            let mut bytes = Vec::new();
+            for x in values {
+                x.extend_le(&mut bytes);
+            }
+            let mut data = (bytes.len() as u32).to_le_bytes().to_vec();
+            data.extend_from_slice(&bytes);
+            write.write_all(format!("          {}\n", encode(&data)).as_bytes())?;
+        }
+    }
+
+    write.write_all(b"        </DataArray>\n")?;
+
+    Ok(())
+}
+
+fn join<T>(values: &[T]) -> String
+where
+    T: Display,
+{
+    values
+        .iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,106 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for types of the vtk file formats
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// Encoding of the geometry and data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VtkFormat {
+    Ascii,
+    /// Big endian for .vtk, base64 encoded little endian for .vtu
+    Binary,
+}
+
+/// Values of a data array, one entry per vertex or face
+#[derive(Debug, Clone, PartialEq)]
+pub enum VtkValues {
+    Scalars(Vec<f64>),
+    Vectors(Vec<[f64; 3]>),
+}
+
+/// A named data array, e.g. distances, curvatures or normals
+#[derive(Debug, Clone, PartialEq)]
+pub struct VtkArray {
+    /// Whitespace is replaced by underscores within .vtk files
+    pub name: String,
+    pub values: VtkValues,
+}
+
+impl VtkArray {
+    pub fn scalars(name: &str, values: Vec<f64>) -> Self {
+        Self {
+            name: name.to_string(),
+            values: VtkValues::Scalars(values),
+        }
+    }
+
+    pub fn vectors<V>(name: &str, values: &[V]) -> Self
+    where
+        V: Is3D,
+    {
+        Self {
+            name: name.to_string(),
+            values: VtkValues::Vectors(values.iter().map(|v| [v.x(), v.y(), v.z()]).collect()),
+        }
+    }
+
+    /// Creates one array per channel of attributes
+    /// Normals become vectors, scalars and labels become scalars and colors become vectors of their components within [0, 255]
+    pub fn from_attributes(attributes: &Attributes) -> Vec<Self> {
+        attributes
+            .channels()
+            .iter()
+            .map(|channel| {
+                let name = channel.name.as_str();
+                match &channel.values {
+                    AttributeValues::Rgb(x) => Self {
+                        name: name.to_string(),
+                        values: VtkValues::Vectors(
+                            x.iter()
+                                .map(|c| [c.r as f64, c.g as f64, c.b as f64])
+                                .collect(),
+                        ),
+                    },
+                    AttributeValues::Norm3D(x) => Self::vectors(name, x),
+                    AttributeValues::Scalar(x) => Self::scalars(name, x.clone()),
+                    AttributeValues::Label(x) => {
+                        Self::scalars(name, x.iter().map(|l| *l as f64).collect())
+                    }
+                }
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.values {
+            VtkValues::Scalars(x) => x.len(),
+            VtkValues::Vectors(x) => x.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -74,7 +74,9 @@ where
         line: &[u8],
     ) -> IOResult<P> {
         if !*delim_determined {
-            *delim = estimate_delimiter(1, &line).ok_or(IOError::EstimateDelimiter)?;
+            *delim = estimate_delimiter(1, &line).ok_or_else(|| {
+                IOError::EstimateDelimiter.context(IOElement::Vertex, IOLocation::Line(i_line))
+            })?;
             *delim_determined = true;
         }
 
@@ -111,7 +113,22 @@ where
         loop {
             if chunk.is_full() {
                 return Some(Ok(chunk));
-            } else if let Ok(line) = fetch_line(&mut self.read, &mut self.line_buffer) {
+            } else {
+                let line = match fetch_line_or_none(&mut self.read, &mut self.line_buffer) {
+                    Ok(Some(line)) => line,
+                    Ok(None) => {
+                        self.is_done = true;
+                        if chunk.has_data() {
+                            return Some(Ok(chunk));
+                        }
+                        return None;
+                    }
+                    Err(e) => {
+                        self.is_done = true;
+                        return Some(Err(e.into()));
+                    }
+                };
+
                 self.i_line += 1;
                 match Self::fetch_one(
                     &mut self.delim_determined,
@@ -125,12 +142,6 @@ where
                     }
                     Ok(x) => chunk.push(DataReserve::Data(x)).unwrap(), // unwrap safe since we only call this if chunk.has_space()
                 }
-            } else {
-                self.is_done = true;
-                if chunk.has_data() {
-                    return Some(Ok(chunk));
-                }
-                return None;
             }
         }
     }
@@ -0,0 +1,124 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! IsAttribute trait used for types which can be stored within Attributes
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// IsAttribute trait used for types which can be stored within Attributes
+pub trait IsAttribute: Sized + Clone {
+    /// Should return the value used for elements added without a value
+    fn default_attribute() -> Self;
+    /// Should return the values if they are of this type
+    fn from_values(values: &AttributeValues) -> Option<&Vec<Self>>;
+    /// Should return the mutable values if they are of this type
+    fn from_values_mut(values: &mut AttributeValues) -> Option<&mut Vec<Self>>;
+    /// Should wrap the values
+    fn into_values(values: Vec<Self>) -> AttributeValues;
+}
+
+//------------------------------------------------------------------------------
+
+impl IsAttribute for Rgb {
+    fn default_attribute() -> Self {
+        Rgb::default()
+    }
+    fn from_values(values: &AttributeValues) -> Option<&Vec<Self>> {
+        match values {
+            AttributeValues::Rgb(x) => Some(x),
+            _ => None,
+        }
+    }
+    fn from_values_mut(values: &mut AttributeValues) -> Option<&mut Vec<Self>> {
+        match values {
+            AttributeValues::Rgb(x) => Some(x),
+            _ => None,
+        }
+    }
+    fn into_values(values: Vec<Self>) -> AttributeValues {
+        AttributeValues::Rgb(values)
+    }
+}
+
+impl IsAttribute for Norm3D {
+    fn default_attribute() -> Self {
+        Norm3D::norm_z()
+    }
+    fn from_values(values: &AttributeValues) -> Option<&Vec<Self>> {
+        match values {
+            AttributeValues::Norm3D(x) => Some(x),
+            _ => None,
+        }
+    }
+    fn from_values_mut(values: &mut AttributeValues) -> Option<&mut Vec<Self>> {
+        match values {
+            AttributeValues::Norm3D(x) => Some(x),
+            _ => None,
+        }
+    }
+    fn into_values(values: Vec<Self>) -> AttributeValues {
+        AttributeValues::Norm3D(values)
+    }
+}
+
+impl IsAttribute for f64 {
+    fn default_attribute() -> Self {
+        0.0
+    }
+    fn from_values(values: &AttributeValues) -> Option<&Vec<Self>> {
+        match values {
+            AttributeValues::Scalar(x) => Some(x),
+            _ => None,
+        }
+    }
+    fn from_values_mut(values: &mut AttributeValues) -> Option<&mut Vec<Self>> {
+        match values {
+            AttributeValues::Scalar(x) => Some(x),
+            _ => None,
+        }
+    }
+    fn into_values(values: Vec<Self>) -> AttributeValues {
+        AttributeValues::Scalar(values)
+    }
+}
+
+impl IsAttribute for u32 {
+    fn default_attribute() -> Self {
+        0
+    }
+    fn from_values(values: &AttributeValues) -> Option<&Vec<Self>> {
+        match values {
+            AttributeValues::Label(x) => Some(x),
+            _ => None,
+        }
+    }
+    fn from_values_mut(values: &mut AttributeValues) -> Option<&mut Vec<Self>> {
+        match values {
+            AttributeValues::Label(x) => Some(x),
+            _ => None,
+        }
+    }
+    fn into_values(values: Vec<Self>) -> AttributeValues {
+        AttributeValues::Label(values)
+    }
+}
@@ -0,0 +1,45 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//! IsEditablePolygonMesh trait used for polygon meshes with editable vertex and face data
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// IsEditablePolygonMesh trait used for polygon meshes with editable vertex and face data
+pub trait IsEditablePolygonMesh<V>: IsPolygonMesh<V> {
+    /// Should add a vertex to the end and return its id
+    fn add_vertex(&mut self, vertex: V) -> VId;
+    /// Should change vertex at vId to the given vertex returning an error on failure
+    fn change_vertex(&mut self, vid: VId, vertex: V) -> Result<()>;
+    /// Should add a face to the mesh by connecting at least 3 vertices via their ids. Should return the id of the newly added face
+    fn try_add_polygon(&mut self, vids: &[VId]) -> Result<FId>;
+    /// Should reserve space for at least n additional vertices
+    fn reserve_vertices(&mut self, n: usize);
+    /// Should reserve space for exactly n additional vertices
+    fn reserve_vertices_exact(&mut self, n: usize);
+    /// Should reserve space for at least n additional faces
+    fn reserve_faces(&mut self, n: usize);
+    /// Should reserve space for exactly n additional faces
+    fn reserve_faces_exact(&mut self, n: usize);
+}
@@ -0,0 +1,64 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//! IsPolygonMesh trait used for meshes with faces of any number of vertices
+
+use crate::*;
+
+//------------------------------------------------------------------------------
+
+/// IsPolygonMesh trait used for meshes with faces of any number of vertices
+pub trait IsPolygonMesh<V> {
+    /// Should return the number of faces within the mesh
+    fn num_faces(&self) -> usize;
+    /// Should return the number of vertices within the mesh
+    fn num_vertices(&self) -> usize;
+    /// Should return the number of vertices of the given face
+    fn face_len(&self, faceid: FId) -> Option<usize>;
+    /// Should return the id of the vertex at index within the given face
+    fn face_vertex_id(&self, faceid: FId, index: usize) -> Option<VId>;
+    /// Should return the vertex with the given id
+    fn vertex(&self, vertexid: VId) -> Option<V>;
+
+    /// Appends the ids of the vertices of the given face (error if id out of bounds)
+    fn face_vertex_ids(&self, faceid: FId, result: &mut Vec<VId>) -> Result<()> {
+        let n = self.face_len(faceid).ok_or(ErrorKind::IncorrectFaceID)?;
+        for i in 0..n {
+            result.push(
+                self.face_vertex_id(faceid, i)
+                    .ok_or(ErrorKind::IncorrectFaceID)?,
+            );
+        }
+        Ok(())
+    }
+    /// Appends the vertices of the given face (error if id out of bounds)
+    fn face_vertices(&self, faceid: FId, result: &mut Vec<V>) -> Result<()> {
+        let n = self.face_len(faceid).ok_or(ErrorKind::IncorrectFaceID)?;
+        for i in 0..n {
+            let vid = self
+                .face_vertex_id(faceid, i)
+                .ok_or(ErrorKind::IncorrectFaceID)?;
+            result.push(self.vertex(vid).ok_or(ErrorKind::IncorrectVertexID)?);
+        }
+        Ok(())
+    }
+}
@@ -223,6 +223,13 @@ pub use self::kd_tree::KdTree;
 mod mesh_3d;
 pub use self::mesh_3d::Mesh3D;
 
+mod triangulation;
+pub(crate) use self::triangulation::FaceTriangulator;
+pub use self::triangulation::Triangulation;
+
+mod polygon_mesh_3d;
+pub use self::polygon_mesh_3d::{PolygonFaceIterator, PolygonFacesIterator, PolygonMesh3D};
+
 mod searchable_mesh;
 pub use self::searchable_mesh::SearchableMesh;
 
@@ -232,6 +239,9 @@ pub use self::oc_tree::OcTree;
 mod view;
 pub use self::view::View;
 
+mod attributes;
+pub use self::attributes::{AttributeChannel, AttributeValues, Attributes};
+
 mod positive;
 pub use self::positive::Positive;
 
@@ -250,6 +260,9 @@ pub use self::face3::Face3;
 mod half_edge;
 pub use self::half_edge::HalfEdge;
 
+mod polygon_half_edge;
+pub use self::polygon_half_edge::PolygonHalfEdge;
+
 mod enums;
 pub use self::enums::*;
 
@@ -352,6 +365,12 @@ pub use self::is_random_insertible::IsRandomInsertible;
 mod is_pushable;
 pub use self::is_pushable::IsPushable;
 
+mod is_attribute;
+pub use self::is_attribute::IsAttribute;
+
+mod has_attributes;
+pub use self::has_attributes::{HasFaceAttributes, HasVertexAttributes};
+
 mod is_face_editable_mesh;
 pub use self::is_face_editable_mesh::IsFaceEditableMesh;
 
@@ -379,6 +398,12 @@ pub use self::is_mesh::IsMesh;
 mod is_mesh_3d;
 pub use self::is_mesh_3d::IsMesh3D;
 
+mod is_polygon_mesh;
+pub use self::is_polygon_mesh::IsPolygonMesh;
+
+mod is_editable_polygon_mesh;
+pub use self::is_editable_polygon_mesh::IsEditablePolygonMesh;
+
 mod is_topology_unit;
 pub use self::is_topology_unit::IsTopologyUnit;
 
@@ -66,6 +66,39 @@ impl Matrix4 {
             ],
         }
     }
+    /// Creates the matrix to transform normals, the cofactor matrix of the upper 3x3 part
+    /// Scaled by the sign of the determinant to keep the orientation
+    pub fn normal_transformation(&self) -> Self {
+        let a = &self.data;
+        let c = [
+            [
+                a[1][1] * a[2][2] - a[1][2] * a[2][1],
+                a[1][2] * a[2][0] - a[1][0] * a[2][2],
+                a[1][0] * a[2][1] - a[1][1] * a[2][0],
+            ],
+            [
+                a[0][2] * a[2][1] - a[0][1] * a[2][2],
+                a[0][0] * a[2][2] - a[0][2] * a[2][0],
+                a[0][1] * a[2][0] - a[0][0] * a[2][1],
+            ],
+            [
+                a[0][1] * a[1][2] - a[0][2] * a[1][1],
+                a[0][2] * a[1][0] - a[0][0] * a[1][2],
+                a[0][0] * a[1][1] - a[0][1] * a[1][0],
+            ],
+        ];
+        let det = a[0][0] * c[0][0] + a[0][1] * c[0][1] + a[0][2] * c[0][2];
+        let s = if det < 0.0 { -1.0 } else { 1.0 };
+
+        Self {
+            data: [
+                [s * c[0][0], s * c[0][1], s * c[0][2], 0.0],
+                [s * c[1][0], s * c[1][1], s * c[1][2], 0.0],
+                [s * c[2][0], s * c[2][1], s * c[2][2], 0.0],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
     /// Creates a new identity matrix
     pub fn identity() -> Matrix4 {
         Matrix4 {
@@ -38,9 +38,30 @@ where
 {
     pc: ID,
     topology: IC,
+    vertex_attributes: Attributes,
+    face_attributes: Attributes,
     _phantom: PhantomData<P>,
 }
 
+impl<P, ID, IC> Mesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P>,
+    IC: IsIndexContainer,
+{
+    /// Resizes the attributes in case vertices or faces were added without them
+    fn sync_attributes(&mut self) {
+        let n_vertices = self.pc.len_d();
+        let n_faces = self.topology.len() / 3;
+        if self.vertex_attributes.len() != n_vertices {
+            self.vertex_attributes.resize(n_vertices)
+        }
+        if self.face_attributes.len() != n_faces {
+            self.face_attributes.resize(n_faces)
+        }
+    }
+}
+
 impl<P, ID, IC> IsMesh<P, Face3> for Mesh3D<P, ID, IC>
 where
     P: Is3D + Clone,
@@ -98,6 +119,7 @@ where
         self.topology.push(vid1.0);
         self.topology.push(vid2.0);
         self.topology.push(vid3.0);
+        self.sync_attributes();
         FId(self.topology.len() / 3 - 1)
     }
 
@@ -111,15 +133,18 @@ where
         self.topology.push(vid1.0);
         self.topology.push(vid2.0);
         self.topology.push(vid3.0);
+        self.sync_attributes();
         Ok(FId(self.topology.len() / 3 - 1))
     }
 
     fn reserve_faces(&mut self, n: usize) {
-        self.topology.reserve(3 * n)
+        self.topology.reserve(3 * n);
+        self.face_attributes.reserve(n)
     }
 
     fn reserve_faces_exact(&mut self, n: usize) {
-        self.topology.reserve_exact(3 * n)
+        self.topology.reserve_exact(3 * n);
+        self.face_attributes.reserve(n)
     }
 }
 
@@ -131,6 +156,7 @@ where
 {
     fn add_vertex(&mut self, vertex: P) -> VId {
         self.pc.push_d(vertex);
+        self.sync_attributes();
         VId(self.pc.len_d() - 1)
     }
 
@@ -144,11 +170,13 @@ where
     }
 
     fn reserve_vertices(&mut self, n: usize) {
-        self.pc.reserve_d(n)
+        self.pc.reserve_d(n);
+        self.vertex_attributes.reserve(n)
     }
 
     fn reserve_vertices_exact(&mut self, n: usize) {
-        self.pc.reserve_d_exact(n)
+        self.pc.reserve_d_exact(n);
+        self.vertex_attributes.reserve(n)
     }
 }
 
@@ -199,6 +227,8 @@ where
 
     fn transform(&mut self, m: &Matrix4) {
         self.pc.transform(m);
+        self.vertex_attributes.transform_normals(m);
+        self.face_attributes.transform_normals(m);
     }
 }
 
@@ -222,6 +252,8 @@ where
     fn clear(&mut self) {
         self.pc.clear();
         self.topology.clear();
+        self.vertex_attributes.clear();
+        self.face_attributes.clear();
     }
 }
 
@@ -232,9 +264,13 @@ where
     IC: IsIndexContainer,
 {
     fn from(pt: (ID, IC)) -> Self {
+        let vertex_attributes = Attributes::new(pt.0.len_d());
+        let face_attributes = Attributes::new(pt.1.len() / 3);
         Self {
             pc: pt.0,
             topology: pt.1,
+            vertex_attributes,
+            face_attributes,
             _phantom: PhantomData::default(),
         }
     }
@@ -250,3 +286,35 @@ where
         (self.pc, self.topology)
     }
 }
+
+impl<P, ID, IC> HasVertexAttributes for Mesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P>,
+    IC: IsIndexContainer,
+{
+    fn vertex_attributes(&self) -> &Attributes {
+        &self.vertex_attributes
+    }
+
+    fn vertex_attributes_mut(&mut self) -> &mut Attributes {
+        self.sync_attributes();
+        &mut self.vertex_attributes
+    }
+}
+
+impl<P, ID, IC> HasFaceAttributes for Mesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P>,
+    IC: IsIndexContainer,
+{
+    fn face_attributes(&self) -> &Attributes {
+        &self.face_attributes
+    }
+
+    fn face_attributes_mut(&mut self) -> &mut Attributes {
+        self.sync_attributes();
+        &mut self.face_attributes
+    }
+}
@@ -23,7 +23,10 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 //! PointCloud3D, a collection of positions within 3D space
 
 use std::{
+    borrow::Cow,
+    cmp::Ordering,
     fmt,
+    hash::{Hash, Hasher},
     ops::{Index, IndexMut},
 };
 
@@ -31,13 +34,16 @@ use crate::*;
 
 //------------------------------------------------------------------------------
 
-#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone, Hash)]
+#[derive(Debug, Clone)]
 /// PointCloud3D, a collection of positions within 3D space
+/// Per point Attributes are kept in sync by all methods, but not if data is changed directly
+/// Comparisons and hashing treat the Attributes as if they were in sync
 pub struct PointCloud3D<P>
 where
     P: Is3D,
 {
     pub data: Vec<P>,
+    attributes: Attributes,
 }
 
 impl<P> PointCloud3D<P>
@@ -46,12 +52,16 @@ where
 {
     /// Creates a new, empty point cloud
     pub fn new() -> PointCloud3D<P> {
-        PointCloud3D { data: Vec::new() }
+        PointCloud3D {
+            data: Vec::new(),
+            attributes: Attributes::default(),
+        }
     }
     /// Creates a new, empty point cloud with capacity
     pub fn with_capacity(n: usize) -> PointCloud3D<P> {
         PointCloud3D {
             data: Vec::with_capacity(n),
+            attributes: Attributes::default(),
         }
     }
     /// Serializes the point cloud
@@ -73,11 +83,46 @@ where
     }
     /// Reserves at least number of vertices
     pub fn reserve_vertices(&mut self, n: usize) {
-        self.data.reserve(n)
+        self.data.reserve(n);
+        self.attributes.reserve(n)
     }
     /// Reserves exactly number of vertices
     pub fn reserve_vertices_exact(&mut self, n: usize) {
-        self.data.reserve_exact(n)
+        self.data.reserve_exact(n);
+        self.attributes.reserve(n)
+    }
+    /// Resizes the attributes in case data was changed directly
+    fn sync_attributes(&mut self) {
+        if self.attributes.len() != self.data.len() {
+            self.attributes.resize(self.data.len())
+        }
+    }
+    /// The attributes as they'd be after syncing
+    fn synced_attributes(&self) -> Cow<'_, Attributes> {
+        if self.attributes.len() == self.data.len() {
+            Cow::Borrowed(&self.attributes)
+        } else {
+            let mut attributes = self.attributes.clone();
+            attributes.resize(self.data.len());
+            Cow::Owned(attributes)
+        }
+    }
+    /// Sorts the points and their attributes
+    fn sort_by<F>(&mut self, cmp: F)
+    where
+        F: Fn(&P, &P) -> Ordering,
+    {
+        if !self.attributes.has_channels() {
+            self.data.sort_by(cmp);
+            return;
+        }
+
+        self.sync_attributes();
+        let data = &self.data;
+        let mut order: Vec<usize> = (0..data.len()).collect();
+        order.sort_by(|a, b| cmp(&data[*a], &data[*b]));
+        permute(&mut self.data, &order);
+        self.attributes.permute(&order);
     }
 }
 
@@ -109,6 +154,7 @@ where
         for i in 0..n {
             self.data.push(ra[i].clone());
         }
+        self.sync_attributes();
     }
 }
 
@@ -119,11 +165,11 @@ where
     P: IsBuildable3D + Clone,
 {
     fn reserve_d(&mut self, n: usize) {
-        self.data.reserve(n);
+        self.reserve_vertices(n);
     }
 
     fn reserve_d_exact(&mut self, n: usize) {
-        self.data.reserve_exact(n);
+        self.reserve_vertices_exact(n);
     }
 
     fn len_d(&self) -> usize {
@@ -179,7 +225,9 @@ where
         if index > self.len() {
             Err(ErrorKind::IncorrectVertexID)
         } else {
+            self.sync_attributes();
             self.data.insert(index, point);
+            self.attributes.insert_default(index);
             Ok(())
         }
     }
@@ -190,13 +238,14 @@ where
     P: Is3D,
 {
     fn push(&mut self, point: P) {
-        self.data.push(point)
+        self.data.push(point);
+        self.sync_attributes();
     }
     fn reserve(&mut self, n: usize) {
-        self.data.reserve(n)
+        self.reserve_vertices(n)
     }
     fn reserve_exact(&mut self, n: usize) {
-        self.data.reserve_exact(n)
+        self.reserve_vertices_exact(n)
     }
 }
 
@@ -273,7 +322,9 @@ where
     P: Is3D + Clone,
 {
     fn apply_view(&mut self, view: &View) -> Result<()> {
+        self.sync_attributes();
         self.data.apply_view(view)?;
+        self.attributes.apply_view(view)?;
         Ok(())
     }
 
@@ -316,15 +367,15 @@ where
     P: Is3D,
 {
     fn sort_x(&mut self) {
-        sort_vec_3d_x(&mut self.data);
+        self.sort_by(cmp_3d_x);
     }
 
     fn sort_y(&mut self) {
-        sort_vec_3d_y(&mut self.data);
+        self.sort_by(cmp_3d_y);
     }
 
     fn sort_z(&mut self) {
-        sort_vec_3d_z(&mut self.data);
+        self.sort_by(cmp_3d_z);
     }
 }
 
@@ -332,10 +383,11 @@ impl<P> IsMergeable for PointCloud3D<P>
 where
     P: Is3D + Clone,
 {
-    fn consume(&mut self, other: Self) {
-        for p in other.data {
-            self.data.push(p.clone());
-        }
+    fn consume(&mut self, mut other: Self) {
+        self.sync_attributes();
+        other.sync_attributes();
+        self.data.append(&mut other.data);
+        self.attributes.append(other.attributes);
     }
 
     fn combine(&self, other: &Self) -> Self {
@@ -373,6 +425,7 @@ where
         for p in &mut self.data {
             p.transform(m);
         }
+        self.attributes.transform_normals(m);
     }
 }
 
@@ -381,7 +434,8 @@ where
     P: Is3D,
 {
     fn clear(&mut self) {
-        self.data.clear()
+        self.data.clear();
+        self.attributes.clear()
     }
 }
 
@@ -391,8 +445,53 @@ where
     P: Is3D,
 {
     fn default() -> Self {
-        let data = Vec::new();
-        PointCloud3D { data }
+        Self::new()
+    }
+}
+
+impl<P> PartialEq for PointCloud3D<P>
+where
+    P: Is3D + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data && self.synced_attributes() == other.synced_attributes()
+    }
+}
+
+impl<P> Eq for PointCloud3D<P> where P: Is3D + Eq {}
+
+impl<P> PartialOrd for PointCloud3D<P>
+where
+    P: Is3D + PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        match self.data.partial_cmp(&other.data) {
+            Some(Ordering::Equal) => self
+                .synced_attributes()
+                .partial_cmp(&other.synced_attributes()),
+            x => x,
+        }
+    }
+}
+
+impl<P> Ord for PointCloud3D<P>
+where
+    P: Is3D + Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.data
+            .cmp(&other.data)
+            .then_with(|| self.synced_attributes().cmp(&other.synced_attributes()))
+    }
+}
+
+impl<P> Hash for PointCloud3D<P>
+where
+    P: Is3D + Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.data.hash(state);
+        self.synced_attributes().hash(state);
     }
 }
 
@@ -423,6 +522,21 @@ where
     P: Is3D,
 {
     fn from(data: Vec<P>) -> Self {
-        Self { data }
+        let attributes = Attributes::new(data.len());
+        Self { data, attributes }
+    }
+}
+
+impl<P> HasVertexAttributes for PointCloud3D<P>
+where
+    P: Is3D,
+{
+    fn vertex_attributes(&self) -> &Attributes {
+        &self.attributes
+    }
+
+    fn vertex_attributes_mut(&mut self) -> &mut Attributes {
+        self.sync_attributes();
+        &mut self.attributes
     }
 }
@@ -0,0 +1,228 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//! PolygonHalfEdge, the half edge data structure for meshes with faces of any number of vertices
+
+use crate::*;
+
+use crate::half_edge::safe_append_at;
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// PolygonHalfEdge, the half edge data structure for meshes with faces of any number of vertices
+/// The edges of a face are stored consecutively, following the order of its vertices
+pub struct PolygonHalfEdge<IC>
+where
+    IC: IsIndexContainer,
+{
+    tails: IC,
+    twins: Vec<Option<EId>>,
+    /// The face of each edge
+    faces: IC,
+    /// The exclusive end of the edges of each face
+    ends: Vec<usize>,
+    vertices_start_edges: Vec<IC>,
+}
+
+impl<IC> PolygonHalfEdge<IC>
+where
+    IC: IsIndexContainer,
+{
+    /// Creates a new PolygonHalfEdge for the given IsPolygonMesh
+    /// This only stays valid if the mesh is not changed after creation
+    /// The mesh must be manifold
+    pub fn new<T, M>(mesh: &M) -> Self
+    where
+        M: IsPolygonMesh<T>,
+    {
+        let n_vertices = mesh.num_vertices();
+        let n_faces = mesh.num_faces();
+        let n_edges = (0..n_faces)
+            .map(|i| mesh.face_len(FId(i)).unwrap_or(0))
+            .sum();
+
+        let mut tails = IC::with_capacity_and_support_for(n_edges, n_vertices);
+        let mut faces = IC::with_capacity_and_support_for(n_edges, n_faces);
+        let mut ends = Vec::with_capacity(n_faces);
+
+        let twins = vec![None; n_edges];
+        let mut vertices_start_edges = Vec::with_capacity(n_vertices);
+        let estimated_edges_per_vertex = 4; // true for all vertices within regular quad meshes
+
+        for i in 0..n_faces {
+            let n = mesh.face_len(FId(i)).unwrap_or(0); // safe since iterating n_faces
+            for j in 0..n {
+                if let Some(vid) = mesh.face_vertex_id(FId(i), j) {
+                    safe_append_at(
+                        &mut vertices_start_edges,
+                        estimated_edges_per_vertex,
+                        n_edges,
+                        vid.0,
+                        tails.len(),
+                    );
+                    tails.push(vid.0);
+                    faces.push(i);
+                }
+            }
+            ends.push(tails.len());
+        }
+
+        let mut result = PolygonHalfEdge {
+            tails,
+            twins,
+            faces,
+            ends,
+            vertices_start_edges,
+        };
+
+        // For each edge, get tail of next
+        // Of this get all edges originating
+        // Of these the one where next has the same tail as the edge must be the twin
+        let mut cache = Vec::new();
+        for i in 0..result.tails.len() {
+            cache.clear();
+            let _ = result.next(EId(i)).and_then(&mut |next_id: EId| {
+                result
+                    .edges_originating(VId(result.tails.get(next_id.0)), &mut cache)
+                    .ok()
+            });
+            for originating_id in cache.iter() {
+                let _ = result.next(*originating_id).map(|candidate_next_id| {
+                    if result.tails.get(candidate_next_id.0) == result.tails.get(i) {
+                        result.twins[i] = Some(*originating_id)
+                    }
+                });
+            }
+        }
+        result
+    }
+    /// Returns the ID of the vertex the edge originates from (None if id out of bounds)
+    pub fn tail(&self, id: EId) -> Option<VId> {
+        self.ensure_edge_id(id).ok()?;
+        Some(VId(self.tails.get(id.0)))
+    }
+    /// Returns the ID of the face the edge belongs to (None if id out of bounds)
+    pub fn face(&self, id: EId) -> Option<FId> {
+        self.ensure_edge_id(id).ok()?;
+        Some(FId(self.faces.get(id.0)))
+    }
+    /// Returns the ID of the twin edge (None if there isn't any / id out of bounds)
+    pub fn twin(&self, id: EId) -> Option<EId> {
+        self.ensure_edge_id(id).ok()?;
+        self.twins.get(id.0).cloned().flatten()
+    }
+    /// Returns the ID of the edge after this edge (None if id out of bounds)
+    pub fn next(&self, id: EId) -> Option<EId> {
+        let face = self.face(id)?;
+        if id.0 + 1 == self.ends[face.0] {
+            return Some(EId(self.face_start(face)));
+        }
+        Some(EId(id.0 + 1))
+    }
+    /// Returns the ID of the edge before this edge (None if id out of bounds)
+    pub fn prev(&self, id: EId) -> Option<EId> {
+        let face = self.face(id)?;
+        if id.0 == self.face_start(face) {
+            return Some(EId(self.ends[face.0] - 1));
+        }
+        Some(EId(id.0 - 1))
+    }
+    /// Appends all edges of the given face, following the order of its vertices (error if id out of bounds)
+    pub fn face_edges(&self, id: FId, result: &mut Vec<EId>) -> Result<()> {
+        self.ensure_face_id(id)?;
+        result.extend((self.face_start(id)..self.ends[id.0]).map(EId));
+        Ok(())
+    }
+    /// Appends all edges originating (pointing away) from the given vertex (error if id out of bounds)
+    pub fn edges_originating(&self, id: VId, result: &mut Vec<EId>) -> Result<()> {
+        self.ensure_vertex_id(id)?;
+        result.extend(self.vertices_start_edges[id.0].iter().map(EId));
+        Ok(())
+    }
+    /// Appends all edges ending (pointing at) the given vertex (error if id out of bounds)
+    /// cache is used to avoid allocations, pass any Vec
+    pub fn edges_ending(&self, id: VId, cache: &mut Vec<EId>, result: &mut Vec<EId>) -> Result<()> {
+        cache.clear();
+        self.edges_originating(id, cache)?;
+        for edge in cache {
+            if let Some(id) = self.prev(*edge) {
+                result.push(id)
+            }
+        }
+        Ok(())
+    }
+    /// Appends all edges connected to the vertex (both originating and ending) (error if id out of bounds)
+    /// cache is used to avoid allocations, pass any Vec
+    pub fn edges_all(&self, id: VId, cache: &mut Vec<EId>, result: &mut Vec<EId>) -> Result<()> {
+        cache.clear();
+        self.edges_originating(id, cache)?;
+        for edge in cache {
+            result.push(*edge);
+            if let Some(id) = self.prev(*edge) {
+                result.push(id)
+            }
+        }
+        Ok(())
+    }
+    /// Appends all faces a vertex is part of (error if id out of bounds)
+    /// cache is used to avoid allocations, pass any Vec
+    pub fn faces(&self, id: VId, cache: &mut Vec<EId>, result: &mut Vec<FId>) -> Result<()> {
+        cache.clear();
+        self.edges_originating(id, cache)?;
+        for edge in cache {
+            if let Some(id) = self.face(*edge) {
+                result.push(id)
+            }
+        }
+        Ok(())
+    }
+    /// Returns the ID of the first edge within the face
+    fn face_start(&self, id: FId) -> usize {
+        if id.0 == 0 {
+            0
+        } else {
+            self.ends[id.0 - 1]
+        }
+    }
+    /// Fails if the edge ID is out of bounds
+    pub fn ensure_edge_id(&self, id: EId) -> Result<()> {
+        if id.0 >= self.tails.len() {
+            return Err(ErrorKind::IncorrectEdgeID);
+        }
+        Ok(())
+    }
+    /// Fails if the face ID is out of bounds
+    pub fn ensure_face_id(&self, id: FId) -> Result<()> {
+        if id.0 >= self.ends.len() {
+            return Err(ErrorKind::IncorrectFaceID);
+        }
+        Ok(())
+    }
+    /// Fails if the vertex ID is out of bounds
+    pub fn ensure_vertex_id(&self, id: VId) -> Result<()> {
+        if id.0 >= self.vertices_start_edges.len() {
+            return Err(ErrorKind::IncorrectVertexID);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,468 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+//! PolygonMesh3D, a mesh with faces of any number of vertices within 3D space
+
+use crate::*;
+
+use std::{iter::FusedIterator, marker::PhantomData};
+
+//------------------------------------------------------------------------------
+
+#[derive(Default, Debug, PartialEq, PartialOrd, Ord, Eq, Clone, Hash)]
+/// PolygonMesh3D, a mesh with faces of any number of vertices within 3D space
+/// E.g. quad dominant meshes or meshes with n-gon faces
+pub struct PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P>,
+    IC: IsIndexContainer,
+{
+    pc: ID,
+    /// The vertex ids of all faces, stored one face after the other
+    indices: IC,
+    /// The exclusive end of each face within indices
+    ends: Vec<usize>,
+    vertex_attributes: Attributes,
+    face_attributes: Attributes,
+    _phantom: PhantomData<P>,
+}
+
+impl<P, ID, IC> PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P>,
+    IC: IsIndexContainer,
+{
+    /// Returns an iterator over the vertex ids of the given face
+    pub fn face(&self, faceid: FId) -> Option<PolygonFaceIterator<'_, IC>> {
+        let end = *self.ends[..].get(faceid.0)?;
+        Some(PolygonFaceIterator::new(
+            &self.indices,
+            self.face_start(faceid.0),
+            end,
+        ))
+    }
+    /// Returns an iterator over all faces, yielding iterators over their vertex ids
+    pub fn faces(&self) -> PolygonFacesIterator<'_, IC> {
+        PolygonFacesIterator::new(&self.indices, &self.ends)
+    }
+    /// Returns true if all faces are triangles
+    pub fn is_triangle_mesh(&self) -> bool {
+        3 * self.ends.len() == self.indices.len()
+    }
+    /// Creates a tri mesh, splitting all faces via triangulation
+    /// Each resulting triangle has the face attributes of its polygon
+    pub fn triangulated(&self, triangulation: Triangulation) -> Mesh3D<P, ID, IC>
+    where
+        P: IsEditable3D + IsBuildable3D + Clone,
+        ID: Clone,
+    {
+        let mut triangulator = FaceTriangulator::new(triangulation);
+        for i in 0..self.pc.len_d() {
+            if let Some(p) = self.pc.get_d(i) {
+                triangulator.add_vertex(&p)
+            }
+        }
+
+        let mut topology = IC::with_capacity_and_support_for(
+            3 * (self.indices.len() - 2 * self.ends.len()),
+            self.pc.len_d(),
+        );
+        let mut polygon_ids = Vec::new();
+        let mut buffer = Vec::new();
+
+        for (i, face) in self.faces().enumerate() {
+            buffer.clear();
+            buffer.extend(face.map(|x| x.0));
+            triangulator.add_face(&buffer);
+            while let Some([a, b, c]) = triangulator.next_face() {
+                topology.push(a);
+                topology.push(b);
+                topology.push(c);
+                polygon_ids.push(i);
+            }
+        }
+
+        let face_attributes = self
+            .face_attributes
+            .select(&polygon_ids)
+            .unwrap_or_else(|_| Attributes::new(polygon_ids.len()));
+
+        let mut mesh = Mesh3D::from((self.pc.clone(), topology));
+        *mesh.vertex_attributes_mut() = self.vertex_attributes.clone();
+        *mesh.face_attributes_mut() = face_attributes;
+        mesh
+    }
+    /// Returns the index of the first vertex id of the face within indices
+    fn face_start(&self, i: usize) -> usize {
+        if i == 0 {
+            0
+        } else {
+            self.ends[i - 1]
+        }
+    }
+    /// Resizes the attributes in case vertices or faces were added without them
+    fn sync_attributes(&mut self) {
+        let n_vertices = self.pc.len_d();
+        let n_faces = self.ends.len();
+        if self.vertex_attributes.len() != n_vertices {
+            self.vertex_attributes.resize(n_vertices)
+        }
+        if self.face_attributes.len() != n_faces {
+            self.face_attributes.resize(n_faces)
+        }
+    }
+}
+
+impl<P, ID, IC> IsPolygonMesh<P> for PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D + Clone,
+    ID: IsDataContainer<P>,
+    IC: IsIndexContainer,
+{
+    fn num_faces(&self) -> usize {
+        self.ends.len()
+    }
+
+    fn num_vertices(&self) -> usize {
+        self.pc.len_d()
+    }
+
+    fn face_len(&self, faceid: FId) -> Option<usize> {
+        let end = *self.ends[..].get(faceid.0)?;
+        Some(end - self.face_start(faceid.0))
+    }
+
+    fn face_vertex_id(&self, faceid: FId, index: usize) -> Option<VId> {
+        let end = *self.ends[..].get(faceid.0)?;
+        let i = self.face_start(faceid.0) + index;
+        if i >= end {
+            return None;
+        }
+        Some(VId(self.indices.get(i)))
+    }
+
+    fn vertex(&self, vertexid: VId) -> Option<P> {
+        self.pc.get_d(vertexid.0)
+    }
+}
+
+impl<P, ID, IC> IsEditablePolygonMesh<P> for PolygonMesh3D<P, ID, IC>
+where
+    P: IsEditable3D + IsBuildable3D + Clone,
+    ID: IsDataContainer<P>,
+    IC: IsIndexContainer,
+{
+    fn add_vertex(&mut self, vertex: P) -> VId {
+        self.pc.push_d(vertex);
+        self.sync_attributes();
+        VId(self.pc.len_d() - 1)
+    }
+
+    fn change_vertex(&mut self, vid: VId, vertex: P) -> Result<()> {
+        if vid.0 < self.pc.len_d() {
+            self.pc.set_d(vid.0, vertex);
+            Ok(())
+        } else {
+            Err(ErrorKind::IncorrectVertexID)
+        }
+    }
+
+    fn try_add_polygon(&mut self, vids: &[VId]) -> Result<FId> {
+        if vids.len() < 3 {
+            return Err(ErrorKind::TooFewPoints);
+        }
+        if vids.iter().any(|x| x.0 >= self.pc.len_d()) {
+            return Err(ErrorKind::IncorrectVertexID);
+        }
+        for (i, vid) in vids.iter().enumerate() {
+            if vids[i + 1..].contains(vid) {
+                return Err(ErrorKind::FaceIDsNotUnique);
+            }
+        }
+        for vid in vids {
+            self.indices.push(vid.0);
+        }
+        self.ends.push(self.indices.len());
+        self.sync_attributes();
+        Ok(FId(self.ends.len() - 1))
+    }
+
+    fn reserve_vertices(&mut self, n: usize) {
+        self.pc.reserve_d(n);
+        self.vertex_attributes.reserve(n)
+    }
+
+    fn reserve_vertices_exact(&mut self, n: usize) {
+        self.pc.reserve_d_exact(n);
+        self.vertex_attributes.reserve(n)
+    }
+
+    fn reserve_faces(&mut self, n: usize) {
+        // estimate for quad dominant meshes
+        self.indices.reserve(4 * n);
+        self.ends.reserve(n);
+        self.face_attributes.reserve(n)
+    }
+
+    fn reserve_faces_exact(&mut self, n: usize) {
+        self.indices.reserve_exact(4 * n);
+        self.ends.reserve_exact(n);
+        self.face_attributes.reserve(n)
+    }
+}
+
+impl<P, ID, IC> HasBoundingBox3DMaybe for PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P> + HasBoundingBox3DMaybe,
+    IC: IsIndexContainer,
+{
+    fn bounding_box_maybe(&self) -> Option<BoundingBox3D> {
+        self.pc.bounding_box_maybe()
+    }
+}
+
+impl<P, ID, IC> HasCenterOfGravity3D for PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P> + HasCenterOfGravity3D,
+    IC: IsIndexContainer,
+{
+    fn center_of_gravity(&self) -> Option<Point3D> {
+        self.pc.center_of_gravity()
+    }
+}
+
+impl<P, ID, IC> IsScalable for PolygonMesh3D<P, ID, IC>
+where
+    P: IsEditable3D,
+    ID: IsDataContainer<P> + IsScalable,
+    IC: IsIndexContainer,
+{
+    fn scale(&mut self, factor: Positive) {
+        self.pc.scale(factor);
+    }
+}
+
+impl<P, ID, IC> IsMatrix4Transformable for PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D + IsMatrix4Transformable + Clone,
+    ID: IsDataContainer<P> + IsMatrix4Transformable + Clone,
+    IC: IsIndexContainer,
+{
+    fn transformed(&self, m: &Matrix4) -> Self {
+        let mut new = self.clone();
+        new.transform(m);
+        new
+    }
+
+    fn transform(&mut self, m: &Matrix4) {
+        self.pc.transform(m);
+        self.vertex_attributes.transform_normals(m);
+        self.face_attributes.transform_normals(m);
+    }
+}
+
+impl<P, ID, IC> IsMovable3D for PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D + IsMovable3D,
+    ID: IsDataContainer<P> + IsMovable3D,
+    IC: IsIndexContainer,
+{
+    fn move_by(&mut self, x: f64, y: f64, z: f64) {
+        self.pc.move_by(x, y, z)
+    }
+}
+
+impl<P, ID, IC> IsClearable for PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P> + IsClearable,
+    IC: IsIndexContainer + IsClearable,
+{
+    fn clear(&mut self) {
+        self.pc.clear();
+        self.indices.clear();
+        self.ends.clear();
+        self.vertex_attributes.clear();
+        self.face_attributes.clear();
+    }
+}
+
+impl<P, ID, IC> From<Mesh3D<P, ID, IC>> for PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P>,
+    IC: IsIndexContainer,
+{
+    fn from(mesh: Mesh3D<P, ID, IC>) -> Self {
+        let vertex_attributes = mesh.vertex_attributes().clone();
+        let face_attributes = mesh.face_attributes().clone();
+        let (pc, indices): (ID, IC) = mesh.into();
+        let ends = (1..=indices.len() / 3).map(|i| 3 * i).collect();
+
+        let mut result = Self {
+            pc,
+            indices,
+            ends,
+            vertex_attributes,
+            face_attributes,
+            _phantom: PhantomData,
+        };
+        result.sync_attributes();
+        result
+    }
+}
+
+impl<P, ID, IC> HasVertexAttributes for PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P>,
+    IC: IsIndexContainer,
+{
+    fn vertex_attributes(&self) -> &Attributes {
+        &self.vertex_attributes
+    }
+
+    fn vertex_attributes_mut(&mut self) -> &mut Attributes {
+        self.sync_attributes();
+        &mut self.vertex_attributes
+    }
+}
+
+impl<P, ID, IC> HasFaceAttributes for PolygonMesh3D<P, ID, IC>
+where
+    P: Is3D,
+    ID: IsDataContainer<P>,
+    IC: IsIndexContainer,
+{
+    fn face_attributes(&self) -> &Attributes {
+        &self.face_attributes
+    }
+
+    fn face_attributes_mut(&mut self) -> &mut Attributes {
+        self.sync_attributes();
+        &mut self.face_attributes
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Iterator over the vertex ids of a single face of a PolygonMesh3D
+pub struct PolygonFaceIterator<'a, IC>
+where
+    IC: IsIndexContainer,
+{
+    indices: &'a IC,
+    index: usize,
+    end: usize,
+}
+
+impl<'a, IC> PolygonFaceIterator<'a, IC>
+where
+    IC: IsIndexContainer,
+{
+    fn new(indices: &'a IC, index: usize, end: usize) -> Self {
+        Self {
+            indices,
+            index,
+            end,
+        }
+    }
+}
+
+impl<'a, IC> Iterator for PolygonFaceIterator<'a, IC>
+where
+    IC: IsIndexContainer,
+{
+    type Item = VId;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<VId> {
+        if self.index < self.end {
+            self.index += 1;
+            Some(VId(self.indices.get(self.index - 1)))
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.end - self.index;
+        (n, Some(n))
+    }
+}
+
+impl<'a, IC> ExactSizeIterator for PolygonFaceIterator<'a, IC> where IC: IsIndexContainer {}
+
+impl<'a, IC> FusedIterator for PolygonFaceIterator<'a, IC> where IC: IsIndexContainer {}
+
+//------------------------------------------------------------------------------
+
+/// Iterator over the faces of a PolygonMesh3D
+pub struct PolygonFacesIterator<'a, IC>
+where
+    IC: IsIndexContainer,
+{
+    indices: &'a IC,
+    ends: &'a [usize],
+    start: usize,
+}
+
+impl<'a, IC> PolygonFacesIterator<'a, IC>
+where
+    IC: IsIndexContainer,
+{
+    fn new(indices: &'a IC, ends: &'a [usize]) -> Self {
+        Self {
+            indices,
+            ends,
+            start: 0,
+        }
+    }
+}
+
+impl<'a, IC> Iterator for PolygonFacesIterator<'a, IC>
+where
+    IC: IsIndexContainer,
+{
+    type Item = PolygonFaceIterator<'a, IC>;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (end, rest) = self.ends.split_first()?;
+        let face = PolygonFaceIterator::new(self.indices, self.start, *end);
+        self.start = *end;
+        self.ends = rest;
+        Some(face)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.ends.len(), Some(self.ends.len()))
+    }
+}
+
+impl<'a, IC> ExactSizeIterator for PolygonFacesIterator<'a, IC> where IC: IsIndexContainer {}
+
+impl<'a, IC> FusedIterator for PolygonFacesIterator<'a, IC> where IC: IsIndexContainer {}
@@ -58,6 +58,7 @@ pub enum ErrorKind {
     ClusterTooBig,
     CantCalculateAngleIfZeroLength,
     TriFace3DNotSpanningVolume,
+    IncorrectAttributeLength,
     IOError(IOError),
 }
 
@@ -96,6 +97,12 @@ impl fmt::Debug for ErrorKind {
                 f,
                 "TriFace3D must be constructed from points spanning a volume"
             ),
+            Self::IncorrectAttributeLength => {
+                write!(
+                    f,
+                    "Number of attribute values doesn't match the number of elements"
+                )
+            }
             Self::IOError(x) => x.fmt(f),
         }
     }
@@ -0,0 +1,179 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+//! Module for splitting polygonal faces into triangles
+
+use crate::*;
+
+use std::collections::VecDeque;
+
+//------------------------------------------------------------------------------
+
+/// Strategy to split polygonal faces into triangles
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Triangulation {
+    /// Connects the first vertex with all other vertices, only correct for convex faces
+    #[default]
+    Fan,
+    /// Repeatedly removes ears of the face, also correct for concave faces
+    EarClipping,
+}
+
+//------------------------------------------------------------------------------
+
+/// Splits polygonal faces into triangles, buffering the resulting faces
+pub struct FaceTriangulator {
+    triangulation: Triangulation,
+    /// Only required and filled for ear clipping
+    positions: Vec<[f64; 3]>,
+    pending: VecDeque<[usize; 3]>,
+}
+
+impl FaceTriangulator {
+    pub fn new(triangulation: Triangulation) -> Self {
+        Self {
+            triangulation,
+            positions: Vec::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Adds the position of the next vertex
+    #[inline(always)]
+    pub fn add_vertex<P>(&mut self, p: &P)
+    where
+        P: Is3D,
+    {
+        if self.triangulation == Triangulation::EarClipping {
+            self.positions.push([p.x(), p.y(), p.z()])
+        }
+    }
+
+    /// Triangulates a face, returning false if it has less than three vertices
+    pub fn add_face(&mut self, ids: &[usize]) -> bool {
+        match ids.len() {
+            0..=2 => return false,
+            3 => self.pending.push_back([ids[0], ids[1], ids[2]]),
+            _ => match self.triangulation {
+                Triangulation::Fan => self.fan(ids),
+                Triangulation::EarClipping => self.ear_clipping(ids),
+            },
+        }
+
+        true
+    }
+
+    /// Returns the next buffered triangle
+    #[inline(always)]
+    pub fn next_face(&mut self) -> Option<[usize; 3]> {
+        self.pending.pop_front()
+    }
+
+    fn fan(&mut self, ids: &[usize]) {
+        for i in 1..ids.len() - 1 {
+            self.pending.push_back([ids[0], ids[i], ids[i + 1]])
+        }
+    }
+
+    fn ear_clipping(&mut self, ids: &[usize]) {
+        let mut ps = Vec::with_capacity(ids.len());
+        for id in ids {
+            match self.positions.get(*id) {
+                Some(p) => ps.push(*p),
+                // Invalid index, the caller reports the error when adding the faces
+                None => return self.fan(ids),
+            }
+        }
+
+        // Project onto the plane of the dominant axis of the Newell normal
+        let mut n = [0.0; 3];
+        for i in 0..ps.len() {
+            let [x1, y1, z1] = ps[i];
+            let [x2, y2, z2] = ps[(i + 1) % ps.len()];
+            n[0] += (y1 - y2) * (z1 + z2);
+            n[1] += (z1 - z2) * (x1 + x2);
+            n[2] += (x1 - x2) * (y1 + y2);
+        }
+
+        let (u, v, sign) = if n[0].abs() >= n[1].abs() && n[0].abs() >= n[2].abs() {
+            (1, 2, n[0].signum())
+        } else if n[1].abs() >= n[2].abs() {
+            (2, 0, n[1].signum())
+        } else {
+            (0, 1, n[2].signum())
+        };
+
+        let projected: Vec<[f64; 2]> = ps.iter().map(|p| [p[u], sign * p[v]]).collect();
+
+        let mut remaining: Vec<usize> = (0..ids.len()).collect();
+
+        while remaining.len() > 3 {
+            let n = remaining.len();
+            let ear = (0..n).find(|&i| {
+                let a = remaining[(i + n - 1) % n];
+                let b = remaining[i];
+                let c = remaining[(i + 1) % n];
+                is_ear(&projected, &remaining, a, b, c)
+            });
+
+            match ear {
+                Some(i) => {
+                    let a = remaining[(i + n - 1) % n];
+                    let b = remaining[i];
+                    let c = remaining[(i + 1) % n];
+                    self.pending.push_back([ids[a], ids[b], ids[c]]);
+                    remaining.remove(i);
+                }
+                // Degenerated or self intersecting face
+                None => {
+                    let rest: Vec<usize> = remaining.iter().map(|i| ids[i]).collect();
+                    return self.fan(&rest);
+                }
+            }
+        }
+
+        self.pending
+            .push_back([ids[remaining[0]], ids[remaining[1]], ids[remaining[2]]]);
+    }
+}
+
+//------------------------------------------------------------------------------
+
+fn cross_2d(a: &[f64; 2], b: &[f64; 2], c: &[f64; 2]) -> f64 {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn is_ear(ps: &[[f64; 2]], remaining: &[usize], a: usize, b: usize, c: usize) -> bool {
+    let (pa, pb, pc) = (&ps[a], &ps[b], &ps[c]);
+
+    // Projection keeps counter clockwise orientation, so ears have to be convex in that direction
+    if cross_2d(pa, pb, pc) <= 0.0 {
+        return false;
+    }
+
+    !remaining.iter().any(|&i| {
+        if i == a || i == b || i == c {
+            return false;
+        }
+        let p = &ps[i];
+        cross_2d(pa, pb, p) >= 0.0 && cross_2d(pb, pc, p) >= 0.0 && cross_2d(pc, pa, p) >= 0.0
+    })
+}
@@ -76,6 +76,13 @@ where
     x.to_bits().hash(state);
 }
 
+/// Reorders xs, the i-th element becomes the element at order[i].
+/// order must only contain valid indices, each at most once
+pub fn permute<T>(xs: &mut Vec<T>, order: &[usize]) {
+    let mut old: Vec<Option<T>> = xs.drain(..).map(Some).collect();
+    xs.extend(order.iter().filter_map(|i| old[*i].take()));
+}
+
 /// Returns a container with duplicates removed and indices representing the original order
 pub fn pack_dupes_indexed<'a, I, T>(idata: I) -> (Vec<T>, Vec<usize>)
 where
@@ -0,0 +1,162 @@
+/*
+Copyright 2020 Martin Buck
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"),
+to deal in the Software without restriction, including without limitation the
+rights to use, copy, modify, merge, publish, distribute, sublicense,
+and/or sell copies of the Software, and to permit persons to whom the Software
+is furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall
+be included all copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND,
+EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF
+MERCHANTABILITY, FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT.
+IN NO EVENT SHALL THE AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM,
+DAMAGES OR OTHER LIABILITY, WHETHER IN AN ACTION OF CONTRACT,
+TORT OR OTHERWISE, ARISING FROM, OUT OF OR IN CONNECTION WITH THE SOFTWARE
+OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
+*/
+
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rust-3d"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn cli_convert_and_info() {
+    let output = run(&[
+        "convert",
+        "tests/data/torus_only_vertex_data.ply",
+        "tests/tmp/cli_torus.stl",
+    ]);
+    assert!(output.status.code() == Some(0));
+
+    let output = run(&["info", "tests/tmp/cli_torus.stl"]);
+    assert!(output.status.code() == Some(0));
+    let text = stdout(&output);
+    assert!(text.contains("format: stl (binary)"));
+    assert!(text.contains("vertices: 576"));
+    assert!(text.contains("faces: 1152"));
+}
+
+#[test]
+fn cli_filter() {
+    let output = run(&[
+        "filter",
+        "tests/data/test_cube.xyz",
+        "tests/tmp/cli_filter_box.xyz",
+        "box",
+        "0",
+        "0",
+        "0",
+        "5",
+        "5",
+        "5",
+    ]);
+    assert!(output.status.code() == Some(0));
+
+    let text = stdout(&run(&["info", "tests/tmp/cli_filter_box.xyz"]));
+    assert!(text.contains("points: 27"));
+    assert!(text.contains("min: 0 0 0"));
+    assert!(text.contains("max: 2 2 2"));
+
+    let output = run(&[
+        "filter",
+        "tests/data/test_cube.xyz",
+        "tests/tmp/cli_filter_sphere.xyz",
+        "sphere",
+        "0",
+        "0",
+        "0",
+        "-1",
+    ]);
+    assert!(output.status.code() == Some(2));
+}
+
+#[test]
+fn cli_transform() {
+    let output = run(&[
+        "transform",
+        "tests/data/test_cube.xyz",
+        "tests/tmp/cli_transform.pcd",
+        "--scale",
+        "2",
+        "2",
+        "2",
+        "--translate",
+        "1",
+        "2",
+        "3",
+    ]);
+    assert!(output.status.code() == Some(0));
+
+    let text = stdout(&run(&["info", "tests/tmp/cli_transform.pcd"]));
+    assert!(text.contains("points: 8000"));
+    assert!(text.contains("min: 1 2 3"));
+    assert!(text.contains("max: 39 40 41"));
+}
+
+#[test]
+fn cli_merge() {
+    let output = run(&[
+        "merge",
+        "tests/tmp/cli_merge.obj",
+        "tests/data/torus_only_vertex_data.ply",
+        "tests/data/torus_only_vertex_data.ply",
+    ]);
+    assert!(output.status.code() == Some(0));
+
+    let text = stdout(&run(&["info", "tests/tmp/cli_merge.obj"]));
+    assert!(text.contains("faces: 2304"));
+
+    let output = run(&[
+        "merge",
+        "tests/tmp/cli_merge.xyz",
+        "tests/data/torus_only_vertex_data.ply",
+        "tests/data/test_cube.xyz",
+    ]);
+    assert!(output.status.code() == Some(0));
+
+    let text = stdout(&run(&["info", "tests/tmp/cli_merge.xyz"]));
+    assert!(text.contains("points: 8576"));
+}
+
+#[test]
+fn cli_exit_codes() {
+    assert!(run(&[]).status.code() == Some(2));
+    assert!(run(&["unknown"]).status.code() == Some(2));
+    assert!(run(&["convert", "tests/data/test_cube.xyz"]).status.code() == Some(2));
+    assert!(
+        run(&["convert", "tests/data/test_cube.xyz", "tests/tmp/cli.stl"])
+            .status
+            .code()
+            == Some(2)
+    );
+    assert!(
+        run(&[
+            "convert",
+            "tests/data/test_cube.xyz",
+            "tests/tmp/cli.unknown"
+        ])
+        .status
+        .code()
+            == Some(2)
+    );
+    assert!(
+        run(&["info", "tests/data/does_not_exist.ply"])
+            .status
+            .code()
+            == Some(1)
+    );
+}
@@ -0,0 +1,23 @@
+; test toolpath with two layers
+G21 ; millimeters
+G90
+M82
+G92 E0
+G28 ; home
+G1 Z0.2 F1200
+G0 X10 Y10 F3000
+G1 X20 Y10 E1.0 F1500
+G1 X20 Y20 E2.0
+G2 X10 Y20 I-5 J0 E3.0 (half circle)
+G1 E2.0 ; retract
+G0 X0 Y0
+G92 E0
+N10 G1 Z0.4*57
+G91
+G1 X5 E0.5
+G3 X5 Y5 R5 E0.5
+G90
+M83
+M117 Print X100
+G20
+G1 X1 Y1 E0.1
@@ -22,7 +22,10 @@ OR THE USE OR OTHER DEALINGS IN THE SOFTWARE.
 
 use rust_3d::{io::*, *};
 
-use std::{fs::File, io::BufReader};
+use std::{
+    fs::File,
+    io::{BufReader, Cursor},
+};
 
 #[test]
 fn mesh_io_test() {
@@ -50,3 +53,1100 @@ fn mesh_io_test() {
     assert!(m.num_faces() == 1152);
     assert!(m.num_vertices() == 576);
 }
+
+#[test]
+fn stl_binary_io_test() {
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/torus_only_vertex_data.ply").unwrap()),
+        &mut m,
+    )
+    .unwrap();
+
+    save_stl_binary(
+        &mut File::create("tests/tmp/torus_only_vertex_data.stl").unwrap(),
+        &m,
+        b"binary STL generated by rust-3d",
+        0,
+    )
+    .unwrap();
+
+    let mut m_loaded = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    let mut normals = Vec::<Point3D>::new();
+    load_stl_mesh_unique::<_, _, _, _, _, 30>(
+        &mut BufReader::new(File::open("tests/tmp/torus_only_vertex_data.stl").unwrap()),
+        StlFormat::Auto,
+        &mut m_loaded,
+        &mut normals,
+    )
+    .unwrap();
+
+    assert!(m_loaded.num_faces() == 1152);
+    assert!(m_loaded.num_vertices() == 576);
+    assert!(normals.len() == 1152);
+
+    for i in 0..m.num_faces() {
+        let [v1, v2, v3] = m.face_vertices(FId(i)).unwrap();
+        let n = normal_of_face(&v1, &v2, &v3);
+        assert!((n.x() - normals[i].x()).abs() < 1e-6);
+        assert!((n.y() - normals[i].y()).abs() < 1e-6);
+        assert!((n.z() - normals[i].z()).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn obj_io_test() {
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/torus_only_vertex_data.ply").unwrap()),
+        &mut m,
+    )
+    .unwrap();
+
+    let normals = normals_of_mesh(&m);
+
+    save_obj_mesh(
+        &mut File::create("tests/tmp/torus_only_vertex_data.obj").unwrap(),
+        &m,
+        Some(&normals),
+        None,
+        Some(6),
+    )
+    .unwrap();
+
+    let mut m_loaded = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_obj_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/tmp/torus_only_vertex_data.obj").unwrap()),
+        &mut m_loaded,
+    )
+    .unwrap();
+
+    assert!(m_loaded.num_faces() == 1152);
+    assert!(m_loaded.num_vertices() == 576);
+}
+
+#[test]
+fn off_io_test() {
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/torus_only_vertex_data.ply").unwrap()),
+        &mut m,
+    )
+    .unwrap();
+
+    let normals = normals_of_mesh(&m);
+    let colors = vec![Rgb::new(255, 0, 0); m.num_vertices()];
+
+    save_off_mesh(
+        &mut File::create("tests/tmp/torus_only_vertex_data.off").unwrap(),
+        &m,
+        Some(&colors),
+        Some(&normals),
+    )
+    .unwrap();
+
+    let mut m_loaded = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_off_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/tmp/torus_only_vertex_data.off").unwrap()),
+        &mut m_loaded,
+    )
+    .unwrap();
+
+    assert!(m_loaded.num_faces() == 1152);
+    assert!(m_loaded.num_vertices() == 576);
+    assert!(m_loaded.vertex(VId(0)).unwrap() == m.vertex(VId(0)).unwrap());
+}
+
+#[test]
+fn gltf_io_test() {
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/torus_only_vertex_data.ply").unwrap()),
+        &mut m,
+    )
+    .unwrap();
+
+    let normals = normals_of_mesh(&m);
+    let colors = vec![Rgb::new(0, 128, 255); m.num_vertices()];
+
+    save_gltf(
+        &mut File::create("tests/tmp/torus_only_vertex_data.gltf").unwrap(),
+        &m,
+        Some(&normals),
+        Some(&colors),
+    )
+    .unwrap();
+
+    save_glb(
+        &mut File::create("tests/tmp/torus_only_vertex_data.glb").unwrap(),
+        &m,
+        Some(&normals),
+        None,
+    )
+    .unwrap();
+
+    let json: serde_json::Value = serde_json::from_reader(BufReader::new(
+        File::open("tests/tmp/torus_only_vertex_data.gltf").unwrap(),
+    ))
+    .unwrap();
+    let bb = m.bounding_box_maybe().unwrap();
+    let min = json["accessors"][0]["min"].as_array().unwrap();
+    let max = json["accessors"][0]["max"].as_array().unwrap();
+    assert!((min[0].as_f64().unwrap() - bb.min_p().x()).abs() < 1e-6);
+    assert!((min[2].as_f64().unwrap() - bb.min_p().z()).abs() < 1e-6);
+    assert!((max[1].as_f64().unwrap() - bb.max_p().y()).abs() < 1e-6);
+
+    for glb in [false, true].iter() {
+        let mut m_loaded = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+        if *glb {
+            load_glb::<_, _, _, 30>(
+                &mut BufReader::new(File::open("tests/tmp/torus_only_vertex_data.glb").unwrap()),
+                "tests/tmp".into(),
+                &mut m_loaded,
+            )
+            .unwrap();
+        } else {
+            load_gltf::<_, _, _, 30>(
+                &mut BufReader::new(File::open("tests/tmp/torus_only_vertex_data.gltf").unwrap()),
+                "tests/tmp".into(),
+                &mut m_loaded,
+            )
+            .unwrap();
+        }
+
+        assert!(m_loaded.num_faces() == 1152);
+        assert!(m_loaded.num_vertices() == 576);
+
+        for i in 0..m.num_vertices() {
+            let p = m.vertex(VId(i)).unwrap();
+            let p_loaded = m_loaded.vertex(VId(i)).unwrap();
+            assert!((p.x() - p_loaded.x()).abs() < 1e-5);
+            assert!((p.y() - p_loaded.y()).abs() < 1e-5);
+            assert!((p.z() - p_loaded.z()).abs() < 1e-5);
+        }
+
+        for i in 0..m.num_faces() {
+            assert!(m.face_vertex_ids(FId(i)) == m_loaded.face_vertex_ids(FId(i)));
+        }
+    }
+}
+
+#[test]
+fn gltf_empty_mesh_io_test() {
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+
+    let mut buffer = Vec::new();
+    assert!(save_gltf(&mut buffer, &m, None, None).is_err());
+    assert!(save_glb(&mut buffer, &m, None, None).is_err());
+
+    // Vertices without any faces
+    m.add_vertex(Point3D::new(0.0, 0.0, 0.0));
+    assert!(save_gltf(&mut buffer, &m, None, None).is_err());
+    assert!(save_glb(&mut buffer, &m, None, None).is_err());
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn gltf_exceeds_u32_io_test() {
+    // Only provides the ids, since those can't be stored without allocating the vertices
+    struct HugeIdMesh;
+
+    impl IsMesh<Point3D, Face3> for HugeIdMesh {
+        fn num_faces(&self) -> usize {
+            1
+        }
+        fn num_vertices(&self) -> usize {
+            1
+        }
+        fn face_vertex_ids(&self, _faceid: FId) -> Option<Face3> {
+            let id = VId(u32::MAX as usize + 1);
+            Some(Face3::new(id, id, id))
+        }
+        fn face_vertices(&self, _faceid: FId) -> Option<[Point3D; 3]> {
+            None
+        }
+        fn vertex(&self, _vertexid: VId) -> Option<Point3D> {
+            Some(Point3D::default())
+        }
+    }
+
+    let mut buffer = Vec::new();
+    for result in [
+        save_gltf(&mut buffer, &HugeIdMesh, None, None),
+        save_glb(&mut buffer, &HugeIdMesh, None, None),
+    ] {
+        assert!(matches!(result, Err(IOError::Gltf(GltfError::ExceedsU32))));
+    }
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn gltf_cyclic_nodes_io_test() {
+    let json = r#"{
+        "asset": {"version": "2.0"},
+        "scene": 0,
+        "scenes": [{"nodes": [0]}],
+        "nodes": [{"children": [1, 1]}, {"children": [0, 0]}],
+        "meshes": [],
+        "accessors": [],
+        "bufferViews": [],
+        "buffers": []
+    }"#;
+
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    let error =
+        load_gltf::<_, _, _, 30>(&mut Cursor::new(json), "tests/tmp".into(), &mut m).unwrap_err();
+    match error.root() {
+        IOError::Gltf(GltfError::NodeCycle) => (),
+        _ => panic!("Expected a node cycle error, got {:?}", error),
+    }
+
+    // Too deep, but without any cycle
+    let nodes = (0..2000)
+        .map(|i| format!(r#"{{"children": [{}]}}"#, i + 1))
+        .chain(std::iter::once("{}".to_string()))
+        .collect::<Vec<_>>()
+        .join(",");
+    let json = format!(
+        r#"{{
+        "asset": {{"version": "2.0"}},
+        "scene": 0,
+        "scenes": [{{"nodes": [0]}}],
+        "nodes": [{}],
+        "meshes": [],
+        "accessors": [],
+        "bufferViews": [],
+        "buffers": []
+    }}"#,
+        nodes
+    );
+
+    let error =
+        load_gltf::<_, _, _, 30>(&mut Cursor::new(json), "tests/tmp".into(), &mut m).unwrap_err();
+    match error.root() {
+        IOError::Gltf(GltfError::NodeDepth) => (),
+        _ => panic!("Expected a node depth error, got {:?}", error),
+    }
+}
+
+#[test]
+fn gltf_attributes_io_test() {
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/torus_only_vertex_data.ply").unwrap()),
+        &mut m,
+    )
+    .unwrap();
+
+    let normals = normals_of_mesh(&m);
+    let colors: Vec<_> = (0..m.num_vertices())
+        .map(|i| Rgb::new((i % 256) as u8, 128, 255))
+        .collect();
+
+    save_gltf(
+        &mut File::create("tests/tmp/torus_attributes.gltf").unwrap(),
+        &m,
+        Some(&normals),
+        Some(&colors),
+    )
+    .unwrap();
+
+    let mut m_loaded = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    let mut attributes = Vec::new();
+    load_gltf_with_attributes::<_, _, _, _, 30>(
+        &mut BufReader::new(File::open("tests/tmp/torus_attributes.gltf").unwrap()),
+        "tests/tmp".into(),
+        &mut m_loaded,
+        &mut attributes,
+    )
+    .unwrap();
+
+    assert!(m_loaded.num_vertices() == 576);
+    assert!(attributes.len() == 576);
+
+    for i in 0..m.num_vertices() {
+        let a: &GltfVertexAttributes = &attributes[i];
+        let n = a.normal.as_ref().unwrap();
+        assert!((n.x() - normals[i].x()).abs() < 1e-5);
+        assert!((n.y() - normals[i].y()).abs() < 1e-5);
+        assert!((n.z() - normals[i].z()).abs() < 1e-5);
+        assert!(a.color == Some(colors[i].clone()));
+        assert!(a.tex_coord.is_none());
+    }
+
+    // Two primitives below a translated parent and scaled child node
+    // The second primitive is non-indexed and moves its second vertex via a sparse accessor
+    let mut m_nodes = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    let mut attributes = Vec::new();
+    load_gltf_with_attributes::<_, _, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/transformed_nodes.gltf").unwrap()),
+        "tests/data".into(),
+        &mut m_nodes,
+        &mut attributes,
+    )
+    .unwrap();
+
+    assert!(m_nodes.num_vertices() == 6);
+    assert!(m_nodes.num_faces() == 2);
+
+    let expected = [
+        [10.0, 0.0, 0.0],
+        [12.0, 0.0, 0.0],
+        [10.0, 2.0, 0.0],
+        [10.0, 0.0, 0.0],
+        [20.0, 0.0, 0.0],
+        [10.0, 2.0, 0.0],
+    ];
+    for (i, e) in expected.iter().enumerate() {
+        let p = m_nodes.vertex(VId(i)).unwrap();
+        assert!((p.x() - e[0]).abs() < 1e-5);
+        assert!((p.y() - e[1]).abs() < 1e-5);
+        assert!((p.z() - e[2]).abs() < 1e-5);
+    }
+
+    assert!(m_nodes.face_vertex_ids(FId(1)).unwrap() == Face3::new(VId(3), VId(4), VId(5)));
+    assert!((attributes[0].normal.as_ref().unwrap().z() - 1.0).abs() < 1e-5);
+    assert!(attributes[3].normal.is_none());
+}
+
+#[test]
+fn ply_attributes_io_test() {
+    for path in [
+        "tests/data/attributes_ascii.ply",
+        "tests/data/attributes_binary.ply",
+    ]
+    .iter()
+    {
+        let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+        let attributes = load_ply_mesh_with_attributes(
+            BufReader::new(File::open(path).unwrap()),
+            &mut m,
+            Triangulation::Fan,
+        )
+        .unwrap();
+
+        assert!(m.num_vertices() == 4);
+        assert!(m.num_faces() == 2);
+        assert!(m.vertex(VId(3)).unwrap() == Point3D::new(1.0, 1.0, 0.0));
+        assert!(m.face_vertex_ids(FId(1)).unwrap() == Face3::new(VId(1), VId(3), VId(2)));
+
+        let colors = attributes.vertex.colors.as_ref().unwrap();
+        assert!(colors[0] == Rgb::new(255, 0, 0));
+        assert!(colors[3] == Rgb::new(10, 20, 30));
+
+        let normals = attributes.vertex.normals.as_ref().unwrap();
+        assert!(normals[0] == Some(Norm3D::norm_z()));
+        assert!(normals[2].is_none());
+
+        assert!(attributes.vertex.scalars.len() == 1);
+        assert!(attributes.vertex.scalar("intensity") == Some(&[0.5, 0.25, 1.0, 0.75][..]));
+
+        assert!(attributes.face.colors.is_none());
+        assert!(attributes.face.scalar("segment") == Some(&[7.0, 9.0][..]));
+
+        let mut pc = PointCloud3D::<Point3D>::default();
+        let attributes =
+            load_ply_points_with_attributes(BufReader::new(File::open(path).unwrap()), &mut pc)
+                .unwrap();
+        assert!(pc.len() == 4);
+        assert!(attributes.colors.unwrap().len() == 4);
+    }
+}
+
+#[test]
+fn ply_attributed_io_test() {
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh_attributed(
+        BufReader::new(File::open("tests/data/attributes_ascii.ply").unwrap()),
+        &mut m,
+        Triangulation::Fan,
+    )
+    .unwrap();
+
+    let vertex = m.vertex_attributes();
+    assert!(vertex.names().collect::<Vec<_>>() == ["color", "normal", "intensity"]);
+    assert!(vertex.get::<Rgb>("color").unwrap()[3] == Rgb::new(10, 20, 30));
+    assert!(vertex.get::<f64>("intensity") == Some(&[0.5, 0.25, 1.0, 0.75][..]));
+    assert!(m.face_attributes().get::<f64>("segment") == Some(&[7.0, 9.0][..]));
+
+    m.face_attributes_mut()
+        .insert("label", vec![1u32, 2])
+        .unwrap();
+
+    let mut ascii = Vec::new();
+    save_ply_ascii_attributed(&mut ascii, &m).unwrap();
+    let mut binary = Vec::new();
+    save_ply_binary_attributed(&mut binary, &m, &Precision::P64).unwrap();
+
+    for bytes in [ascii, binary].iter() {
+        let mut loaded = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+        load_ply_mesh_attributed(&bytes[..], &mut loaded, Triangulation::Fan).unwrap();
+        assert!(loaded.num_faces() == 2);
+        assert!(loaded.vertex_attributes() == m.vertex_attributes());
+        // labels are loaded as scalars
+        assert!(loaded.face_attributes().get::<f64>("label") == Some(&[1.0, 2.0][..]));
+    }
+
+    let mut pc = PointCloud3D::<Point3D>::new();
+    load_ply_points_attributed(
+        BufReader::new(File::open("tests/data/attributes_binary.ply").unwrap()),
+        &mut pc,
+    )
+    .unwrap();
+    assert!(pc.vertex_attributes() == m.vertex_attributes());
+
+    let mut bytes = Vec::new();
+    save_ply_points_ascii_attributed(&mut bytes, &pc).unwrap();
+    let mut loaded = PointCloud3D::<Point3D>::new();
+    load_ply_points_attributed(&bytes[..], &mut loaded).unwrap();
+    assert!(loaded == pc);
+}
+
+#[test]
+fn attributed_io_test() {
+    type M = Mesh3D<Point3D, PointCloud3D<Point3D>, Vec<usize>>;
+
+    let mut m = M::default();
+    load_ply_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/torus_only_vertex_data.ply").unwrap()),
+        &mut m,
+    )
+    .unwrap();
+
+    let normals = normals_of_mesh(&m);
+    let colors: Vec<_> = (0..m.num_vertices())
+        .map(|i| Rgb::new((i % 256) as u8, 128, 255))
+        .collect();
+
+    let same_normals = |loaded: &M| {
+        let loaded_normals = loaded.vertex_attributes().get::<Norm3D>("normal").unwrap();
+        loaded_normals.len() == normals.len()
+            && loaded_normals.iter().zip(&normals).all(|(a, b)| {
+                (a.x() - b.x()).abs() < 1e-5
+                    && (a.y() - b.y()).abs() < 1e-5
+                    && (a.z() - b.z()).abs() < 1e-5
+            })
+    };
+
+    {
+        let mut bytes = Vec::new();
+        save_obj_mesh(&mut bytes, &m, Some(&normals), None, None).unwrap();
+        let mut loaded = M::default();
+        load_obj_mesh_attributed(&bytes[..], &mut loaded, Triangulation::Fan).unwrap();
+        assert!(loaded.num_faces() == m.num_faces());
+        assert!(same_normals(&loaded));
+        assert!(loaded.vertex_attributes().values("color").is_none());
+    }
+
+    {
+        let mut bytes = Vec::new();
+        save_off_mesh(&mut bytes, &m, Some(&colors), Some(&normals)).unwrap();
+        let mut loaded = M::default();
+        load_off_mesh_attributed(&bytes[..], &mut loaded, Triangulation::Fan).unwrap();
+        assert!(loaded.num_faces() == m.num_faces());
+        assert!(same_normals(&loaded));
+        assert!(loaded.vertex_attributes().get::<Rgb>("color") == Some(&colors[..]));
+    }
+
+    {
+        // Colors as floating point values within [0, 1]
+        let off = "COFF\n3 1 0\n0 0 0 1.0 0.5 0.0 1.0\n1 0 0 0 0 0 1\n0 1 0 0 0 0 1\n3 0 1 2\n";
+        let mut loaded = M::default();
+        load_off_mesh_attributed(off.as_bytes(), &mut loaded, Triangulation::Fan).unwrap();
+        assert!(
+            loaded.vertex_attributes().get::<Rgb>("color").unwrap()[0] == Rgb::new(255, 128, 0)
+        );
+        assert!(loaded.vertex_attributes().values("normal").is_none());
+    }
+
+    for glb in [false, true].iter() {
+        let mut bytes = Vec::new();
+        let mut loaded = M::default();
+        if *glb {
+            save_glb(&mut bytes, &m, Some(&normals), Some(&colors)).unwrap();
+            load_glb_attributed::<_, _, _, 30>(Cursor::new(bytes), "tests/tmp".into(), &mut loaded)
+                .unwrap();
+        } else {
+            save_gltf(&mut bytes, &m, Some(&normals), Some(&colors)).unwrap();
+            load_gltf_attributed::<_, _, _, 30>(
+                Cursor::new(bytes),
+                "tests/tmp".into(),
+                &mut loaded,
+            )
+            .unwrap();
+        }
+        assert!(loaded.num_faces() == m.num_faces());
+        assert!(same_normals(&loaded));
+        assert!(loaded.vertex_attributes().get::<Rgb>("color") == Some(&colors[..]));
+    }
+
+    // Vertices referenced with different normals keep the last one
+    let obj = "v 0 0 0\nv 1 0 0\nv 0 1 0\nv 1 1 0\nv 2 2 2\nvn 0 0 1\nvn 0 0 -1\nf 1//1 2//1 3//1\nf 2//2 4//-1 3//2\n";
+    let mut loaded = M::default();
+    load_obj_mesh_attributed(obj.as_bytes(), &mut loaded, Triangulation::Fan).unwrap();
+    let loaded_normals = loaded.vertex_attributes().get::<Norm3D>("normal").unwrap();
+    assert!(loaded_normals.len() == 5);
+    assert!(loaded_normals[0].z() == 1.0);
+    assert!(loaded_normals[1].z() == -1.0);
+    assert!(loaded_normals[3].z() == -1.0);
+    assert!(loaded_normals[4] == Norm3D::default_attribute());
+
+    let invalid = "v 0 0 0\nv 1 0 0\nv 0 1 0\nvn 0 0 1\nf 1//1 2//2 3//1\n";
+    let mut loaded = M::default();
+    assert!(load_obj_mesh_attributed(invalid.as_bytes(), &mut loaded, Triangulation::Fan).is_err());
+}
+
+#[test]
+fn polygon_mesh_io_test() {
+    type PM = PolygonMesh3D<Point3D, PointCloud3D<Point3D>, Vec<usize>>;
+
+    // Concave 'L' shape and a quad, referenced relatively
+    let obj = "v 2 1 0\n\
+               v 1 1 0\n\
+               v 1 2 0\n\
+               v 0 2 0\n\
+               v 0 0 0\n\
+               v 2 0 0\n\
+               f 1 2 3 4 5 6\n\
+               v 5 0 0\n\
+               v 6 0 0\n\
+               v 6 1 0\n\
+               v 5 1 0\n\
+               f -4//1 -3//1 -2//1 -1//1\n";
+
+    let mut m = PM::default();
+    load_obj_polygon_mesh(obj.as_bytes(), &mut m).unwrap();
+    assert!(m.num_vertices() == 10);
+    assert!(m.num_faces() == 2);
+    assert!(m.face_len(FId(0)) == Some(6));
+    assert!(m.face_vertex_id(FId(1), 0) == Some(VId(6)));
+
+    let too_few = "v 0 0 0\nv 1 0 0\nf 1 2\n";
+    assert!(load_obj_polygon_mesh(too_few.as_bytes(), &mut PM::default()).is_err());
+    let out_of_bounds = "OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n";
+    assert!(load_off_polygon_mesh(out_of_bounds.as_bytes(), &mut PM::default()).is_err());
+
+    let mut obj = Vec::new();
+    save_obj_polygon_mesh(&mut obj, &m, None).unwrap();
+    let mut loaded = PM::default();
+    load_obj_polygon_mesh(&obj[..], &mut loaded).unwrap();
+    assert!(loaded == m);
+
+    let mut off = Vec::new();
+    save_off_polygon_mesh(&mut off, &m, None, None).unwrap();
+    let mut loaded = PM::default();
+    load_off_polygon_mesh(&off[..], &mut loaded).unwrap();
+    assert!(loaded == m);
+
+    let mut ascii = Vec::new();
+    save_ply_polygon_ascii(&mut ascii, &m).unwrap();
+    let mut binary = Vec::new();
+    save_ply_polygon_binary(&mut binary, &m, &Precision::P32).unwrap();
+    for bytes in [ascii, binary].iter() {
+        let mut loaded = PM::default();
+        load_ply_polygon_mesh(&bytes[..], &mut loaded).unwrap();
+        assert!(loaded == m);
+    }
+
+    // the triangle loaders split the polygons of the written files
+    let mut triangulated = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_obj_mesh_with_triangulation::<_, _, _, 3>(
+        &obj[..],
+        &mut triangulated,
+        Triangulation::EarClipping,
+    )
+    .unwrap();
+    assert!(triangulated == m.triangulated(Triangulation::EarClipping));
+
+    m.face_attributes_mut()
+        .insert("segment", vec![4.0, 2.0])
+        .unwrap();
+    m.vertex_attributes_mut()
+        .insert("color", vec![Rgb::new(1, 2, 3); 10])
+        .unwrap();
+
+    let mut ascii = Vec::new();
+    save_ply_polygon_ascii_attributed(&mut ascii, &m).unwrap();
+    let mut binary = Vec::new();
+    save_ply_polygon_binary_attributed(&mut binary, &m, &Precision::P64).unwrap();
+    for bytes in [ascii, binary].iter() {
+        let mut loaded = PM::default();
+        load_ply_polygon_mesh_attributed(&bytes[..], &mut loaded).unwrap();
+        assert!(loaded == m);
+    }
+
+    let mut ply = Vec::new();
+    save_ply_polygon_ascii_attributed(&mut ply, &m).unwrap();
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh_attributed(&ply[..], &mut m, Triangulation::Fan).unwrap();
+    assert!(m.num_faces() == 6);
+    assert!(m.face_attributes().get::<f64>("segment") == Some(&[4.0, 4.0, 4.0, 4.0, 2.0, 2.0][..]));
+}
+
+#[test]
+fn polygon_faces_io_test() {
+    // Concave 'L' shape, where a fan from the first vertex would leave the polygon
+    let obj = "v 2 1 0\n\
+               v 1 1 0\n\
+               v 1 2 0\n\
+               v 0 2 0\n\
+               v 0 0 0\n\
+               v 2 0 0\n\
+               vt 0 0\n\
+               vn 0 0 1\n\
+               f 1/1/1 2/1/1 3/1/1 4/1/1 5/1/1 6/1/1\n\
+               v 5 0 0\n\
+               v 6 0 0\n\
+               v 6 1 0\n\
+               v 5 1 0\n\
+               f -4//1 -3//1 -2//1 -1//1\n";
+
+    for triangulation in [Triangulation::Fan, Triangulation::EarClipping].iter() {
+        let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+        load_obj_mesh_with_triangulation::<_, _, _, 3>(obj.as_bytes(), &mut m, *triangulation)
+            .unwrap();
+
+        assert!(m.num_vertices() == 10);
+        assert!(m.num_faces() == 6);
+        if *triangulation == Triangulation::Fan {
+            assert!(m.face_vertex_ids(FId(4)).unwrap() == Face3::new(VId(6), VId(7), VId(8)));
+            assert!(m.face_vertex_ids(FId(5)).unwrap() == Face3::new(VId(6), VId(8), VId(9)));
+        }
+
+        // The L has an area of 3, the fan covers parts outside of it twice with opposite orientation
+        let mut area = 0.0;
+        let mut all_ccw = true;
+        for i in 0..4 {
+            let [a, b, c] = m.face_vertices(FId(i)).unwrap();
+            let z = cross(&conn(&a, &b), &conn(&a, &c)).z();
+            area += 0.5 * z;
+            all_ccw &= z > 0.0;
+        }
+        assert!((area - 3.0).abs() < 1e-9);
+        assert!(all_ccw == (*triangulation == Triangulation::EarClipping));
+    }
+
+    for invalid in [
+        "v 0 0 0\nf -2 1 1\n",
+        "v 0 0 0\nf -9223372036854775808 1 1\n",
+    ]
+    .iter()
+    {
+        let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+        assert!(load_obj_mesh::<_, _, _, 3>(invalid.as_bytes(), &mut m).is_err());
+    }
+
+    let ply_header = "ply\n\
+                      format ascii 1.0\n\
+                      element vertex 5\n\
+                      property float x\n\
+                      property float y\n\
+                      property float z\n\
+                      element face 2\n\
+                      property list uchar int vertex_indices\n\
+                      end_header\n";
+    let ply = ply_header.to_string() + "0 0 0\n1 0 0\n1 1 0\n0 1 0\n2 0 0\n4 0 1 2 3\n3 1 4 2\n";
+
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh::<_, _, _, 2>(ply.as_bytes(), &mut m).unwrap();
+    assert!(m.num_faces() == 3);
+    assert!(m.face_vertex_ids(FId(1)).unwrap() == Face3::new(VId(0), VId(2), VId(3)));
+    assert!(m.face_vertex_ids(FId(2)).unwrap() == Face3::new(VId(1), VId(4), VId(2)));
+
+    let mut binary = ply_header
+        .replace("ascii", "binary_little_endian")
+        .into_bytes();
+    for p in [
+        [0.0f32, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [1.0, 1.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [2.0, 0.0, 0.0],
+    ]
+    .iter()
+    {
+        for x in p.iter() {
+            binary.extend_from_slice(&x.to_le_bytes());
+        }
+    }
+    for face in [vec![0i32, 1, 2, 3], vec![1, 4, 2]].iter() {
+        binary.push(face.len() as u8);
+        for id in face.iter() {
+            binary.extend_from_slice(&id.to_le_bytes());
+        }
+    }
+
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh_with_triangulation::<_, _, _, 2>(&binary[..], &mut m, Triangulation::EarClipping)
+        .unwrap();
+    assert!(m.num_faces() == 3);
+    assert!(m.face_vertex_ids(FId(2)).unwrap() == Face3::new(VId(1), VId(4), VId(2)));
+
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    let attributes =
+        load_ply_mesh_with_attributes(ply.as_bytes(), &mut m, Triangulation::Fan).unwrap();
+    assert!(m.num_faces() == 3);
+    assert!(attributes.face.scalars.is_empty());
+}
+
+#[test]
+fn auto_io_test() {
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/torus_only_vertex_data.ply").unwrap()),
+        &mut m,
+    )
+    .unwrap();
+
+    // Misleading or missing extensions, detected by magic bytes or content
+    save_stl_binary(
+        &mut File::create("tests/tmp/auto_stl_binary.dat").unwrap(),
+        &m,
+        b"xsolid but binary",
+        0,
+    )
+    .unwrap();
+    // Other writers might start binary files with 'solid'
+    let mut bytes = std::fs::read("tests/tmp/auto_stl_binary.dat").unwrap();
+    bytes.drain(..1);
+    bytes.insert(79, 0);
+    std::fs::write("tests/tmp/auto_stl_binary.dat", bytes).unwrap();
+    save_stl_ascii(&mut File::create("tests/tmp/auto_stl_ascii").unwrap(), &m).unwrap();
+    save_obj_mesh(
+        &mut File::create("tests/tmp/auto_obj").unwrap(),
+        &m,
+        None,
+        None,
+        None,
+    )
+    .unwrap();
+    save_off_mesh(
+        &mut File::create("tests/tmp/auto_off").unwrap(),
+        &m,
+        None,
+        None,
+    )
+    .unwrap();
+    save_glb(
+        &mut File::create("tests/tmp/auto_glb.bin").unwrap(),
+        &m,
+        None,
+        None,
+    )
+    .unwrap();
+
+    let expected = [
+        ("tests/data/torus_only_vertex_data.ply", FileFormat::Ply),
+        ("tests/tmp/auto_stl_binary.dat", FileFormat::StlBinary),
+        ("tests/tmp/auto_stl_ascii", FileFormat::StlAscii),
+        ("tests/tmp/auto_obj", FileFormat::Obj),
+        ("tests/tmp/auto_off", FileFormat::Off),
+        ("tests/tmp/auto_glb.bin", FileFormat::Glb),
+    ];
+
+    for (path, format) in expected.iter() {
+        let path = std::path::Path::new(path);
+        assert!(detect_format(path).unwrap() == *format);
+
+        let mut loaded = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+        assert!(load_mesh_auto::<_, _, 30>(path, &mut loaded).unwrap() == *format);
+        assert!(loaded.num_faces() == 1152);
+        assert!(loaded.num_vertices() == 576);
+
+        let mut points = PointCloud3D::<Point3D>::new();
+        assert!(load_points_auto::<_, _, 30>(path, &mut points).unwrap() == *format);
+        assert!(points.len() >= 576);
+    }
+
+    std::fs::copy("tests/data/toolpath.gcode", "tests/tmp/auto_toolpath").unwrap();
+    std::fs::copy("tests/data/two_scans.ptx", "tests/tmp/auto_scans").unwrap();
+
+    let expected_points = [
+        ("tests/data/test_cube.xyz", FileFormat::Xyz, 8000),
+        ("tests/data/test_cube.laz", FileFormat::Las, 8000),
+        ("tests/data/two_scans.e57", FileFormat::E57, 4),
+        ("tests/data/organized.pcd", FileFormat::Pcd, 6),
+        ("tests/tmp/auto_toolpath", FileFormat::Gcode, 62),
+        ("tests/tmp/auto_scans", FileFormat::Ptx, 6),
+    ];
+
+    for (path, format, n) in expected_points.iter() {
+        let path = std::path::Path::new(path);
+        let mut points = PointCloud3D::<Point3D>::new();
+        assert!(load_points_auto::<_, _, 30>(path, &mut points).unwrap() == *format);
+        assert!(points.len() == *n);
+
+        let mut mesh = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+        let error = load_mesh_auto::<_, _, 30>(path, &mut mesh).unwrap_err();
+        assert!(matches!(error.root(), IOError::NoMeshFormat(_)));
+    }
+
+    std::fs::write("tests/tmp/auto_unknown", [0u8, 159, 146, 150, 1, 2, 3]).unwrap();
+    let mut points = PointCloud3D::<Point3D>::new();
+    let error =
+        load_points_auto::<_, _, 30>(std::path::Path::new("tests/tmp/auto_unknown"), &mut points)
+            .unwrap_err();
+    assert!(matches!(error.root(), IOError::UnknownFileFormat));
+    assert!(error.to_string().contains("tests/tmp/auto_unknown"));
+
+    let expected_formats: [(&str, &[u8], FileFormat); 4] = [
+        (
+            "tests/tmp/auto_crlf",
+            b"ply \r\nformat ascii 1.0\r\n",
+            FileFormat::Ply,
+        ),
+        (
+            "tests/tmp/auto_empty.stl",
+            b"solid empty\nendsolid empty\n",
+            FileFormat::StlAscii,
+        ),
+        (
+            "tests/tmp/auto_facet",
+            b"solid\n  facet normal 0 0 1\n    outer loop\n      vertex 0 0 0\n      vertex 1 0 0\n      vertex 0 1 0\n    endloop\n  endfacet\n",
+            FileFormat::StlAscii,
+        ),
+        (
+            "tests/tmp/auto_glb",
+            b"glTF\x02\x00\x00\x00",
+            FileFormat::Glb,
+        ),
+    ];
+
+    for (path, content, format) in expected_formats.iter() {
+        std::fs::write(path, content).unwrap();
+        assert!(detect_format(std::path::Path::new(path)).unwrap() == *format);
+    }
+}
+
+#[test]
+fn vtk_io_test() {
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/torus_only_vertex_data.ply").unwrap()),
+        &mut m,
+    )
+    .unwrap();
+
+    let n_vertices = m.num_vertices();
+    let n_faces = m.num_faces();
+
+    let distances: Vec<f64> = (0..n_vertices)
+        .map(|i| m.vertex(VId(i)).unwrap().abs().into())
+        .collect();
+    let normals: Vec<Norm3D> = (0..n_faces)
+        .map(|i| m.face_normal(FId(i)).unwrap())
+        .collect();
+
+    let point_data = [VtkArray::scalars("distance to origin", distances)];
+    let cell_data = [VtkArray::vectors("normals", &normals)];
+
+    {
+        let mut data = Vec::new();
+        save_vtk_mesh(&mut data, &m, VtkFormat::Ascii, &point_data, &cell_data).unwrap();
+        let text = String::from_utf8(data).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert!(lines[2] == "ASCII");
+        assert!(lines[3] == "DATASET POLYDATA");
+        assert!(lines[4] == format!("POINTS {} double", n_vertices));
+        assert!(lines[5 + n_vertices] == format!("POLYGONS {} {}", n_faces, 4 * n_faces));
+        assert!(lines[6 + n_vertices].starts_with("3 "));
+        assert!(text.contains(&format!(
+            "POINT_DATA {}\nSCALARS distance_to_origin double 1\nLOOKUP_TABLE default\n",
+            n_vertices
+        )));
+        assert!(text.contains(&format!("CELL_DATA {}\nVECTORS normals double\n", n_faces)));
+        assert!(lines.len() == 11 + 2 * n_vertices + 2 * n_faces);
+    }
+
+    {
+        let mut data = Vec::new();
+        save_vtk_mesh(&mut data, &m, VtkFormat::Binary, &point_data, &cell_data).unwrap();
+
+        let header = format!(
+            "# vtk DataFile Version 3.0\nCreated by rust-3d\nBINARY\nDATASET POLYDATA\nPOINTS {} double\n",
+            n_vertices
+        );
+        assert!(data.starts_with(header.as_bytes()));
+
+        let mut first_x = [0u8; 8];
+        first_x.copy_from_slice(&data[header.len()..header.len() + 8]);
+        assert!(f64::from_be_bytes(first_x) == m.vertex(VId(0)).unwrap().x);
+
+        let polygons = header.len() + 24 * n_vertices + 1;
+        assert!(data[polygons..]
+            .starts_with(format!("POLYGONS {} {}\n", n_faces, 4 * n_faces).as_bytes()));
+    }
+
+    for format in [VtkFormat::Ascii, VtkFormat::Binary].iter() {
+        let mut data = Vec::new();
+        save_vtu_mesh(&mut data, &m, *format, &point_data, &cell_data).unwrap();
+        let text = String::from_utf8(data).unwrap();
+
+        assert!(text.contains(&format!(
+            "<Piece NumberOfPoints=\"{}\" NumberOfCells=\"{}\">",
+            n_vertices, n_faces
+        )));
+        assert!(text.contains(
+            "<DataArray type=\"Float64\" Name=\"distance to origin\" NumberOfComponents=\"1\""
+        ));
+        assert!(
+            text.contains("<DataArray type=\"Float64\" Name=\"normals\" NumberOfComponents=\"3\"")
+        );
+        assert!(text.contains("<DataArray type=\"Int64\" Name=\"connectivity\""));
+        assert!(text.contains("<DataArray type=\"UInt8\" Name=\"types\""));
+        assert!(text.trim_end().ends_with("</VTKFile>"));
+    }
+
+    {
+        let mut data = Vec::new();
+        save_vtu_mesh(&mut data, &m, VtkFormat::Ascii, &[], &[]).unwrap();
+        let text = String::from_utf8(data).unwrap();
+        assert!(text.contains(&format!("          {}\n", 3 * n_faces)));
+    }
+
+    {
+        let mut attributed = m.clone();
+        let labels: Vec<u32> = (0..n_faces as u32).collect();
+        attributed
+            .vertex_attributes_mut()
+            .insert("normal", normals_of_mesh(&m))
+            .unwrap();
+        attributed
+            .vertex_attributes_mut()
+            .insert("color", vec![Rgb::new(0, 128, 255); n_vertices])
+            .unwrap();
+        attributed
+            .face_attributes_mut()
+            .insert("label", labels)
+            .unwrap();
+
+        let mut data = Vec::new();
+        save_vtk_mesh_attributed(&mut data, &attributed, VtkFormat::Ascii).unwrap();
+        let text = String::from_utf8(data).unwrap();
+        assert!(text.contains(&format!(
+            "POINT_DATA {}\nVECTORS normal double\n",
+            n_vertices
+        )));
+        assert!(text.contains("VECTORS color double\n0 128 255\n"));
+        assert!(text.contains(&format!(
+            "CELL_DATA {}\nSCALARS label double 1\nLOOKUP_TABLE default\n0\n1\n",
+            n_faces
+        )));
+
+        let mut data = Vec::new();
+        save_vtu_mesh_attributed(&mut data, &attributed, VtkFormat::Binary).unwrap();
+        let text = String::from_utf8(data).unwrap();
+        assert!(text.contains("Name=\"color\" NumberOfComponents=\"3\""));
+        assert!(text.contains("Name=\"label\" NumberOfComponents=\"1\""));
+
+        let pc = PointCloud3D::from(vec![Point3D::new(0.0, 0.0, 0.0)]);
+        let mut data = Vec::new();
+        save_vtk_points_attributed(&mut data, &pc, VtkFormat::Ascii).unwrap();
+        assert!(!String::from_utf8(data).unwrap().contains("POINT_DATA"));
+    }
+
+    assert!(matches!(
+        save_vtk_mesh(&mut Vec::new(), &m, VtkFormat::Ascii, &cell_data, &[]),
+        Err(IOError::VectorArrayLength)
+    ));
+    assert!(matches!(
+        save_vtu_mesh(&mut Vec::new(), &m, VtkFormat::Ascii, &[], &point_data),
+        Err(IOError::ScalarArrayLength)
+    ));
+}
+
+#[test]
+fn error_context_io_test() {
+    use std::{error::Error, io::Cursor};
+
+    // vertices of attributes_binary.ply have 6 floats, 3 uchars and 1 float
+    let bytes = std::fs::read("tests/data/attributes_binary.ply").unwrap();
+    let header_size = bytes
+        .windows(11)
+        .position(|x| x == b"end_header\n")
+        .unwrap()
+        + 11;
+    let truncated = &bytes[..header_size + 31 + 10];
+
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    let error = load_ply_mesh::<_, _, _, 30>(Cursor::new(truncated), &mut m).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(&format!("vertex at byte {}", header_size + 31)));
+    assert!(error.source().is_some());
+
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    let error =
+        load_ply_mesh_with_attributes(Cursor::new(truncated), &mut m, Triangulation::default())
+            .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains(&format!("vertex at byte {}", header_size + 31)));
+
+    let mut m = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    load_ply_mesh::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/torus_only_vertex_data.ply").unwrap()),
+        &mut m,
+    )
+    .unwrap();
+    let mut bytes = Vec::new();
+    save_stl_binary(&mut bytes, &m, b"", 0).unwrap();
+
+    let mut m_loaded = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+    let error = load_stl_mesh_duped::<_, _, _, _, _, 30>(
+        Cursor::new(&bytes[..84 + 3 * 50 + 20]),
+        StlFormat::Binary,
+        &mut m_loaded,
+        &mut Vec::<Point3D>::new(),
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("face at byte 234"));
+    assert!(matches!(error.root(), IOError::AccessFile(_)));
+
+    // Each cause is displayed once when walking the chain of sources
+    let mut messages = vec![error.to_string()];
+    let mut source = error.source();
+    while let Some(x) = source {
+        messages.push(x.to_string());
+        source = x.source();
+    }
+    assert!(messages.len() == 3);
+    assert!(messages.windows(2).all(|x| !x[0].contains(x[1].as_str())));
+    assert!(format!("{:?}", error).ends_with(&messages[2]));
+
+    let error = load_off_mesh::<_, _, _, 30>(
+        Cursor::new(b"OFF\n3 1 0\n0 0 0\n1 0 0\n0 1 0\n4 0 1 2\n".as_ref()),
+        &mut m_loaded,
+    )
+    .unwrap_err();
+    assert!(error.to_string().contains("face on line 6"));
+
+    // Failures of the reader are reported as such, instead of as end of file
+    struct Disconnected;
+    impl std::io::Read for Disconnected {
+        fn read(&mut self, _buffer: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "disconnected",
+            ))
+        }
+    }
+
+    let lines = b"v 0 0 0\nv 1 0 0\nv 0 1 0\n".as_ref();
+    let error = load_obj_mesh_with_triangulation::<_, _, _, 30>(
+        BufReader::new(std::io::Read::chain(lines, Disconnected)),
+        &mut m_loaded,
+        Triangulation::default(),
+    )
+    .unwrap_err();
+    assert!(matches!(error.root(), IOError::AccessFile(e) if e.to_string() == "disconnected"));
+
+    let mut pc = PointCloud3D::<Point3D>::new();
+    let error = load_obj_points::<_, _, _, 30>(
+        BufReader::new(std::io::Read::chain(lines, Disconnected)),
+        &mut pc,
+    )
+    .unwrap_err();
+    assert!(matches!(error.root(), IOError::AccessFile(e) if e.to_string() == "disconnected"));
+}
@@ -80,3 +80,140 @@ fn mesh_test() {
         Some([p1, p2, p3]) => assert!(p1.x() == 1.0 && p2.x() == 2.0 && p3.x() == 3.0),
     };
 }
+
+#[test]
+fn mesh_attributes_test() {
+    let mut mesh = Mesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+
+    mesh.add_vertex(Point3D::new(0.0, 0.0, 0.0));
+    mesh.add_vertex(Point3D::new(1.0, 0.0, 0.0));
+    mesh.add_vertex(Point3D::new(0.0, 1.0, 0.0));
+    mesh.try_add_connection(VId(0), VId(1), VId(2)).unwrap();
+
+    let normals = normals_of_mesh(&mesh);
+    mesh.vertex_attributes_mut()
+        .insert("normal", normals)
+        .unwrap();
+    mesh.face_attributes_mut()
+        .insert("segment", vec![7u32])
+        .unwrap();
+
+    mesh.add_face(
+        Point3D::new(1.0, 1.0, 1.0),
+        Point3D::new(2.0, 2.0, 2.0),
+        Point3D::new(3.0, 3.0, 3.0),
+    );
+    assert!(mesh.vertex_attributes().len() == 6);
+    assert!(mesh.face_attributes().get::<u32>("segment") == Some(&[7, 0][..]));
+    assert!(mesh.vertex_attributes().get::<Norm3D>("normal").unwrap()[0] == Norm3D::norm_z());
+
+    mesh.transform(&Matrix4::rotation(
+        Rad(std::f64::consts::PI),
+        Rad(0.0),
+        Rad(0.0),
+    ));
+    let normals = mesh.vertex_attributes().get::<Norm3D>("normal").unwrap();
+    assert!((normals[0].z() + 1.0).abs() < 1e-9);
+
+    mesh.clear();
+    assert!(mesh.vertex_attributes().is_empty());
+    assert!(mesh.face_attributes().is_empty());
+}
+
+#[test]
+fn polygon_mesh_test() {
+    let mut mesh = PolygonMesh3D::<Point3D, PointCloud3D<Point3D>, Vec<usize>>::default();
+
+    assert!(mesh.num_faces() == 0);
+    assert!(mesh.face_len(FId(0)).is_none());
+    assert!(mesh.face(FId(0)).is_none());
+
+    for p in [
+        [0.0, 0.0],
+        [1.0, 0.0],
+        [2.0, 0.0],
+        [0.0, 1.0],
+        [1.0, 1.0],
+        [2.0, 1.0],
+        [1.0, 2.0],
+    ]
+    .iter()
+    {
+        mesh.add_vertex(Point3D::new(p[0], p[1], 0.0));
+    }
+
+    assert!(mesh.try_add_polygon(&[VId(0), VId(1)]).is_err());
+    assert!(mesh.try_add_polygon(&[VId(0), VId(1), VId(7)]).is_err());
+    assert!(mesh
+        .try_add_polygon(&[VId(0), VId(1), VId(4), VId(1)])
+        .is_err());
+    assert!(mesh.num_faces() == 0);
+
+    mesh.try_add_polygon(&[VId(0), VId(1), VId(4), VId(3)])
+        .unwrap();
+    mesh.try_add_polygon(&[VId(1), VId(2), VId(5), VId(4)])
+        .unwrap();
+    assert!(mesh.try_add_polygon(&[VId(3), VId(4), VId(6)]).unwrap() == FId(2));
+
+    assert!(mesh.num_faces() == 3);
+    assert!(mesh.num_vertices() == 7);
+    assert!(mesh.face_len(FId(1)) == Some(4));
+    assert!(mesh.face_len(FId(2)) == Some(3));
+    assert!(mesh.face_vertex_id(FId(1), 2) == Some(VId(5)));
+    assert!(mesh.face_vertex_id(FId(2), 3).is_none());
+    assert!(!mesh.is_triangle_mesh());
+
+    let mut vertices = Vec::new();
+    mesh.face_vertices(FId(2), &mut vertices).unwrap();
+    assert!(vertices[2] == Point3D::new(1.0, 2.0, 0.0));
+
+    let faces: Vec<Vec<usize>> = mesh
+        .faces()
+        .map(|face| face.map(|vid| vid.0).collect())
+        .collect();
+    assert!(faces == vec![vec![0, 1, 4, 3], vec![1, 2, 5, 4], vec![3, 4, 6]]);
+    assert!(mesh.face(FId(1)).unwrap().len() == 4);
+
+    let he = PolygonHalfEdge::<Vec<usize>>::new(&mesh);
+    assert!(he.tail(EId(5)) == Some(VId(2)));
+    assert!(he.face(EId(9)) == Some(FId(2)));
+    assert!(he.next(EId(3)) == Some(EId(0)));
+    assert!(he.prev(EId(4)) == Some(EId(7)));
+    assert!(he.next(EId(10)) == Some(EId(8)));
+    assert!(he.next(EId(11)).is_none());
+    assert!(he.twin(EId(1)) == Some(EId(7)));
+    assert!(he.twin(EId(7)) == Some(EId(1)));
+    assert!(he.twin(EId(2)) == Some(EId(8)));
+    assert!(he.twin(EId(0)).is_none());
+
+    let mut edges = Vec::new();
+    he.face_edges(FId(1), &mut edges).unwrap();
+    assert!(edges == vec![EId(4), EId(5), EId(6), EId(7)]);
+    assert!(he.face_edges(FId(3), &mut edges).is_err());
+
+    let mut cache = Vec::new();
+    let mut faces = Vec::new();
+    he.faces(VId(4), &mut cache, &mut faces).unwrap();
+    assert!(faces == vec![FId(0), FId(1), FId(2)]);
+
+    mesh.face_attributes_mut()
+        .insert("label", vec![1u32, 2, 3])
+        .unwrap();
+
+    let triangulated = mesh.triangulated(io::Triangulation::Fan);
+    assert!(triangulated.num_faces() == 5);
+    assert!(triangulated.num_vertices() == 7);
+    assert!(triangulated.face_vertex_ids(FId(3)).unwrap() == Face3::new(VId(1), VId(5), VId(4)));
+    assert!(triangulated.face_attributes().get::<u32>("label") == Some(&[1, 1, 2, 2, 3][..]));
+
+    let converted = PolygonMesh3D::from(triangulated);
+    assert!(converted.is_triangle_mesh());
+    assert!(converted.num_faces() == 5);
+    assert!(converted.face_len(FId(4)) == Some(3));
+    assert!(converted.face_attributes().get::<u32>("label") == Some(&[1, 1, 2, 2, 3][..]));
+
+    mesh.clear();
+    assert!(mesh.num_faces() == 0);
+    assert!(mesh.faces().next().is_none());
+    assert!(mesh.face_attributes().is_empty());
+}
@@ -58,3 +58,235 @@ fn point_cloud_2d_io_test() {
         assert!(pc.len() == 20 * 20);
     }
 }
+
+#[test]
+fn svg_io_test() {
+    {
+        let path_expected = "tests/data/expected_svg_save1.svg";
+        let path_tmp = "tests/tmp/svg_save1.tmp";
+
+        let mut pc = PointCloud2D::<Point2D>::new();
+        for i in 0..10 {
+            pc.push(Point2D::new(0.5 * i as f64, (0.5 * i as f64).sin()));
+        }
+
+        let hull = PointCloud2D::from(convex_hull_2d(&pc));
+        let simplified = douglas_peucker_2d(pc.clone(), 0.2);
+        let segment = LineSegment2D {
+            start: Point2D::new(0.0, -2.0),
+            end: Point2D::new(4.5, -2.0),
+        };
+        let circle = Circle {
+            center: Point2D::new(2.0, 0.0),
+            radius: Positive::new(0.5).unwrap(),
+        };
+
+        let elements = vec![
+            SvgElement::new(
+                SvgShape::polygon(&Polygon2D::from(hull)),
+                SvgStyle {
+                    fill: Some(Rgb::new(200, 200, 255)),
+                    opacity: 0.5,
+                    ..SvgStyle::default()
+                },
+            ),
+            SvgElement::new(SvgShape::polyline(&simplified), SvgStyle::default()),
+            SvgElement::new(
+                SvgShape::points(&pc),
+                SvgStyle {
+                    stroke: Some(Rgb::new(255, 0, 0)),
+                    ..SvgStyle::default()
+                },
+            ),
+            SvgElement::new(SvgShape::from(&segment), SvgStyle::default()),
+            SvgElement::new(SvgShape::from(&circle), SvgStyle::default()),
+        ];
+
+        let config = SvgConfig {
+            precision: Some(3),
+            ..SvgConfig::default()
+        };
+
+        if GENERATE_EXCEPTED_RESULT_FILES {
+            save_svg(
+                &mut File::create(&path_expected).unwrap(),
+                &elements,
+                &config,
+            )
+            .unwrap();
+        }
+
+        save_svg(&mut File::create(&path_tmp).unwrap(), &elements, &config).unwrap();
+
+        assert_files_equal(path_expected, path_tmp);
+    }
+
+    {
+        let square = vec![
+            Point2D::new(0.0, 0.0),
+            Point2D::new(2.0, 0.0),
+            Point2D::new(2.0, 1.0),
+            Point2D::new(0.0, 1.0),
+        ];
+        let elements = vec![SvgElement::new(
+            SvgShape::Polygon(square),
+            SvgStyle::default(),
+        )];
+
+        let mut config = SvgConfig {
+            width: Some(200),
+            margin: 0.0,
+            flip_y: true,
+            precision: None,
+        };
+
+        let mut flipped = Vec::new();
+        save_svg(&mut flipped, &elements, &config).unwrap();
+        let flipped = String::from_utf8(flipped).unwrap();
+        assert!(flipped.contains("width=\"200\" height=\"100\" viewBox=\"0 0 2 1\""));
+        assert!(flipped.contains("points=\"0,1 2,1 2,0 0,0\""));
+
+        config.flip_y = false;
+        let mut unflipped = Vec::new();
+        save_svg(&mut unflipped, &elements, &config).unwrap();
+        let unflipped = String::from_utf8(unflipped).unwrap();
+        assert!(unflipped.contains("points=\"0,0 2,0 2,1 0,1\""));
+    }
+
+    {
+        // Degenerated dimensions are extended relative to the magnitude of the coordinates
+        let config = SvgConfig::default();
+        for shape in [
+            SvgShape::Points(vec![Point2D::new(1e17, 1e17)]),
+            SvgShape::Line(Point2D::new(1e17, 0.0), Point2D::new(1e17, 1.0)),
+        ] {
+            let elements = vec![SvgElement::new(shape, SvgStyle::default())];
+            assert!(save_svg(&mut Vec::new(), &elements, &config).is_ok());
+        }
+
+        for p in [
+            Point2D::new(f64::INFINITY, 0.0),
+            Point2D::new(0.0, f64::NAN),
+            Point2D::new(f64::MAX, 0.0),
+        ] {
+            let elements = vec![SvgElement::new(
+                SvgShape::Line(Point2D::new(-f64::MAX, 0.0), p),
+                SvgStyle::default(),
+            )];
+            match save_svg(&mut Vec::new(), &elements, &config) {
+                Err(IOError::NonFiniteCoordinates) => {}
+                x => panic!("unexpected result {:?}", x),
+            }
+        }
+    }
+}
+
+#[test]
+fn dxf_io_test() {
+    let max_angle = Rad(std::f64::consts::PI / 4.0);
+
+    let entities = load_dxf::<Point2D, _>(
+        BufReader::new(File::open("tests/data/profile.dxf").unwrap()),
+        max_angle,
+    )
+    .unwrap();
+
+    // BLOCKS and unsupported entities are skipped
+    assert!(entities.len() == 7);
+
+    let layers: Vec<&str> = entities.iter().map(|x| x.layer.as_str()).collect();
+    assert!(layers == vec!["outline", "outline", "path", "holes", "holes", "0", "marks"]);
+
+    match &entities[0].geometry {
+        DxfGeometry::Line(line) => {
+            assert!(line.start == Point2D::new(0.0, 0.0));
+            assert!(line.end == Point2D::new(10.0, 0.0));
+        }
+        _ => panic!("expected a line"),
+    }
+
+    match &entities[1].geometry {
+        DxfGeometry::Polygon(pg) => {
+            // The semicircle between the 2nd and 3rd vertex adds 3 points
+            assert!(pg.num_vertices() == 7);
+            let p = pg.vertex(VId(3)).unwrap();
+            assert!((p.x - 15.0).abs() < 1e-9);
+            assert!((p.y - 5.0).abs() < 1e-9);
+            assert!(pg.vertex(VId(5)).unwrap() == Point2D::new(10.0, 10.0));
+        }
+        _ => panic!("expected a polygon"),
+    }
+
+    match &entities[2].geometry {
+        DxfGeometry::Polyline(pc) => {
+            // Clockwise quarter circle around (1, -1)
+            assert!(pc.len() == 3);
+            assert!((pc[1].x - 1.0).abs() < 1e-9);
+            assert!((pc[1].y - (2.0f64.sqrt() - 1.0)).abs() < 1e-9);
+        }
+        _ => panic!("expected a polyline"),
+    }
+
+    match &entities[4].geometry {
+        // Extrusion direction (0, 0, -1) mirrors the circle
+        DxfGeometry::Circle(circle) => assert!(circle.center == Point2D::new(-2.0, 3.0)),
+        _ => panic!("expected a circle"),
+    }
+
+    match &entities[5].geometry {
+        DxfGeometry::Arc { circle, start, end } => {
+            assert!(*circle.radius == 2.0);
+            assert!(start.0 == 0.0);
+            assert!((end.0 - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+        }
+        _ => panic!("expected an arc"),
+    }
+
+    assert!(entities[6].geometry == DxfGeometry::Point(Point2D::new(3.0, 4.0)));
+
+    save_dxf(
+        &mut File::create("tests/tmp/profile.dxf").unwrap(),
+        &entities,
+    )
+    .unwrap();
+
+    let reloaded = load_dxf::<Point2D, _>(
+        BufReader::new(File::open("tests/tmp/profile.dxf").unwrap()),
+        max_angle,
+    )
+    .unwrap();
+
+    assert!(reloaded == entities);
+
+    // Saved as R12, which doesn't require handles
+    let saved = std::fs::read_to_string("tests/tmp/profile.dxf").unwrap();
+    assert!(saved.contains("AC1009"));
+    assert!(saved.contains("POLYLINE") && saved.contains("SEQEND"));
+    assert!(!saved.contains("LWPOLYLINE"));
+
+    // POLYLINE with a counter clockwise semicircle from its 1st to its 2nd VERTEX
+    let polyline = concat!(
+        "  0\nSECTION\n  2\nENTITIES\n",
+        "  0\nPOLYLINE\n  8\nbulged\n 66\n1\n 10\n0.0\n 20\n0.0\n 30\n0.0\n 70\n0\n",
+        "  0\nVERTEX\n  8\nbulged\n 10\n0.0\n 20\n0.0\n 42\n1.0\n",
+        "  0\nVERTEX\n  8\nbulged\n 10\n2.0\n 20\n0.0\n",
+        "  0\nSEQEND\n  0\nENDSEC\n  0\nEOF\n"
+    );
+    let entities = load_dxf::<Point2D, _>(polyline.as_bytes(), max_angle).unwrap();
+    assert!(entities.len() == 1);
+    assert!(entities[0].layer == "bulged");
+    match &entities[0].geometry {
+        DxfGeometry::Polyline(pc) => {
+            assert!(pc.len() == 5);
+            assert!((pc[2].x - 1.0).abs() < 1e-9);
+            assert!((pc[2].y + 1.0).abs() < 1e-9);
+            assert!(pc[4] == Point2D::new(2.0, 0.0));
+        }
+        _ => panic!("expected a polyline"),
+    }
+
+    for invalid in [0.0, -1.0, f64::NAN].iter() {
+        let error = load_dxf::<Point2D, _>(polyline.as_bytes(), Rad(*invalid)).unwrap_err();
+        assert!(matches!(error.root(), IOError::Dxf(DxfError::MaxAngle)));
+    }
+}
@@ -58,3 +58,732 @@ fn point_cloud_3d_io_test() {
         assert!(pc.len() == 20 * 20 * 20);
     }
 }
+
+#[test]
+fn las_io_test() {
+    let mut pc = PointCloud3D::<Point3D>::new();
+    load_xyz::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/test_cube.xyz").unwrap()),
+        &mut pc,
+    )
+    .unwrap();
+
+    let colors = vec![Rgb::new(0, 128, 255); pc.len()];
+
+    let wkt = "PROJCS[\"WGS 84 / UTM zone 32N\",AUTHORITY[\"EPSG\",\"32632\"]]";
+    let mut geo_keys = Vec::new();
+    for x in [1u16, 1, 0, 1, 3072, 0, 1, 32632].iter() {
+        geo_keys.extend_from_slice(&x.to_le_bytes());
+    }
+    let vlrs = vec![
+        LasVlr {
+            user_id: "LASF_Projection".to_string(),
+            record_id: 34735,
+            description: "GeoKeyDirectoryTag".to_string(),
+            is_extended: false,
+            data: geo_keys,
+        },
+        LasVlr {
+            is_extended: true,
+            ..LasVlr::ogc_wkt(wkt)
+        },
+    ];
+
+    for (version, point_format) in [(LasVersion::V1_2, 0), (LasVersion::V1_4, 3)].iter() {
+        let path = "tests/tmp/test_cube.las";
+        save_las(
+            &mut File::create(path).unwrap(),
+            &pc,
+            *version,
+            *point_format,
+            Some(&colors),
+            &vlrs,
+        )
+        .unwrap();
+
+        let vlrs_loaded = load_las_vlrs(&mut BufReader::new(File::open(path).unwrap())).unwrap();
+        assert!(vlrs_loaded.len() == 2);
+        match vlrs_loaded[0].content().unwrap() {
+            LasVlrContent::GeoKeyDirectory(x) => assert!(x.epsg() == Some(32632)),
+            _ => panic!("GeoKeyDirectory expected"),
+        }
+        match vlrs_loaded[1].content().unwrap() {
+            LasVlrContent::OgcCoordinateSystemWkt(x) => assert!(x == wkt),
+            _ => panic!("OGC WKT expected"),
+        }
+        assert!(vlrs_loaded[1].is_extended == (*version == LasVersion::V1_4));
+
+        let mut pc_loaded = PointCloud3D::<Point3D>::new();
+        load_las::<_, _, _, 30>(
+            &mut BufReader::new(File::open(path).unwrap()),
+            &mut pc_loaded,
+        )
+        .unwrap();
+
+        assert!(pc_loaded.len() == pc.len());
+        for i in 0..pc.len() {
+            assert!((pc[i].x() - pc_loaded[i].x()).abs() < 1e-6);
+            assert!((pc[i].y() - pc_loaded[i].y()).abs() < 1e-6);
+            assert!((pc[i].z() - pc_loaded[i].z()).abs() < 1e-6);
+        }
+
+        let mut pc_loaded = PointCloud3D::<Point3D>::new();
+        let mut las_points = Vec::<LasPoint>::new();
+        load_las_points::<_, _, _, _, 30>(
+            &mut BufReader::new(File::open(path).unwrap()),
+            &mut pc_loaded,
+            &mut las_points,
+        )
+        .unwrap();
+
+        assert!(pc_loaded.len() == pc.len());
+        assert!(las_points.len() == pc.len());
+        for las_point in las_points.iter() {
+            assert!(las_point.return_number == 1);
+            assert!(las_point.number_of_returns == 1);
+            if *point_format == 3 {
+                assert!(las_point.gps_time == Some(0.0));
+                assert!(las_point.rgb8() == Some(Rgb::new(0, 128, 255)));
+            } else {
+                assert!(las_point.gps_time.is_none());
+                assert!(las_point.rgb.is_none());
+            }
+        }
+    }
+}
+
+#[test]
+fn laz_io_test() {
+    let mut pc = PointCloud3D::<Point3D>::new();
+    load_xyz::<_, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/test_cube.xyz").unwrap()),
+        &mut pc,
+    )
+    .unwrap();
+
+    let mut pc_loaded = PointCloud3D::<Point3D>::new();
+    let mut las_points = Vec::<LasPoint>::new();
+    load_las_points::<_, _, _, _, 30>(
+        &mut BufReader::new(File::open("tests/data/test_cube.laz").unwrap()),
+        &mut pc_loaded,
+        &mut las_points,
+    )
+    .unwrap();
+
+    assert!(pc_loaded.len() == pc.len());
+    assert!(las_points.len() == pc.len());
+    for i in 0..pc.len() {
+        assert!((pc[i].x() - pc_loaded[i].x()).abs() < 1e-6);
+        assert!((pc[i].y() - pc_loaded[i].y()).abs() < 1e-6);
+        assert!((pc[i].z() - pc_loaded[i].z()).abs() < 1e-6);
+
+        let las_point = &las_points[i];
+        assert!(las_point.intensity == (i * 13 % 700) as u16);
+        assert!(las_point.classification == (i % 4) as u8);
+        assert!(las_point.gps_time == Some(524288.0 + i as f64 * 0.125));
+        assert!(las_point.rgb8() == Some(Rgb::new((i % 256) as u8, 128, (255 - i % 256) as u8)));
+    }
+}
+
+#[test]
+fn laz_layered_io_test() {
+    for point_format in &[6, 7, 8] {
+        let load = |path: String| {
+            let mut pc = PointCloud3D::<Point3D>::new();
+            let mut las_points = Vec::<LasPoint>::new();
+            load_las_points::<_, _, _, _, 30>(
+                &mut BufReader::new(File::open(path).unwrap()),
+                &mut pc,
+                &mut las_points,
+            )
+            .unwrap();
+            (pc, las_points)
+        };
+
+        let (pc, las_points) = load(format!("tests/data/test_cube_layered_{}.las", point_format));
+        let (pc_laz, las_points_laz) =
+            load(format!("tests/data/test_cube_layered_{}.laz", point_format));
+
+        assert!(pc.len() == 1000);
+        assert!(pc_laz.len() == pc.len());
+        assert!(las_points_laz.len() == las_points.len());
+        for i in 0..pc.len() {
+            assert!(pc_laz[i].x() == pc[i].x());
+            assert!(pc_laz[i].y() == pc[i].y());
+            assert!(pc_laz[i].z() == pc[i].z());
+            assert!(las_points_laz[i] == las_points[i]);
+        }
+
+        assert!((0..4).all(|c| las_points.iter().any(|x| x.scanner_channel == c)));
+        assert!(las_points
+            .iter()
+            .all(|x| x.rgb.is_some() == (*point_format >= 7)));
+        assert!(las_points
+            .iter()
+            .all(|x| x.nir.is_some() == (*point_format == 8)));
+    }
+
+    // Formats 9 and 10 store waveform packets, which can't be decompressed
+    let mut bytes = std::fs::read("tests/data/test_cube_layered_6.laz").unwrap();
+    bytes[104] = 0x80 | 9;
+    bytes[105..107].copy_from_slice(&59u16.to_le_bytes());
+
+    let mut pc = PointCloud3D::<Point3D>::new();
+    let error = load_las::<_, _, _, 30>(std::io::Cursor::new(bytes), &mut pc).unwrap_err();
+    match error.root() {
+        IOError::Laz(LazError::UnsupportedPointFormat(9)) => {}
+        _ => panic!("unexpected error {:?}", error),
+    }
+}
+
+#[test]
+fn ply_points_io_test() {
+    let mut pc = PointCloud3D::<Point3D>::new();
+    let mut colors = Vec::new();
+    let mut normals = Vec::new();
+    let mut intensity = Vec::new();
+
+    for i in 0..100 {
+        pc.push(Point3D::new(0.5 * i as f64, -0.25 * i as f64, 2.0));
+        colors.push(Rgb::new(i as u8, 255 - i as u8, 7));
+        normals.push(Norm3D::new(Point3D::new(1.0, i as f64, 0.0)).unwrap());
+        intensity.push(0.5 * i as f64);
+    }
+
+    let scalars = vec![PlyScalarColumn {
+        name: "intensity".to_string(),
+        values: intensity.clone(),
+    }];
+
+    save_ply_points_ascii(
+        &mut File::create("tests/tmp/points_ascii.ply").unwrap(),
+        &pc,
+        Some(&colors),
+        Some(&normals),
+        &scalars,
+    )
+    .unwrap();
+
+    for precision in [Precision::P32, Precision::P64].iter() {
+        save_ply_points_binary(
+            &mut File::create("tests/tmp/points_binary.ply").unwrap(),
+            &pc,
+            precision,
+            Some(&colors),
+            Some(&normals),
+            &scalars,
+        )
+        .unwrap();
+
+        for path in ["tests/tmp/points_ascii.ply", "tests/tmp/points_binary.ply"].iter() {
+            let mut loaded = PointCloud3D::<Point3D>::new();
+            load_ply_points::<_, _, _, 30>(BufReader::new(File::open(path).unwrap()), &mut loaded)
+                .unwrap();
+            assert!(loaded.len() == 100);
+            assert!(loaded.data[10] == pc.data[10]);
+
+            let mut loaded = PointCloud3D::<Point3D>::new();
+            let attributes = load_ply_points_with_attributes(
+                BufReader::new(File::open(path).unwrap()),
+                &mut loaded,
+            )
+            .unwrap();
+            assert!(attributes.colors.as_ref() == Some(&colors));
+            let loaded_normals = attributes.normals.as_ref().unwrap();
+            assert!((loaded_normals[42].as_ref().unwrap().y() - normals[42].y()).abs() < 1e-6);
+            assert!(attributes.scalar("intensity") == Some(&intensity[..]));
+        }
+    }
+
+    assert!(save_ply_points_ascii(&mut Vec::new(), &pc, Some(&colors[1..]), None, &[],).is_err());
+
+    for name in ["", "two words", "line\nbreak"].iter() {
+        let scalars = vec![PlyScalarColumn {
+            name: name.to_string(),
+            values: intensity.clone(),
+        }];
+        let mut buffer = Vec::new();
+        assert!(matches!(
+            save_ply_points_ascii(&mut buffer, &pc, None, None, &scalars),
+            Err(IOError::InvalidPlyPropertyName(_))
+        ));
+        assert!(matches!(
+            save_ply_points_binary(&mut buffer, &pc, &Precision::P32, None, None, &scalars),
+            Err(IOError::InvalidPlyPropertyName(_))
+        ));
+        assert!(buffer.is_empty());
+    }
+}
+
+#[test]
+fn ptx_io_test() {
+    let mut pc = PointCloud3D::<Point3D>::new();
+    let mut ptx_points = Vec::new();
+    load_ptx_points::<_, _, _, _, 3>(
+        BufReader::new(File::open("tests/data/two_scans.ptx").unwrap()),
+        &mut pc,
+        &mut ptx_points,
+    )
+    .unwrap();
+
+    assert!(pc.len() == 6);
+    assert!(ptx_points.len() == 6);
+
+    assert!(pc.data[3] == Point3D::new(1.0, 1.0, 0.0));
+    assert!(ptx_points[3].scan == 0);
+    assert!(ptx_points[3].row == 1);
+    assert!(ptx_points[3].column == 1);
+    assert!(ptx_points[3].intensity == Some(1.0));
+    assert!(ptx_points[3].color == Some(Rgb::new(10, 20, 30)));
+
+    // The second scan is rotated around z by 90 degrees and translated
+    assert!((pc.data[4].x() - 10.0).abs() < 1e-9);
+    assert!((pc.data[4].y() - 21.0).abs() < 1e-9);
+    assert!((pc.data[4].z() - 30.0).abs() < 1e-9);
+    assert!((pc.data[5].x() - 8.0).abs() < 1e-9);
+    assert!((pc.data[5].y() - 20.0).abs() < 1e-9);
+    assert!(ptx_points[5].scan == 1);
+    assert!(ptx_points[5].row == 1);
+    assert!(ptx_points[5].column == 0);
+    assert!(ptx_points[5].intensity == Some(0.2));
+    assert!(ptx_points[5].color.is_none());
+
+    let mut pc_only = PointCloud3D::<Point3D>::new();
+    load_ptx::<_, _, _, 30>(
+        BufReader::new(File::open("tests/data/two_scans.ptx").unwrap()),
+        &mut pc_only,
+    )
+    .unwrap();
+    assert!(pc_only.data == pc.data);
+}
+
+#[test]
+fn e57_io_test() {
+    let mut pc = PointCloud3D::<Point3D>::new();
+    let mut e57_points = Vec::new();
+    load_e57_points::<_, _, _, _, 2>(
+        BufReader::new(File::open("tests/data/two_scans.e57").unwrap()),
+        &mut pc,
+        &mut e57_points,
+    )
+    .unwrap();
+
+    // The third point of the first scan is flagged as invalid
+    assert!(pc.len() == 4);
+    assert!(e57_points.len() == 4);
+
+    // The first scan is rotated around z by 90 degrees and translated
+    assert!((pc.data[0].x() - 8.0).abs() < 1e-9);
+    assert!((pc.data[0].y() - 1.0).abs() < 1e-9);
+    assert!((pc.data[0].z() - 3.0).abs() < 1e-9);
+    assert!((pc.data[1].x() - 10.0).abs() < 1e-9);
+    assert!((pc.data[1].y() + 0.5).abs() < 1e-9);
+    assert!((pc.data[1].z() - 4.25).abs() < 1e-9);
+    assert!(e57_points[1].scan == 0);
+    assert!(e57_points[1].intensity == Some(4095.0));
+    assert!(e57_points[1].color == Some(Rgb::new(0, 128, 255)));
+    assert!(e57_points[1].row.is_none());
+
+    // The second scan is spherical with 16 bit colors
+    assert!((pc.data[2].x() - 2.0).abs() < 1e-6);
+    assert!(pc.data[2].y().abs() < 1e-6);
+    assert!(pc.data[3].x().abs() < 1e-6);
+    assert!((pc.data[3].y() - 1.0).abs() < 1e-6);
+    assert!(pc.data[3].z().abs() < 1e-6);
+    assert!(e57_points[2].scan == 1);
+    assert!(e57_points[2].intensity.is_none());
+    assert!(e57_points[2].color == Some(Rgb::new(255, 0, 128)));
+    assert!(e57_points[3].row == Some(3));
+    assert!(e57_points[3].column == Some(4));
+
+    let mut pc_only = PointCloud3D::<Point3D>::new();
+    load_e57::<_, _, _, 3>(
+        BufReader::new(File::open("tests/data/two_scans.e57").unwrap()),
+        &mut pc_only,
+    )
+    .unwrap();
+    assert!(pc_only.data == pc.data);
+
+    // Corrupting a byte breaks the checksum of its page
+    let mut bytes = std::fs::read("tests/data/two_scans.e57").unwrap();
+    bytes[1030] ^= 0xFF;
+    let mut pc_corrupt = PointCloud3D::<Point3D>::new();
+    assert!(load_e57::<_, _, _, 3>(std::io::Cursor::new(bytes), &mut pc_corrupt).is_err());
+
+    // Huge sizes within the header are rejected before allocating
+    for range in [32..40, 40..48].iter() {
+        let mut bytes = std::fs::read("tests/data/two_scans.e57").unwrap();
+        bytes[range.clone()].copy_from_slice(&(u64::MAX / 2).to_le_bytes());
+        let error =
+            load_e57::<_, _, _, 3>(std::io::Cursor::new(bytes), &mut pc_corrupt).unwrap_err();
+        match error.root() {
+            IOError::E57(E57Error::Header) => (),
+            _ => panic!("Expected a header error, got {:?}", error),
+        }
+    }
+}
+
+#[test]
+fn pcd_io_test() {
+    let mut pc = PointCloud3D::<Point3D>::new();
+    let mut pcd_points = Vec::new();
+    let layout = load_pcd_points::<_, _, _, _, 4>(
+        BufReader::new(File::open("tests/data/organized.pcd").unwrap()),
+        &mut pc,
+        &mut pcd_points,
+    )
+    .unwrap();
+
+    assert!(layout.width == 3);
+    assert!(layout.height == 2);
+    assert!(layout.is_organized());
+    assert!(layout.viewpoint.translation == [1.0, 2.0, 3.0]);
+    assert!(layout.viewpoint.orientation == [1.0, 0.0, 0.0, 0.0]);
+
+    // Invalid points are kept to preserve the organization
+    assert!(pc.len() == 6);
+    assert!(pc.data[4].x().is_nan());
+    assert!(pcd_points[4].normal.is_none());
+    assert!(pc.data[5] == Point3D::new(2.0, 1.0, 1.5));
+    assert!(pcd_points[5].row == 1);
+    assert!(pcd_points[5].column == 2);
+    assert!(pcd_points[5].color == Some(Rgb::new(128, 128, 128)));
+    assert!(pcd_points[5].normal == Some(Norm3D::new(Point3D::new(0.0, 1.0, 0.0)).unwrap()));
+    assert!(pcd_points[5].intensity == Some(60.0));
+    assert!(pcd_points[0].color == Some(Rgb::new(255, 0, 0)));
+    assert!(pcd_points[1].color == Some(Rgb::new(0, 255, 0)));
+    // Packed color written as float
+    let packed = 4.808e+06_f32.to_bits();
+    assert!(
+        pcd_points[3].color
+            == Some(Rgb::new(
+                (packed >> 16) as u8,
+                (packed >> 8) as u8,
+                packed as u8
+            ))
+    );
+
+    let mut compressed = PointCloud3D::<Point3D>::new();
+    load_pcd::<_, _, _, 2>(
+        BufReader::new(File::open("tests/data/compressed.pcd").unwrap()),
+        &mut compressed,
+    )
+    .unwrap();
+    assert!(compressed.len() == 3);
+    assert!(compressed.data[2] == Point3D::new(3.0, 0.5, -1.0));
+
+    let mut pc = PointCloud3D::<Point3D>::new();
+    let mut colors = Vec::new();
+    let mut normals = Vec::new();
+    let mut intensities = Vec::new();
+
+    for i in 0..100 {
+        pc.push(Point3D::new(0.5 * i as f64, -0.25 * i as f64, 2.0));
+        colors.push(Rgb::new(i as u8, 255 - i as u8, 7));
+        normals.push(Norm3D::new(Point3D::new(0.0, 0.0, 1.0)).unwrap());
+        intensities.push(0.5 * i as f64);
+    }
+
+    let layout = PcdLayout {
+        width: 10,
+        height: 10,
+        viewpoint: PcdViewpoint {
+            translation: [1.0, 0.0, 0.0],
+            orientation: [0.0, 1.0, 0.0, 0.0],
+        },
+    };
+
+    for format in [
+        PcdDataFormat::Ascii,
+        PcdDataFormat::Binary,
+        PcdDataFormat::BinaryCompressed,
+    ]
+    .iter()
+    {
+        save_pcd_points(
+            &mut File::create("tests/tmp/points.pcd").unwrap(),
+            &pc,
+            format,
+            &layout,
+            Some(&colors),
+            Some(&normals),
+            Some(&intensities),
+        )
+        .unwrap();
+
+        let mut loaded = PointCloud3D::<Point3D>::new();
+        let mut loaded_points = Vec::new();
+        let loaded_layout = load_pcd_points::<_, _, _, _, 30>(
+            BufReader::new(File::open("tests/tmp/points.pcd").unwrap()),
+            &mut loaded,
+            &mut loaded_points,
+        )
+        .unwrap();
+
+        assert!(loaded_layout == layout);
+        assert!(loaded.data == pc.data);
+        assert!(loaded_points[42].row == 4);
+        assert!(loaded_points[42].column == 2);
+        assert!(loaded_points[42].color == Some(colors[42].clone()));
+        assert!(loaded_points[42].normal == Some(normals[42].clone()));
+        assert!(loaded_points[42].intensity == Some(21.0));
+
+        save_pcd(
+            &mut File::create("tests/tmp/points.pcd").unwrap(),
+            &pc,
+            format,
+        )
+        .unwrap();
+        let mut loaded = PointCloud3D::<Point3D>::new();
+        load_pcd::<_, _, _, 30>(
+            BufReader::new(File::open("tests/tmp/points.pcd").unwrap()),
+            &mut loaded,
+        )
+        .unwrap();
+        assert!(loaded.data == pc.data);
+    }
+}
+
+#[test]
+fn gcode_io_test() {
+    let mut pc = PointCloud3D::<Point3D>::new();
+    let mut moves = Vec::new();
+    load_gcode_moves::<_, _, _, _, 7>(
+        BufReader::new(File::open("tests/data/toolpath.gcode").unwrap()),
+        &mut pc,
+        &mut moves,
+    )
+    .unwrap();
+
+    let close = |p: &Point3D, x: f64, y: f64, z: f64| {
+        (p.x() - x).abs() < 1e-9 && (p.y() - y).abs() < 1e-9 && (p.z() - z).abs() < 1e-9
+    };
+
+    // Arcs are split into segments of 5 degrees at most
+    assert!(pc.len() == 62);
+    assert!(moves.len() == 62);
+
+    assert!(close(&pc.data[0], 0.0, 0.0, 0.2));
+    assert!(moves[0].kind == GcodeMoveKind::Travel);
+    assert!(moves[1].feed_rate == Some(3000.0));
+    assert!(moves[2].kind == GcodeMoveKind::Extrude);
+    assert!(moves[2].feed_rate == Some(1500.0));
+    assert!((moves[2].extrusion - 1.0).abs() < 1e-9);
+
+    // Clockwise half circle around (15, 20)
+    assert!(close(&pc.data[21], 15.0, 15.0, 0.2));
+    assert!(close(&pc.data[39], 10.0, 20.0, 0.2));
+    assert!((moves[21].extrusion - 1.0 / 36.0).abs() < 1e-9);
+
+    // Relative counter clockwise quarter circle around (5, 5) defined by its radius
+    assert!(close(&pc.data[42], 5.0, 0.0, 0.4));
+    let d = 5.0 * std::f64::consts::FRAC_1_SQRT_2;
+    assert!(close(&pc.data[51], 5.0 + d, 5.0 - d, 0.4));
+    assert!(close(&pc.data[60], 10.0, 5.0, 0.4));
+
+    // Inches with relative extrusion
+    assert!(close(&pc.data[61], 25.4, 25.4, 0.4));
+    assert!((moves[61].extrusion - 2.54).abs() < 1e-9);
+    assert!(moves[61].feed_rate == Some(1500.0));
+
+    let layers = load_gcode_layers::<Point3D, _, 10>(BufReader::new(
+        File::open("tests/data/toolpath.gcode").unwrap(),
+    ))
+    .unwrap();
+
+    assert!(layers.len() == 2);
+    assert!((layers[0].z - 0.2).abs() < 1e-9);
+    assert!(layers[0].paths.len() == 1);
+    assert!(layers[0].paths[0].num_segments() == 39);
+    assert!(layers[0].paths[0].vertex(VId(0)) == Some(Point3D::new(10.0, 10.0, 0.2)));
+    assert!((layers[1].z - 0.4).abs() < 1e-9);
+    assert!(layers[1].paths.len() == 1);
+    assert!(layers[1].paths[0].num_segments() == 21);
+
+    let mut only_points = PointCloud3D::<Point3D>::new();
+    load_gcode_points::<_, _, _, 7>(
+        BufReader::new(File::open("tests/data/toolpath.gcode").unwrap()),
+        &mut only_points,
+    )
+    .unwrap();
+    assert!(only_points.data == pc.data);
+
+    let mut invalid = PointCloud3D::<Point3D>::new();
+    assert!(
+        load_gcode_points::<_, _, _, 7>(BufReader::new(&b"G1 X1\nG1 X-\n"[..]), &mut invalid)
+            .is_err()
+    );
+}
+
+#[test]
+fn gcode_planes_io_test() {
+    let close = |p: &Point3D, x: f64, y: f64, z: f64| {
+        (p.x() - x).abs() < 1e-9 && (p.y() - y).abs() < 1e-9 && (p.z() - z).abs() < 1e-9
+    };
+
+    // Counter clockwise half circle within the ZX plane around the origin
+    let mut pc = PointCloud3D::<Point3D>::new();
+    load_gcode_points::<_, _, _, 7>(
+        BufReader::new(&b"G18\nG0 X10 Y0 Z0\nG3 X-10 Z0 I-10 K0\n"[..]),
+        &mut pc,
+    )
+    .unwrap();
+    assert!(pc.len() == 37);
+    assert!(close(&pc.data[0], 10.0, 0.0, 0.0));
+    assert!(close(&pc.data[18], 0.0, 0.0, -10.0));
+    assert!(close(&pc.data[36], -10.0, 0.0, 0.0));
+    assert!(pc.data.iter().all(|p| p.y() == 0.0));
+
+    // Within the YZ plane, J and K define the center
+    let mut pc = PointCloud3D::<Point3D>::new();
+    load_gcode_points::<_, _, _, 7>(
+        BufReader::new(&b"G19 G0 Y10\nG2 Y-10 J-10 K0\n"[..]),
+        &mut pc,
+    )
+    .unwrap();
+    assert!(pc.len() == 37);
+    assert!(close(&pc.data[18], 0.0, 0.0, -10.0));
+
+    // Unrelated words don't prevent the motion, axis words of M codes are parameters
+    let mut pc = PointCloud3D::<Point3D>::new();
+    load_gcode_points::<_, _, _, 7>(
+        BufReader::new(&b"G0 G54 X1 Y2\nG1 X3 M3\nM92 X80\nG4 P100\nG1 G17 X4 Y4\n"[..]),
+        &mut pc,
+    )
+    .unwrap();
+    assert!(pc.len() == 3);
+    assert!(close(&pc.data[0], 1.0, 2.0, 0.0));
+    assert!(close(&pc.data[1], 3.0, 2.0, 0.0));
+    assert!(close(&pc.data[2], 4.0, 4.0, 0.0));
+}
+
+#[test]
+fn gcode_save_test() {
+    let mut square = PointCloud2D::<Point2D>::new();
+    square.push(Point2D::new(0.0, 0.0));
+    square.push(Point2D::new(10.0, 0.0));
+    square.push(Point2D::new(10.0, 10.0));
+    square.push(Point2D::new(0.0, 10.0));
+    let square = Polygon2D::from(square);
+
+    let config = GcodeConfig::default();
+    let layers = vec![vec![square.clone()], vec![square]];
+    save_gcode_layers_2d(
+        &mut File::create("tests/tmp/layers.gcode").unwrap(),
+        &layers,
+        &config,
+    )
+    .unwrap();
+
+    let mut pc = PointCloud3D::<Point3D>::new();
+    let mut moves = Vec::new();
+    load_gcode_moves::<_, _, _, _, 30>(
+        BufReader::new(File::open("tests/tmp/layers.gcode").unwrap()),
+        &mut pc,
+        &mut moves,
+    )
+    .unwrap();
+
+    // Travel to the start, then closed squares
+    assert!(pc.len() == 10);
+    assert!(pc.data[0] == Point3D::new(0.0, 0.0, 0.2));
+    assert!(pc.data[2] == Point3D::new(10.0, 10.0, 0.2));
+    assert!(pc.data[4] == Point3D::new(0.0, 0.0, 0.2));
+    assert!(pc.data[5] == Point3D::new(0.0, 0.0, 0.4));
+    assert!(pc.data[8] == Point3D::new(0.0, 10.0, 0.4));
+    assert!(moves[0].kind == GcodeMoveKind::Travel);
+    assert!(moves[0].feed_rate == Some(config.travel_feed_rate));
+    assert!(moves[1].kind == GcodeMoveKind::Extrude);
+    assert!(moves[1].feed_rate == Some(config.print_feed_rate));
+    let per_mm = 0.2 * 0.4 / (0.25 * std::f64::consts::PI * 1.75 * 1.75);
+    assert!((moves[1].extrusion - 10.0 * per_mm).abs() < 1e-4);
+    // Retraction before the travel is undone afterwards
+    assert!(moves[5].kind == GcodeMoveKind::Travel);
+    assert!((moves[6].extrusion - 10.0 * per_mm).abs() < 1e-4);
+
+    let loaded_layers = load_gcode_layers::<Point3D, _, 30>(BufReader::new(
+        File::open("tests/tmp/layers.gcode").unwrap(),
+    ))
+    .unwrap();
+    assert!(loaded_layers.len() == 2);
+    assert!(loaded_layers[1].z == 0.4);
+    assert!(loaded_layers[1].paths[0].num_vertices() == 5);
+
+    let mut path = PointCloud3D::<Point3D>::new();
+    path.push(Point3D::new(1.0, 1.0, 0.3));
+    path.push(Point3D::new(2.0, 1.0, 0.3));
+    path.push(Point3D::new(2.0, 2.0, 0.3));
+    let path = Polygon3D::from(path);
+
+    let config = GcodeConfig {
+        z_hop: 0.5,
+        close_paths: false,
+        ..GcodeConfig::default()
+    };
+    save_gcode_layers_3d(
+        &mut File::create("tests/tmp/layers_3d.gcode").unwrap(),
+        &[vec![path.clone(), path]],
+        &config,
+    )
+    .unwrap();
+
+    let mut pc = PointCloud3D::<Point3D>::new();
+    load_gcode_points::<_, _, _, 30>(
+        BufReader::new(File::open("tests/tmp/layers_3d.gcode").unwrap()),
+        &mut pc,
+    )
+    .unwrap();
+
+    let expected = [
+        [1.0, 1.0, 0.8],
+        [1.0, 1.0, 0.3],
+        [2.0, 1.0, 0.3],
+        [2.0, 2.0, 0.3],
+        [2.0, 2.0, 0.8],
+        [1.0, 1.0, 0.8],
+        [1.0, 1.0, 0.3],
+        [2.0, 1.0, 0.3],
+        [2.0, 2.0, 0.3],
+    ];
+    assert!(pc.len() == expected.len());
+    for (p, [x, y, z]) in pc.data.iter().zip(expected.iter()) {
+        assert!((p.x() - x).abs() < 1e-9 && (p.y() - y).abs() < 1e-9 && (p.z() - z).abs() < 1e-9);
+    }
+}
+
+#[test]
+fn vtk_points_io_test() {
+    let mut pc = PointCloud3D::<Point3D>::new();
+    for i in 0..4 {
+        pc.push(Point3D::new(i as f64, 0.5 * i as f64, 1.0));
+    }
+    let point_data = [
+        VtkArray::scalars("intensity", vec![0.0, 0.25, 0.5, 1.0]),
+        VtkArray::vectors("direction", &pc.data),
+    ];
+
+    let mut data = Vec::new();
+    save_vtk_points(&mut data, &pc, VtkFormat::Ascii, &point_data).unwrap();
+    let text = String::from_utf8(data).unwrap();
+    assert!(text.contains("POINTS 4 double\n0 0 1\n1 0.5 1\n2 1 1\n3 1.5 1\n"));
+    assert!(text.contains("VERTICES 4 8\n1 0\n1 1\n1 2\n1 3\n"));
+    assert!(text.contains(
+        "POINT_DATA 4\nSCALARS intensity double 1\nLOOKUP_TABLE default\n0\n0.25\n0.5\n1\n"
+    ));
+    assert!(text.contains("VECTORS direction double\n0 0 1\n"));
+    assert!(!text.contains("CELL_DATA"));
+
+    let mut data = Vec::new();
+    save_vtu_points(&mut data, &pc, VtkFormat::Ascii, &point_data).unwrap();
+    let text = String::from_utf8(data).unwrap();
+    assert!(text.contains("<Piece NumberOfPoints=\"4\" NumberOfCells=\"4\">"));
+    assert!(text.contains(
+        "Name=\"types\" NumberOfComponents=\"1\" format=\"ascii\">\n          1\n          1\n"
+    ));
+
+    let mut data = Vec::new();
+    save_vtu_points(&mut data, &pc, VtkFormat::Binary, &[]).unwrap();
+    let text = String::from_utf8(data).unwrap();
+    // 4 bytes of size followed by 4 * 3 Float64
+    assert!(text.contains("format=\"binary\">\n          YAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAADw"));
+}
@@ -71,3 +71,148 @@ fn test_point_cloud_3d() {
     println!("pc: {}", pc);
     assert!(pc.to_str() == "1.1 2.2 3.3\n1.2 2.3 3.4\n");
 }
+
+#[test]
+fn test_point_cloud_3d_attributes() {
+    let mut pc = PointCloud3D::<Point3D>::new();
+    pc.push(Point3D::new(2.0, 0.0, 0.0));
+    pc.push(Point3D::new(0.0, 0.0, 0.0));
+    pc.push(Point3D::new(1.0, 0.0, 0.0));
+
+    assert!(pc
+        .vertex_attributes_mut()
+        .insert("label", vec![2u32, 0])
+        .is_err());
+    pc.vertex_attributes_mut()
+        .insert("label", vec![2u32, 0, 1])
+        .unwrap();
+    pc.vertex_attributes_mut()
+        .insert("intensity", vec![0.2, 0.0, 0.1])
+        .unwrap();
+    pc.vertex_attributes_mut()
+        .insert("normal", vec![Norm3D::norm_x(); 3])
+        .unwrap();
+
+    assert!(pc.vertex_attributes().get::<f64>("label").is_none());
+    assert!(pc.vertex_attributes().get::<u32>("label") == Some(&[2, 0, 1][..]));
+
+    pc.push(Point3D::new(3.0, 0.0, 0.0));
+    assert!(pc.vertex_attributes().len() == 4);
+    assert!(pc.vertex_attributes().get::<u32>("label") == Some(&[2, 0, 1, 0][..]));
+    pc.vertex_attributes_mut().get_mut::<u32>("label").unwrap()[3] = 3;
+
+    pc.sort_x();
+    assert!(pc.vertex_attributes().get::<u32>("label") == Some(&[0, 1, 2, 3][..]));
+    assert!(pc.vertex_attributes().get::<f64>("intensity") == Some(&[0.0, 0.1, 0.2, 0.0][..]));
+
+    pc.apply_view(&View::Restricted([1, 3].iter().cloned().collect()))
+        .unwrap();
+    assert!(pc.len() == 2);
+    assert!(pc.vertex_attributes().get::<u32>("label") == Some(&[1, 3][..]));
+
+    let mut other = PointCloud3D::<Point3D>::new();
+    other.push(Point3D::new(4.0, 0.0, 0.0));
+    other
+        .vertex_attributes_mut()
+        .insert("color", vec![Rgb::new(1, 2, 3)])
+        .unwrap();
+    pc.consume(other);
+    assert!(pc.len() == 3);
+    assert!(pc.vertex_attributes().get::<u32>("label") == Some(&[1, 3, 0][..]));
+    assert!(
+        pc.vertex_attributes().get::<Rgb>("color")
+            == Some(&[Rgb::default(), Rgb::default(), Rgb::new(1, 2, 3)][..])
+    );
+
+    pc.transform(&Matrix4::scale(-1.0, 2.0, 2.0));
+    let normals = pc.vertex_attributes().get::<Norm3D>("normal").unwrap();
+    assert!((normals[0].x() + 1.0).abs() < 1e-9);
+    assert!((normals[2].z() - 1.0).abs() < 1e-9);
+
+    assert!(pc.vertex_attributes_mut().remove("normal").is_some());
+    assert!(pc.vertex_attributes().names().collect::<Vec<_>>() == ["label", "intensity", "color"]);
+
+    pc.clear();
+    assert!(pc.vertex_attributes().is_empty());
+    assert!(pc.vertex_attributes().has_channels());
+}
+
+#[test]
+fn test_point_cloud_3d_attributes_comparison() {
+    use std::{
+        cmp::Ordering,
+        collections::hash_map::DefaultHasher,
+        hash::{Hash, Hasher},
+    };
+
+    let hash = |pc: &PointCloud3D<Point3D>| {
+        let mut hasher = DefaultHasher::new();
+        pc.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    // Changing data directly doesn't make the attributes differ
+    let p = Point3D::new(1.0, 2.0, 3.0);
+    let mut direct = PointCloud3D::<Point3D>::new();
+    direct.data.push(p.clone());
+    let pushed = PointCloud3D::from(vec![p.clone()]);
+    assert!(direct == pushed);
+    assert!(direct.cmp(&pushed) == Ordering::Equal);
+    assert!(hash(&direct) == hash(&pushed));
+
+    // Channels are compared as if they were resized to the number of points
+    let mut with_channel = PointCloud3D::from(vec![p.clone()]);
+    with_channel
+        .vertex_attributes_mut()
+        .insert("label", vec![0u32])
+        .unwrap();
+    let mut stale = PointCloud3D::<Point3D>::new();
+    stale
+        .vertex_attributes_mut()
+        .insert::<u32>("label", vec![])
+        .unwrap();
+    stale.data.push(p.clone());
+    assert!(stale == with_channel);
+    assert!(hash(&stale) == hash(&with_channel));
+    assert!(stale != pushed);
+}
+
+#[test]
+fn test_attribute_values_order() {
+    use std::cmp::Ordering;
+
+    let nan = AttributeValues::Scalar(vec![f64::NAN]);
+    assert!(nan == nan.clone());
+    assert!(nan.partial_cmp(&nan) == Some(Ordering::Equal));
+
+    let small = AttributeValues::Scalar(vec![1.0, 2.0]);
+    let large = AttributeValues::Scalar(vec![1.0, 3.0]);
+    assert!(small < large);
+    assert!(small.cmp(&large) == Ordering::Less);
+    assert!(small < AttributeValues::Scalar(vec![1.0, 2.0, 0.0]));
+    assert!(large < nan);
+
+    // Different types are never equal
+    let labels = AttributeValues::Label(vec![1]);
+    assert!(labels != AttributeValues::Scalar(vec![1.0]));
+    assert!(labels.cmp(&small) == small.cmp(&labels).reverse());
+}
+
+#[test]
+fn test_attributes_append() {
+    let mut a = Attributes::new(1);
+    a.insert("x", vec![1.0]).unwrap();
+    a.insert("kept", vec![1u32]).unwrap();
+
+    let mut b = Attributes::new(1);
+    b.insert("x", vec![7u32]).unwrap();
+    b.insert("added", vec![Rgb::new(1, 2, 3)]).unwrap();
+
+    // "x" has different types and is dropped from both
+    a.append(b);
+    assert!(a.len() == 2);
+    assert!(a.values("x").is_none());
+    assert!(a.get::<u32>("kept") == Some(&[1, 0][..]));
+    assert!(a.get::<Rgb>("added") == Some(&[Rgb::default(), Rgb::new(1, 2, 3)][..]));
+    assert!(a.names().collect::<Vec<_>>() == ["kept", "added"]);
+}